@@ -0,0 +1,139 @@
+use crate::collector::FileCollector;
+use crate::utils::decode_text;
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Release entries kept for the "Recent Changes" section.
+const MAX_ENTRIES: usize = 3;
+/// Changes listed per release; the rest are counted.
+const MAX_CHANGES: usize = 10;
+/// File names (case-insensitive, without extension) recognized as release notes.
+const CHANGELOG_NAMES: [&str; 6] = [
+    "changelog",
+    "changes",
+    "history",
+    "news",
+    "releases",
+    "release_notes",
+];
+
+/// One release from a changelog.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangelogEntry {
+    /// The release heading without its `#` markers, e.g. `[1.2.0] - 2024-05-01`.
+    pub version: String,
+    /// The first `YYYY-MM-DD` date in the heading.
+    pub date: Option<String>,
+    /// Bullet points, prefixed with their subsection (`Added: ...`) where there is one.
+    pub changes: Vec<String>,
+}
+
+/// The newest entries of a project's changelog.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Changelog {
+    pub path: String,
+    pub entries: Vec<ChangelogEntry>,
+}
+
+/// Whether `path` looks like a changelog or release notes file.
+pub fn is_changelog(path: &Path) -> bool {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase().replace('-', "_"))
+        .is_some_and(|stem| CHANGELOG_NAMES.contains(&stem.as_str()))
+}
+
+/// Reads the shallowest changelog among `docs` and extracts its most recent releases.
+///
+/// Returns `None` when there is no changelog, it can't be read, or no release headings
+/// are found in it.
+pub async fn recent_changes(docs: &[String], source: &dyn FileCollector) -> Option<Changelog> {
+    let path = docs
+        .iter()
+        .filter(|doc| is_changelog(Path::new(doc)))
+        .min_by_key(|doc| Path::new(doc).components().count())?;
+    let contents = match source.read(path).await {
+        Ok(bytes) => decode_text(&bytes)?.0,
+        Err(e) => {
+            debug!("Skipping changelog {}: {}", path, e);
+            return None;
+        }
+    };
+    let entries = parse_changelog(&contents);
+    if entries.is_empty() {
+        debug!("No release headings found in {}", path);
+        return None;
+    }
+    Some(Changelog {
+        path: path.clone(),
+        entries,
+    })
+}
+
+/// Splits a markdown changelog into releases, newest (first) first.
+///
+/// A release starts at a heading mentioning a version number or `Unreleased`; later
+/// headings at the same level start the next one, deeper ones name its subsections. An
+/// `Unreleased` entry without changes is dropped.
+pub fn parse_changelog(contents: &str) -> Vec<ChangelogEntry> {
+    let heading = Regex::new(r"^(#{1,4})\s+(.+?)\s*#*\s*$").unwrap();
+    let version = Regex::new(r"(?i)\bv?\d+\.\d+(\.\d+)?|\bunreleased\b").unwrap();
+    let date = Regex::new(r"\d{4}-\d{2}-\d{2}").unwrap();
+    let bullet = Regex::new(r"^\s{0,3}[-*+]\s+(.+)$").unwrap();
+
+    let mut entries: Vec<ChangelogEntry> = Vec::new();
+    let mut omitted = Vec::new();
+    let mut release_level = None;
+    let mut subsection: Option<String> = None;
+    for line in contents.lines() {
+        if let Some(caps) = heading.captures(line) {
+            let level = caps[1].len();
+            let text = caps[2].to_string();
+            if release_level.is_none_or(|release| level <= release) {
+                if !version.is_match(&text) {
+                    if release_level.is_some() {
+                        break;
+                    }
+                    continue;
+                }
+                if entries.len() == MAX_ENTRIES + 1 {
+                    break;
+                }
+                release_level = Some(level);
+                subsection = None;
+                entries.push(ChangelogEntry {
+                    date: date.find(&text).map(|found| found.as_str().to_string()),
+                    version: text,
+                    changes: Vec::new(),
+                });
+                omitted.push(0);
+            } else {
+                subsection = Some(text);
+            }
+            continue;
+        }
+        let (Some(entry), Some(caps)) = (entries.last_mut(), bullet.captures(line)) else {
+            continue;
+        };
+        if entry.changes.len() == MAX_CHANGES {
+            *omitted.last_mut().unwrap() += 1;
+            continue;
+        }
+        entry.changes.push(match &subsection {
+            Some(subsection) => format!("{}: {}", subsection, &caps[1]),
+            None => caps[1].to_string(),
+        });
+    }
+
+    for (entry, omitted) in entries.iter_mut().zip(omitted) {
+        if omitted > 0 {
+            entry.changes.push(format!("... and {} more", omitted));
+        }
+    }
+    entries.retain(|entry| {
+        !(entry.changes.is_empty() && entry.version.to_lowercase().contains("unreleased"))
+    });
+    entries.truncate(MAX_ENTRIES);
+    entries
+}
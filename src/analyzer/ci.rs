@@ -0,0 +1,206 @@
+use crate::collector::FileCollector;
+use crate::utils::decode_text;
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::path::Path;
+
+/// Commands listed per job; the rest are left out.
+const MAX_COMMANDS: usize = 5;
+/// GitLab CI top-level keys that configure the pipeline rather than name a job.
+const GITLAB_KEYWORDS: [&str; 10] = [
+    "stages",
+    "variables",
+    "image",
+    "services",
+    "before_script",
+    "after_script",
+    "cache",
+    "include",
+    "default",
+    "workflow",
+];
+/// Words in a job's name, commands or actions that say what it does, by activity.
+const ACTIVITIES: [(&str, &[&str]); 4] = [
+    ("lint", &["lint", "clippy", "fmt", "format", "flake8", "ruff", "black", "prettier"]),
+    ("test", &["test", "pytest", "jest", "mocha", "tox", "coverage", "spec"]),
+    ("build", &["build", "compile", "make", "bundle", "package", "docker"]),
+    ("deploy", &["deploy", "publish", "release", "kubectl", "helm", "terraform"]),
+];
+
+/// One job (or Jenkins stage) of a CI pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CiJob {
+    pub name: String,
+    /// What the job appears to do: `lint`, `test`, `build` and/or `deploy`.
+    pub activities: Vec<String>,
+    /// The first shell commands the job runs, or the actions it uses.
+    pub commands: Vec<String>,
+}
+
+/// What a CI pipeline definition runs, and when.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CiPipeline {
+    pub path: String,
+    /// `GitHub Actions`, `GitLab CI` or `Jenkins`.
+    pub provider: String,
+    /// Events that start the pipeline, where the file says (GitHub's `on`).
+    pub triggers: Vec<String>,
+    pub jobs: Vec<CiJob>,
+}
+
+/// Whether `path` is a GitHub Actions workflow, `.gitlab-ci.yml` or `Jenkinsfile`.
+pub fn is_ci_file(path: &Path) -> bool {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let in_workflows = path.to_string_lossy().replace('\\', "/").contains(".github/workflows/");
+    file_name == ".gitlab-ci.yml"
+        || file_name == "Jenkinsfile"
+        || (in_workflows && (file_name.ends_with(".yml") || file_name.ends_with(".yaml")))
+}
+
+/// Reads each CI definition in `files` and describes its jobs.
+///
+/// Files that can't be read or parsed are logged and skipped.
+pub async fn summarize_ci_files(files: &[String], source: &dyn FileCollector) -> Vec<CiPipeline> {
+    let mut pipelines = Vec::new();
+    for file in files {
+        let contents = match source.read(file).await {
+            Ok(bytes) => match decode_text(&bytes) {
+                Some((contents, _)) => contents,
+                None => continue,
+            },
+            Err(e) => {
+                debug!("Skipping CI file {}: {}", file, e);
+                continue;
+            }
+        };
+        match parse_ci_file(file, &contents) {
+            Some(pipeline) => pipelines.push(pipeline),
+            None => debug!("Could not parse CI file {}", file),
+        }
+    }
+    pipelines
+}
+
+/// Describes a GitHub Actions workflow, GitLab CI file or declarative `Jenkinsfile`.
+pub fn parse_ci_file(path: &str, contents: &str) -> Option<CiPipeline> {
+    if path.ends_with("Jenkinsfile") {
+        return Some(CiPipeline {
+            path: path.to_string(),
+            provider: "Jenkins".to_string(),
+            triggers: Vec::new(),
+            jobs: jenkins_jobs(contents),
+        });
+    }
+
+    let document: Value = serde_yaml::from_str(contents).ok()?;
+    let Value::Mapping(document) = document else {
+        return None;
+    };
+    if path.ends_with(".gitlab-ci.yml") {
+        let jobs = document
+            .iter()
+            .filter_map(|(name, job)| Some((name.as_str()?, job)))
+            .filter(|(name, _)| !name.starts_with('.') && !GITLAB_KEYWORDS.contains(name))
+            .filter(|(_, job)| job.get("script").is_some() || job.get("trigger").is_some())
+            .map(|(name, job)| {
+                let commands = strings(job.get("script"));
+                let stage = job.get("stage").and_then(Value::as_str).unwrap_or("");
+                new_job(name, &format!("{} {}", name, stage), commands)
+            })
+            .collect();
+        return Some(CiPipeline {
+            path: path.to_string(),
+            provider: "GitLab CI".to_string(),
+            triggers: Vec::new(),
+            jobs,
+        });
+    }
+
+    let triggers = match document.get("on") {
+        Some(Value::Mapping(events)) => events
+            .keys()
+            .filter_map(|event| event.as_str().map(str::to_string))
+            .collect(),
+        other => strings(other),
+    };
+    let jobs = match document.get("jobs") {
+        Some(Value::Mapping(jobs)) => jobs
+            .iter()
+            .filter_map(|(name, job)| {
+                let name = name.as_str()?;
+                let mut commands = Vec::new();
+                if let Some(Value::Sequence(steps)) = job.get("steps") {
+                    for step in steps {
+                        if let Some(run) = step.get("run").and_then(Value::as_str) {
+                            let lines = run.lines().map(str::trim).filter(|line| !line.is_empty());
+                            commands.extend(lines.map(str::to_string));
+                        } else if let Some(uses) = step.get("uses").and_then(Value::as_str) {
+                            commands.push(format!("uses {}", uses));
+                        }
+                    }
+                }
+                let display = job.get("name").and_then(Value::as_str).unwrap_or(name);
+                Some(new_job(name, &format!("{} {}", name, display), commands))
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    Some(CiPipeline {
+        path: path.to_string(),
+        provider: "GitHub Actions".to_string(),
+        triggers,
+        jobs,
+    })
+}
+
+// A declarative pipeline's stages, each with the `sh`/`bat` commands up to the next stage.
+fn jenkins_jobs(contents: &str) -> Vec<CiJob> {
+    let stage = Regex::new(r#"stage\s*\(\s*['"]([^'"]+)['"]"#).unwrap();
+    let command = Regex::new(r#"\b(?:sh|bat)\s*\(?\s*(?:script:\s*)?['"]{1,3}([^'"]+)['"]"#).unwrap();
+    let mut stages: Vec<(String, Vec<String>)> = Vec::new();
+    for line in contents.lines() {
+        if let Some(caps) = stage.captures(line) {
+            stages.push((caps[1].to_string(), Vec::new()));
+        } else if let (Some((_, commands)), Some(caps)) = (stages.last_mut(), command.captures(line)) {
+            commands.push(caps[1].trim().to_string());
+        }
+    }
+    stages
+        .into_iter()
+        .map(|(name, commands)| new_job(&name, &name, commands))
+        .collect()
+}
+
+// Activities come from `clue` (the job's name and stage) and every command, but only
+// the first few commands are kept for the report.
+fn new_job(name: &str, clue: &str, mut commands: Vec<String>) -> CiJob {
+    let text = format!("{} {}", clue, commands.join(" ")).to_lowercase();
+    let activities = ACTIVITIES
+        .iter()
+        .filter(|(_, words)| words.iter().any(|word| text.contains(word)))
+        .map(|(activity, _)| activity.to_string())
+        .collect();
+    commands.truncate(MAX_COMMANDS);
+    CiJob {
+        name: name.to_string(),
+        activities,
+        commands,
+    }
+}
+
+// A string, or the strings in a sequence.
+fn strings(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(text)) => vec![text.clone()],
+        Some(Value::Sequence(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,162 @@
+use crate::collector::FileCollector;
+use crate::llm::LLM;
+use crate::utils::decode_text;
+use log::{debug, warn};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Most keys listed for one configuration file; the rest are counted.
+const MAX_OUTLINE_ENTRIES: usize = 20;
+/// Larger "configuration" files are almost always data, and are left out.
+const MAX_CONFIG_BYTES: usize = 256 * 1024;
+/// How much of a file goes into its summary prompt.
+const MAX_PROMPT_CHARS: usize = 4000;
+
+/// What a configuration file sets, for the report's "Configuration" section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigFileSummary {
+    pub path: String,
+    /// `TOML`, `YAML`, `JSON`, `Environment` or `CI workflow`.
+    pub kind: String,
+    /// Top-level keys (tables as `[name]`), workflow jobs or environment variable names.
+    pub outline: Vec<String>,
+    /// One-line LLM summary, unless config contents are kept out of prompts.
+    pub summary: Option<String>,
+}
+
+/// Lists the top-level structure of a configuration file without interpreting values.
+pub fn outline_config_file(path: &str, contents: &str) -> ConfigFileSummary {
+    let kind = config_kind(Path::new(path));
+    let mut outline = match kind {
+        "TOML" => toml_outline(contents),
+        "JSON" => json_outline(contents),
+        "Environment" => env_outline(contents),
+        _ => yaml_outline(contents),
+    };
+    if outline.len() > MAX_OUTLINE_ENTRIES {
+        let more = outline.len() - MAX_OUTLINE_ENTRIES;
+        outline.truncate(MAX_OUTLINE_ENTRIES);
+        outline.push(format!("... and {} more", more));
+    }
+    ConfigFileSummary {
+        path: path.to_string(),
+        kind: kind.to_string(),
+        outline,
+        summary: None,
+    }
+}
+
+/// Outlines every configuration file and, given an `llm`, adds a one-line summary of each.
+///
+/// Unreadable, binary and oversized files are skipped; a failed summary only loses the
+/// summary, never the outline.
+pub async fn summarize_config_files(
+    files: &[String],
+    source: &dyn FileCollector,
+    llm: Option<&dyn LLM>,
+) -> Vec<ConfigFileSummary> {
+    let mut summaries = Vec::new();
+    for file in files {
+        let contents = match source.read(file).await {
+            Ok(bytes) if bytes.len() <= MAX_CONFIG_BYTES => match decode_text(&bytes) {
+                Some((contents, _)) => contents,
+                None => continue,
+            },
+            Ok(_) => {
+                debug!("Skipping oversized configuration file {}", file);
+                continue;
+            }
+            Err(e) => {
+                debug!("Skipping configuration file {}: {}", file, e);
+                continue;
+            }
+        };
+        let mut summary = outline_config_file(file, &contents);
+        if let Some(llm) = llm {
+            let excerpt: String = contents.chars().take(MAX_PROMPT_CHARS).collect();
+            let prompt = format!(
+                "Summarize what the following {} configuration file configures:\n\nPath: {}\nContents:\n{}",
+                summary.kind, file, excerpt
+            );
+            match llm.summarize(&prompt).await {
+                Ok(text) => summary.summary = Some(text),
+                Err(e) => warn!("Could not summarize configuration file {}: {}", file, e),
+            }
+        }
+        summaries.push(summary);
+    }
+    summaries
+}
+
+fn config_kind(path: &Path) -> &'static str {
+    let path_str = path.to_string_lossy().replace('\\', "/");
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if path_str.contains(".github/workflows/")
+        || path_str.contains(".circleci/")
+        || file_name == ".gitlab-ci.yml"
+    {
+        return "CI workflow";
+    }
+    if file_name.starts_with(".env") {
+        return "Environment";
+    }
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => "TOML",
+        Some("json") => "JSON",
+        _ => "YAML",
+    }
+}
+
+fn toml_outline(contents: &str) -> Vec<String> {
+    match contents.parse::<toml::Value>() {
+        Ok(toml::Value::Table(table)) => table
+            .iter()
+            .map(|(key, value)| match value {
+                toml::Value::Table(_) => format!("[{}]", key),
+                _ => key.clone(),
+            })
+            .collect(),
+        _ => vec!["(could not be parsed)".to_string()],
+    }
+}
+
+fn json_outline(contents: &str) -> Vec<String> {
+    match serde_json::from_str::<serde_json::Value>(contents) {
+        Ok(serde_json::Value::Object(object)) => object.keys().cloned().collect(),
+        Ok(serde_json::Value::Array(items)) => vec![format!("(array of {} items)", items.len())],
+        Ok(_) => Vec::new(),
+        Err(_) => vec!["(could not be parsed)".to_string()],
+    }
+}
+
+// Unindented keys, plus the jobs of CI workflows as `jobs.<name>`.
+fn yaml_outline(contents: &str) -> Vec<String> {
+    let top_level = Regex::new(r"^([^\s#\-][^:]*):").unwrap();
+    let nested = Regex::new(r"^  ([^\s#\-][^:]*):").unwrap();
+    let mut outline = Vec::new();
+    let mut in_jobs = false;
+    for line in contents.lines() {
+        if let Some(caps) = top_level.captures(line) {
+            let key = caps[1].trim().trim_matches('"').to_string();
+            in_jobs = key == "jobs";
+            outline.push(key);
+        } else if in_jobs {
+            if let Some(caps) = nested.captures(line) {
+                outline.push(format!("jobs.{}", caps[1].trim()));
+            }
+        }
+    }
+    outline
+}
+
+fn env_outline(contents: &str) -> Vec<String> {
+    let variable = Regex::new(r"^\s*(?:export\s+)?([A-Za-z_][A-Za-z0-9_]*)\s*=").unwrap();
+    contents
+        .lines()
+        .filter_map(|line| variable.captures(line).map(|caps| caps[1].to_string()))
+        .collect()
+}
@@ -0,0 +1,352 @@
+use crate::collector::FileCollector;
+use crate::utils::decode_text;
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Tables listed in the "Data Model" section; the rest are counted.
+const MAX_TABLES: usize = 50;
+/// Fields listed per table; the rest are counted.
+const MAX_FIELDS: usize = 30;
+
+/// A table (or model) and what points out of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Table {
+    pub name: String,
+    /// `name type` for each column or field, in definition order.
+    pub fields: Vec<String>,
+    /// `column -> table` for each foreign key or relation field.
+    pub relations: Vec<String>,
+}
+
+/// The project's data model, rebuilt from its schema files and migrations in order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DataModel {
+    /// Where the model came from: `Django migrations`, `Prisma schema`, ...
+    pub sources: Vec<String>,
+    pub tables: Vec<Table>,
+    pub omitted_tables: usize,
+}
+
+/// What kind of schema definition `path` is, if any: Django or Rails migrations, a Rails
+/// `schema.rb`, a Diesel `schema.rs`, SQL migrations (Diesel, Flyway and the like) or a
+/// Prisma schema.
+pub fn schema_kind(path: &Path) -> Option<&'static str> {
+    let file_name = path.file_name()?.to_string_lossy();
+    let in_dir = |names: &[&str]| {
+        path.parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .any(|dir| names.contains(&dir.as_os_str().to_string_lossy().as_ref()))
+    };
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    match extension {
+        Some("prisma") => Some("Prisma schema"),
+        Some("py") if in_dir(&["migrations"]) && file_name != "__init__.py" => {
+            Some("Django migrations")
+        }
+        Some("rb") if in_dir(&["db"]) && (file_name == "schema.rb" || in_dir(&["migrate"])) => {
+            Some("Rails migrations")
+        }
+        Some("rs") if file_name == "schema.rs" => Some("Diesel schema"),
+        Some("sql") if in_dir(&["migrations", "migration", "migrate"]) => Some("SQL migrations"),
+        _ => None,
+    }
+}
+
+/// Replays the schema files in `files` (in path order, which is migration order for
+/// timestamped or numbered migrations) into one data model.
+///
+/// Returns `None` when none of them defines a table.
+pub async fn summarize_data_model(files: &[String], source: &dyn FileCollector) -> Option<DataModel> {
+    let mut tables: BTreeMap<String, Table> = BTreeMap::new();
+    let mut sources = Vec::new();
+    for file in files {
+        let Some(kind) = schema_kind(Path::new(file)) else {
+            continue;
+        };
+        let contents = match source.read(file).await {
+            Ok(bytes) => match decode_text(&bytes) {
+                Some((contents, _)) => contents,
+                None => continue,
+            },
+            Err(e) => {
+                debug!("Skipping schema file {}: {}", file, e);
+                continue;
+            }
+        };
+        match kind {
+            "Prisma schema" => apply_prisma(&mut tables, &contents),
+            "Django migrations" => apply_django(&mut tables, &contents),
+            "Rails migrations" => apply_rails(&mut tables, &contents),
+            "Diesel schema" => apply_diesel(&mut tables, &contents),
+            _ => apply_sql(&mut tables, &contents),
+        }
+        if !sources.contains(&kind.to_string()) {
+            sources.push(kind.to_string());
+        }
+    }
+    if tables.is_empty() {
+        return None;
+    }
+
+    let mut tables: Vec<Table> = tables.into_values().collect();
+    let omitted_tables = tables.len().saturating_sub(MAX_TABLES);
+    tables.truncate(MAX_TABLES);
+    for table in &mut tables {
+        if table.fields.len() > MAX_FIELDS {
+            let more = table.fields.len() - MAX_FIELDS;
+            table.fields.truncate(MAX_FIELDS);
+            table.fields.push(format!("... and {} more", more));
+        }
+    }
+    Some(DataModel {
+        sources,
+        tables,
+        omitted_tables,
+    })
+}
+
+// Tables are keyed case-insensitively, since Django migrations name a model `Author` when
+// creating it and `author` when altering it.
+fn table_entry<'a>(tables: &'a mut BTreeMap<String, Table>, name: &str) -> &'a mut Table {
+    tables.entry(name.to_lowercase()).or_insert_with(|| Table {
+        name: name.to_string(),
+        fields: Vec::new(),
+        relations: Vec::new(),
+    })
+}
+
+fn apply_sql(tables: &mut BTreeMap<String, Table>, contents: &str) {
+    let create =
+        Regex::new(r#"(?i)create\s+table\s+(?:if\s+not\s+exists\s+)?[`"\[]?([\w.]+)[`"\]]?\s*\("#).unwrap();
+    let add_column = Regex::new(
+        r#"(?i)alter\s+table\s+(?:only\s+)?[`"\[]?([\w.]+)[`"\]]?\s+add\s+(?:column\s+)?(?:if\s+not\s+exists\s+)?[`"\[]?(\w+)[`"\]]?\s+([\w()]+)"#,
+    )
+    .unwrap();
+    let drop = Regex::new(r#"(?i)drop\s+table\s+(?:if\s+exists\s+)?[`"\[]?([\w.]+)"#).unwrap();
+    let column = Regex::new(r#"^[`"\[]?(\w+)[`"\]]?\s+(\w+(?:\s*\([^)]*\))?)"#).unwrap();
+    let references = Regex::new(r#"(?i)references\s+[`"\[]?([\w.]+)"#).unwrap();
+    let foreign_key =
+        Regex::new(r#"(?i)foreign\s+key\s*\(\s*[`"\[]?(\w+)[`"\]]?\s*\)\s*references\s+[`"\[]?([\w.]+)"#)
+            .unwrap();
+
+    for caps in create.captures_iter(contents) {
+        let body_start = caps.get(0).unwrap().end();
+        let body = parenthesized(&contents[body_start..]);
+        let table = table_entry(tables, &caps[1]);
+        for definition in split_top_level(body) {
+            let definition = definition.trim();
+            let first = definition.split_whitespace().next().unwrap_or("").to_lowercase();
+            if let Some(fk) = foreign_key.captures(definition) {
+                table.relations.push(format!("{} -> {}", &fk[1], &fk[2]));
+            } else if matches!(
+                first.as_str(),
+                "constraint" | "primary" | "unique" | "key" | "index" | "check" | "foreign" | ""
+            ) {
+                continue;
+            } else {
+                let Some(caps) = column.captures(definition) else {
+                    continue;
+                };
+                let name = &caps[1];
+                table.fields.push(format!("{} {}", name, &caps[2]));
+                if let Some(target) = references.captures(definition) {
+                    table.relations.push(format!("{} -> {}", name, &target[1]));
+                }
+            }
+        }
+    }
+    for caps in add_column.captures_iter(contents) {
+        table_entry(tables, &caps[1]).fields.push(format!("{} {}", &caps[2], &caps[3]));
+    }
+    for caps in drop.captures_iter(contents) {
+        tables.remove(&caps[1].to_lowercase());
+    }
+}
+
+fn apply_django(tables: &mut BTreeMap<String, Table>, contents: &str) {
+    let name = Regex::new(r#"\bname\s*=\s*['"](\w+)['"]"#).unwrap();
+    let model_name = Regex::new(r#"model_name\s*=\s*['"](\w+)['"]"#).unwrap();
+    let field = Regex::new(r#"\(\s*['"](\w+)['"]\s*,\s*models\.(\w+)\(([^\n]*)"#).unwrap();
+    let added = Regex::new(r#"field\s*=\s*models\.(\w+)\(([^\n]*)"#).unwrap();
+
+    for operation in contents.split("migrations.").skip(1) {
+        if operation.starts_with("CreateModel(") {
+            let Some(model) = name.captures(operation) else {
+                continue;
+            };
+            let table = table_entry(tables, &model[1]);
+            for caps in field.captures_iter(operation) {
+                table.fields.push(format!("{} {}", &caps[1], &caps[2]));
+                if let Some(target) = django_relation(&caps[2], &caps[3]) {
+                    table.relations.push(format!("{} -> {}", &caps[1], target));
+                }
+            }
+        } else if operation.starts_with("AddField(") {
+            let (Some(model), Some(field_name), Some(caps)) = (
+                model_name.captures(operation),
+                name.captures(operation),
+                added.captures(operation),
+            ) else {
+                continue;
+            };
+            let table = table_entry(tables, &model[1]);
+            table.fields.push(format!("{} {}", &field_name[1], &caps[1]));
+            if let Some(target) = django_relation(&caps[1], &caps[2]) {
+                table.relations.push(format!("{} -> {}", &field_name[1], target));
+            }
+        } else if operation.starts_with("DeleteModel(") {
+            if let Some(model) = name.captures(operation) {
+                tables.remove(&model[1].to_lowercase());
+            }
+        }
+    }
+}
+
+// The model a ForeignKey, OneToOneField or ManyToManyField points at, without its app.
+fn django_relation(kind: &str, arguments: &str) -> Option<String> {
+    if !matches!(kind, "ForeignKey" | "OneToOneField" | "ManyToManyField") {
+        return None;
+    }
+    let target = Regex::new(r#"(?:to\s*=\s*)?['"]([\w.]+)['"]"#).unwrap();
+    let target = target.captures(arguments)?;
+    Some(target[1].rsplit('.').next().unwrap_or(&target[1]).to_string())
+}
+
+fn apply_rails(tables: &mut BTreeMap<String, Table>, contents: &str) {
+    let create = Regex::new(r#"^\s*create_table\s+[:"'](\w+)"#).unwrap();
+    let column = Regex::new(r#"^\s*t\.(\w+)\s+[:"'](\w+)"#).unwrap();
+    let add_column = Regex::new(r#"^\s*add_column\s+[:"'](\w+)["']?\s*,\s*[:"'](\w+)["']?\s*,\s*:(\w+)"#).unwrap();
+    let add_reference = Regex::new(r#"^\s*add_(?:reference|belongs_to)\s+[:"'](\w+)["']?\s*,\s*[:"'](\w+)"#).unwrap();
+    let foreign_key = Regex::new(r#"^\s*add_foreign_key\s+[:"'](\w+)["']?\s*,\s*[:"'](\w+)"#).unwrap();
+    let drop = Regex::new(r#"^\s*drop_table\s+[:"'](\w+)"#).unwrap();
+
+    let mut current: Option<String> = None;
+    for line in contents.lines() {
+        if let Some(caps) = create.captures(line) {
+            table_entry(tables, &caps[1]);
+            current = Some(caps[1].to_string());
+        } else if line.trim() == "end" {
+            current = None;
+        } else if let (Some(name), Some(caps)) = (&current, column.captures(line)) {
+            let table = table_entry(tables, name);
+            if matches!(&caps[1], "references" | "belongs_to") {
+                table.fields.push(format!("{}_id references", &caps[2]));
+                table.relations.push(format!("{}_id -> {}s", &caps[2], &caps[2]));
+            } else if &caps[1] != "index" {
+                table.fields.push(format!("{} {}", &caps[2], &caps[1]));
+            }
+        } else if let Some(caps) = add_column.captures(line) {
+            table_entry(tables, &caps[1]).fields.push(format!("{} {}", &caps[2], &caps[3]));
+        } else if let Some(caps) = add_reference.captures(line) {
+            let table = table_entry(tables, &caps[1]);
+            table.fields.push(format!("{}_id references", &caps[2]));
+            table.relations.push(format!("{}_id -> {}s", &caps[2], &caps[2]));
+        } else if let Some(caps) = foreign_key.captures(line) {
+            let column = caps[2].trim_end_matches('s');
+            let relation = format!("{}_id -> {}", column, &caps[2]);
+            let table = table_entry(tables, &caps[1]);
+            if !table.relations.contains(&relation) {
+                table.relations.push(relation);
+            }
+        } else if let Some(caps) = drop.captures(line) {
+            tables.remove(&caps[1].to_lowercase());
+        }
+    }
+}
+
+fn apply_diesel(tables: &mut BTreeMap<String, Table>, contents: &str) {
+    // Plenty of crates have a `schema.rs` that has nothing to do with Diesel.
+    if !contents.contains("table!") {
+        return;
+    }
+    let header = Regex::new(r"^\s*(?:[\w:]+\.)?(\w+)\s*\([^)]*\)\s*\{").unwrap();
+    let column = Regex::new(r"^\s*(\w+)\s*->\s*([^,]+),").unwrap();
+    let joinable = Regex::new(r"joinable!\s*\(\s*(\w+)\s*->\s*(\w+)\s*\(\s*(\w+)\s*\)\s*\)").unwrap();
+
+    let mut current: Option<String> = None;
+    for line in contents.lines() {
+        if let Some(caps) = header.captures(line) {
+            table_entry(tables, &caps[1]);
+            current = Some(caps[1].to_string());
+        } else if let (Some(name), Some(caps)) = (&current, column.captures(line)) {
+            table_entry(tables, name).fields.push(format!("{} {}", &caps[1], caps[2].trim()));
+        } else if line.trim_start().starts_with('}') {
+            current = None;
+        }
+    }
+    for caps in joinable.captures_iter(contents) {
+        table_entry(tables, &caps[1]).relations.push(format!("{} -> {}", &caps[3], &caps[2]));
+    }
+}
+
+fn apply_prisma(tables: &mut BTreeMap<String, Table>, contents: &str) {
+    let model = Regex::new(r"^\s*model\s+(\w+)\s*\{").unwrap();
+    let field = Regex::new(r"^\s*(\w+)\s+(\w+)(\[\])?(\?)?(.*)$").unwrap();
+    let models: Vec<String> = contents
+        .lines()
+        .filter_map(|line| model.captures(line).map(|caps| caps[1].to_string()))
+        .collect();
+
+    let mut current: Option<String> = None;
+    for line in contents.lines() {
+        if let Some(caps) = model.captures(line) {
+            table_entry(tables, &caps[1]);
+            current = Some(caps[1].to_string());
+        } else if line.trim_start().starts_with('}') {
+            current = None;
+        } else if let (Some(name), Some(caps)) = (&current, field.captures(line)) {
+            if caps[1].starts_with("//") {
+                continue;
+            }
+            let suffix = |group| caps.get(group).map_or("", |found| found.as_str());
+            let kind = format!("{}{}{}", &caps[2], suffix(3), suffix(4));
+            let table = table_entry(tables, name);
+            table.fields.push(format!("{} {}", &caps[1], kind));
+            if models.iter().any(|model| model == &caps[2]) {
+                table.relations.push(format!("{} -> {}", &caps[1], &caps[2]));
+            }
+        }
+    }
+}
+
+// The text up to the parenthesis closing the one just before `text`.
+fn parenthesized(text: &str) -> &str {
+    let mut depth = 1;
+    for (index, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &text[..index];
+                }
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+// Splits at commas outside parentheses, so `numeric(10, 2)` stays one definition.
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&text[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
@@ -0,0 +1,147 @@
+use super::{
+    find_suppressions,
+    static_analysis::{line_of, FunctionAnalysis},
+    summarize_types, CodeAnalysis, JavaScriptAnalyzer, LanguageAnalyzer, ParserKind,
+    TypeAnalysis, MIN_TYPE_MEMBERS,
+};
+use crate::error::FolderSummaryError;
+use crate::llm::LLM;
+use async_trait::async_trait;
+use regex::Regex;
+
+/// TypeScript declaration files (`.d.ts`), which describe an API without implementing it.
+/// Declared functions are listed by signature and never sent to the LLM; interfaces and
+/// classes with enough members are summarized as the API surface.
+pub struct DeclarationAnalyzer;
+
+impl DeclarationAnalyzer {
+    fn extract_functions(content: &str) -> Vec<FunctionAnalysis> {
+        let function_regex = Regex::new(
+            r"(?m)^[ \t]*(?:export\s+)?(?:default\s+)?(?:declare\s+)?function\s+(\w+)\s*(?:<[^>(]*>)?\s*\(([^)]*)\)\s*(?::\s*([^;{]+))?;",
+        )
+        .unwrap();
+        function_regex
+            .captures_iter(content)
+            .map(|caps| {
+                let params = caps.get(2).map_or("", |m| m.as_str());
+                let return_type = caps.get(3).map_or("", |m| m.as_str().trim());
+                // Parameters may be split over several lines.
+                let signature = caps[0]
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .replace("( ", "(")
+                    .replace(", )", ")");
+                FunctionAnalysis {
+                    name: caps[1].to_string(),
+                    line: Some(line_of(content, caps.get(1).unwrap().start())),
+                    signature: signature.trim_end_matches(';').to_string(),
+                    types: return_type.to_string(),
+                    body: None,
+                    lines_of_code: caps[0].trim().lines().count(),
+                    cyclomatic_complexity: 1,
+                    parameters: params.split(',').filter(|p| !p.trim().is_empty()).count(),
+                    returns: !return_type.is_empty() && return_type != "void",
+                    summary: None,
+                    is_async: return_type.starts_with("Promise<"),
+                    blocking_calls: Vec::new(),
+                    calls: Vec::new(),
+                    decorators: Vec::new(),
+                    skip_reason: None,
+                }
+            })
+            .collect()
+    }
+
+    // Interfaces with enough members and classes with enough methods, each member on a
+    // line of its own.
+    fn extract_significant_types(content: &str) -> Vec<TypeAnalysis> {
+        let type_regex = Regex::new(
+            r"(?m)^[ \t]*(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(interface|class)\s+(\w+)[^{\n]*\{",
+        )
+        .unwrap();
+        let method_regex = Regex::new(
+            r"^(?:(?:static|async|get|set|public|private|protected|abstract)\s+)*#?\w+\??\s*[<(]",
+        )
+        .unwrap();
+        let mut types = Vec::new();
+        for caps in type_regex.captures_iter(content) {
+            let header = caps.get(0).unwrap();
+            let body = JavaScriptAnalyzer::extract_function_body(content, header.end() - 1);
+            let declaration = header.as_str().trim().trim_end_matches('{').trim_end();
+            let mut outline = vec![declaration.to_string()];
+            let mut depth = 0;
+            for line in body.lines() {
+                let member = line.trim();
+                let comment = member.starts_with("//") || member.starts_with('*')
+                    || member.starts_with("/*");
+                let counted = &caps[1] == "interface" || method_regex.is_match(member);
+                if depth == 1 && !member.is_empty() && !comment && member != "}" && counted {
+                    outline.push(format!("  {}", member.trim_end_matches([';', ','])));
+                }
+                depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+            }
+            let members = outline.len() - 1;
+            if members >= MIN_TYPE_MEMBERS {
+                types.push(TypeAnalysis {
+                    name: caps[2].to_string(),
+                    kind: caps[1].to_string(),
+                    line: Some(line_of(content, caps.get(2).unwrap().start())),
+                    members,
+                    definition: outline.join("\n"),
+                    summary: None,
+                });
+            }
+        }
+        types
+    }
+}
+
+#[async_trait]
+impl LanguageAnalyzer for DeclarationAnalyzer {
+    fn name(&self) -> &'static str {
+        "TypeScript declarations"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".d.ts"]
+    }
+
+    fn parser_kind(&self) -> ParserKind {
+        ParserKind::Regex
+    }
+
+    fn fence_language(&self) -> &'static str {
+        "typescript"
+    }
+
+    // A declaration has no body; its signature already says what there is to say.
+    fn needs_summary(&self, _func: &FunctionAnalysis) -> bool {
+        false
+    }
+
+    fn analyze(&self, content: &str) -> Result<CodeAnalysis, FolderSummaryError> {
+        Ok(CodeAnalysis {
+            imports: JavaScriptAnalyzer::extract_imports(content),
+            functions: Self::extract_functions(content),
+            types: JavaScriptAnalyzer::extract_types(content),
+            significant_types: Self::extract_significant_types(content),
+            exports: JavaScriptAnalyzer::extract_exports(content),
+            generated: None,
+            suppressions: find_suppressions(content),
+            warnings: Vec::new(),
+            commands: Vec::new(),
+            fence_language: None,
+        })
+    }
+
+    async fn summarize(
+        &self,
+        analysis: &CodeAnalysis,
+        llm: &dyn LLM,
+    ) -> Result<CodeAnalysis, FolderSummaryError> {
+        let mut summarized = analysis.clone();
+        summarize_types(&mut summarized, "TypeScript declaration", llm).await?;
+        Ok(summarized)
+    }
+}
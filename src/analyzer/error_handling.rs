@@ -0,0 +1,235 @@
+use crate::collector::FileCollector;
+use crate::utils::decode_text;
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Files listed individually, riskiest first.
+const MAX_FILES: usize = 15;
+
+/// How one file deals with errors. Which constructs are counted depends on the language.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileErrorHandling {
+    pub file: String,
+    /// `Rust`, `Python` or `JavaScript`.
+    pub language: String,
+    /// Errors passed on to the caller: `?` in Rust, `raise` in Python, `throw` in JavaScript.
+    pub propagated: usize,
+    /// Errors dealt with in place: `Err(` patterns and `map_err`/`unwrap_or*` in Rust,
+    /// `except` clauses in Python, `catch` blocks and `.catch(` calls in JavaScript.
+    pub handled: usize,
+    /// Calls that abort on error: `unwrap`, `expect` and `panic!`-like macros in Rust,
+    /// `sys.exit`/`os._exit` in Python, `process.exit` in JavaScript.
+    pub panics: usize,
+    /// Python `except:`, `except Exception` and `except BaseException` clauses.
+    pub broad_handlers: usize,
+    /// Handlers that drop the error: `.ok();` in Rust, `except ...: pass` in Python, empty
+    /// `catch` blocks in JavaScript.
+    pub swallowed: usize,
+    /// JavaScript `.then(` chains with no `.catch(` that are neither awaited nor returned.
+    pub unhandled_promises: usize,
+}
+
+impl FileErrorHandling {
+    /// Constructs that lose or abort on errors, used to rank files.
+    pub fn risky(&self) -> usize {
+        self.panics + self.broad_handlers + self.swallowed + self.unhandled_promises
+    }
+
+    fn add(&mut self, other: &FileErrorHandling) {
+        self.propagated += other.propagated;
+        self.handled += other.handled;
+        self.panics += other.panics;
+        self.broad_handlers += other.broad_handlers;
+        self.swallowed += other.swallowed;
+        self.unhandled_promises += other.unhandled_promises;
+    }
+}
+
+/// The project's error-handling posture.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ErrorHandling {
+    /// One sentence per language describing how errors are mostly dealt with.
+    pub posture: Vec<String>,
+    /// Counts summed per language; `file` is the number of files.
+    pub totals: Vec<FileErrorHandling>,
+    /// Files with risky constructs, most first.
+    pub files: Vec<FileErrorHandling>,
+    /// Files with risky constructs left out of `files`.
+    pub omitted_files: usize,
+}
+
+/// Counts error-handling constructs in the Rust, Python and JavaScript/TypeScript files
+/// among `files` and sums them up per language.
+///
+/// Rust code after a `#[cfg(test)]` attribute is ignored, since tests unwrap freely.
+/// Returns `None` when none of the files is in a supported language.
+pub async fn summarize_error_handling(
+    files: &[String],
+    source: &dyn FileCollector,
+) -> Option<ErrorHandling> {
+    let patterns = ErrorPatterns::new();
+    let mut totals: BTreeMap<&str, (usize, FileErrorHandling)> = BTreeMap::new();
+    let mut risky = Vec::new();
+    for file in files {
+        let Some(language) = language(Path::new(file)) else {
+            continue;
+        };
+        let contents = match source.read(file).await {
+            Ok(bytes) => match decode_text(&bytes) {
+                Some((contents, _)) => contents,
+                None => continue,
+            },
+            Err(e) => {
+                debug!("Skipping {} while analyzing error handling: {}", file, e);
+                continue;
+            }
+        };
+        let counts = patterns.count(file, language, &contents);
+        let (count, total) = totals
+            .entry(language)
+            .or_insert_with(|| (0, FileErrorHandling::default()));
+        *count += 1;
+        total.add(&counts);
+        if counts.risky() > 0 {
+            risky.push(counts);
+        }
+    }
+    if totals.is_empty() {
+        return None;
+    }
+
+    let totals: Vec<FileErrorHandling> = totals
+        .into_iter()
+        .map(|(language, (count, total))| FileErrorHandling {
+            file: count.to_string(),
+            language: language.to_string(),
+            ..total
+        })
+        .collect();
+    risky.sort_by(|a, b| b.risky().cmp(&a.risky()).then_with(|| a.file.cmp(&b.file)));
+    let omitted_files = risky.len().saturating_sub(MAX_FILES);
+    risky.truncate(MAX_FILES);
+    Some(ErrorHandling {
+        posture: totals.iter().map(posture).collect(),
+        totals,
+        files: risky,
+        omitted_files,
+    })
+}
+
+fn language(path: &Path) -> Option<&'static str> {
+    match path.extension().and_then(|ext| ext.to_str())? {
+        "rs" => Some("Rust"),
+        "py" => Some("Python"),
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => Some("JavaScript"),
+        _ => None,
+    }
+}
+
+fn posture(total: &FileErrorHandling) -> String {
+    let careful = total.propagated + total.handled;
+    let verdict = if careful == 0 && total.risky() == 0 {
+        "no explicit error handling"
+    } else if total.risky() > careful {
+        "errors are more often dropped or fatal than handled"
+    } else if total.propagated >= total.handled {
+        "errors are mostly propagated to callers"
+    } else {
+        "errors are mostly handled where they occur"
+    };
+    let details = match total.language.as_str() {
+        "Rust" => format!(
+            "{} `?`, {} handled errors, {} unwrap/expect/panic calls, {} discarded with `.ok()`",
+            total.propagated, total.handled, total.panics, total.swallowed
+        ),
+        "Python" => format!(
+            "{} raises, {} except clauses ({} broad, {} silenced with `pass`), {} exits",
+            total.propagated, total.handled, total.broad_handlers, total.swallowed, total.panics
+        ),
+        _ => format!(
+            "{} throws, {} catches ({} empty), {} unhandled promise chains, {} process exits",
+            total.propagated, total.handled, total.swallowed, total.unhandled_promises, total.panics
+        ),
+    };
+    format!("{} ({} files): {}; {}.", total.language, total.file, verdict, details)
+}
+
+struct ErrorPatterns {
+    rust_propagated: Regex,
+    rust_handled: Regex,
+    rust_panics: Regex,
+    rust_swallowed: Regex,
+    python_raise: Regex,
+    python_except: Regex,
+    python_broad: Regex,
+    python_pass: Regex,
+    python_exit: Regex,
+    js_throw: Regex,
+    js_catch: Regex,
+    js_empty_catch: Regex,
+    js_exit: Regex,
+}
+
+impl ErrorPatterns {
+    fn new() -> Self {
+        ErrorPatterns {
+            rust_propagated: Regex::new(r"[\w)\]]\?").unwrap(),
+            rust_handled: Regex::new(r"\bErr\s*\(|\.map_err\s*\(|\.unwrap_or(?:_else|_default)?\s*\(").unwrap(),
+            rust_panics: Regex::new(
+                r"\.unwrap\(\)|\.expect\s*\(|\b(?:panic|unreachable|todo|unimplemented)!\s*\(",
+            )
+            .unwrap(),
+            rust_swallowed: Regex::new(r"\.ok\(\)\s*;").unwrap(),
+            python_raise: Regex::new(r"(?m)^\s*raise\b").unwrap(),
+            python_except: Regex::new(r"(?m)^\s*except\b").unwrap(),
+            python_broad: Regex::new(r"(?m)^\s*except\s*(?::|\(?\s*(?:Base)?Exception\b)").unwrap(),
+            python_pass: Regex::new(r"(?m)^\s*except\b[^\n]*:\s*(?:#[^\n]*)?\n\s*pass\b").unwrap(),
+            python_exit: Regex::new(r"\b(?:sys\.exit|os\._exit)\s*\(").unwrap(),
+            js_throw: Regex::new(r"\bthrow\b").unwrap(),
+            js_catch: Regex::new(r"\bcatch\s*[({]|\.catch\s*\(").unwrap(),
+            js_empty_catch: Regex::new(r"\bcatch\s*(?:\(\s*\w*\s*\))?\s*\{\s*\}").unwrap(),
+            js_exit: Regex::new(r"\bprocess\.exit\s*\(").unwrap(),
+        }
+    }
+
+    fn count(&self, file: &str, language: &str, contents: &str) -> FileErrorHandling {
+        let mut counts = FileErrorHandling {
+            file: file.to_string(),
+            language: language.to_string(),
+            ..FileErrorHandling::default()
+        };
+        match language {
+            "Rust" => {
+                let code = contents.split("#[cfg(test)]").next().unwrap_or(contents);
+                counts.propagated = self.rust_propagated.find_iter(code).count();
+                counts.handled = self.rust_handled.find_iter(code).count();
+                counts.panics = self.rust_panics.find_iter(code).count();
+                counts.swallowed = self.rust_swallowed.find_iter(code).count();
+            }
+            "Python" => {
+                counts.propagated = self.python_raise.find_iter(contents).count();
+                counts.handled = self.python_except.find_iter(contents).count();
+                counts.broad_handlers = self.python_broad.find_iter(contents).count();
+                counts.swallowed = self.python_pass.find_iter(contents).count();
+                counts.panics = self.python_exit.find_iter(contents).count();
+            }
+            _ => {
+                counts.propagated = self.js_throw.find_iter(contents).count();
+                counts.handled = self.js_catch.find_iter(contents).count();
+                counts.swallowed = self.js_empty_catch.find_iter(contents).count();
+                counts.panics = self.js_exit.find_iter(contents).count();
+                // Roughly one statement per `;`, without the block it opens or closes.
+                counts.unhandled_promises = contents
+                    .split(';')
+                    .map(|statement| statement.rsplit(['{', '}']).next().unwrap_or(statement).trim())
+                    .filter(|statement| statement.contains(".then(") && !statement.contains(".catch("))
+                    .filter(|statement| !statement.contains("await ") && !statement.starts_with("return"))
+                    .count();
+            }
+        }
+        counts
+    }
+}
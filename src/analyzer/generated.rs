@@ -0,0 +1,63 @@
+/// File name endings of generated and minified code.
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".pb.go",
+    ".pb.rs",
+    ".pb.cc",
+    ".pb.h",
+    "_pb2.py",
+    "_pb2_grpc.py",
+    "_pb2.pyi",
+    "_pb.js",
+    "_pb.d.ts",
+    "_grpc_pb.js",
+    ".g.dart",
+    ".freezed.dart",
+    ".designer.cs",
+];
+/// File name endings of minified bundles.
+const MINIFIED_SUFFIXES: &[&str] = &[".min.js", ".min.mjs", ".min.css", ".bundle.js"];
+/// Header phrases code generators leave behind, looked for in the first lines.
+const GENERATED_MARKERS: &[&str] = &[
+    "@generated",
+    "do not edit",
+    "auto-generated",
+    "autogenerated",
+    "automatically generated",
+    "this file was generated",
+    "this file is generated",
+];
+/// Lines of a file searched for a marker.
+const HEADER_LINES: usize = 10;
+/// No hand-written source has lines this long.
+const MINIFIED_LINE_LENGTH: usize = 1000;
+
+/// Why `path` looks generated or minified, or `None` if it looks hand-written.
+///
+/// Generated files are still analyzed, but not summarized by the LLM unless
+/// `summarize_generated_files` is set.
+pub fn generated_reason(path: &str, contents: &str) -> Option<String> {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path).to_lowercase();
+    if let Some(suffix) = GENERATED_SUFFIXES.iter().find(|suffix| file_name.ends_with(*suffix)) {
+        return Some(format!("`{}` file", suffix));
+    }
+    for line in contents.lines().take(HEADER_LINES) {
+        let lower = line.to_lowercase();
+        if let Some(marker) = GENERATED_MARKERS.iter().find(|marker| lower.contains(*marker)) {
+            return Some(format!("\"{}\" header", marker));
+        }
+    }
+    minified_reason(path, contents)
+}
+
+/// Why `path` looks like a minified bundle, or `None`.
+///
+/// Minified files are reported as generated without being analyzed; the function
+/// extractors find nothing worth reporting in them.
+pub fn minified_reason(path: &str, contents: &str) -> Option<String> {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path).to_lowercase();
+    if let Some(suffix) = MINIFIED_SUFFIXES.iter().find(|suffix| file_name.ends_with(*suffix)) {
+        return Some(format!("`{}` file", suffix));
+    }
+    let longest = contents.lines().map(str::len).max().unwrap_or(0);
+    (longest > MINIFIED_LINE_LENGTH).then(|| format!("minified ({}-character line)", longest))
+}
@@ -0,0 +1,182 @@
+use crate::collector::FileCollector;
+use crate::utils::decode_text;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use syn::visit::{self, Visit};
+
+/// Items listed, heaviest first.
+const MAX_ITEMS: usize = 20;
+/// Items scoring less than this are simple enough to leave out.
+const MIN_SCORE: usize = 3;
+
+/// Generic parameters and trait bounds of one Rust function or type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenericItem {
+    pub file: String,
+    pub line: usize,
+    /// `fn`, `struct`, `enum`, `trait`, `impl` or `type`.
+    pub kind: String,
+    /// The item's name; methods are prefixed with their type or trait, e.g. `Cache::get`.
+    pub name: String,
+    /// Type parameters, including `impl Trait` arguments.
+    pub type_params: usize,
+    pub lifetimes: usize,
+    pub const_params: usize,
+    /// Trait and lifetime bounds, inline and in `where` clauses.
+    pub bounds: usize,
+}
+
+impl GenericItem {
+    pub fn score(&self) -> usize {
+        self.type_params + self.lifetimes + self.const_params + self.bounds
+    }
+}
+
+/// Measures the generics of every function and type in the Rust files among `files` and
+/// returns the heaviest ones.
+///
+/// Files that fail to parse are logged and skipped.
+pub async fn generics_heavy_items(files: &[String], source: &dyn FileCollector) -> Vec<GenericItem> {
+    let mut items = Vec::new();
+    for file in files.iter().filter(|file| Path::new(file).extension().is_some_and(|ext| ext == "rs")) {
+        let contents = match source.read(file).await {
+            Ok(bytes) => match decode_text(&bytes) {
+                Some((contents, _)) => contents,
+                None => continue,
+            },
+            Err(e) => {
+                debug!("Skipping {} while measuring generics: {}", file, e);
+                continue;
+            }
+        };
+        match syn::parse_file(&contents) {
+            Ok(ast) => {
+                let mut visitor = GenericsVisitor {
+                    file,
+                    owner: None,
+                    items: &mut items,
+                };
+                visitor.visit_file(&ast);
+            }
+            Err(e) => debug!("Skipping {} while measuring generics: {}", file, e),
+        }
+    }
+    items.retain(|item| item.score() >= MIN_SCORE);
+    items.sort_by(|a, b| {
+        b.score()
+            .cmp(&a.score())
+            .then_with(|| a.file.cmp(&b.file))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    items.truncate(MAX_ITEMS);
+    items
+}
+
+struct GenericsVisitor<'a> {
+    file: &'a str,
+    /// The type or trait whose methods are being visited.
+    owner: Option<String>,
+    items: &'a mut Vec<GenericItem>,
+}
+
+impl GenericsVisitor<'_> {
+    fn push(&mut self, kind: &str, ident: &syn::Ident, generics: &syn::Generics, inputs: Option<&syn::Signature>) {
+        let mut item = GenericItem {
+            file: self.file.to_string(),
+            line: ident.span().start().line,
+            kind: kind.to_string(),
+            name: match &self.owner {
+                Some(owner) if kind == "fn" => format!("{}::{}", owner, ident),
+                _ => ident.to_string(),
+            },
+            type_params: 0,
+            lifetimes: 0,
+            const_params: 0,
+            bounds: 0,
+        };
+        measure(generics, &mut item);
+        // `impl Trait` arguments are anonymous type parameters.
+        for input in inputs.iter().flat_map(|sig| &sig.inputs) {
+            if let syn::FnArg::Typed(arg) = input {
+                if let syn::Type::ImplTrait(impl_trait) = &*arg.ty {
+                    item.type_params += 1;
+                    item.bounds += impl_trait.bounds.len();
+                }
+            }
+        }
+        self.items.push(item);
+    }
+}
+
+fn measure(generics: &syn::Generics, item: &mut GenericItem) {
+    for param in &generics.params {
+        match param {
+            syn::GenericParam::Type(param) => {
+                item.type_params += 1;
+                item.bounds += param.bounds.len();
+            }
+            syn::GenericParam::Lifetime(param) => {
+                item.lifetimes += 1;
+                item.bounds += param.bounds.len();
+            }
+            syn::GenericParam::Const(_) => item.const_params += 1,
+        }
+    }
+    for predicate in generics.where_clause.iter().flat_map(|clause| &clause.predicates) {
+        item.bounds += match predicate {
+            syn::WherePredicate::Type(predicate) => predicate.bounds.len(),
+            syn::WherePredicate::Lifetime(predicate) => predicate.bounds.len(),
+            syn::WherePredicate::Eq(_) => 1,
+        };
+    }
+}
+
+impl<'ast> Visit<'ast> for GenericsVisitor<'_> {
+    fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        self.push("fn", &node.sig.ident, &node.sig.generics, Some(&node.sig));
+    }
+
+    fn visit_item_struct(&mut self, node: &'ast syn::ItemStruct) {
+        self.push("struct", &node.ident, &node.generics, None);
+    }
+
+    fn visit_item_enum(&mut self, node: &'ast syn::ItemEnum) {
+        self.push("enum", &node.ident, &node.generics, None);
+    }
+
+    fn visit_item_type(&mut self, node: &'ast syn::ItemType) {
+        self.push("type", &node.ident, &node.generics, None);
+    }
+
+    fn visit_item_trait(&mut self, node: &'ast syn::ItemTrait) {
+        self.push("trait", &node.ident, &node.generics, None);
+        let owner = self.owner.replace(node.ident.to_string());
+        visit::visit_item_trait(self, node);
+        self.owner = owner;
+    }
+
+    fn visit_trait_item_method(&mut self, node: &'ast syn::TraitItemMethod) {
+        self.push("fn", &node.sig.ident, &node.sig.generics, Some(&node.sig));
+    }
+
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let self_ty = match &*node.self_ty {
+            syn::Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.clone()),
+            _ => None,
+        };
+        if let Some(ident) = &self_ty {
+            // Only impl headers that add generics of their own are worth listing.
+            if !node.generics.params.is_empty() {
+                self.push("impl", ident, &node.generics, None);
+            }
+        }
+        let owner = std::mem::replace(&mut self.owner, self_ty.map(|ident| ident.to_string()));
+        visit::visit_item_impl(self, node);
+        self.owner = owner;
+    }
+
+    fn visit_impl_item_method(&mut self, node: &'ast syn::ImplItemMethod) {
+        self.push("fn", &node.sig.ident, &node.sig.generics, Some(&node.sig));
+    }
+}
@@ -0,0 +1,255 @@
+use crate::collector::FileCollector;
+use crate::utils::decode_text;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::path::Path;
+
+/// One `FROM` of a Dockerfile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildStage {
+    /// The `AS` name, if the stage has one.
+    pub name: Option<String>,
+    /// The image the stage starts from, or an earlier stage's name.
+    pub base_image: String,
+}
+
+/// The build stages and exposed ports of a Dockerfile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dockerfile {
+    pub path: String,
+    pub stages: Vec<BuildStage>,
+    /// `EXPOSE`d ports, such as `8080` or `53/udp`.
+    pub exposed_ports: Vec<String>,
+}
+
+/// One service of a Compose file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeService {
+    pub name: String,
+    pub image: Option<String>,
+    /// The build context, when the service is built from source.
+    pub build: Option<String>,
+    /// Port mappings as written, such as `8080:80`.
+    pub ports: Vec<String>,
+    pub depends_on: Vec<String>,
+}
+
+impl ComposeService {
+    /// Where the service comes from, its ports and what it depends on, on one line:
+    /// `build ./api; ports 8080:80; depends on db`.
+    pub fn details(&self) -> String {
+        let mut details = Vec::new();
+        if let Some(build) = &self.build {
+            details.push(format!("build {}", build));
+        }
+        if let Some(image) = &self.image {
+            details.push(format!("image {}", image));
+        }
+        if !self.ports.is_empty() {
+            details.push(format!("ports {}", self.ports.join(", ")));
+        }
+        if !self.depends_on.is_empty() {
+            details.push(format!("depends on {}", self.depends_on.join(", ")));
+        }
+        details.join("; ")
+    }
+}
+
+/// The services a `docker-compose.yml` or `compose.yaml` defines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeFile {
+    pub path: String,
+    pub services: Vec<ComposeService>,
+}
+
+/// How the project is containerized, from its Dockerfiles and Compose files.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Infrastructure {
+    pub dockerfiles: Vec<Dockerfile>,
+    pub compose_files: Vec<ComposeFile>,
+}
+
+/// Whether `path` is a Dockerfile or a Compose file (`docker-compose*.yml`, `compose.yaml`).
+pub fn is_infrastructure_file(path: &Path) -> bool {
+    is_dockerfile(path) || is_compose_file(path)
+}
+
+/// Whether `path` is a `Dockerfile`, a variant such as `Dockerfile.dev` or
+/// `api.dockerfile`, or a `Containerfile`.
+pub fn is_dockerfile(path: &Path) -> bool {
+    let file_name = file_name(path);
+    ["dockerfile", "containerfile"].iter().any(|base| {
+        file_name == *base || file_name.starts_with(&format!("{}.", base))
+    }) || file_name.ends_with(".dockerfile")
+}
+
+fn is_compose_file(path: &Path) -> bool {
+    let file_name = file_name(path);
+    (file_name.starts_with("docker-compose") || file_name.starts_with("compose."))
+        && (file_name.ends_with(".yml") || file_name.ends_with(".yaml"))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Reads each Dockerfile and Compose file in `files`; `None` when there are none.
+///
+/// Files that can't be read or parsed are logged and skipped.
+pub async fn summarize_infrastructure(
+    files: &[String],
+    source: &dyn FileCollector,
+) -> Option<Infrastructure> {
+    let mut infrastructure = Infrastructure::default();
+    for file in files {
+        let contents = match source.read(file).await {
+            Ok(bytes) => match decode_text(&bytes) {
+                Some((contents, _)) => contents,
+                None => continue,
+            },
+            Err(e) => {
+                debug!("Skipping infrastructure file {}: {}", file, e);
+                continue;
+            }
+        };
+        if is_dockerfile(Path::new(file)) {
+            infrastructure.dockerfiles.push(parse_dockerfile(file, &contents));
+        } else {
+            match parse_compose_file(file, &contents) {
+                Some(compose) => infrastructure.compose_files.push(compose),
+                None => debug!("Could not parse Compose file {}", file),
+            }
+        }
+    }
+    (!infrastructure.dockerfiles.is_empty() || !infrastructure.compose_files.is_empty())
+        .then_some(infrastructure)
+}
+
+/// The stages and exposed ports of a Dockerfile. Instructions continued with `\` are
+/// joined first; build arguments in image names are left as written.
+pub fn parse_dockerfile(path: &str, contents: &str) -> Dockerfile {
+    let mut dockerfile = Dockerfile {
+        path: path.to_string(),
+        stages: Vec::new(),
+        exposed_ports: Vec::new(),
+    };
+    let mut instruction = String::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        match line.strip_suffix('\\') {
+            Some(continued) => {
+                instruction.push_str(continued);
+                instruction.push(' ');
+                continue;
+            }
+            None => instruction.push_str(line),
+        }
+        let words: Vec<&str> = instruction.split_whitespace().collect();
+        match words.split_first() {
+            Some((keyword, arguments)) if keyword.eq_ignore_ascii_case("FROM") => {
+                // `FROM [--platform=...] image [AS name]`
+                let arguments: Vec<&str> =
+                    arguments.iter().copied().filter(|word| !word.starts_with("--")).collect();
+                if let Some(image) = arguments.first() {
+                    let name = match arguments.get(1..) {
+                        Some([as_keyword, name, ..]) if as_keyword.eq_ignore_ascii_case("AS") => {
+                            Some(name.to_string())
+                        }
+                        _ => None,
+                    };
+                    dockerfile.stages.push(BuildStage {
+                        name,
+                        base_image: image.to_string(),
+                    });
+                }
+            }
+            Some((keyword, ports)) if keyword.eq_ignore_ascii_case("EXPOSE") => {
+                for port in ports {
+                    if !dockerfile.exposed_ports.iter().any(|exposed| exposed == port) {
+                        dockerfile.exposed_ports.push(port.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+        instruction.clear();
+    }
+    dockerfile
+}
+
+/// The services of a Compose file, or `None` if it isn't YAML with a `services` mapping.
+pub fn parse_compose_file(path: &str, contents: &str) -> Option<ComposeFile> {
+    let document: Value = serde_yaml::from_str(contents).ok()?;
+    let Some(Value::Mapping(services)) = document.get("services") else {
+        return None;
+    };
+    let services = services
+        .iter()
+        .filter_map(|(name, service)| {
+            let build = match service.get("build") {
+                Some(Value::String(context)) => Some(context.clone()),
+                Some(build) => Some(
+                    build
+                        .get("context")
+                        .and_then(Value::as_str)
+                        .unwrap_or(".")
+                        .to_string(),
+                ),
+                None => None,
+            };
+            let ports = match service.get("ports") {
+                Some(Value::Sequence(ports)) => ports.iter().filter_map(port).collect(),
+                _ => Vec::new(),
+            };
+            // A list of names, or a mapping from each name to a start condition.
+            let depends_on = match service.get("depends_on") {
+                Some(Value::Sequence(names)) => {
+                    names.iter().filter_map(|name| name.as_str().map(String::from)).collect()
+                }
+                Some(Value::Mapping(names)) => {
+                    names.keys().filter_map(|name| name.as_str().map(String::from)).collect()
+                }
+                _ => Vec::new(),
+            };
+            Some(ComposeService {
+                name: name.as_str()?.to_string(),
+                image: service.get("image").and_then(Value::as_str).map(String::from),
+                build,
+                ports,
+                depends_on,
+            })
+        })
+        .collect();
+    Some(ComposeFile {
+        path: path.to_string(),
+        services,
+    })
+}
+
+// A port as written in short syntax (`"8080:80"`, `3000`), or the long syntax's
+// `published:target`.
+fn port(port: &Value) -> Option<String> {
+    match port {
+        Value::String(port) => Some(port.clone()),
+        Value::Number(port) => Some(port.to_string()),
+        Value::Mapping(_) => {
+            let value = |key| match port.get(key) {
+                Some(Value::String(value)) => Some(value.clone()),
+                Some(Value::Number(value)) => Some(value.to_string()),
+                _ => None,
+            };
+            let target = value("target")?;
+            Some(match value("published") {
+                Some(published) => format!("{}:{}", published, target),
+                None => target,
+            })
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,268 @@
+use super::{
+    find_suppressions,
+    static_analysis::{extract_block, find_calls, line_of, FunctionAnalysis, CALL_PATTERN},
+    summarize_types, CodeAnalysis, LanguageAnalyzer, ParserKind, TypeAnalysis, MIN_TYPE_MEMBERS,
+};
+use crate::error::FolderSummaryError;
+use crate::llm::LLM;
+use async_trait::async_trait;
+use regex::Regex;
+
+/// Words that can precede `(` and `{` the way a method name does.
+const KEYWORDS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "synchronized", "try", "return", "new", "else",
+    "throw", "do",
+];
+
+pub struct JavaAnalyzer;
+
+impl JavaAnalyzer {
+    fn extract_package(content: &str) -> Option<String> {
+        let package_regex = Regex::new(r"(?m)^\s*package\s+([\w.]+)\s*;").unwrap();
+        package_regex.captures(content).map(|caps| caps[1].to_string())
+    }
+
+    fn extract_imports(content: &str) -> Vec<String> {
+        let import_regex = Regex::new(r"(?m)^\s*import\s+(static\s+)?([\w.]+(?:\.\*)?)\s*;").unwrap();
+        import_regex
+            .captures_iter(content)
+            .map(|caps| match caps.get(1) {
+                Some(_) => format!("static {}", &caps[2]),
+                None => caps[2].to_string(),
+            })
+            .collect()
+    }
+
+    // Also returns a warning for each method whose closing brace wasn't found.
+    fn extract_functions(content: &str) -> (Vec<FunctionAnalysis>, Vec<String>) {
+        let method_regex = Regex::new(
+            r"(?m)^[ \t]*((?:@\w+(?:\([^)]*\))?\s+)*)((?:(?:public|protected|private|static|final|abstract|synchronized|native|default|strictfp)\s+)*)(<.*?>\s+)?(?:([\w.]+(?:<[^{;]*?>)?(?:\[\])*)\s+)?(\w+)\s*\(([^)]*)\)\s*(?:throws\s+[\w.,\s]+?)?\s*\{",
+        )
+        .unwrap();
+        let annotation_regex = Regex::new(r"@\w+(?:\([^)]*\))?").unwrap();
+        let call_regex = Regex::new(CALL_PATTERN).unwrap();
+        let mut functions = Vec::new();
+        let mut warnings = Vec::new();
+
+        for caps in method_regex.captures_iter(content) {
+            let name = caps[5].to_string();
+            let return_type = caps.get(4).map_or("", |m| m.as_str());
+            // `record Point(int x, int y) {` declares a type, not a method.
+            if KEYWORDS.contains(&name.as_str())
+                || KEYWORDS.contains(&return_type)
+                || return_type == "record"
+            {
+                continue;
+            }
+            let line = line_of(content, caps.get(5).unwrap().start());
+            let open = caps.get(0).unwrap().end() - 1;
+            let function_body = extract_block(content, open, false).unwrap_or_else(|| {
+                warnings.push(format!(
+                    "line {}: no closing brace found for `{}`; its body runs to the end of the file",
+                    line, name
+                ));
+                content[open..].to_string()
+            });
+            let params = caps[6].split_whitespace().collect::<Vec<_>>().join(" ");
+            let signature = format!(
+                "{}{}{}{}({})",
+                caps[2].split_whitespace().map(|m| format!("{} ", m)).collect::<String>(),
+                caps.get(3).map_or(String::new(), |m| format!("{} ", m.as_str().trim())),
+                if return_type.is_empty() {
+                    String::new()
+                } else {
+                    format!("{} ", return_type)
+                },
+                name,
+                params
+            );
+            let decorators = annotation_regex
+                .find_iter(&caps[1])
+                .map(|m| m.as_str().to_string())
+                .collect();
+            let calls = find_calls(&function_body, &name, &call_regex);
+
+            functions.push(FunctionAnalysis {
+                name,
+                line: Some(line),
+                signature,
+                types: return_type.to_string(),
+                body: Some(function_body.clone()),
+                lines_of_code: function_body.lines().count(),
+                cyclomatic_complexity: Self::calculate_cyclomatic_complexity(&function_body),
+                parameters: Self::count_parameters(&params),
+                returns: !return_type.is_empty() && return_type != "void",
+                summary: None,
+                is_async: false,
+                blocking_calls: Vec::new(),
+                calls,
+                decorators,
+                skip_reason: None,
+            });
+        }
+
+        (functions, warnings)
+    }
+
+    // Parameters separated by commas outside generic arguments, so `Map<K, V> map` is one.
+    fn count_parameters(params: &str) -> usize {
+        if params.trim().is_empty() {
+            return 0;
+        }
+        let mut depth = 0;
+        let mut count = 1;
+        for c in params.chars() {
+            match c {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                ',' if depth == 0 => count += 1,
+                _ => {}
+            }
+        }
+        count
+    }
+
+    fn extract_types(content: &str) -> Vec<String> {
+        let type_regex = Regex::new(
+            r"(?m)^\s*(?:(?:public|protected|private|abstract|final|static|sealed|non-sealed|strictfp)\s+)*(?:class|interface|enum|record|@interface)\s+(\w+)",
+        )
+        .unwrap();
+        type_regex
+            .captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .collect()
+    }
+
+    // Classes, interfaces and records with enough methods, outlined as their declaration
+    // and method signatures.
+    fn extract_significant_types(content: &str) -> Vec<TypeAnalysis> {
+        let type_regex = Regex::new(
+            r"(?m)^[ \t]*(?:(?:public|protected|private|abstract|final|static|sealed|non-sealed|strictfp)\s+)*(class|interface|record)\s+(\w+)[^{;]*\{",
+        )
+        .unwrap();
+        let member_regex = Regex::new(
+            r"^\s*(?:(?:public|protected|private|static|final|abstract|synchronized|native|default|strictfp)\s+)*(?:<.*?>\s+)?(?:[\w.]+(?:<.*?>)?(?:\[\])*\s+)?(\w+)\s*\(",
+        )
+        .unwrap();
+        let mut types = Vec::new();
+        for caps in type_regex.captures_iter(content) {
+            let header = caps.get(0).unwrap();
+            let open = header.end() - 1;
+            let body = extract_block(content, open, false).unwrap_or_else(|| content[open..].to_string());
+            let declaration = header.as_str().split_whitespace().collect::<Vec<_>>().join(" ");
+            let mut outline = vec![declaration.trim_end_matches('{').trim_end().to_string()];
+            let mut depth = 0;
+            for line in body.lines() {
+                if depth == 1 {
+                    if let Some(member) = member_regex.captures(line) {
+                        if !KEYWORDS.contains(&&member[1]) {
+                            let signature = line.split(['{', ';']).next().unwrap_or_default();
+                            outline.push(format!("  {}", signature.trim()));
+                        }
+                    }
+                }
+                depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+            }
+            let members = outline.len() - 1;
+            if members >= MIN_TYPE_MEMBERS {
+                types.push(TypeAnalysis {
+                    name: caps[2].to_string(),
+                    kind: caps[1].to_string(),
+                    line: Some(line_of(content, caps.get(2).unwrap().start())),
+                    members,
+                    definition: outline.join("\n"),
+                    summary: None,
+                });
+            }
+        }
+        types
+    }
+
+    // Public top-level types, qualified with the package.
+    fn extract_exports(content: &str) -> Vec<String> {
+        let public_regex = Regex::new(
+            r"(?m)^public\s+(?:(?:abstract|final|sealed|non-sealed|static|strictfp)\s+)*(?:class|interface|enum|record|@interface)\s+(\w+)",
+        )
+        .unwrap();
+        let package = Self::extract_package(content);
+        public_regex
+            .captures_iter(content)
+            .map(|caps| match &package {
+                Some(package) => format!("{}.{}", package, &caps[1]),
+                None => caps[1].to_string(),
+            })
+            .collect()
+    }
+
+    fn calculate_cyclomatic_complexity(function_body: &str) -> usize {
+        let control_flow_regex = Regex::new(r"\b(?:if|for|while|case|catch)\b|&&|\|\|").unwrap();
+        1 + control_flow_regex.find_iter(function_body).count()
+    }
+}
+
+#[async_trait]
+impl LanguageAnalyzer for JavaAnalyzer {
+    fn name(&self) -> &'static str {
+        "Java"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".java"]
+    }
+
+    fn parser_kind(&self) -> ParserKind {
+        ParserKind::Regex
+    }
+
+    fn fence_language(&self) -> &'static str {
+        "java"
+    }
+
+    // Getters, setters and other one-statement methods say what they do in their name.
+    fn needs_summary(&self, func: &FunctionAnalysis) -> bool {
+        func.lines_of_code > 3
+    }
+
+    fn analyze(&self, content: &str) -> Result<CodeAnalysis, FolderSummaryError> {
+        let (functions, warnings) = Self::extract_functions(content);
+        Ok(CodeAnalysis {
+            imports: Self::extract_imports(content),
+            functions,
+            types: Self::extract_types(content),
+            significant_types: Self::extract_significant_types(content),
+            exports: Self::extract_exports(content),
+            generated: None,
+            suppressions: find_suppressions(content),
+            warnings,
+            commands: Vec::new(),
+            fence_language: None,
+        })
+    }
+
+    async fn summarize(
+        &self,
+        analysis: &CodeAnalysis,
+        llm: &dyn LLM,
+    ) -> Result<CodeAnalysis, FolderSummaryError> {
+        let mut summarized = analysis.clone();
+        for func in &mut summarized.functions {
+            if self.needs_summary(func) {
+                let annotations = if func.decorators.is_empty() {
+                    String::new()
+                } else {
+                    format!("Annotations: {}\n", func.decorators.join(" "))
+                };
+                let prompt = format!(
+                    "Summarize the following Java method:\n\nName: {}\n{}Signature: {}\nBody: {}",
+                    func.name,
+                    annotations,
+                    func.signature,
+                    func.body.as_deref().unwrap_or("(Method body not available)")
+                );
+                func.summary = Some(llm.summarize(&prompt).await?);
+            }
+        }
+        summarize_types(&mut summarized, "Java", llm).await?;
+        Ok(summarized)
+    }
+}
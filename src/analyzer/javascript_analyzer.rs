@@ -1,6 +1,6 @@
 use regex::Regex;
 
-use super::{static_analysis::FunctionAnalysis, CodeAnalysis, LanguageAnalyzer};
+use super::{find_suppressions, static_analysis::{find_blocking_calls, find_calls, line_of, unrecognized_declarations, FunctionAnalysis, CALL_PATTERN}, summarize_types, CodeAnalysis, LanguageAnalyzer, ParserKind, TypeAnalysis, MIN_TYPE_MEMBERS};
 use crate::error::FolderSummaryError;
 use crate::llm::LLM;
 use async_trait::async_trait;
@@ -8,7 +8,7 @@ use async_trait::async_trait;
 pub struct JavaScriptAnalyzer;
 
 impl JavaScriptAnalyzer {
-    fn extract_imports(content: &str) -> Vec<String> {
+    pub(super) fn extract_imports(content: &str) -> Vec<String> {
         let import_regex = Regex::new(r#"(?m)^(?:import\s+(?:(?:\{[^}]*\}|\*\s+as\s+\w+|\w+)(?:\s*,\s*(?:\{[^}]*\}|\*\s+as\s+\w+|\w+))*\s+from\s+)?['"](.+?)['"]|(?:const|let|var)\s+(?:\{[^}]*\}|\w+)\s*=\s*require\s*\(\s*['"](.+?)['"]\s*\))(?:;|\s*$)"#).unwrap();
         import_regex
             .captures_iter(content)
@@ -18,15 +18,20 @@ impl JavaScriptAnalyzer {
     }
 
     fn extract_functions(content: &str) -> Vec<FunctionAnalysis> {
-        let function_regex = Regex::new(r"(?m)^\s*(?:export\s+)?(?:async\s+)?function\s+(\w+)\s*\((.*?)\)(?:\s*:\s*([^{]+))?\s*\{").unwrap();
-        let arrow_function_regex = Regex::new(r"(?m)^\s*(?:export\s+)?(?:const|let|var)\s+(\w+)\s*=\s*(?:async\s+)?\((.*?)\)(?:\s*:\s*([^=]+))?\s*=>").unwrap();
+        let function_regex = Regex::new(r"(?m)^\s*(?:export\s+)?(async\s+)?function\s+(\w+)\s*\((.*?)\)(?:\s*:\s*([^{]+))?\s*\{").unwrap();
+        let arrow_function_regex = Regex::new(r"(?m)^\s*(?:export\s+)?(?:const|let|var)\s+(\w+)\s*=\s*(async\s+)?\((.*?)\)(?:\s*:\s*([^=]+))?\s*=>").unwrap();
+
+        // Node's `*Sync` APIs and `Atomics.wait` block the event loop.
+        let blocking_regex = Regex::new(r"\b(?:\w+Sync|Atomics\.wait)\(").unwrap();
+        let call_regex = Regex::new(CALL_PATTERN).unwrap();
 
         let mut functions = Vec::new();
 
         for caps in function_regex.captures_iter(content) {
-            let name = caps.get(1).map_or("", |m| m.as_str()).to_string();
-            let params = caps.get(2).map_or("", |m| m.as_str());
-            let return_type = caps.get(3).map_or("", |m| m.as_str());
+            let is_async = caps.get(1).is_some();
+            let name = caps.get(2).map_or("", |m| m.as_str()).to_string();
+            let params = caps.get(3).map_or("", |m| m.as_str());
+            let return_type = caps.get(4).map_or("", |m| m.as_str());
             let signature = format!(
                 "const {} = ({}){}=> ",
                 name,
@@ -38,11 +43,14 @@ impl JavaScriptAnalyzer {
                 }
             );
 
-            let function_body = Self::extract_function_body(content, caps.get(0).unwrap().end());
+            // Start at the opening brace so the body is read up to its matching close.
+            let function_body = Self::extract_function_body(content, caps.get(0).unwrap().end() - 1);
             let lines_of_code = function_body.lines().count();
 
+            let calls = find_calls(&function_body, &name, &call_regex);
             functions.push(FunctionAnalysis {
                 name,
+                line: caps.get(2).map(|m| line_of(content, m.start())),
                 signature,
                 types: "".to_string(),
                 body: Some(function_body.clone()),
@@ -51,13 +59,23 @@ impl JavaScriptAnalyzer {
                 parameters: params.split(',').filter(|p| !p.trim().is_empty()).count(),
                 returns: !return_type.is_empty(),
                 summary: None,
+                is_async,
+                blocking_calls: if is_async {
+                    find_blocking_calls(&function_body, &blocking_regex)
+                } else {
+                    Vec::new()
+                },
+                calls,
+                decorators: Vec::new(),
+                skip_reason: None,
             });
         }
 
         for caps in arrow_function_regex.captures_iter(content) {
             let name = caps.get(1).map_or("", |m| m.as_str()).to_string();
-            let params = caps.get(2).map_or("", |m| m.as_str());
-            let return_type = caps.get(3).map_or("", |m| m.as_str());
+            let is_async = caps.get(2).is_some();
+            let params = caps.get(3).map_or("", |m| m.as_str());
+            let return_type = caps.get(4).map_or("", |m| m.as_str());
             let signature = format!(
                 "const {} = ({}){}=> ",
                 name,
@@ -71,8 +89,10 @@ impl JavaScriptAnalyzer {
             let function_body = Self::extract_function_body(content, caps.get(0).unwrap().end());
             let lines_of_code = function_body.lines().count();
 
+            let calls = find_calls(&function_body, &name, &call_regex);
             functions.push(FunctionAnalysis {
                 name,
+                line: caps.get(1).map(|m| line_of(content, m.start())),
                 signature,
                 types: "".to_string(),
                 body: Some(function_body.clone()),
@@ -81,14 +101,26 @@ impl JavaScriptAnalyzer {
                 parameters: params.split(',').filter(|p| !p.trim().is_empty()).count(),
                 returns: !return_type.is_empty(),
                 summary: None,
+                is_async,
+                blocking_calls: if is_async {
+                    find_blocking_calls(&function_body, &blocking_regex)
+                } else {
+                    Vec::new()
+                },
+                calls,
+                decorators: Vec::new(),
+                skip_reason: None,
             });
         }
 
         functions
     }
 
-    fn extract_types(content: &str) -> Vec<String> {
-        let type_regex = Regex::new(r"(?m)^\s*(?:export\s+)?(?:type|interface)\s+(\w+)").unwrap();
+    pub(super) fn extract_types(content: &str) -> Vec<String> {
+        let type_regex = Regex::new(
+            r"(?m)^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:const\s+)?(?:type|interface|class|enum)\s+(\w+)\s*(?:[=<{]|extends\b|implements\b)",
+        )
+        .unwrap();
         type_regex
             .captures_iter(content)
             .filter_map(|cap| cap.get(1))
@@ -96,15 +128,99 @@ impl JavaScriptAnalyzer {
             .collect()
     }
 
-    fn extract_exports(content: &str) -> Vec<String> {
-        let export_regex =
-            Regex::new(r"(?m)^export\s+(?:const|let|var|function|class|type|interface)\s+(\w+)")
-                .unwrap();
-        export_regex
+    // Classes with enough methods, outlined as their declaration and method signatures.
+    fn extract_significant_types(content: &str) -> Vec<TypeAnalysis> {
+        let class_regex =
+            Regex::new(r"(?m)^[ \t]*(?:export\s+)?(?:default\s+)?class\s+(\w+)[^{\n]*\{").unwrap();
+        let method_regex =
+            Regex::new(r"^\s*(?:(?:static|async|get|set)\s+|\*)*#?\w+\s*\([^)]*\)[^{;]*\{").unwrap();
+        let mut types = Vec::new();
+        for caps in class_regex.captures_iter(content) {
+            let header = caps.get(0).unwrap();
+            let body = Self::extract_function_body(content, header.end() - 1);
+            let mut outline = vec![header.as_str().trim().trim_end_matches('{').trim_end().to_string()];
+            let mut depth = 0;
+            for line in body.lines() {
+                if depth == 1 && method_regex.is_match(line) {
+                    let signature = line.split('{').next().unwrap_or_default();
+                    outline.push(format!("  {}", signature.trim()));
+                }
+                depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+            }
+            let members = outline.len() - 1;
+            if members >= MIN_TYPE_MEMBERS {
+                types.push(TypeAnalysis {
+                    name: caps[1].to_string(),
+                    kind: "class".to_string(),
+                    line: Some(line_of(content, caps.get(1).unwrap().start())),
+                    members,
+                    definition: outline.join("\n"),
+                    summary: None,
+                });
+            }
+        }
+        types
+    }
+
+    // Exported declarations by name, `export default` as the name it declares (or
+    // `default`), and re-exports with the module they come from.
+    pub(super) fn extract_exports(content: &str) -> Vec<String> {
+        let declaration_regex = Regex::new(
+            r"(?m)^export\s+(?:declare\s+)?(?:abstract\s+)?(?:async\s+)?(?:const\s+enum|const|let|var|function\*?|class|type|interface|enum)\s+(\w+)",
+        )
+        .unwrap();
+        let default_regex = Regex::new(
+            r"(?m)^export\s+default\s+(?:(?:abstract\s+)?class\s+(\w+)|(?:async\s+)?function\*?\s*(\w+)?|(\w+)\s*;?[ \t]*$)?",
+        )
+        .unwrap();
+        let list_regex = Regex::new(
+            r#"(?m)^export\s+(?:type\s+)?\{([^}]*)\}(?:\s*from\s*['"]([^'"]+)['"])?"#,
+        )
+        .unwrap();
+        let star_regex =
+            Regex::new(r#"(?m)^export\s+\*(?:\s+as\s+(\w+))?\s+from\s*['"]([^'"]+)['"]"#).unwrap();
+
+        let mut exports: Vec<(usize, String)> = declaration_regex
             .captures_iter(content)
-            .filter_map(|cap| cap.get(1))
-            .map(|m| m.as_str().to_string())
-            .collect()
+            .map(|cap| (cap.get(0).unwrap().start(), cap[1].to_string()))
+            .collect();
+        for cap in default_regex.captures_iter(content) {
+            let export = match cap.get(1).or(cap.get(2)).or(cap.get(3)) {
+                Some(name) => format!("{} (default)", name.as_str()),
+                None => "default".to_string(),
+            };
+            exports.push((cap.get(0).unwrap().start(), export));
+        }
+        for cap in list_regex.captures_iter(content) {
+            let start = cap.get(0).unwrap().start();
+            for item in cap[1].split(',') {
+                // `a as b` is exported as `b`; `type` marks a type-only export.
+                let item = item.trim().trim_start_matches("type ").trim();
+                let Some(name) = item.split_whitespace().last() else {
+                    continue;
+                };
+                exports.push((
+                    start,
+                    match cap.get(2) {
+                        Some(module) => format!("{} (from {})", name, module.as_str()),
+                        None => name.to_string(),
+                    },
+                ));
+            }
+        }
+        for cap in star_regex.captures_iter(content) {
+            let start = cap.get(0).unwrap().start();
+            exports.push((
+                start,
+                match cap.get(1) {
+                    Some(namespace) => format!("{} (from {})", namespace.as_str(), &cap[2]),
+                    None => format!("everything (from {})", &cap[2]),
+                },
+            ));
+        }
+        // In the order they appear in the file.
+        exports.sort_by_key(|(start, _)| *start);
+        exports.into_iter().map(|(_, export)| export).collect()
     }
 
     fn calculate_cyclomatic_complexity(function_body: &str) -> usize {
@@ -112,7 +228,26 @@ impl JavaScriptAnalyzer {
             Regex::new(r"\b(if|else|for|while|do|switch|case|catch|&&|\|\|)\b").unwrap();
         1 + control_flow_regex.find_iter(function_body).count()
     }
-    fn extract_function_body(content: &str, start: usize) -> String {
+    // Function declarations the patterns skip, such as generators and anonymous default
+    // exports, and bodies whose braces never balance, which run to the end of the file.
+    fn find_warnings(content: &str, functions: &[FunctionAnalysis]) -> Vec<String> {
+        let declaration_regex =
+            Regex::new(r"(?m)^[ \t]*(?:export[ \t]+)?(?:default[ \t]+)?(?:async[ \t]+)?function\b").unwrap();
+        let mut warnings = unrecognized_declarations(content, &declaration_regex, functions);
+        for func in functions {
+            let body = func.body.as_deref().unwrap_or_default();
+            if body.matches('{').count() != body.matches('}').count() {
+                warnings.push(format!(
+                    "line {}: no closing brace found for `{}`; its body runs to the end of the file",
+                    func.line.unwrap_or_default(),
+                    func.name
+                ));
+            }
+        }
+        warnings
+    }
+
+    pub(super) fn extract_function_body(content: &str, start: usize) -> String {
         let mut brace_count = 0;
         let mut body = String::new();
         let lines: Vec<&str> = content[start..].lines().collect();
@@ -133,23 +268,48 @@ impl JavaScriptAnalyzer {
 
 #[async_trait]
 impl LanguageAnalyzer for JavaScriptAnalyzer {
-    fn can_analyze(&self, file_path: &str) -> bool {
-        file_path.ends_with(".js") || file_path.ends_with(".ts")
+    fn name(&self) -> &'static str {
+        "JavaScript/TypeScript"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".js", ".ts"]
+    }
+
+    fn parser_kind(&self) -> ParserKind {
+        ParserKind::Regex
+    }
+
+    // TypeScript highlighting covers plain JavaScript too.
+    fn fence_language(&self) -> &'static str {
+        "typescript"
+    }
+
+    fn needs_summary(&self, func: &FunctionAnalysis) -> bool {
+        func.lines_of_code > 6
     }
 
     fn analyze(&self, content: &str) -> Result<CodeAnalysis, FolderSummaryError> {
+        let functions = Self::extract_functions(content);
+        let warnings = Self::find_warnings(content, &functions);
         Ok(CodeAnalysis {
             imports: Self::extract_imports(content),
-            functions: Self::extract_functions(content),
+            functions,
             types: Self::extract_types(content),
+            significant_types: Self::extract_significant_types(content),
             exports: Self::extract_exports(content),
+            generated: None,
+            suppressions: find_suppressions(content),
+            warnings,
+            commands: Vec::new(),
+            fence_language: None,
         })
     }
 
-    async fn summarize(&self, analysis: &CodeAnalysis, llm: &Box<dyn LLM>) -> Result<CodeAnalysis, FolderSummaryError> {
+    async fn summarize(&self, analysis: &CodeAnalysis, llm: &dyn LLM) -> Result<CodeAnalysis, FolderSummaryError> {
         let mut summarized = analysis.clone();
         for func in &mut summarized.functions {
-            if func.lines_of_code > 6 {
+            if self.needs_summary(func) {
                 let prompt = format!(
                     "Summarize the following JavaScript/TypeScript function:\n\nName: {}\nSignature: {}\nBody: {}",
                     func.name,
@@ -159,6 +319,7 @@ impl LanguageAnalyzer for JavaScriptAnalyzer {
                 func.summary = Some(llm.summarize(&prompt).await?);
             }
         }
+        summarize_types(&mut summarized, "JavaScript/TypeScript", llm).await?;
         Ok(summarized)
     }
 }
\ No newline at end of file
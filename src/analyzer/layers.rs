@@ -0,0 +1,204 @@
+use super::CodeAnalysis;
+use crate::error::FolderSummaryError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// An import that goes against the declared layering: a lower layer depending on a
+/// higher one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerViolation {
+    pub file: String,
+    /// The layer `file` belongs to.
+    pub layer: String,
+    /// The higher layer it imports from.
+    pub target: String,
+    /// The import as the analyzer recorded it.
+    pub import: String,
+}
+
+/// Parses layering chains like `ui -> core -> db`, where each layer may depend on the
+/// ones after it but not on the ones before it.
+pub fn parse_layers(chains: &[String]) -> Result<Vec<Vec<String>>, FolderSummaryError> {
+    chains
+        .iter()
+        .map(|chain| {
+            let layers: Vec<String> = chain.split("->").map(|layer| layer.trim().to_string()).collect();
+            if layers.len() < 2 || layers.iter().any(|layer| layer.is_empty()) {
+                return Err(FolderSummaryError::ConfigError(format!(
+                    "Invalid layering \"{}\": expected layers separated by `->`, e.g. \"ui -> core -> db\"",
+                    chain
+                )));
+            }
+            Ok(layers)
+        })
+        .collect()
+}
+
+/// Checks the imports of every analyzed file against `chains`.
+///
+/// A file belongs to the first layer named by a directory (or its own file stem) on its
+/// path below `root`; an import targets the first layer named by its module path. Only
+/// in-project imports are resolved: Rust `crate::`/`super::`/`self::` paths, relative
+/// JavaScript/TypeScript paths, and Python modules. Layers that share no chain may
+/// depend on each other freely.
+pub fn check_layers(
+    chains: &[Vec<String>],
+    files: &BTreeMap<String, CodeAnalysis>,
+    root: &Path,
+) -> Vec<LayerViolation> {
+    let mut violations = Vec::new();
+    for (file, analysis) in files {
+        let path = Path::new(file);
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let Some(layer) = layer_of(chains, &components(&relative.with_extension(""))) else {
+            continue;
+        };
+        let directory = relative.parent().unwrap_or(Path::new(""));
+        for import in &analysis.imports {
+            for target in import_targets(path, directory, import) {
+                let Some(target) = layer_of(chains, &target) else {
+                    continue;
+                };
+                if target != layer && depends_on(chains, &target, &layer) {
+                    violations.push(LayerViolation {
+                        file: file.clone(),
+                        layer: layer.clone(),
+                        target,
+                        import: import.clone(),
+                    });
+                    break;
+                }
+            }
+        }
+    }
+    violations
+}
+
+// Whether some chain puts `upper` above `lower`.
+fn depends_on(chains: &[Vec<String>], upper: &str, lower: &str) -> bool {
+    chains.iter().any(|chain| {
+        let upper = chain.iter().position(|layer| layer == upper);
+        let lower = chain.iter().position(|layer| layer == lower);
+        matches!((upper, lower), (Some(upper), Some(lower)) if upper < lower)
+    })
+}
+
+fn layer_of(chains: &[Vec<String>], components: &[String]) -> Option<String> {
+    components
+        .iter()
+        .find(|component| chains.iter().flatten().any(|layer| layer == *component))
+        .cloned()
+}
+
+fn components(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+// The module paths an import refers to, as path components below the analyzed directory.
+// One import can name several modules (`use crate::{a, b}`).
+pub(crate) fn import_targets(file: &Path, directory: &Path, import: &str) -> Vec<Vec<String>> {
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some("rs") => {
+            let tree = import.replace(' ', "");
+            let tree = tree.split_once("use").map_or(tree.as_str(), |(_, tree)| tree).trim_end_matches(';');
+            // The module `file` defines: `foo.rs` is module `foo`, `mod.rs` its directory.
+            let mut module = components(directory);
+            match file.file_stem().and_then(|stem| stem.to_str()) {
+                Some("mod") | Some("lib") | Some("main") | None => {}
+                Some(stem) => module.push(stem.to_string()),
+            }
+            expand_use(tree)
+                .into_iter()
+                .filter_map(|path| {
+                    let segments: Vec<&str> = path.split("::").collect();
+                    let mut resolved = match segments[0] {
+                        "crate" => Vec::new(),
+                        "self" => module.clone(),
+                        "super" => module[..module.len().saturating_sub(1)].to_vec(),
+                        _ => return None,
+                    };
+                    for segment in &segments[1..] {
+                        match *segment {
+                            "super" => {
+                                resolved.pop();
+                            }
+                            "self" => {}
+                            segment => resolved.push(segment.to_string()),
+                        }
+                    }
+                    Some(resolved)
+                })
+                .collect()
+        }
+        Some("py") => {
+            // `name from module` or `module[ as alias]`.
+            let module = match import.split_once(" from ") {
+                Some((_, module)) => module.trim(),
+                None => import.split([',', ' ']).next().unwrap_or("").trim(),
+            };
+            let dots = module.chars().take_while(|c| *c == '.').count();
+            let mut resolved = if dots > 0 {
+                let mut base = components(directory);
+                base.truncate(base.len().saturating_sub(dots - 1));
+                base
+            } else {
+                Vec::new()
+            };
+            resolved.extend(module[dots..].split('.').filter(|s| !s.is_empty()).map(str::to_string));
+            vec![resolved]
+        }
+        _ if import.starts_with('.') => vec![normalize(&directory.join(import))],
+        _ => match import.strip_prefix("@/").or_else(|| import.strip_prefix("~/")) {
+            Some(path) => vec![normalize(Path::new(path))],
+            None => Vec::new(),
+        },
+    }
+}
+
+// `a::{b, c::{d, e}}` as `a::b`, `a::c::d` and `a::c::e`.
+fn expand_use(tree: &str) -> Vec<String> {
+    let Some(open) = tree.find('{') else {
+        return vec![tree.to_string()];
+    };
+    let prefix = &tree[..open];
+    let inner = tree[open + 1..].strip_suffix('}').unwrap_or(&tree[open + 1..]);
+    let mut members = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (index, c) in inner.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                members.push(&inner[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    members.push(&inner[start..]);
+    members
+        .into_iter()
+        .filter(|member| !member.is_empty())
+        .flat_map(|member| expand_use(&format!("{}{}", prefix, member)))
+        .collect()
+}
+
+fn normalize(path: &Path) -> Vec<String> {
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(name) => resolved.push(name),
+            _ => {}
+        }
+    }
+    components(&resolved)
+}
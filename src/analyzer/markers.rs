@@ -0,0 +1,97 @@
+use crate::collector::FileCollector;
+use crate::utils::decode_text;
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Directories shown in the heatmap, most markers first.
+const MAX_DIRECTORIES: usize = 20;
+
+/// How many debt markers the code files directly inside one directory contain.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DirectoryMarkers {
+    /// Relative to the analyzed directory; `.` for the directory itself.
+    pub directory: String,
+    /// `TODO`, `FIXME`, `HACK` and `XXX` comments.
+    pub todos: usize,
+    /// Feature flag checks: `cfg(feature = ...)`, `is_enabled(...)`, `FEATURE_*` constants
+    /// and the like.
+    pub feature_flags: usize,
+    /// `#[deprecated]`, `@deprecated`, `@Deprecated` and `DeprecationWarning`.
+    pub deprecations: usize,
+}
+
+impl DirectoryMarkers {
+    pub fn total(&self) -> usize {
+        self.todos + self.feature_flags + self.deprecations
+    }
+}
+
+/// Counts TODOs, feature flags and deprecation markers in `files`, per directory.
+///
+/// Directories without markers are left out; the rest are sorted by total, and only the
+/// densest ones are kept.
+pub async fn marker_heatmap(
+    files: &[String],
+    source: &dyn FileCollector,
+    root: &Path,
+) -> Vec<DirectoryMarkers> {
+    let patterns = MarkerPatterns::new();
+    let mut directories: BTreeMap<String, DirectoryMarkers> = BTreeMap::new();
+    for file in files {
+        let contents = match source.read(file).await {
+            Ok(bytes) => match decode_text(&bytes) {
+                Some((contents, _)) => contents,
+                None => continue,
+            },
+            Err(e) => {
+                debug!("Skipping {} while counting markers: {}", file, e);
+                continue;
+            }
+        };
+        let path = Path::new(file);
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let directory = match relative.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.display().to_string(),
+            _ => ".".to_string(),
+        };
+        let counts = directories
+            .entry(directory.clone())
+            .or_insert_with(|| DirectoryMarkers {
+                directory,
+                ..DirectoryMarkers::default()
+            });
+        counts.todos += patterns.todo.find_iter(&contents).count();
+        counts.feature_flags += patterns.feature_flag.find_iter(&contents).count();
+        counts.deprecations += patterns.deprecation.find_iter(&contents).count();
+    }
+
+    let mut heatmap: Vec<DirectoryMarkers> = directories
+        .into_values()
+        .filter(|counts| counts.total() > 0)
+        .collect();
+    heatmap.sort_by(|a, b| b.total().cmp(&a.total()).then_with(|| a.directory.cmp(&b.directory)));
+    heatmap.truncate(MAX_DIRECTORIES);
+    heatmap
+}
+
+struct MarkerPatterns {
+    todo: Regex,
+    feature_flag: Regex,
+    deprecation: Regex,
+}
+
+impl MarkerPatterns {
+    fn new() -> Self {
+        MarkerPatterns {
+            todo: Regex::new(r"\b(?:TODO|FIXME|HACK|XXX)\b").unwrap(),
+            feature_flag: Regex::new(
+                r#"cfg!?\(\s*(?:not\s*\(\s*)?feature\s*=|(?i:feature[_-]?flag)|\bis_?(?:[Ff]eature_?)?[Ee]nabled\s*\(|\bFEATURE_[A-Z0-9_]+|\.variation\s*\("#,
+            )
+            .unwrap(),
+            deprecation: Regex::new(r"#\[deprecated\b|@[Dd]eprecated\b|\bDeprecationWarning\b").unwrap(),
+        }
+    }
+}
@@ -1,25 +1,76 @@
+mod changelog;
+mod ci;
+mod config_files;
+mod data_model;
+mod declaration_analyzer;
+mod error_handling;
+mod generated;
+mod generics;
+mod infrastructure;
+mod java_analyzer;
 mod javascript_analyzer;
+mod layers;
+mod markers;
+mod openapi;
+mod php_analyzer;
+mod pipeline;
+mod priority;
+mod public_api;
 mod python_analyzer;
+mod routes;
 mod rust_analyzer;
+mod shell_analyzer;
 mod static_analysis;
+mod suppressions;
+mod swift_analyzer;
+mod types;
 
+pub use changelog::{is_changelog, parse_changelog, recent_changes, Changelog, ChangelogEntry};
+pub use ci::{is_ci_file, parse_ci_file, summarize_ci_files, CiJob, CiPipeline};
+pub use config_files::{outline_config_file, summarize_config_files, ConfigFileSummary};
+pub use data_model::{schema_kind, summarize_data_model, DataModel, Table};
+pub use declaration_analyzer::DeclarationAnalyzer;
+pub use error_handling::{summarize_error_handling, ErrorHandling, FileErrorHandling};
+pub use generated::{generated_reason, minified_reason};
+pub use generics::{generics_heavy_items, GenericItem};
+pub use infrastructure::{
+    is_dockerfile, is_infrastructure_file, parse_compose_file, parse_dockerfile,
+    summarize_infrastructure, BuildStage, ComposeFile, ComposeService, Dockerfile, Infrastructure,
+};
+pub use java_analyzer::JavaAnalyzer;
 pub use javascript_analyzer::JavaScriptAnalyzer;
+pub use layers::{check_layers, parse_layers, LayerViolation};
+pub use markers::{marker_heatmap, DirectoryMarkers};
+pub use openapi::{is_api_spec, parse_api_spec, summarize_api_specs, ApiEndpoint, ApiSpec};
+pub use pipeline::{
+    analyze_code_files, analyze_file, warm_cache, AnalysisFailure, AnalysisOptions,
+    AnalysisResults, FunctionRoute, WarmResults,
+};
+pub(crate) use pipeline::summarize_within_limit;
+pub use php_analyzer::PhpAnalyzer;
+pub use priority::{interleave_packages, order_for_summaries, sample_files, SummaryOrder};
+pub use public_api::{
+    diff_public_api, load_api_snapshot, public_api, ApiChange, ApiDiff, ApiItem, CrateApi,
+};
 pub use python_analyzer::PythonAnalyzer;
+pub use routes::{collect_routes, extract_routes, Route};
 pub use rust_analyzer::RustAnalyzer;
+pub use shell_analyzer::ShellAnalyzer;
+pub use static_analysis::FunctionAnalysis;
+pub use suppressions::{find_suppressions, Suppression};
+pub use swift_analyzer::SwiftAnalyzer;
+pub use types::{summarize_types, TypeAnalysis, MIN_TYPE_MEMBERS};
 
 use crate::cache::Cache;
+use crate::config::Config;
 use crate::error::FolderSummaryError;
 use crate::llm::LLM;
 use async_trait::async_trait;
-use futures::future::join_all;
-use indicatif::ProgressBar;
-use std::collections::HashMap;
-use std::fs;
-use std::sync::{Arc, Mutex};
-use tokio::task;
-
-use crate::analyzer::static_analysis::FunctionAnalysis;
+use std::sync::Arc;
+use tokio::sync::Mutex;
 
+/// The cache is shared across workers behind an async mutex, since it is held while
+/// the cache file is written.
 pub type ThreadSafeCache = Arc<Mutex<Cache>>;
 
 #[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
@@ -27,96 +78,151 @@ pub struct CodeAnalysis {
     pub imports: Vec<String>,
     pub functions: Vec<FunctionAnalysis>,
     pub types: Vec<String>,
+    /// Structs, enums and classes with at least [`MIN_TYPE_MEMBERS`] members, summarized
+    /// along with the functions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub significant_types: Vec<TypeAnalysis>,
     pub exports: Vec<String>,
+    /// Why the file looks generated or minified; such files skip the LLM by default.
+    #[serde(default)]
+    pub generated: Option<String>,
+    /// `folder-summary:ignore` comments; see [`Suppression`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<Suppression>,
+    /// What the analyzer came across but couldn't parse, such as a declaration its
+    /// patterns don't recognize or a body without a closing brace. The rest of the
+    /// analysis still stands; the report lists these with the analysis errors.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// External programs a script runs, such as `curl` or `kubectl`, for languages where
+    /// that is most of what the code does.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<String>,
+    /// The [fence language](LanguageAnalyzer::fence_language) of the analyzer that
+    /// produced this, which `types` are highlighted as. Missing from analyses cached by
+    /// older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fence_language: Option<String>,
+}
+
+impl CodeAnalysis {
+    /// Whether an inline comment silences a finding of `rule` at `line`.
+    pub fn suppresses(&self, rule: &str, line: Option<usize>) -> bool {
+        self.suppressions.iter().any(|s| s.covers(rule, line))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParserKind {
+    Regex,
+    Ast,
+}
+
+impl std::fmt::Display for ParserKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParserKind::Regex => write!(f, "regex"),
+            ParserKind::Ast => write!(f, "AST"),
+        }
+    }
 }
 
 #[async_trait]
 pub trait LanguageAnalyzer: Send + Sync {
-    fn can_analyze(&self, file_path: &str) -> bool;
+    fn name(&self) -> &'static str;
+    fn extensions(&self) -> &'static [&'static str];
+    fn parser_kind(&self) -> ParserKind;
+    /// The language name of a markdown code fence for this analyzer's code, such as
+    /// `rust`; empty for a fence without one.
+    fn fence_language(&self) -> &'static str {
+        ""
+    }
+    fn can_analyze(&self, file_path: &str) -> bool {
+        self.extensions().iter().any(|ext| file_path.ends_with(ext))
+    }
     fn analyze(&self, content: &str) -> Result<CodeAnalysis, FolderSummaryError>;
+    /// Whether `summarize` will send this function to the LLM.
+    fn needs_summary(&self, _func: &FunctionAnalysis) -> bool {
+        true
+    }
     async fn summarize(
         &self,
         analysis: &CodeAnalysis,
-        llm: &Box<dyn LLM>,
+        llm: &dyn LLM,
     ) -> Result<CodeAnalysis, FolderSummaryError>;
 }
 
 pub fn get_analyzers() -> Vec<Box<dyn LanguageAnalyzer>> {
     vec![
         Box::new(RustAnalyzer),
+        // Before JavaScript/TypeScript, which would otherwise claim `.d.ts` files.
+        Box::new(DeclarationAnalyzer),
         Box::new(JavaScriptAnalyzer),
         Box::new(PythonAnalyzer),
+        Box::new(JavaAnalyzer),
+        Box::new(PhpAnalyzer),
+        Box::new(SwiftAnalyzer),
+        Box::new(ShellAnalyzer),
     ]
 }
 
-pub async fn analyze_code_files(
-    files: &[String],
-    llm: &Box<dyn LLM>,
-    pb: &ProgressBar,
-    cache: &ThreadSafeCache,
-) -> Result<HashMap<String, CodeAnalysis>, FolderSummaryError> {
-    let analysis_futures: Vec<_> = files
-        .iter()
-        .map(|file| {
-            let file = file.clone();
-            let llm = llm.clone();
-            let pb = pb.clone();
-            let cache = cache.clone();
-
-            task::spawn(async move {
-                let cached_analysis = {
-                    let cache_lock = cache.lock().map_err(|_| {
-                        FolderSummaryError::CacheError("Failed to acquire cache lock".to_string())
-                    })?;
-                    cache_lock.get(&file).cloned()
-                };
-
-                let analysis = if let Some(cached) = cached_analysis {
-                    cached
-                } else {
-                    let new_analysis = analyze_file(&file, &llm).await?;
-                    let mut cache_lock = cache.lock().map_err(|_| {
-                        FolderSummaryError::CacheError("Failed to acquire cache lock".to_string())
-                    })?;
-                    cache_lock.set(file.clone(), new_analysis.clone())?;
-                    new_analysis
-                };
-
-                pb.inc(1);
-                Ok::<_, FolderSummaryError>((file, analysis))
-            })
-        })
-        .collect();
+/// Looks an analyzer up by language name (`rust`, `typescript`) or bare extension (`py`).
+pub fn find_analyzer_by_language(language: &str) -> Option<Box<dyn LanguageAnalyzer>> {
+    let language = language.to_lowercase();
+    get_analyzers().into_iter().find(|analyzer| {
+        analyzer
+            .name()
+            .to_lowercase()
+            .split('/')
+            .any(|name| name == language)
+            || analyzer
+                .extensions()
+                .iter()
+                .any(|ext| ext.trim_start_matches('.') == language)
+    })
+}
 
-    let results: Vec<Result<(String, CodeAnalysis), FolderSummaryError>> =
-        join_all(analysis_futures)
-            .await
-            .into_iter()
-            .map(|res| {
-                res.map_err(|e| FolderSummaryError::TaskJoinError(e.to_string()))
-                    .and_then(|inner| inner)
-            })
-            .collect();
+pub fn find_analyzer_for_file(file_path: &str) -> Option<Box<dyn LanguageAnalyzer>> {
+    get_analyzers()
+        .into_iter()
+        .find(|analyzer| analyzer.can_analyze(file_path))
+}
 
-    results.into_iter().collect()
+/// File extensions sent to a language of the project's choosing rather than the analyzer
+/// the extension would otherwise pick, from `[language_overrides]` and `--language`: say
+/// whether `.h` files are C or C++, or have `.mjs` files read as JavaScript.
+#[derive(Debug, Clone, Default)]
+pub struct LanguageOverrides {
+    // Longest extension first.
+    overrides: Vec<(String, String)>,
 }
 
-pub async fn analyze_file(
-    file_path: &str,
-    llm: &Box<dyn LLM>,
-) -> Result<CodeAnalysis, FolderSummaryError> {
-    let analyzers = get_analyzers();
-    for analyzer in analyzers {
-        if analyzer.can_analyze(file_path) {
-            let content =
-                fs::read_to_string(file_path).map_err(|e| FolderSummaryError::IoError(e))?;
-            let mut analysis = analyzer.analyze(&content)?;
-            analysis = analyzer.summarize(&analysis, llm).await?;
-            return Ok(analysis);
+impl LanguageOverrides {
+    /// Reads the config's overrides, rejecting languages no analyzer handles.
+    pub fn from_config(config: &Config) -> Result<Self, FolderSummaryError> {
+        let overrides = config.get_language_overrides();
+        for (extension, language) in &overrides {
+            if find_analyzer_by_language(language).is_none() {
+                return Err(FolderSummaryError::ConfigError(format!(
+                    "No analyzer for language \"{}\" given for {} files in [language_overrides]; \
+                     `folder-summary languages` lists the supported ones",
+                    language, extension
+                )));
+            }
+        }
+        Ok(LanguageOverrides { overrides })
+    }
+
+    /// The analyzer for `file_path`: the one for its extension's overriding language if
+    /// there is one, otherwise the one its extension picks.
+    pub fn analyzer_for(&self, file_path: &str) -> Option<Box<dyn LanguageAnalyzer>> {
+        match self
+            .overrides
+            .iter()
+            .find(|(extension, _)| file_path.ends_with(extension.as_str()))
+        {
+            Some((_, language)) => find_analyzer_by_language(language),
+            None => find_analyzer_for_file(file_path),
         }
     }
-    Err(FolderSummaryError::AnalysisError(format!(
-        "No suitable analyzer found for file: {}",
-        file_path
-    )))
 }
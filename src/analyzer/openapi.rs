@@ -0,0 +1,144 @@
+use crate::collector::FileCollector;
+use crate::utils::decode_text;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// Endpoints listed per spec; the rest are counted.
+const MAX_ENDPOINTS: usize = 50;
+/// Schema names listed per spec; the rest are counted.
+const MAX_SCHEMAS: usize = 30;
+const METHODS: [&str; 8] = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// One operation of a REST API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiEndpoint {
+    /// Upper-case HTTP method.
+    pub method: String,
+    pub path: String,
+    /// The operation's `summary`, falling back to its `operationId`.
+    pub summary: Option<String>,
+}
+
+/// The REST API surface described by an OpenAPI or Swagger document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiSpec {
+    pub path: String,
+    /// `OpenAPI 3.0.1`, `Swagger 2.0`, ...
+    pub spec_version: String,
+    pub title: Option<String>,
+    pub version: Option<String>,
+    pub endpoints: Vec<ApiEndpoint>,
+    pub omitted_endpoints: usize,
+    /// Names of the schemas under `components.schemas` (or Swagger's `definitions`).
+    pub schemas: Vec<String>,
+    pub omitted_schemas: usize,
+}
+
+/// Whether `path` is named like an API spec: a YAML or JSON file whose name mentions
+/// `openapi` or `swagger`.
+pub fn is_api_spec(path: &Path) -> bool {
+    let named = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .is_some_and(|stem| stem.contains("openapi") || stem.contains("swagger"));
+    named
+        && matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("yaml" | "yml" | "json")
+        )
+}
+
+/// Parses every file in `files` that turns out to be an OpenAPI or Swagger document.
+///
+/// Files that can't be read or parsed, or lack a top-level `openapi`/`swagger` version,
+/// are logged and skipped.
+pub async fn summarize_api_specs(files: &[String], source: &dyn FileCollector) -> Vec<ApiSpec> {
+    let mut specs = Vec::new();
+    for file in files {
+        let contents = match source.read(file).await {
+            Ok(bytes) => match decode_text(&bytes) {
+                Some((contents, _)) => contents,
+                None => continue,
+            },
+            Err(e) => {
+                debug!("Skipping API spec {}: {}", file, e);
+                continue;
+            }
+        };
+        match parse_api_spec(file, &contents) {
+            Some(spec) => specs.push(spec),
+            None => debug!("{} is not an OpenAPI or Swagger document", file),
+        }
+    }
+    specs
+}
+
+/// Reads the endpoints and schema names of an OpenAPI 3 or Swagger 2 document, in YAML
+/// or JSON.
+pub fn parse_api_spec(path: &str, contents: &str) -> Option<ApiSpec> {
+    let document: Value = if path.ends_with(".json") {
+        serde_json::from_str(contents).ok()?
+    } else {
+        serde_yaml::from_str(contents).ok()?
+    };
+    let spec_version = match (document.get("openapi"), document.get("swagger")) {
+        (Some(version), _) => format!("OpenAPI {}", text(version)?),
+        (None, Some(version)) => format!("Swagger {}", text(version)?),
+        (None, None) => return None,
+    };
+    let info = document.get("info");
+
+    let mut endpoints = Vec::new();
+    if let Some(Value::Object(paths)) = document.get("paths") {
+        for (route, operations) in paths {
+            for method in METHODS {
+                let Some(operation) = operations.get(method) else {
+                    continue;
+                };
+                let summary = operation
+                    .get("summary")
+                    .or_else(|| operation.get("operationId"))
+                    .and_then(text);
+                endpoints.push(ApiEndpoint {
+                    method: method.to_uppercase(),
+                    path: route.clone(),
+                    summary,
+                });
+            }
+        }
+    }
+    let omitted_endpoints = endpoints.len().saturating_sub(MAX_ENDPOINTS);
+    endpoints.truncate(MAX_ENDPOINTS);
+
+    let schemas = document
+        .pointer("/components/schemas")
+        .or_else(|| document.get("definitions"));
+    let mut schemas: Vec<String> = match schemas {
+        Some(Value::Object(schemas)) => schemas.keys().cloned().collect(),
+        _ => Vec::new(),
+    };
+    let omitted_schemas = schemas.len().saturating_sub(MAX_SCHEMAS);
+    schemas.truncate(MAX_SCHEMAS);
+
+    Some(ApiSpec {
+        path: path.to_string(),
+        spec_version,
+        title: info.and_then(|info| info.get("title")).and_then(text),
+        version: info.and_then(|info| info.get("version")).and_then(text),
+        endpoints,
+        omitted_endpoints,
+        schemas,
+        omitted_schemas,
+    })
+}
+
+// Versions are sometimes written as bare YAML numbers (`swagger: 2.0`).
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
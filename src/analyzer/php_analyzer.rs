@@ -0,0 +1,273 @@
+use super::{
+    find_suppressions,
+    static_analysis::{
+        extract_block, find_calls, line_of, unrecognized_declarations, FunctionAnalysis,
+        CALL_PATTERN,
+    },
+    summarize_types, CodeAnalysis, LanguageAnalyzer, ParserKind, TypeAnalysis, MIN_TYPE_MEMBERS,
+};
+use crate::error::FolderSummaryError;
+use crate::llm::LLM;
+use async_trait::async_trait;
+use regex::Regex;
+
+const MODIFIERS: &str = r"(?:(?:public|protected|private|static|abstract|final|readonly)\s+)*";
+
+pub struct PhpAnalyzer;
+
+impl PhpAnalyzer {
+    fn extract_namespace(content: &str) -> Option<String> {
+        let namespace_regex = Regex::new(r"(?m)^\s*namespace\s+([\w\\]+)\s*[;{]").unwrap();
+        namespace_regex.captures(content).map(|caps| caps[1].to_string())
+    }
+
+    // Top-level `use` statements; trait `use`s inside classes are indented and left out.
+    // Group uses (`use App\{Foo, Bar as Baz};`) are listed one name at a time.
+    fn extract_imports(content: &str) -> Vec<String> {
+        let use_regex =
+            Regex::new(r"(?m)^use\s+(function\s+|const\s+)?([\w\\]+?)\\?(?:\{([^}]*)\})?(\s+as\s+\w+)?\s*;")
+                .unwrap();
+        let mut imports = Vec::new();
+        for caps in use_regex.captures_iter(content) {
+            let kind = caps.get(1).map_or("", |m| m.as_str());
+            let prefix = &caps[2];
+            match caps.get(3) {
+                Some(group) => imports.extend(
+                    group
+                        .as_str()
+                        .split(',')
+                        .map(|name| name.split_whitespace().collect::<Vec<_>>().join(" "))
+                        .filter(|name| !name.is_empty())
+                        .map(|name| format!("{}{}\\{}", kind, prefix, name)),
+                ),
+                None => imports.push(format!(
+                    "{}{}{}",
+                    kind,
+                    prefix,
+                    caps.get(4).map_or("", |m| m.as_str())
+                )),
+            }
+        }
+        imports
+    }
+
+    // Also returns a warning for each function whose closing brace wasn't found.
+    fn extract_functions(content: &str) -> (Vec<FunctionAnalysis>, Vec<String>) {
+        // Default values may call a function once, as in `$items = array()`.
+        let function_regex = Regex::new(&format!(
+            r"(?m)^[ \t]*((?:#\[[^\]]*\]\s*)*){}function\s+&?(\w+)\s*\(((?:[^()]|\([^()]*\))*)\)\s*(?::\s*([?\w\\|]+)\s*)?\{{",
+            MODIFIERS
+        ))
+        .unwrap();
+        let attribute_regex = Regex::new(r"#\[[^\]]*\]").unwrap();
+        let parameter_regex = Regex::new(r"\$\w+").unwrap();
+        let return_regex = Regex::new(r"\breturn\s+[^;\s]").unwrap();
+        let call_regex = Regex::new(CALL_PATTERN).unwrap();
+        let mut functions = Vec::new();
+        let mut warnings = Vec::new();
+
+        for caps in function_regex.captures_iter(content) {
+            let name = caps[2].to_string();
+            let line = line_of(content, caps.get(2).unwrap().start());
+            let open = caps.get(0).unwrap().end() - 1;
+            let function_body = extract_block(content, open, true).unwrap_or_else(|| {
+                warnings.push(format!(
+                    "line {}: no closing brace found for `{}`; its body runs to the end of the file",
+                    line, name
+                ));
+                content[open..].to_string()
+            });
+            let params = caps[3].split_whitespace().collect::<Vec<_>>().join(" ");
+            let return_type = caps.get(4).map_or("", |m| m.as_str());
+            // The declaration without its attributes, with parameters on one line.
+            let header = &content[caps.get(1).unwrap().end()..open];
+            let signature = header[..header.find('(').unwrap_or(header.len())].trim().to_string();
+            let signature = format!(
+                "{}({}){}",
+                signature,
+                params,
+                if return_type.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {}", return_type)
+                }
+            );
+            let returns = match return_type.trim_start_matches('?') {
+                "" => return_regex.is_match(&function_body),
+                "void" | "never" => false,
+                _ => true,
+            };
+            let calls = find_calls(&function_body, &name, &call_regex);
+
+            functions.push(FunctionAnalysis {
+                name,
+                line: Some(line),
+                signature,
+                types: return_type.to_string(),
+                body: Some(function_body.clone()),
+                lines_of_code: function_body.lines().count(),
+                cyclomatic_complexity: Self::calculate_cyclomatic_complexity(&function_body),
+                parameters: parameter_regex.find_iter(&params).count(),
+                returns,
+                summary: None,
+                is_async: false,
+                blocking_calls: Vec::new(),
+                calls,
+                decorators: attribute_regex
+                    .find_iter(&caps[1])
+                    .map(|m| m.as_str().to_string())
+                    .collect(),
+                skip_reason: None,
+            });
+        }
+
+        (functions, warnings)
+    }
+
+    fn extract_types(content: &str) -> Vec<String> {
+        let type_regex = Regex::new(
+            r"(?m)^\s*(?:(?:abstract|final|readonly)\s+)*(?:class|interface|trait|enum)\s+(\w+)",
+        )
+        .unwrap();
+        type_regex
+            .captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .collect()
+    }
+
+    // Classes, interfaces and traits with enough methods, outlined as their declaration
+    // and method signatures.
+    fn extract_significant_types(content: &str) -> Vec<TypeAnalysis> {
+        let type_regex = Regex::new(
+            r"(?m)^[ \t]*(?:(?:abstract|final|readonly)\s+)*(class|interface|trait)\s+(\w+)[^{;]*\{",
+        )
+        .unwrap();
+        let method_regex = Regex::new(&format!(r"^\s*{}function\s+&?\w+\s*\(", MODIFIERS)).unwrap();
+        let mut types = Vec::new();
+        for caps in type_regex.captures_iter(content) {
+            let header = caps.get(0).unwrap();
+            let open = header.end() - 1;
+            let body = extract_block(content, open, true).unwrap_or_else(|| content[open..].to_string());
+            let declaration = header.as_str().split_whitespace().collect::<Vec<_>>().join(" ");
+            let mut outline = vec![declaration.trim_end_matches('{').trim_end().to_string()];
+            let mut depth = 0;
+            for line in body.lines() {
+                if depth == 1 && method_regex.is_match(line) {
+                    let signature = line.split(['{', ';']).next().unwrap_or_default();
+                    outline.push(format!("  {}", signature.trim()));
+                }
+                // Braces in a trailing `//` comment don't open or close anything.
+                let code = line.split("//").next().unwrap_or_default();
+                depth += code.matches('{').count() as i32 - code.matches('}').count() as i32;
+            }
+            let members = outline.len() - 1;
+            if members >= MIN_TYPE_MEMBERS {
+                types.push(TypeAnalysis {
+                    name: caps[2].to_string(),
+                    kind: caps[1].to_string(),
+                    line: Some(line_of(content, caps.get(2).unwrap().start())),
+                    members,
+                    definition: outline.join("\n"),
+                    summary: None,
+                });
+            }
+        }
+        types
+    }
+
+    // PHP has no exports; everything declared at the top level of a file can be used from
+    // elsewhere, so those types and functions are listed under the namespace.
+    fn extract_exports(content: &str) -> Vec<String> {
+        let declaration_regex = Regex::new(
+            r"(?m)^(?:(?:abstract|final|readonly)\s+)*(?:class|interface|trait|enum|function)\s+&?(\w+)",
+        )
+        .unwrap();
+        let namespace = Self::extract_namespace(content);
+        declaration_regex
+            .captures_iter(content)
+            .map(|caps| match &namespace {
+                Some(namespace) => format!("{}\\{}", namespace, &caps[1]),
+                None => caps[1].to_string(),
+            })
+            .collect()
+    }
+
+    fn calculate_cyclomatic_complexity(function_body: &str) -> usize {
+        let control_flow_regex =
+            Regex::new(r"\b(?:if|elseif|for|foreach|while|case|catch)\b|&&|\|\||\?\?").unwrap();
+        1 + control_flow_regex.find_iter(function_body).count()
+    }
+}
+
+#[async_trait]
+impl LanguageAnalyzer for PhpAnalyzer {
+    fn name(&self) -> &'static str {
+        "PHP"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".php"]
+    }
+
+    fn parser_kind(&self) -> ParserKind {
+        ParserKind::Regex
+    }
+
+    fn fence_language(&self) -> &'static str {
+        "php"
+    }
+
+    // Getters, setters and other one-statement methods say what they do in their name.
+    fn needs_summary(&self, func: &FunctionAnalysis) -> bool {
+        func.lines_of_code > 3
+    }
+
+    fn analyze(&self, content: &str) -> Result<CodeAnalysis, FolderSummaryError> {
+        let (functions, mut warnings) = Self::extract_functions(content);
+        // Functions with a body that the pattern skips, such as ones with nested calls in a
+        // default value; abstract and interface methods end in `;` and are left alone.
+        let declaration_regex =
+            Regex::new(&format!(r"(?m)^[ \t]*{}function\s+&?\w+\s*\([^{{;]*\{{", MODIFIERS))
+                .unwrap();
+        warnings.extend(unrecognized_declarations(content, &declaration_regex, &functions));
+        Ok(CodeAnalysis {
+            imports: Self::extract_imports(content),
+            functions,
+            types: Self::extract_types(content),
+            significant_types: Self::extract_significant_types(content),
+            exports: Self::extract_exports(content),
+            generated: None,
+            suppressions: find_suppressions(content),
+            warnings,
+            commands: Vec::new(),
+            fence_language: None,
+        })
+    }
+
+    async fn summarize(
+        &self,
+        analysis: &CodeAnalysis,
+        llm: &dyn LLM,
+    ) -> Result<CodeAnalysis, FolderSummaryError> {
+        let mut summarized = analysis.clone();
+        for func in &mut summarized.functions {
+            if self.needs_summary(func) {
+                let attributes = if func.decorators.is_empty() {
+                    String::new()
+                } else {
+                    format!("Attributes: {}\n", func.decorators.join(" "))
+                };
+                let prompt = format!(
+                    "Summarize the following PHP function:\n\nName: {}\n{}Signature: {}\nBody: {}",
+                    func.name,
+                    attributes,
+                    func.signature,
+                    func.body.as_deref().unwrap_or("(Function body not available)")
+                );
+                func.summary = Some(llm.summarize(&prompt).await?);
+            }
+        }
+        summarize_types(&mut summarized, "PHP", llm).await?;
+        Ok(summarized)
+    }
+}
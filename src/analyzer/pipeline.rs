@@ -0,0 +1,1080 @@
+use super::{
+    generated_reason, interleave_packages, minified_reason, order_for_summaries, CodeAnalysis,
+    FunctionAnalysis, LanguageAnalyzer, LanguageOverrides, SummaryOrder, ThreadSafeCache,
+};
+use crate::cache::modified_secs;
+use crate::collector::{FileCollector, WalkCollector};
+use crate::config::Config;
+use crate::error::FolderSummaryError;
+use crate::llm::{provider_llm, Timed, WithContext, LLM};
+use crate::progress::{Phase, ProgressReporter};
+use crate::run_log::{CacheOutcome, FileRecord, RunLog, CURRENT_FILE};
+use crate::timings::Timings;
+use crate::utils::{decode_text, format_bytes, hash_contents, normalize_text, TextEncoding};
+use futures::future::{join_all, try_join};
+use log::{debug, info, warn};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AnalysisFailure {
+    pub file: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Default)]
+pub struct AnalysisResults {
+    pub analyses: HashMap<String, CodeAnalysis>,
+    pub failures: Vec<AnalysisFailure>,
+    /// Files byte-identical to another analyzed file, mapped to the file that was analyzed.
+    pub duplicates: BTreeMap<String, String>,
+    /// Report sections from an earlier run for files whose analysis came unchanged from
+    /// the cache; the renderer reuses them instead of formatting the analysis again.
+    pub rendered: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnalysisOptions {
+    /// Files statically analyzed at once.
+    pub concurrency: usize,
+    /// Files being summarized by the LLM at once.
+    pub llm_concurrency: usize,
+    /// Skip the LLM phase entirely and report static analysis only.
+    pub no_llm: bool,
+    /// Abort the whole run on the first failing file instead of recording it and moving on.
+    pub fail_fast: bool,
+    /// Upper bound on static analysis of a single file.
+    pub file_timeout: Duration,
+    /// Files larger than this fail without being analyzed.
+    pub max_file_bytes: u64,
+    /// Functions longer than this are never sent to the LLM.
+    pub max_function_lines: usize,
+    /// Send generated and minified files to the LLM too; see [`generated_reason`].
+    pub summarize_generated: bool,
+    /// A second provider for large or complex functions, from the config's `[route]` table.
+    pub route: Option<FunctionRoute>,
+    /// Which files are summarized first.
+    pub summary_order: SummaryOrder,
+    /// The project's code directories. Files are analyzed and summarized one package at a
+    /// time in turn, so partial results and progress cover the whole of a monorepo.
+    pub package_dirs: Vec<PathBuf>,
+    /// The size in bytes of each file, as the collector recorded it.
+    pub file_sizes: BTreeMap<String, u64>,
+    /// Extensions analyzed as a language other than the one they'd get by default.
+    pub languages: LanguageOverrides,
+    /// Where per-phase, per-language and per-provider durations are recorded.
+    pub timings: Arc<Timings>,
+    /// The project's name and description, opening every function prompt along with the
+    /// file's path and imports; `None` leaves prompts to the function alone.
+    pub project_context: Option<String>,
+    /// Summarize functions a second time, told what the functions they call do.
+    pub callee_context: bool,
+    /// Where each file's cache outcome and each LLM call are recorded.
+    pub run_log: Arc<RunLog>,
+    /// Stops the run: no new files are started and in-flight ones are dropped.
+    pub cancel: CancellationToken,
+    /// Reads file contents; the cache is only consulted when it is backed by the disk.
+    pub source: Arc<dyn FileCollector>,
+}
+
+impl AnalysisOptions {
+    pub fn from_config(config: &Config) -> Result<Self, FolderSummaryError> {
+        Ok(AnalysisOptions {
+            concurrency: config.get_concurrency(),
+            llm_concurrency: config.get_llm_concurrency(),
+            no_llm: config.no_llm.unwrap_or(false),
+            fail_fast: config.fail_fast.unwrap_or(false),
+            file_timeout: config.get_file_timeout(),
+            max_file_bytes: config.get_max_file_bytes(),
+            max_function_lines: config.get_max_function_lines(),
+            summarize_generated: config.summarize_generated_files.unwrap_or(false),
+            route: FunctionRoute::from_config(config)?,
+            summary_order: SummaryOrder::parse(config.summary_order.as_deref().unwrap_or("priority"))?,
+            package_dirs: Vec::new(),
+            file_sizes: BTreeMap::new(),
+            languages: LanguageOverrides::from_config(config)?,
+            timings: Arc::new(Timings::new()),
+            project_context: None,
+            callee_context: config.callee_context.unwrap_or(false),
+            run_log: Arc::new(RunLog::new()),
+            cancel: CancellationToken::new(),
+            source: Arc::new(WalkCollector::new(".")),
+        })
+    }
+}
+
+/// Sends functions at or over either threshold to `llm` instead of the main provider, so
+/// a cheap local model can handle the bulk and a stronger one the functions that need it.
+#[derive(Clone)]
+pub struct FunctionRoute {
+    pub llm: Arc<dyn LLM>,
+    pub min_lines: usize,
+    pub min_complexity: usize,
+}
+
+impl FunctionRoute {
+    pub fn from_config(config: &Config) -> Result<Option<Self>, FolderSummaryError> {
+        let Some(route) = &config.route else {
+            return Ok(None);
+        };
+        Ok(Some(FunctionRoute {
+            llm: provider_llm(config, &route.large)?,
+            min_lines: route.get_min_lines(),
+            min_complexity: route.get_min_complexity(),
+        }))
+    }
+
+    fn is_large(&self, func: &FunctionAnalysis) -> bool {
+        func.lines_of_code >= self.min_lines || func.cyclomatic_complexity >= self.min_complexity
+    }
+}
+
+impl fmt::Debug for FunctionRoute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FunctionRoute")
+            .field("llm", &self.llm.model_name())
+            .field("min_lines", &self.min_lines)
+            .field("min_complexity", &self.min_complexity)
+            .finish()
+    }
+}
+
+enum StaticOutcome {
+    /// Fully summarized analysis straight from the cache.
+    Summarized(CodeAnalysis),
+    /// Static analysis still waiting for the LLM phase.
+    Pending(CodeAnalysis),
+    /// Binary content, nothing to report.
+    Skipped,
+    Failed(String),
+}
+
+/// Analyzes `files` in two phases and returns the per-file analyses plus any failures.
+///
+/// Byte-identical files are analyzed once; the copies share the analysis and are listed
+/// in [`AnalysisResults::duplicates`]. All static analysis runs first on blocking threads, bounded by `concurrency`; the
+/// results are cached so a failed or interrupted LLM phase doesn't redo it. The LLM
+/// phase then summarizes the pending files with its own concurrency; `llm` and the
+/// route provider come rate limited by the caller.
+/// Failing files are returned as [`AnalysisFailure`]s unless `fail_fast` is set.
+///
+/// Cancelling `options.cancel` returns [`FolderSummaryError::Cancelled`]; every file
+/// finished before that is already in the cache, so the next run picks up from there.
+pub async fn analyze_code_files(
+    files: &[String],
+    llm: Arc<dyn LLM>,
+    progress: &Arc<dyn ProgressReporter>,
+    cache: &ThreadSafeCache,
+    options: &AnalysisOptions,
+) -> Result<AnalysisResults, FolderSummaryError> {
+    let (unique_files, duplicates) = group_duplicates(files, options).await;
+    if !duplicates.is_empty() {
+        info!("Skipping {} duplicate files", duplicates.len());
+    }
+    let unique_files = interleave_packages(unique_files, &options.package_dirs, String::as_str);
+    progress.start(Phase::Analysis, unique_files.len() as u64);
+    let static_start = Instant::now();
+    let static_results = run_static_phase(&unique_files, progress, cache, options).await?;
+    options
+        .timings
+        .record("Static analysis phase", static_start.elapsed());
+    progress.finish(Phase::Analysis, "done");
+
+    let mut analyses = HashMap::new();
+    let mut failures = Vec::new();
+    let mut pending = Vec::new();
+    let mut unchanged = Vec::new();
+    for (file, outcome) in static_results {
+        match outcome {
+            StaticOutcome::Summarized(analysis) => {
+                unchanged.push(file.clone());
+                analyses.insert(file, analysis);
+            }
+            StaticOutcome::Pending(analysis) => pending.push((file, analysis)),
+            StaticOutcome::Skipped => {}
+            StaticOutcome::Failed(reason) => failures.push(AnalysisFailure { file, reason }),
+        }
+    }
+    if !options.summarize_generated {
+        let (generated, rest): (Vec<_>, Vec<_>) = pending
+            .into_iter()
+            .partition(|(_, analysis)| analysis.generated.is_some());
+        if !generated.is_empty() {
+            info!("Not summarizing {} generated files", generated.len());
+        }
+        unchanged.extend(generated.iter().map(|(file, _)| file.clone()));
+        analyses.extend(generated);
+        pending = rest;
+    }
+    order_for_summaries(&mut pending, &analyses, options.summary_order);
+    let pending = interleave_packages(pending, &options.package_dirs, |(file, _)| file.as_str());
+    progress.start(Phase::Summarization, pending.len() as u64);
+
+    if options.no_llm {
+        info!("Skipping LLM summarization for {} files", pending.len());
+        unchanged.extend(pending.iter().map(|(file, _)| file.clone()));
+        analyses.extend(pending);
+        progress.finish(Phase::Summarization, "skipped (--no-llm)");
+    } else {
+        let wrap = |llm: Arc<dyn LLM>| -> Arc<dyn LLM> {
+            Arc::new(Timed::new(llm, options.timings.clone()))
+        };
+        let llm = wrap(llm);
+        let route = options.route.as_ref().map(|route| FunctionRoute {
+            llm: wrap(route.llm.clone()),
+            ..route.clone()
+        });
+        let llm_start = Instant::now();
+        let mut summarized = Vec::new();
+        for (file, analysis, failure) in
+            run_llm_phase(pending, llm.clone(), route.clone(), progress, cache, options).await?
+        {
+            if failure.is_none() {
+                summarized.push(file.clone());
+            }
+            analyses.insert(file, analysis);
+            failures.extend(failure);
+        }
+        options.timings.record("LLM phase", llm_start.elapsed());
+        if options.callee_context && !summarized.is_empty() {
+            let callee_start = Instant::now();
+            for (file, analysis) in
+                run_callee_pass(summarized, &analyses, llm, route, cache, options).await?
+            {
+                analyses.insert(file, analysis);
+            }
+            options.timings.record("Callee pass", callee_start.elapsed());
+        }
+        progress.finish(Phase::Summarization, "done");
+    }
+
+    for (duplicate, original) in &duplicates {
+        if let Some(analysis) = analyses.get(original).cloned() {
+            analyses.insert(duplicate.clone(), analysis);
+        }
+    }
+
+    // Freshly analyzed files were just written to the cache, which clears their sections.
+    let mut stamped = Vec::new();
+    for file in unchanged {
+        if let Ok(modified) = modified_secs(&file).await {
+            stamped.push((file, modified));
+        }
+    }
+    let mut rendered = HashMap::new();
+    let cache = cache.lock().await;
+    for (file, modified) in stamped {
+        if let Some(section) = cache.get_rendered(&file, modified) {
+            rendered.insert(file, section.to_string());
+        }
+    }
+    debug!("Reusing {} rendered sections", rendered.len());
+
+    failures.sort_by(|a, b| a.file.cmp(&b.file));
+    Ok(AnalysisResults {
+        analyses,
+        failures,
+        duplicates,
+        rendered,
+    })
+}
+
+/// What [`warm_cache`] did.
+#[derive(Debug, Default)]
+pub struct WarmResults {
+    /// Files analyzed and added to the cache.
+    pub analyzed: usize,
+    /// Files whose analysis the cache already had.
+    pub cached: usize,
+    pub failures: Vec<AnalysisFailure>,
+}
+
+/// Runs the static phase of [`analyze_code_files`] alone: every file not already in the
+/// cache is analyzed and cached, unsummarized, so the next run only spends time on the
+/// LLM. Nothing is summarized, whatever `options.no_llm` says.
+pub async fn warm_cache(
+    files: &[String],
+    progress: &Arc<dyn ProgressReporter>,
+    cache: &ThreadSafeCache,
+    options: &AnalysisOptions,
+) -> Result<WarmResults, FolderSummaryError> {
+    let (unique_files, _) = group_duplicates(files, options).await;
+    let unique_files = interleave_packages(unique_files, &options.package_dirs, String::as_str);
+    progress.start(Phase::Analysis, unique_files.len() as u64);
+    let static_results = run_static_phase(&unique_files, progress, cache, options).await?;
+    progress.finish(Phase::Analysis, "done");
+
+    let cached: HashSet<String> = options
+        .run_log
+        .take()
+        .files
+        .into_iter()
+        .filter(|record| matches!(record.cache, CacheOutcome::Hit | CacheOutcome::Partial))
+        .map(|record| record.file)
+        .collect();
+    let mut results = WarmResults::default();
+    for (file, outcome) in static_results {
+        match outcome {
+            StaticOutcome::Failed(reason) => results.failures.push(AnalysisFailure { file, reason }),
+            StaticOutcome::Skipped => {}
+            _ if cached.contains(&file) => results.cached += 1,
+            _ => results.analyzed += 1,
+        }
+    }
+    results.failures.sort_by(|a, b| a.file.cmp(&b.file));
+    Ok(results)
+}
+
+/// Splits `files` into one representative per distinct content and a map of the copies.
+///
+/// Only files can be copies whose size, from `options.file_sizes`, another file shares, so
+/// the rest aren't read here; files of unknown size are. Empty files and files over
+/// `max_file_bytes` are never treated as duplicates: there is nothing to save by aliasing
+/// the first, and the second aren't analyzed.
+async fn group_duplicates(
+    files: &[String],
+    options: &AnalysisOptions,
+) -> (Vec<String>, BTreeMap<String, String>) {
+    let mut sorted = files.to_vec();
+    sorted.sort();
+
+    let mut per_size: HashMap<Option<u64>, usize> = HashMap::new();
+    for file in &sorted {
+        *per_size.entry(options.file_sizes.get(file).copied()).or_default() += 1;
+    }
+    let mut originals: HashMap<String, String> = HashMap::new();
+    let mut unique = Vec::new();
+    let mut duplicates = BTreeMap::new();
+    for file in sorted {
+        let size = options.file_sizes.get(&file).copied();
+        let may_be_copy = size.is_none_or(|size| {
+            size > 0 && size <= options.max_file_bytes && per_size[&Some(size)] > 1
+        });
+        if !may_be_copy {
+            unique.push(file);
+            continue;
+        }
+        let hash = match options.source.read(&file).await {
+            Ok(bytes) if !bytes.is_empty() => hash_contents(&bytes),
+            _ => {
+                unique.push(file);
+                continue;
+            }
+        };
+        match originals.get(&hash) {
+            Some(original) => {
+                debug!("{} is identical to {}", file, original);
+                duplicates.insert(file, original.clone());
+            }
+            None => {
+                originals.insert(hash, file.clone());
+                unique.push(file);
+            }
+        }
+    }
+    (unique, duplicates)
+}
+
+async fn run_static_phase(
+    files: &[String],
+    progress: &Arc<dyn ProgressReporter>,
+    cache: &ThreadSafeCache,
+    options: &AnalysisOptions,
+) -> Result<Vec<(String, StaticOutcome)>, FolderSummaryError> {
+    let progress = progress.clone();
+    let cache = cache.clone();
+    let timeout = options.file_timeout;
+    let max_bytes = options.max_file_bytes;
+    let sizes = Arc::new(options.file_sizes.clone());
+    let fail_fast = options.fail_fast;
+    let timings = options.timings.clone();
+    let run_log = options.run_log.clone();
+    let source = options.source.clone();
+    let use_cache = source.on_disk();
+    let languages = options.languages.clone();
+
+    run_bounded(files.to_vec(), options.concurrency, &options.cancel, move |file| {
+        let progress = progress.clone();
+        let cache = cache.clone();
+        let timings = timings.clone();
+        let run_log = run_log.clone();
+        let source = source.clone();
+        let languages = languages.clone();
+        let size = sizes.get(&file).copied();
+        async move {
+            let cache_start = Instant::now();
+            // Stat before taking the lock, so workers don't queue behind each other's IO.
+            let modified = if use_cache {
+                modified_secs(&file).await.ok()
+            } else {
+                None
+            };
+            let cached = match modified {
+                Some(modified) => cache
+                    .lock()
+                    .await
+                    .get_any(&file, modified)
+                    .map(|(analysis, summarized)| (analysis.clone(), summarized)),
+                None => None,
+            };
+            timings.record("Cache IO", cache_start.elapsed());
+
+            let language = languages.analyzer_for(&file).map_or("unknown", |a| a.name());
+            let cache_outcome = match (&cached, use_cache) {
+                (_, false) => CacheOutcome::Unused,
+                (Some((_, true)), _) => CacheOutcome::Hit,
+                (Some((_, false)), _) => CacheOutcome::Partial,
+                (None, _) => CacheOutcome::Miss,
+            };
+            let analysis_start = Instant::now();
+            let outcome = match cached {
+                Some((analysis, true)) => StaticOutcome::Summarized(analysis),
+                Some((analysis, false)) => StaticOutcome::Pending(analysis),
+                None => match timed(
+                    &timings,
+                    &format!("Static analysis ({})", language),
+                    analyze_static(&file, size, source.as_ref(), &languages, timeout, max_bytes),
+                )
+                .await
+                {
+                    Ok(Some(analysis)) => {
+                        if let Some(modified) = modified {
+                            let cache_start = Instant::now();
+                            cache.lock().await.set(file.clone(), modified, analysis.clone(), false);
+                            timings.record("Cache IO", cache_start.elapsed());
+                        }
+                        StaticOutcome::Pending(analysis)
+                    }
+                    Ok(None) => StaticOutcome::Skipped,
+                    Err(e) if fail_fast => return Err(e),
+                    Err(e) => {
+                        warn!("Failed to analyze {}: {}", file, e);
+                        StaticOutcome::Failed(e.to_string())
+                    }
+                },
+            };
+            run_log.record_file(FileRecord {
+                file: file.clone(),
+                cache: cache_outcome,
+                analysis_secs: match cache_outcome {
+                    CacheOutcome::Hit | CacheOutcome::Partial => 0.0,
+                    _ => analysis_start.elapsed().as_secs_f64(),
+                },
+            });
+            progress.advance(Phase::Analysis, 1);
+            Ok((file, outcome))
+        }
+    })
+    .await
+}
+
+// A file whose summarization fails keeps its static analysis in the report.
+async fn run_llm_phase(
+    pending: Vec<(String, CodeAnalysis)>,
+    llm: Arc<dyn LLM>,
+    route: Option<FunctionRoute>,
+    progress: &Arc<dyn ProgressReporter>,
+    cache: &ThreadSafeCache,
+    options: &AnalysisOptions,
+) -> Result<Vec<(String, CodeAnalysis, Option<AnalysisFailure>)>, FolderSummaryError> {
+    let progress = progress.clone();
+    let cache = cache.clone();
+    let max_lines = options.max_function_lines;
+    let fail_fast = options.fail_fast;
+    let timings = options.timings.clone();
+    let use_cache = options.source.on_disk();
+    let project_context = options.project_context.clone();
+    let languages = options.languages.clone();
+
+    run_bounded(pending, options.llm_concurrency, &options.cancel, move |(file, analysis)| {
+        let progress = progress.clone();
+        let languages = languages.clone();
+        let cache = cache.clone();
+        let mut llm = llm.clone();
+        let mut route = route.clone();
+        if let Some(project) = &project_context {
+            let context = prompt_context(project, &file, &analysis);
+            llm = Arc::new(WithContext::new(llm, context.clone()));
+            if let Some(route) = &mut route {
+                route.llm = Arc::new(WithContext::new(route.llm.clone(), context));
+            }
+        }
+        let timings = timings.clone();
+        let current_file = file.clone();
+        CURRENT_FILE.scope(current_file, async move {
+            let result = match languages.analyzer_for(&file) {
+                Some(analyzer) => {
+                    summarize_within_limit(
+                        analyzer.as_ref(),
+                        analysis.clone(),
+                        llm.as_ref(),
+                        max_lines,
+                        route.as_ref(),
+                    )
+                    .await
+                }
+                None => Err(FolderSummaryError::AnalysisError(format!(
+                    "No suitable analyzer found for file: {}",
+                    file
+                ))),
+            };
+            progress.advance(Phase::Summarization, 1);
+
+            match result {
+                Ok(summarized) => {
+                    if use_cache {
+                        let cache_start = Instant::now();
+                        let modified = modified_secs(&file).await?;
+                        cache.lock().await.set(file.clone(), modified, summarized.clone(), true);
+                        timings.record("Cache IO", cache_start.elapsed());
+                    }
+                    Ok((file, summarized, None))
+                }
+                // The static analysis stays cached as pending, for the next run to summarize.
+                Err(FolderSummaryError::TokenBudgetExhausted) => Ok((file, analysis, None)),
+                Err(e) if fail_fast => Err(e),
+                Err(e) => {
+                    warn!("Failed to summarize {}: {}", file, e);
+                    let failure = AnalysisFailure {
+                        file: file.clone(),
+                        reason: e.to_string(),
+                    };
+                    Ok((file, analysis, Some(failure)))
+                }
+            }
+        })
+    })
+    .await
+}
+
+/// Summarizes the functions of `files` again, each with the summaries of the functions it
+/// calls, so the summary can explain what it does in terms of its collaborators. Calls
+/// are matched to functions in `analyses` by name; functions with no summary yet, or
+/// whose callees have none, keep the summary they have. A failed second summary keeps
+/// the first one.
+async fn run_callee_pass(
+    files: Vec<String>,
+    analyses: &HashMap<String, CodeAnalysis>,
+    llm: Arc<dyn LLM>,
+    route: Option<FunctionRoute>,
+    cache: &ThreadSafeCache,
+    options: &AnalysisOptions,
+) -> Result<Vec<(String, CodeAnalysis)>, FolderSummaryError> {
+    let index = Arc::new(summaries_by_name(analyses));
+    let work: Vec<_> = files
+        .into_iter()
+        .filter_map(|file| analyses.get(&file).cloned().map(|analysis| (file, analysis)))
+        .collect();
+    info!("Summarizing {} files again with their callees", work.len());
+    let cache = cache.clone();
+    let fail_fast = options.fail_fast;
+    let use_cache = options.source.on_disk();
+    let project_context = options.project_context.clone();
+    let languages = options.languages.clone();
+
+    run_bounded(work, options.llm_concurrency, &options.cancel, move |(file, mut analysis)| {
+        let cache = cache.clone();
+        let languages = languages.clone();
+        let llm = llm.clone();
+        let route = route.clone();
+        let index = index.clone();
+        let project_context = project_context.clone();
+        CURRENT_FILE.scope(file.clone(), async move {
+            let Some(analyzer) = languages.analyzer_for(&file) else {
+                return Ok((file, analysis));
+            };
+            let mut revised = false;
+            for i in 0..analysis.functions.len() {
+                let func = &analysis.functions[i];
+                if func.summary.is_none() {
+                    continue;
+                }
+                let Some(callees) = callee_context(&file, func, &index) else {
+                    continue;
+                };
+                let context = match &project_context {
+                    Some(project) => {
+                        format!("{}\n{}", prompt_context(project, &file, &analysis), callees)
+                    }
+                    None => callees,
+                };
+                let provider = match &route {
+                    Some(route) if route.is_large(func) => route.llm.clone(),
+                    _ => llm.clone(),
+                };
+                let single = CodeAnalysis {
+                    functions: vec![func.clone()],
+                    ..Default::default()
+                };
+                let with_callees = WithContext::new(provider, context);
+                match analyzer.summarize(&single, &with_callees).await {
+                    Ok(summarized) => {
+                        if let Some(summary) =
+                            summarized.functions.into_iter().next().and_then(|f| f.summary)
+                        {
+                            analysis.functions[i].summary = Some(summary);
+                            revised = true;
+                        }
+                    }
+                    Err(FolderSummaryError::TokenBudgetExhausted) => break,
+                    Err(e) if fail_fast => return Err(e),
+                    Err(e) => warn!(
+                        "Keeping the first summary of {} in {}: {}",
+                        analysis.functions[i].name, file, e
+                    ),
+                }
+            }
+            if revised && use_cache {
+                let modified = modified_secs(&file).await?;
+                cache.lock().await.set(file.clone(), modified, analysis.clone(), true);
+            }
+            Ok((file, analysis))
+        })
+    })
+    .await
+}
+
+// Every summarized function by name, with the file it is in and its summary.
+fn summaries_by_name(
+    analyses: &HashMap<String, CodeAnalysis>,
+) -> HashMap<String, Vec<(String, String)>> {
+    let mut index: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for (file, analysis) in analyses {
+        for func in &analysis.functions {
+            if let Some(summary) = &func.summary {
+                index
+                    .entry(func.name.clone())
+                    .or_default()
+                    .push((file.clone(), summary.clone()));
+            }
+        }
+    }
+    index
+}
+
+// Callees listed in a prompt's context; the rest are left out.
+const MAX_CONTEXT_CALLEES: usize = 10;
+
+// What the functions `func` calls do, or `None` if none of them has a summary. A name
+// defined in the same file is taken to mean that definition; one defined in several other
+// files is left out, as there's no telling which is meant.
+fn callee_context(
+    file: &str,
+    func: &FunctionAnalysis,
+    index: &HashMap<String, Vec<(String, String)>>,
+) -> Option<String> {
+    let mut callees = Vec::new();
+    for name in func.calls.iter().filter(|name| **name != func.name) {
+        let Some(definitions) = index.get(name) else {
+            continue;
+        };
+        let definition = definitions
+            .iter()
+            .find(|(defined_in, _)| defined_in == file)
+            .or(if definitions.len() == 1 { definitions.first() } else { None });
+        if let Some((defined_in, summary)) = definition {
+            callees.push(format!(
+                "- {} ({}): {}",
+                name,
+                defined_in.trim_start_matches("./"),
+                summary.trim()
+            ));
+        }
+        if callees.len() == MAX_CONTEXT_CALLEES {
+            break;
+        }
+    }
+    (!callees.is_empty()).then(|| {
+        format!(
+            "It calls these functions; explain what it does in terms of them:\n{}",
+            callees.join("\n")
+        )
+    })
+}
+
+// Imports listed in a prompt's context; the rest are counted.
+const MAX_CONTEXT_IMPORTS: usize = 15;
+
+// What the LLM is told about where a function comes from: the project, the file and what
+// the file imports.
+fn prompt_context(project: &str, file: &str, analysis: &CodeAnalysis) -> String {
+    let mut context = format!("{}\nFile: {}", project, file.trim_start_matches("./"));
+    if !analysis.imports.is_empty() {
+        let listed = analysis.imports.len().min(MAX_CONTEXT_IMPORTS);
+        let mut imports = analysis.imports[..listed].join(", ");
+        if analysis.imports.len() > listed {
+            imports.push_str(&format!(" and {} more", analysis.imports.len() - listed));
+        }
+        context.push_str(&format!("\nImports: {}", imports));
+    }
+    context
+}
+
+async fn timed<T>(timings: &Timings, phase: &str, work: impl Future<Output = T>) -> T {
+    let start = Instant::now();
+    let result = work.await;
+    timings.record(phase, start.elapsed());
+    result
+}
+
+/// Runs `work` over `items` with at most `concurrency` items in flight at once.
+///
+/// Items are fed through a bounded channel to a fixed set of workers, so memory and
+/// open file handles stay flat no matter how large the repository is. The first error
+/// stops every worker from picking up new items and is returned. Cancelling `cancel`
+/// drops the in-flight items and returns [`FolderSummaryError::Cancelled`].
+async fn run_bounded<T, R, F, Fut>(
+    items: Vec<T>,
+    concurrency: usize,
+    cancel: &CancellationToken,
+    work: F,
+) -> Result<Vec<R>, FolderSummaryError>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<R, FolderSummaryError>> + Send + 'static,
+{
+    let concurrency = concurrency.max(1);
+    let (tx, rx) = mpsc::channel::<T>(concurrency * 2);
+    let rx = Arc::new(Mutex::new(rx));
+    let work = Arc::new(work);
+    let aborted = Arc::new(AtomicBool::new(false));
+
+    let workers: Vec<_> = (0..concurrency)
+        .map(|_| {
+            let rx = rx.clone();
+            let work = work.clone();
+            let aborted = aborted.clone();
+            let cancel = cancel.clone();
+
+            task::spawn(async move {
+                let mut results = Vec::new();
+                while !aborted.load(Ordering::Relaxed) {
+                    let next = rx.lock().await.recv().await;
+                    let Some(item) = next else { break };
+                    let outcome = tokio::select! {
+                        _ = cancel.cancelled() => Err(FolderSummaryError::Cancelled),
+                        outcome = work(item) => outcome,
+                    };
+                    match outcome {
+                        Ok(result) => results.push(result),
+                        Err(e) => {
+                            aborted.store(true, Ordering::Relaxed);
+                            return Err(e);
+                        }
+                    }
+                }
+                Ok::<_, FolderSummaryError>(results)
+            })
+        })
+        .collect();
+    // Only the workers hold the receiver, so sends fail once they have all stopped.
+    drop(rx);
+
+    for item in items {
+        if aborted.load(Ordering::Relaxed) || cancel.is_cancelled() {
+            break;
+        }
+        let sent = tokio::select! {
+            _ = cancel.cancelled() => break,
+            sent = tx.send(item) => sent,
+        };
+        if sent.is_err() {
+            break;
+        }
+    }
+    drop(tx);
+
+    let mut results = Vec::new();
+    for worker in join_all(workers).await {
+        results.extend(worker.map_err(|e| FolderSummaryError::TaskJoinError(e.to_string()))??);
+    }
+    Ok(results)
+}
+
+/// Analyzes and summarizes one file, returning `None` when it turns out to be binary.
+pub async fn analyze_file(
+    file_path: &str,
+    llm: &dyn LLM,
+    options: &AnalysisOptions,
+) -> Result<Option<CodeAnalysis>, FolderSummaryError> {
+    let Some(analysis) = analyze_static(
+        file_path,
+        options.file_sizes.get(file_path).copied(),
+        options.source.as_ref(),
+        &options.languages,
+        options.file_timeout,
+        options.max_file_bytes,
+    )
+    .await?
+    else {
+        return Ok(None);
+    };
+    if options.no_llm || (analysis.generated.is_some() && !options.summarize_generated) {
+        return Ok(Some(analysis));
+    }
+    let analyzer = options.languages.analyzer_for(file_path).ok_or_else(|| {
+        FolderSummaryError::AnalysisError(format!(
+            "No suitable analyzer found for file: {}",
+            file_path
+        ))
+    })?;
+    summarize_within_limit(
+        analyzer.as_ref(),
+        analysis,
+        llm,
+        options.max_function_lines,
+        options.route.as_ref(),
+    )
+    .await
+    .map(Some)
+}
+
+// Regex-heavy analysis of pathological files can run long enough to stall the async
+// workers, so it runs on a blocking thread under a timeout. The timeout only stops the
+// wait: a blocking task can't be interrupted and keeps its thread until the analyzer
+// returns. What bounds the work itself is `max_bytes`; larger files fail before they are
+// read, by the `size` the collector recorded or else the one on disk.
+async fn analyze_static(
+    file_path: &str,
+    size: Option<u64>,
+    source: &dyn FileCollector,
+    languages: &LanguageOverrides,
+    timeout: Duration,
+    max_bytes: u64,
+) -> Result<Option<CodeAnalysis>, FolderSummaryError> {
+    let analyzer = languages.analyzer_for(file_path).ok_or_else(|| {
+        FolderSummaryError::AnalysisError(format!(
+            "No suitable analyzer found for file: {}",
+            file_path
+        ))
+    })?;
+
+    let over_limit = |size: u64| {
+        FolderSummaryError::AnalysisError(format!(
+            "file is {}, over the max_file_bytes limit of {}",
+            format_bytes(size),
+            format_bytes(max_bytes)
+        ))
+    };
+    let size = match size {
+        Some(size) => Some(size),
+        None if source.on_disk() => tokio::fs::metadata(file_path).await.ok().map(|m| m.len()),
+        None => None,
+    };
+    if let Some(size) = size.filter(|&size| size > max_bytes) {
+        return Err(over_limit(size));
+    }
+    let bytes = source
+        .read(file_path)
+        .await
+        .map_err(|e| FolderSummaryError::file(file_path, e))?;
+    // Sources without a size up front, and files that grew since they were collected.
+    if bytes.len() as u64 > max_bytes {
+        return Err(over_limit(bytes.len() as u64));
+    }
+    let Some((content, encoding)) = decode_text(&bytes) else {
+        warn!("Skipping binary file: {}", file_path);
+        return Ok(None);
+    };
+    if encoding != TextEncoding::Utf8 {
+        debug!("Decoded {} as {}", file_path, encoding);
+    }
+
+    // Regex analyzers assume `\n` line breaks; CRLF or stray `\r`s would end up in names
+    // and signatures.
+    let content = normalize_text(&content);
+    let path = file_path.to_string();
+    let analysis = task::spawn_blocking(move || {
+        if let Some(reason) = minified_reason(&path, &content) {
+            return Ok(CodeAnalysis {
+                generated: Some(reason),
+                ..Default::default()
+            });
+        }
+        let mut analysis = analyzer.analyze(&content)?;
+        analysis.generated = generated_reason(&path, &content);
+        analysis.fence_language = Some(analyzer.fence_language().to_string())
+            .filter(|language| !language.is_empty());
+        Ok(analysis)
+    });
+    match tokio::time::timeout(timeout, analysis).await {
+        Ok(joined) => joined
+            .map_err(|e| FolderSummaryError::TaskJoinError(e.to_string()))?
+            .map(Some),
+        Err(_) => Err(FolderSummaryError::AnalysisError(format!(
+            "static analysis timed out after {}s",
+            timeout.as_secs()
+        ))),
+    }
+}
+
+/// Summarizes `analysis`, keeping functions over `max_lines` away from the LLM. With a
+/// `route`, the functions it claims are summarized by its provider, alongside the rest.
+pub(crate) async fn summarize_within_limit(
+    analyzer: &dyn LanguageAnalyzer,
+    analysis: CodeAnalysis,
+    llm: &dyn LLM,
+    max_lines: usize,
+    route: Option<&FunctionRoute>,
+) -> Result<CodeAnalysis, FolderSummaryError> {
+    let is_large = |func: &FunctionAnalysis| route.is_some_and(|route| route.is_large(func));
+    if analysis
+        .functions
+        .iter()
+        .all(|f| f.lines_of_code <= max_lines && !is_large(f))
+    {
+        return analyzer.summarize(&analysis, llm).await;
+    }
+
+    let mut within_limit = analysis.clone();
+    within_limit.functions.retain(|f| f.lines_of_code <= max_lines && !is_large(f));
+    let mut large = analysis.clone();
+    large.functions.retain(|f| f.lines_of_code <= max_lines && is_large(f));
+    // Types are summarized once, by the main provider.
+    large.significant_types.clear();
+    let (summarized, summarized_large) = match route {
+        Some(route) if !large.functions.is_empty() => {
+            try_join(
+                analyzer.summarize(&within_limit, llm),
+                analyzer.summarize(&large, route.llm.as_ref()),
+            )
+            .await?
+        }
+        _ => (analyzer.summarize(&within_limit, llm).await?, large),
+    };
+    let mut result = analysis;
+    result.significant_types = summarized.significant_types;
+    let mut summarized = summarized.functions.into_iter();
+    let mut summarized_large = summarized_large.functions.into_iter();
+
+    for func in &mut result.functions {
+        if func.lines_of_code > max_lines {
+            warn!(
+                "Not summarizing {}: {} lines exceeds the {}-line limit",
+                func.name, func.lines_of_code, max_lines
+            );
+            func.skip_reason = Some(format!(
+                "{} lines exceeds the {}-line limit",
+                func.lines_of_code, max_lines
+            ));
+        } else if is_large(func) {
+            if let Some(summarized_func) = summarized_large.next() {
+                *func = summarized_func;
+            }
+        } else if let Some(summarized_func) = summarized.next() {
+            *func = summarized_func;
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::Cache;
+    use crate::collector::MemoryCollector;
+    use crate::llm::Mock;
+    use crate::progress::NoProgress;
+
+    fn options(source: MemoryCollector) -> AnalysisOptions {
+        AnalysisOptions {
+            no_llm: true,
+            source: Arc::new(source),
+            ..AnalysisOptions::from_config(&Config::default()).unwrap()
+        }
+    }
+
+    async fn analyze(files: &[&str], options: &AnalysisOptions) -> AnalysisResults {
+        let files: Vec<String> = files.iter().map(|file| file.to_string()).collect();
+        let progress: Arc<dyn ProgressReporter> = Arc::new(NoProgress);
+        let cache = Arc::new(Mutex::new(Cache::in_memory()));
+        analyze_code_files(&files, Arc::new(Mock), &progress, &cache, options).await.unwrap()
+    }
+
+    // One unbroken line of nested generics, the kind of input regex analyzers backtrack on.
+    fn pathological() -> String {
+        format!("fn f() -> {}(){}", "Option<".repeat(2000), ">".repeat(2000))
+    }
+
+    #[tokio::test]
+    async fn duplicates_map_to_the_first_copy_by_path() {
+        let source = MemoryCollector::new()
+            .with_file("b/util.rs", "fn util() {}\n")
+            .with_file("a/util.rs", "fn util() {}\n")
+            .with_file("c/util.rs", "fn other() {}\n")
+            .with_file("a/empty.rs", "")
+            .with_file("b/empty.rs", "");
+        let files: Vec<String> = ["b/util.rs", "c/util.rs", "a/util.rs", "b/empty.rs", "a/empty.rs"]
+            .iter()
+            .map(|file| file.to_string())
+            .collect();
+
+        let collected = source.collect(&Config::default(), &CancellationToken::new()).unwrap();
+        let options = AnalysisOptions {
+            file_sizes: collected.code_sizes,
+            ..options(source)
+        };
+
+        let (unique, duplicates) = group_duplicates(&files, &options).await;
+
+        // Empty files are all alike but not copies of each other.
+        assert_eq!(unique, ["a/empty.rs", "a/util.rs", "b/empty.rs", "c/util.rs"]);
+        assert_eq!(
+            duplicates,
+            BTreeMap::from([("b/util.rs".to_string(), "a/util.rs".to_string())])
+        );
+    }
+
+    #[tokio::test]
+    async fn duplicates_share_the_analysis() {
+        let source = MemoryCollector::new()
+            .with_file("a.rs", "fn shared() {}\n")
+            .with_file("b.rs", "fn shared() {}\n");
+        let results = analyze(&["a.rs", "b.rs"], &options(source)).await;
+
+        assert_eq!(results.duplicates.get("b.rs").map(String::as_str), Some("a.rs"));
+        assert_eq!(results.analyses["b.rs"].functions[0].name, "shared");
+    }
+
+    #[tokio::test]
+    async fn files_over_max_file_bytes_are_skipped_and_reported() {
+        let source = MemoryCollector::new()
+            .with_file("huge.rs", pathological())
+            .with_file("small.rs", "fn small() {}\n");
+        let options = AnalysisOptions {
+            max_file_bytes: 1024,
+            ..options(source)
+        };
+        let results = analyze(&["huge.rs", "small.rs"], &options).await;
+
+        assert!(!results.analyses.contains_key("huge.rs"));
+        assert!(results.analyses.contains_key("small.rs"));
+        assert_eq!(results.failures.len(), 1);
+        assert_eq!(results.failures[0].file, "huge.rs");
+        assert!(results.failures[0].reason.contains("max_file_bytes"), "{}", results.failures[0].reason);
+    }
+
+    #[tokio::test]
+    async fn files_over_max_file_bytes_by_recorded_size_are_never_read() {
+        // The source doesn't have the file: reading it would fail some other way.
+        let options = AnalysisOptions {
+            max_file_bytes: 1024,
+            file_sizes: BTreeMap::from([("huge.rs".to_string(), 10 * 1024 * 1024)]),
+            ..options(MemoryCollector::new())
+        };
+        let results = analyze(&["huge.rs"], &options).await;
+
+        assert_eq!(results.failures.len(), 1);
+        assert!(results.failures[0].reason.contains("max_file_bytes"), "{}", results.failures[0].reason);
+    }
+}
@@ -0,0 +1,234 @@
+use super::layers::import_targets;
+use super::CodeAnalysis;
+use crate::error::FolderSummaryError;
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// File names that start a program or define a package's surface.
+const ENTRY_POINTS: &[&str] = &[
+    "main.rs", "lib.rs", "main.py", "__main__.py", "app.py", "manage.py", "wsgi.py", "asgi.py",
+    "index.js", "index.ts", "index.mjs", "main.js", "main.ts", "app.js", "app.ts", "server.js",
+    "server.ts",
+];
+/// Files that stand for their directory when imported.
+const PACKAGE_FILES: &[&str] = &["mod", "index", "__init__"];
+
+/// Which files the LLM phase summarizes first, so an interrupted or rate-limited run
+/// still has the summaries that matter most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryOrder {
+    /// Entry points, then the most imported files, then those exporting the most.
+    Priority,
+    /// The most imported files first.
+    Imports,
+    /// Alphabetically by path.
+    Path,
+}
+
+impl SummaryOrder {
+    pub fn parse(order: &str) -> Result<Self, FolderSummaryError> {
+        match order {
+            "priority" => Ok(SummaryOrder::Priority),
+            "imports" => Ok(SummaryOrder::Imports),
+            "path" => Ok(SummaryOrder::Path),
+            other => Err(FolderSummaryError::ConfigError(format!(
+                "Invalid summary_order \"{}\": expected \"priority\", \"imports\" or \"path\"",
+                other
+            ))),
+        }
+    }
+}
+
+/// Sorts `pending` into the order their summaries are requested in. Imports from the
+/// already `summarized` files count too.
+pub fn order_for_summaries(
+    pending: &mut [(String, CodeAnalysis)],
+    summarized: &HashMap<String, CodeAnalysis>,
+    order: SummaryOrder,
+) {
+    if order == SummaryOrder::Path {
+        pending.sort_by(|a, b| a.0.cmp(&b.0));
+        return;
+    }
+    let imported = import_counts(pending, summarized);
+    let count = |file: &String| imported.get(file).copied().unwrap_or(0);
+    match order {
+        SummaryOrder::Priority => pending.sort_by_cached_key(|(file, analysis)| {
+            (
+                !is_entry_point(file),
+                Reverse(count(file)),
+                Reverse(analysis.exports.len()),
+                file.clone(),
+            )
+        }),
+        _ => pending.sort_by_cached_key(|(file, _)| (Reverse(count(file)), file.clone())),
+    }
+}
+
+/// Picks `count` of `files`, given as (path, size in bytes), to stand for a project too
+/// large to analyze whole: entry points first, then files that define a package's public
+/// surface (`lib.rs`, `mod.rs`, `index.js`, `__init__.py`), then the largest files. Import
+/// counts would need the analysis sampling is there to avoid, so size stands in for them.
+/// The sample is returned sorted by path.
+pub fn sample_files(files: &[(String, u64)], count: usize) -> Vec<String> {
+    let mut ranked: Vec<&(String, u64)> = files.iter().collect();
+    ranked.sort_by_cached_key(|(file, size)| {
+        let path = Path::new(file);
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        let public_api = name == "lib.rs" || PACKAGE_FILES.contains(&stem);
+        (!is_entry_point(file), !public_api, Reverse(*size), file.clone())
+    });
+    let mut sample: Vec<String> =
+        ranked.into_iter().take(count).map(|(file, _)| file.clone()).collect();
+    sample.sort();
+    sample
+}
+
+/// Reorders `items` so consecutive ones come from different packages: one from each
+/// package in turn, keeping each package's own order. A file belongs to the deepest of
+/// `package_dirs` it sits in; files outside all of them form a package of their own.
+pub fn interleave_packages<T>(
+    items: Vec<T>,
+    package_dirs: &[PathBuf],
+    file: impl Fn(&T) -> &str,
+) -> Vec<T> {
+    let mut packages: Vec<(Option<usize>, VecDeque<T>)> = Vec::new();
+    for item in items {
+        let path = Path::new(file(&item));
+        let package = (0..package_dirs.len())
+            .filter(|&i| path.starts_with(&package_dirs[i]))
+            .max_by_key(|&i| package_dirs[i].components().count());
+        match packages.iter_mut().find(|(key, _)| *key == package) {
+            Some((_, queue)) => queue.push_back(item),
+            None => packages.push((package, VecDeque::from([item]))),
+        }
+    }
+    let mut interleaved = Vec::new();
+    while !packages.is_empty() {
+        for (_, queue) in &mut packages {
+            interleaved.extend(queue.pop_front());
+        }
+        packages.retain(|(_, queue)| !queue.is_empty());
+    }
+    interleaved
+}
+
+fn is_entry_point(file: &str) -> bool {
+    let name = Path::new(file).file_name().and_then(|name| name.to_str());
+    name.is_some_and(|name| ENTRY_POINTS.contains(&name))
+}
+
+// How many imports across all files name each pending file. An import names a file when
+// its module path contains the file's module name, directly after the file's directory
+// unless the name comes first (`crate::cache::Cache`, `from utils import x`).
+fn import_counts(
+    pending: &[(String, CodeAnalysis)],
+    summarized: &HashMap<String, CodeAnalysis>,
+) -> HashMap<String, usize> {
+    let mut targets: Vec<(&str, Vec<String>)> = Vec::new();
+    let all = pending.iter().map(|(file, analysis)| (file, analysis)).chain(summarized);
+    for (file, analysis) in all {
+        let path = Path::new(file.as_str());
+        let directory = path.parent().unwrap_or(Path::new(""));
+        for import in &analysis.imports {
+            targets.extend(
+                import_targets(path, directory, import)
+                    .into_iter()
+                    .map(|target| (file.as_str(), target)),
+            );
+        }
+    }
+
+    let mut counts = HashMap::new();
+    for (file, _) in pending {
+        let Some((name, parent)) = module_name(Path::new(file)) else {
+            continue;
+        };
+        let count = targets
+            .iter()
+            .filter(|(importer, target)| {
+                *importer != file
+                    && target.iter().enumerate().any(|(i, component)| {
+                        *component == name && (i == 0 || parent.as_deref() == Some(&target[i - 1]))
+                    })
+            })
+            .count();
+        counts.insert(file.clone(), count);
+    }
+    counts
+}
+
+// The name a file is imported by and the directory it sits in: `cache` and `src` for
+// `src/cache.rs`, `components` and `src` for `src/components/index.js`.
+fn module_name(path: &Path) -> Option<(String, Option<String>)> {
+    let name = |path: &Path| path.file_name().map(|name| name.to_string_lossy().into_owned());
+    let stem = path.file_stem()?.to_string_lossy().into_owned();
+    let directory = path.parent()?;
+    if PACKAGE_FILES.contains(&stem.as_str()) {
+        Some((name(directory)?, directory.parent().and_then(name)))
+    } else {
+        Some((stem, name(directory)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sizes(files: &[(&str, u64)]) -> Vec<(String, u64)> {
+        files.iter().map(|(file, size)| (file.to_string(), *size)).collect()
+    }
+
+    #[test]
+    fn sample_prefers_entry_points_then_package_files_then_size() {
+        let files = sizes(&[
+            ("src/big.rs", 9000),
+            ("src/small.rs", 10),
+            ("src/main.rs", 5),
+            ("src/net/mod.rs", 20),
+            ("src/medium.rs", 500),
+        ]);
+        assert_eq!(sample_files(&files, 1), ["src/main.rs"]);
+        assert_eq!(sample_files(&files, 2), ["src/main.rs", "src/net/mod.rs"]);
+        // Returned by path, whatever order they were picked in.
+        assert_eq!(
+            sample_files(&files, 4),
+            ["src/big.rs", "src/main.rs", "src/medium.rs", "src/net/mod.rs"]
+        );
+        assert_eq!(sample_files(&files, 10).len(), 5);
+    }
+
+    #[test]
+    fn sample_breaks_size_ties_by_path() {
+        let files = sizes(&[("b.py", 100), ("c.py", 100), ("a.py", 100)]);
+        assert_eq!(sample_files(&files, 2), ["a.py", "b.py"]);
+    }
+
+    #[test]
+    fn interleave_takes_one_file_per_package_in_turn() {
+        let packages = [PathBuf::from("api"), PathBuf::from("web")];
+        let files = vec!["api/a.rs", "api/b.rs", "api/c.rs", "web/a.js", "web/b.js", "README.md"];
+        assert_eq!(
+            interleave_packages(files, &packages, |file| file),
+            ["api/a.rs", "web/a.js", "README.md", "api/b.rs", "web/b.js", "api/c.rs"]
+        );
+    }
+
+    #[test]
+    fn interleave_uses_the_deepest_package() {
+        let packages = [PathBuf::from("app"), PathBuf::from("app/plugins/auth")];
+        let files = vec!["app/a.rs", "app/b.rs", "app/plugins/auth/x.rs", "app/plugins/auth/y.rs"];
+        assert_eq!(
+            interleave_packages(files, &packages, |file| file),
+            ["app/a.rs", "app/plugins/auth/x.rs", "app/b.rs", "app/plugins/auth/y.rs"]
+        );
+    }
+
+    #[test]
+    fn interleave_without_packages_keeps_the_order() {
+        let files = vec!["b.rs", "a.rs"];
+        assert_eq!(interleave_packages(files, &[], |file| file), ["b.rs", "a.rs"]);
+    }
+}
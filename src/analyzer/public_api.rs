@@ -0,0 +1,462 @@
+use crate::collector::FileCollector;
+use crate::error::FolderSummaryError;
+use crate::utils::decode_text;
+use log::{debug, warn};
+use quote::ToTokens;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One public item of a library crate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiItem {
+    /// Full path from the crate root, e.g. `my_crate::config::Config::load`.
+    pub path: String,
+    /// `fn`, `struct`, `enum`, `trait`, `type`, `const`, `static`, `use`, `impl` or `macro`.
+    pub kind: String,
+    /// The item's declaration without bodies or private fields, as token text.
+    pub signature: String,
+}
+
+/// The public API of one library crate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateApi {
+    pub name: String,
+    pub items: Vec<ApiItem>,
+}
+
+/// One difference between a baseline public API and the current one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiChange {
+    /// `added`, `removed` or `changed`.
+    pub change: String,
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// How the public API moved since a baseline snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiDiff {
+    pub baseline: String,
+    pub changes: Vec<ApiChange>,
+    /// The semver bump the changes call for: `major`, `minor` or `patch`.
+    pub bump: String,
+}
+
+/// Lists the public API of every library crate among `manifests`.
+///
+/// Starting from `src/lib.rs`, only modules reachable through `pub mod` are followed, so
+/// `pub` items in private modules are left out unless re-exported (re-exports are
+/// listed as `use` items, not resolved). Files that fail to parse are logged and skipped.
+pub async fn public_api(
+    manifests: &[String],
+    code: &[String],
+    source: &dyn FileCollector,
+) -> Vec<CrateApi> {
+    let mut crates = Vec::new();
+    for manifest in manifests.iter().filter(|manifest| manifest.ends_with("Cargo.toml")) {
+        let root = Path::new(manifest).parent().unwrap_or(Path::new(""));
+        let lib = root.join("src").join("lib.rs");
+        if !code.iter().any(|file| Path::new(file) == lib) {
+            continue;
+        }
+        let Some(name) = crate_name(manifest, source).await else {
+            continue;
+        };
+        let src = root.join("src");
+        let mut sources = HashMap::new();
+        for file in code.iter().filter(|file| Path::new(file).starts_with(&src)) {
+            match source.read(file).await.map(|bytes| decode_text(&bytes)) {
+                Ok(Some((contents, _))) => {
+                    sources.insert(PathBuf::from(file), contents);
+                }
+                Ok(None) => {}
+                Err(e) => debug!("Skipping {} while listing the public API: {}", file, e),
+            }
+        }
+        let mut items = Vec::new();
+        module_file(&lib, &name, &sources, &mut items);
+        crates.push(CrateApi { name, items });
+    }
+    crates
+}
+
+/// Compares `current` against a `baseline` snapshot, crate by crate and item by item.
+///
+/// Removed and changed items call for a major release, added ones for a minor release.
+pub fn diff_public_api(baseline: &str, previous: &[CrateApi], current: &[CrateApi]) -> ApiDiff {
+    let index = |crates: &[CrateApi]| -> BTreeMap<String, String> {
+        crates
+            .iter()
+            .flat_map(|krate| &krate.items)
+            .map(|item| (format!("{} {}", item.kind, item.path), item.signature.clone()))
+            .collect()
+    };
+    let before = index(previous);
+    let after = index(current);
+
+    let mut changes = Vec::new();
+    for (key, signature) in &before {
+        let path = key.split_once(' ').map_or(key.as_str(), |(_, path)| path).to_string();
+        match after.get(key) {
+            None => changes.push(ApiChange {
+                change: "removed".to_string(),
+                path,
+                before: Some(signature.clone()),
+                after: None,
+            }),
+            Some(new) if new != signature => changes.push(ApiChange {
+                change: "changed".to_string(),
+                path,
+                before: Some(signature.clone()),
+                after: Some(new.clone()),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (key, signature) in after.iter().filter(|(key, _)| !before.contains_key(*key)) {
+        changes.push(ApiChange {
+            change: "added".to_string(),
+            path: key.split_once(' ').map_or(key.as_str(), |(_, path)| path).to_string(),
+            before: None,
+            after: Some(signature.clone()),
+        });
+    }
+
+    let bump = if changes.iter().any(|change| change.change != "added") {
+        "major"
+    } else if changes.is_empty() {
+        "patch"
+    } else {
+        "minor"
+    };
+    ApiDiff {
+        baseline: baseline.to_string(),
+        changes,
+        bump: bump.to_string(),
+    }
+}
+
+/// Reads a snapshot written by an earlier run.
+pub fn load_api_snapshot(path: &Path) -> Result<Vec<CrateApi>, FolderSummaryError> {
+    let contents =
+        fs::read(path).map_err(|e| FolderSummaryError::file(path.display().to_string(), e))?;
+    Ok(serde_json::from_slice(&contents)?)
+}
+
+async fn crate_name(manifest: &str, source: &dyn FileCollector) -> Option<String> {
+    let bytes = source.read(manifest).await.ok()?;
+    let (contents, _) = decode_text(&bytes)?;
+    let manifest: toml::Value = contents.parse().ok()?;
+    let name = manifest.get("package")?.get("name")?.as_str()?;
+    Some(name.replace('-', "_"))
+}
+
+fn module_file(
+    file: &Path,
+    module: &str,
+    sources: &HashMap<PathBuf, String>,
+    items: &mut Vec<ApiItem>,
+) {
+    let Some(contents) = sources.get(file) else {
+        debug!("Module {} has no source file at {}", module, file.display());
+        return;
+    };
+    let ast = match syn::parse_file(contents) {
+        Ok(ast) => ast,
+        Err(e) => {
+            warn!("Could not parse {} for the public API: {}", file.display(), e);
+            return;
+        }
+    };
+    // `lib.rs` and `mod.rs` keep their submodules beside them; `foo.rs` keeps them in `foo/`.
+    let file_name = file.file_name().and_then(|name| name.to_str()).unwrap_or("");
+    let children = match file_name {
+        "lib.rs" | "mod.rs" => file.parent().unwrap_or(Path::new("")).to_path_buf(),
+        _ => file.with_extension(""),
+    };
+    module_items(&ast.items, module, &children, sources, items);
+}
+
+fn module_items(
+    declarations: &[syn::Item],
+    module: &str,
+    children: &Path,
+    sources: &HashMap<PathBuf, String>,
+    items: &mut Vec<ApiItem>,
+) {
+    let mut push = |name: String, kind: &str, signature: String| {
+        items.push(ApiItem {
+            path: format!("{}::{}", module, name),
+            kind: kind.to_string(),
+            signature,
+        })
+    };
+    let mut submodules = Vec::new();
+    let mut macros = Vec::new();
+    for item in declarations {
+        match item {
+            syn::Item::Fn(item) if is_public(&item.vis) => {
+                push(item.sig.ident.to_string(), "fn", tokens(&item.sig))
+            }
+            syn::Item::Struct(item) if is_public(&item.vis) => {
+                let mut item = item.clone();
+                item.attrs.clear();
+                let fields = match &mut item.fields {
+                    syn::Fields::Named(fields) => Some(&mut fields.named),
+                    syn::Fields::Unnamed(fields) => Some(&mut fields.unnamed),
+                    syn::Fields::Unit => None,
+                };
+                if let Some(fields) = fields {
+                    *fields = fields
+                        .iter()
+                        .filter(|field| is_public(&field.vis))
+                        .cloned()
+                        .map(|mut field| {
+                            field.attrs.clear();
+                            field
+                        })
+                        .collect();
+                }
+                push(item.ident.to_string(), "struct", tokens(&item))
+            }
+            syn::Item::Enum(item) if is_public(&item.vis) => {
+                let mut item = item.clone();
+                item.attrs.clear();
+                for variant in &mut item.variants {
+                    variant.attrs.clear();
+                }
+                push(item.ident.to_string(), "enum", tokens(&item))
+            }
+            syn::Item::Trait(item) if is_public(&item.vis) => {
+                let mut header = item.clone();
+                header.attrs.clear();
+                header.items.clear();
+                push(item.ident.to_string(), "trait", tokens(&header));
+                for trait_item in &item.items {
+                    if let syn::TraitItem::Method(method) = trait_item {
+                        push(
+                            format!("{}::{}", item.ident, method.sig.ident),
+                            "fn",
+                            tokens(&method.sig),
+                        );
+                    }
+                }
+            }
+            syn::Item::Type(item) if is_public(&item.vis) => {
+                let mut item = item.clone();
+                item.attrs.clear();
+                push(item.ident.to_string(), "type", tokens(&item))
+            }
+            syn::Item::Const(item) if is_public(&item.vis) => {
+                let signature = format!("const {}: {}", item.ident, tokens(&item.ty));
+                push(item.ident.to_string(), "const", signature)
+            }
+            syn::Item::Static(item) if is_public(&item.vis) => {
+                let signature = format!("static {}: {}", item.ident, tokens(&item.ty));
+                push(item.ident.to_string(), "static", signature)
+            }
+            syn::Item::Use(item) if is_public(&item.vis) => {
+                let tree = tokens(&item.tree);
+                push(tree.clone(), "use", format!("pub use {}", tree))
+            }
+            syn::Item::Macro(item) if has_attribute(&item.attrs, "macro_export") => {
+                macros.extend(item.ident.as_ref().map(|ident| ident.to_string()))
+            }
+            syn::Item::Impl(item) => {
+                let self_ty = tokens(&item.self_ty);
+                match &item.trait_ {
+                    Some((negative, path, _)) => {
+                        let bang = if negative.is_some() { "!" } else { "" };
+                        let signature = format!(
+                            "impl{} {}{} for {}",
+                            tokens(&item.generics),
+                            bang,
+                            tokens(path),
+                            self_ty
+                        );
+                        push(format!("{} as {}", self_ty, tokens(path)), "impl", signature)
+                    }
+                    None => {
+                        for impl_item in &item.items {
+                            if let syn::ImplItem::Method(method) = impl_item {
+                                if is_public(&method.vis) {
+                                    push(
+                                        format!("{}::{}", self_ty, method.sig.ident),
+                                        "fn",
+                                        tokens(&method.sig),
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            syn::Item::Mod(item) if is_public(&item.vis) => submodules.push(item),
+            _ => {}
+        }
+    }
+
+    // Exported macros live at the crate root, whatever module defines them.
+    let root = module.split("::").next().unwrap_or(module);
+    for name in macros {
+        items.push(ApiItem {
+            path: format!("{}::{}", root, name),
+            kind: "macro".to_string(),
+            signature: format!("macro_rules! {}", name),
+        });
+    }
+
+    for item in submodules {
+        let name = item.ident.to_string();
+        let path = format!("{}::{}", module, name);
+        match &item.content {
+            Some((_, inline)) => module_items(inline, &path, &children.join(&name), sources, items),
+            None => {
+                let file = children.join(format!("{}.rs", name));
+                let file = if sources.contains_key(&file) {
+                    file
+                } else {
+                    children.join(&name).join("mod.rs")
+                };
+                module_file(&file, &path, sources, items);
+            }
+        }
+    }
+}
+
+fn is_public(vis: &syn::Visibility) -> bool {
+    matches!(vis, syn::Visibility::Public(_))
+}
+
+fn has_attribute(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| attr.path.is_ident(name))
+}
+
+// Token text spaced the way the code would be written: `fn run(x: u32) -> Vec<u8>`
+// rather than `fn run (x : u32) -> Vec < u8 >`.
+fn tokens(node: &impl ToTokens) -> String {
+    let text = node.to_token_stream().to_string();
+    let text = text
+        .replace(" :: ", "::")
+        .replace(" : ", ": ")
+        .replace(" ,", ",")
+        .replace(" ;", ";")
+        .replace("( ", "(")
+        .replace(" )", ")")
+        .replace(" < ", "<")
+        .replace(" <", "<")
+        .replace(" >", ">")
+        .replace("& ", "&");
+    let call = Regex::new(r"(\w) \(").unwrap();
+    call.replace_all(&text, "$1(").into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (kind, path, signature)
+    type Item = (&'static str, &'static str, &'static str);
+
+    struct Case {
+        name: &'static str,
+        previous: &'static [Item],
+        current: &'static [Item],
+        bump: &'static str,
+        /// (change, path) in order.
+        changes: &'static [(&'static str, &'static str)],
+    }
+
+    const LOAD: Item = ("fn", "demo::load", "pub fn load(path: &Path) -> Config");
+    const CONFIG: Item = ("struct", "demo::Config", "pub struct Config");
+
+    const CASES: &[Case] = &[
+        Case {
+            name: "unchanged",
+            previous: &[LOAD, CONFIG],
+            current: &[LOAD, CONFIG],
+            bump: "patch",
+            changes: &[],
+        },
+        Case {
+            name: "removed",
+            previous: &[LOAD, CONFIG],
+            current: &[CONFIG],
+            bump: "major",
+            changes: &[("removed", "demo::load")],
+        },
+        Case {
+            name: "added",
+            previous: &[CONFIG],
+            current: &[LOAD, CONFIG],
+            bump: "minor",
+            changes: &[("added", "demo::load")],
+        },
+        Case {
+            name: "signature changed",
+            previous: &[LOAD],
+            current: &[("fn", "demo::load", "pub fn load(path: &str) -> Config")],
+            bump: "major",
+            changes: &[("changed", "demo::load")],
+        },
+        // An item that changes kind is one removed and one added.
+        Case {
+            name: "kind changed",
+            previous: &[CONFIG],
+            current: &[("enum", "demo::Config", "pub enum Config")],
+            bump: "major",
+            changes: &[("removed", "demo::Config"), ("added", "demo::Config")],
+        },
+        Case {
+            name: "added and removed",
+            previous: &[LOAD],
+            current: &[CONFIG],
+            bump: "major",
+            changes: &[("removed", "demo::load"), ("added", "demo::Config")],
+        },
+    ];
+
+    fn api(items: &[Item]) -> Vec<CrateApi> {
+        vec![CrateApi {
+            name: "demo".to_string(),
+            items: items
+                .iter()
+                .map(|(kind, path, signature)| ApiItem {
+                    path: path.to_string(),
+                    kind: kind.to_string(),
+                    signature: signature.to_string(),
+                })
+                .collect(),
+        }]
+    }
+
+    #[test]
+    fn bump_and_changes() {
+        for case in CASES {
+            let diff = diff_public_api("v1", &api(case.previous), &api(case.current));
+            let changes: Vec<(&str, &str)> = diff
+                .changes
+                .iter()
+                .map(|change| (change.change.as_str(), change.path.as_str()))
+                .collect();
+            assert_eq!(diff.bump, case.bump, "{}", case.name);
+            assert_eq!(changes, case.changes, "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn changed_item_keeps_both_signatures() {
+        let diff = diff_public_api(
+            "v1",
+            &api(&[("fn", "demo::load", "pub fn load()")]),
+            &api(&[("fn", "demo::load", "pub fn load(strict: bool)")]),
+        );
+        assert_eq!(diff.baseline, "v1");
+        assert_eq!(diff.changes[0].before.as_deref(), Some("pub fn load()"));
+        assert_eq!(diff.changes[0].after.as_deref(), Some("pub fn load(strict: bool)"));
+    }
+}
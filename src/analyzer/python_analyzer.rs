@@ -1,4 +1,4 @@
-use super::{static_analysis::FunctionAnalysis, CodeAnalysis, LanguageAnalyzer};
+use super::{find_suppressions, static_analysis::{find_blocking_calls, find_calls, line_of, unrecognized_declarations, FunctionAnalysis, CALL_PATTERN}, summarize_types, CodeAnalysis, LanguageAnalyzer, ParserKind, TypeAnalysis, MIN_TYPE_MEMBERS};
 use crate::error::FolderSummaryError;
 use crate::llm::LLM;
 use async_trait::async_trait;
@@ -26,16 +26,32 @@ impl PythonAnalyzer {
 
     fn extract_functions(content: &str) -> Vec<FunctionAnalysis> {
         let function_regex =
-            Regex::new(r"(?m)^(\s*)def\s+(\w+)\s*\((.*?)\)(?:\s*->\s*([^:]+))?\s*:").unwrap();
+            Regex::new(r"(?m)^([ \t]*)(async[ \t]+)?def\s+(\w+)\s*\(([^)]*)\)(?:\s*->\s*([^:]+?))?\s*:").unwrap();
+        // Synchronous sleeps, HTTP, subprocess and file calls that block the event loop.
+        let blocking_regex = Regex::new(
+            r"\b(?:time\.sleep|requests\.(?:get|post|put|patch|delete|head|request)|urllib\.request\.urlopen|subprocess\.(?:run|call|check_call|check_output)|open)\(",
+        )
+        .unwrap();
+        let call_regex = Regex::new(CALL_PATTERN).unwrap();
         let mut functions = Vec::new();
 
         for caps in function_regex.captures_iter(content) {
             let indentation = caps.get(1).map_or("", |m| m.as_str());
-            let name = caps.get(2).map_or("", |m| m.as_str()).to_string();
-            let params = caps.get(3).map_or("", |m| m.as_str());
-            let return_type = caps.get(4).map_or("", |m| m.as_str());
+            let is_async = caps.get(2).is_some();
+            let name = caps.get(3).map_or("", |m| m.as_str()).to_string();
+            // Parameters may be split over several lines.
+            let params = caps
+                .get(4)
+                .map_or("", |m| m.as_str())
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let params = params.trim_end_matches(',').trim_end();
+            let return_type = caps.get(5).map_or("", |m| m.as_str()).trim();
+            let start = caps.get(0).unwrap().start();
             let signature = format!(
-                "def {}({}){}:",
+                "{}def {}({}){}:",
+                if is_async { "async " } else { "" },
                 name,
                 params,
                 if return_type.is_empty() {
@@ -47,9 +63,17 @@ impl PythonAnalyzer {
 
             let function_body = Self::extract_function_body(content, indentation, caps.get(0).unwrap().end());
             let lines_of_code = function_body.lines().count();
+            let blocking_calls = if is_async {
+                find_blocking_calls(&function_body, &blocking_regex)
+            } else {
+                Vec::new()
+            };
 
+            let calls = find_calls(&function_body, &name, &call_regex);
+            let decorators = Self::extract_decorators(content, indentation, start);
             functions.push(FunctionAnalysis {
                 name,
+                line: caps.get(3).map(|m| line_of(content, m.start())),
                 signature,
                 types: return_type.to_string(),
                 body: Some(function_body.clone()),
@@ -58,33 +82,54 @@ impl PythonAnalyzer {
                 parameters: params.split(',').filter(|p| !p.trim().is_empty()).count(),
                 returns: !return_type.is_empty(),
                 summary: None,
+                is_async,
+                blocking_calls,
+                calls,
+                decorators,
+                skip_reason: None,
             });
         }
 
         functions
     }
 
+    // The lines indented under a `def` whose header ends at `start`, blank lines inside
+    // the body included. A one-line `def f(): return x` is its own body.
     fn extract_function_body(content: &str, indentation: &str, start: usize) -> String {
-        let lines: Vec<&str> = content[start..].lines().collect();
-        let mut body = Vec::new();
-        let mut in_body = false;
+        let mut lines = content[start..].lines();
+        let rest_of_header = lines.next().unwrap_or("").trim();
+        if !rest_of_header.is_empty() && !rest_of_header.starts_with('#') {
+            return rest_of_header.to_string();
+        }
 
+        let mut body = Vec::new();
         for line in lines {
-            if !in_body && line.trim().is_empty() {
-                continue;
-            }
-            if !in_body {
-                in_body = true;
-            }
-            if in_body && (!line.starts_with(indentation) || line.trim().is_empty()) {
+            let line_indentation = line.len() - line.trim_start().len();
+            if !line.trim().is_empty() && line_indentation <= indentation.len() {
                 break;
             }
             body.push(line);
         }
-
+        while body.last().is_some_and(|line| line.trim().is_empty()) {
+            body.pop();
+        }
         body.join("\n")
     }
 
+    // The `@decorator` lines right above the `def` starting at `start`, top to bottom.
+    fn extract_decorators(content: &str, indentation: &str, start: usize) -> Vec<String> {
+        let mut decorators: Vec<String> = content[..start]
+            .lines()
+            .rev()
+            .take_while(|line| {
+                line.starts_with(indentation) && line[indentation.len()..].starts_with('@')
+            })
+            .map(|line| line.trim().to_string())
+            .collect();
+        decorators.reverse();
+        decorators
+    }
+
     fn extract_types(content: &str) -> Vec<String> {
         let class_regex = Regex::new(r"(?m)^\s*class\s+(\w+)").unwrap();
         class_regex
@@ -94,9 +139,71 @@ impl PythonAnalyzer {
             .collect()
     }
 
-    fn extract_exports(_content: &str) -> Vec<String> {
-        // Python doesn't have explicit exports, so we'll leave this empty
-        Vec::new()
+    // Classes with enough methods, outlined as their declaration and method signatures.
+    fn extract_significant_types(content: &str) -> Vec<TypeAnalysis> {
+        let class_regex = Regex::new(r"(?m)^([ \t]*)class\s+(\w+)[^:\n]*:").unwrap();
+        let method_regex = Regex::new(r"^([ \t]*)(?:async\s+)?def\s+\w+").unwrap();
+        let mut types = Vec::new();
+        for caps in class_regex.captures_iter(content) {
+            let indentation = caps[1].len();
+            let header = caps.get(0).unwrap();
+            let mut outline = vec![header.as_str().trim().to_string()];
+            // Methods are the functions at the indentation of the class's first statement.
+            let mut method_indentation = None;
+            for line in content[header.end()..].lines().skip(1) {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let line_indentation = line.len() - line.trim_start().len();
+                if line_indentation <= indentation {
+                    break;
+                }
+                let method_indentation = *method_indentation.get_or_insert(line_indentation);
+                if let Some(method) = method_regex.captures(line) {
+                    if method[1].len() == method_indentation {
+                        outline.push(format!("  {}", line.trim()));
+                    }
+                }
+            }
+            let members = outline.len() - 1;
+            if members >= MIN_TYPE_MEMBERS {
+                types.push(TypeAnalysis {
+                    name: caps[2].to_string(),
+                    kind: "class".to_string(),
+                    line: Some(line_of(content, caps.get(2).unwrap().start())),
+                    members,
+                    definition: outline.join("\n"),
+                    summary: None,
+                });
+            }
+        }
+        types
+    }
+
+    // The names in `__all__` when the module defines it; otherwise its top-level
+    // ALL_CAPS constants, the closest thing to an export list Python has.
+    fn extract_exports(content: &str) -> Vec<String> {
+        let all_regex = Regex::new(r"(?m)^__all__\s*\+?=\s*[\[(]([^\])]*)[\])]").unwrap();
+        let name_regex = Regex::new(r#"['"](\w+)['"]"#).unwrap();
+        let mut exports: Vec<String> = Vec::new();
+        for caps in all_regex.captures_iter(content) {
+            for name in name_regex.captures_iter(&caps[1]) {
+                if !exports.iter().any(|export| export == &name[1]) {
+                    exports.push(name[1].to_string());
+                }
+            }
+        }
+        if all_regex.is_match(content) {
+            return exports;
+        }
+
+        let constant_regex = Regex::new(r"(?m)^([A-Z][A-Z0-9_]*)\s*(?::[^=\n]+)?=[^=]").unwrap();
+        for caps in constant_regex.captures_iter(content) {
+            if !exports.iter().any(|export| export == &caps[1]) {
+                exports.push(caps[1].to_string());
+            }
+        }
+        exports
     }
 
     fn calculate_cyclomatic_complexity(function_body: &str) -> usize {
@@ -131,30 +238,63 @@ impl PythonAnalyzer {
 
 #[async_trait]
 impl LanguageAnalyzer for PythonAnalyzer {
-    fn can_analyze(&self, file_path: &str) -> bool {
-        file_path.ends_with(".py")
+    fn name(&self) -> &'static str {
+        "Python"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".py"]
+    }
+
+    fn parser_kind(&self) -> ParserKind {
+        ParserKind::Regex
+    }
+
+    fn fence_language(&self) -> &'static str {
+        "python"
+    }
+
+    fn needs_summary(&self, func: &FunctionAnalysis) -> bool {
+        func.lines_of_code > 6
     }
 
     fn analyze(&self, content: &str) -> Result<CodeAnalysis, FolderSummaryError> {
+        let functions = Self::extract_functions(content);
+        // `def`s the function pattern skips, such as ones with a parenthesis in a default
+        // value or annotation.
+        let declaration_regex = Regex::new(r"(?m)^[ \t]*(?:async[ \t]+)?def\b").unwrap();
+        let warnings = unrecognized_declarations(content, &declaration_regex, &functions);
         Ok(CodeAnalysis {
             imports: Self::extract_imports(content),
-            functions: Self::extract_functions(content),
+            functions,
             types: Self::extract_types(content),
+            significant_types: Self::extract_significant_types(content),
             exports: Self::extract_exports(content),
+            generated: None,
+            suppressions: find_suppressions(content),
+            warnings,
+            commands: Vec::new(),
+            fence_language: None,
         })
     }
 
     async fn summarize(
         &self,
         analysis: &CodeAnalysis,
-        llm: &Box<dyn LLM>,
+        llm: &dyn LLM,
     ) -> Result<CodeAnalysis, FolderSummaryError> {
         let mut summarized = analysis.clone();
         for func in &mut summarized.functions {
-            if func.lines_of_code > 6 {
+            if self.needs_summary(func) {
+                let decorators = if func.decorators.is_empty() {
+                    String::new()
+                } else {
+                    format!("Decorators: {}\n", func.decorators.join(" "))
+                };
                 let prompt = format!(
-                    "Summarize the following Python function:\n\nName: {}\nSignature: {}\nTypes: {}\nBody: {}",
+                    "Summarize the following Python function:\n\nName: {}\n{}Signature: {}\nTypes: {}\nBody: {}",
                     func.name,
+                    decorators,
                     func.signature,
                     func.types,
                     func.body.as_deref().unwrap_or("(Function body not available)")
@@ -162,6 +302,7 @@ impl LanguageAnalyzer for PythonAnalyzer {
                 func.summary = Some(llm.summarize(&prompt).await?);
             }
         }
+        summarize_types(&mut summarized, "Python", llm).await?;
         Ok(summarized)
     }
 }
@@ -0,0 +1,190 @@
+use crate::collector::FileCollector;
+use crate::utils::decode_text;
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// An HTTP endpoint defined in code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Route {
+    /// Upper-case HTTP method, `ALL` for catch-alls, or several joined with `, `.
+    pub method: String,
+    pub path: String,
+    /// The function handling the route, when it is named rather than inline.
+    pub handler: Option<String>,
+    pub file: String,
+    pub line: usize,
+}
+
+/// Reads the JavaScript/TypeScript, Python and Rust files among `files` and lists the
+/// routes they define, in file order.
+pub async fn collect_routes(files: &[String], source: &dyn FileCollector) -> Vec<Route> {
+    let patterns = RoutePatterns::new();
+    let mut routes = Vec::new();
+    for file in files {
+        let Some(language) = language(Path::new(file)) else {
+            continue;
+        };
+        let contents = match source.read(file).await {
+            Ok(bytes) => match decode_text(&bytes) {
+                Some((contents, _)) => contents,
+                None => continue,
+            },
+            Err(e) => {
+                debug!("Skipping {} while looking for routes: {}", file, e);
+                continue;
+            }
+        };
+        routes.extend(patterns.extract(file, language, &contents));
+    }
+    routes
+}
+
+/// Lists the routes defined in one file: Express/Fastify `app.get(...)` calls,
+/// FastAPI/Flask decorators, Actix attribute macros and axum `.route(...)` calls.
+pub fn extract_routes(file: &str, contents: &str) -> Vec<Route> {
+    match language(Path::new(file)) {
+        Some(language) => RoutePatterns::new().extract(file, language, contents),
+        None => Vec::new(),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Language {
+    JavaScript,
+    Python,
+    Rust,
+}
+
+fn language(path: &Path) -> Option<Language> {
+    match path.extension().and_then(|ext| ext.to_str())? {
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => Some(Language::JavaScript),
+        "py" => Some(Language::Python),
+        "rs" => Some(Language::Rust),
+        _ => None,
+    }
+}
+
+struct RoutePatterns {
+    express: Regex,
+    decorator: Regex,
+    decorator_methods: Regex,
+    python_def: Regex,
+    actix: Regex,
+    actix_route: Regex,
+    actix_method: Regex,
+    rust_fn: Regex,
+    axum: Regex,
+    axum_method: Regex,
+}
+
+impl RoutePatterns {
+    fn new() -> Self {
+        RoutePatterns {
+            // Only receivers that are conventionally servers or routers, so HTTP client
+            // calls like `axios.get('/api/users')` are not mistaken for routes.
+            express: Regex::new(
+                r#"\b(?:app|router|server|api|routes|fastify|\w+Router)\.(get|post|put|delete|patch|head|options|all)\s*\(\s*['"`]([^'"`]+)['"`]\s*(?:,(.*))?"#,
+            )
+            .unwrap(),
+            decorator: Regex::new(
+                r#"^\s*@\w+\.(get|post|put|delete|patch|head|options|route|api_route)\s*\(\s*[rf]?['"]([^'"]*)['"](.*)"#,
+            )
+            .unwrap(),
+            decorator_methods: Regex::new(r#"methods\s*=\s*[\[(]([^\])]*)"#).unwrap(),
+            python_def: Regex::new(r"^\s*(?:async\s+)?def\s+(\w+)").unwrap(),
+            actix: Regex::new(r#"^\s*#\[(?:actix_web::)?(get|post|put|delete|patch|head|options)\s*\(\s*"([^"]+)""#).unwrap(),
+            actix_route: Regex::new(r#"^\s*#\[(?:actix_web::)?route\s*\(\s*"([^"]+)"(.*)"#).unwrap(),
+            actix_method: Regex::new(r#"method\s*=\s*"(\w+)""#).unwrap(),
+            rust_fn: Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:async\s+)?fn\s+(\w+)").unwrap(),
+            axum: Regex::new(r#"\.route\s*\(\s*"([^"]+)"\s*,(.*)"#).unwrap(),
+            axum_method: Regex::new(r"\b(get|post|put|delete|patch|head|options|any)\s*\(\s*([\w:]+)\s*\)").unwrap(),
+        }
+    }
+
+    fn extract(&self, file: &str, language: Language, contents: &str) -> Vec<Route> {
+        let lines: Vec<&str> = contents.lines().collect();
+        let route = |method: &str, path: &str, handler: Option<String>, index: usize| Route {
+            method: method.to_uppercase(),
+            path: path.to_string(),
+            handler,
+            file: file.to_string(),
+            line: index + 1,
+        };
+        let mut routes = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            match language {
+                Language::JavaScript => {
+                    if let Some(caps) = self.express.captures(line) {
+                        let handler = caps.get(3).and_then(|rest| express_handler(rest.as_str()));
+                        routes.push(route(&caps[1], &caps[2], handler, index));
+                    }
+                }
+                Language::Python => {
+                    if let Some(caps) = self.decorator.captures(line) {
+                        let method = match &caps[1] {
+                            "route" | "api_route" => self
+                                .decorator_methods
+                                .captures(&caps[3])
+                                .map(|methods| {
+                                    methods[1]
+                                        .split(',')
+                                        .map(|method| method.trim().trim_matches(['"', '\'']))
+                                        .filter(|method| !method.is_empty())
+                                        .collect::<Vec<_>>()
+                                        .join(", ")
+                                })
+                                .unwrap_or_else(|| "GET".to_string()),
+                            method => method.to_string(),
+                        };
+                        let handler = next_definition(&lines[index + 1..], &self.python_def);
+                        routes.push(route(&method, &caps[2], handler, index));
+                    }
+                }
+                Language::Rust => {
+                    if let Some(caps) = self.actix.captures(line) {
+                        let handler = next_definition(&lines[index + 1..], &self.rust_fn);
+                        routes.push(route(&caps[1], &caps[2], handler, index));
+                    } else if let Some(caps) = self.actix_route.captures(line) {
+                        let methods: Vec<&str> = self
+                            .actix_method
+                            .captures_iter(&caps[2])
+                            .map(|method| method.get(1).unwrap().as_str())
+                            .collect();
+                        let method = if methods.is_empty() { "ALL".to_string() } else { methods.join(", ") };
+                        let handler = next_definition(&lines[index + 1..], &self.rust_fn);
+                        routes.push(route(&method, &caps[1], handler, index));
+                    } else if let Some(caps) = self.axum.captures(line) {
+                        for method in self.axum_method.captures_iter(&caps[2]) {
+                            let name = if &method[1] == "any" { "all" } else { &method[1] };
+                            routes.push(route(name, &caps[1], Some(method[2].to_string()), index));
+                        }
+                    }
+                }
+            }
+        }
+        routes
+    }
+}
+
+// The last argument of an Express registration, if it names a function rather than
+// defining one inline.
+fn express_handler(arguments: &str) -> Option<String> {
+    let arguments = arguments.trim().trim_end_matches(';').trim_end_matches(')');
+    let last = arguments.rsplit(',').next()?.trim();
+    let is_name = !last.is_empty()
+        && last
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.' || c == '$');
+    is_name.then(|| last.to_string())
+}
+
+// The function a decorator or attribute applies to: the first definition within the
+// next few lines, past any stacked decorators.
+fn next_definition(lines: &[&str], definition: &Regex) -> Option<String> {
+    lines
+        .iter()
+        .take(10)
+        .find_map(|line| definition.captures(line).map(|caps| caps[1].to_string()))
+}
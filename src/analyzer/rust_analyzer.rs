@@ -1,9 +1,10 @@
 use super::{
+    find_suppressions,
     static_analysis::{extract_function_metrics, FunctionAnalysis},
-    CodeAnalysis, LanguageAnalyzer,
+    summarize_types, CodeAnalysis, LanguageAnalyzer, ParserKind, TypeAnalysis, MIN_TYPE_MEMBERS,
 };
 use crate::error::FolderSummaryError;
-use crate::llm::LLM;
+use crate::llm::{calculate_tokens, LLM};
 use async_trait::async_trait;
 use syn::parse_file;
 use quote::ToTokens;
@@ -41,6 +42,36 @@ impl RustAnalyzer {
             .collect()
     }
 
+    fn extract_significant_types(ast: &syn::File) -> Vec<TypeAnalysis> {
+        ast.items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Struct(item_struct) => Some((
+                    &item_struct.ident,
+                    "struct",
+                    item_struct.fields.len(),
+                    item_struct.to_token_stream(),
+                )),
+                syn::Item::Enum(item_enum) => Some((
+                    &item_enum.ident,
+                    "enum",
+                    item_enum.variants.len(),
+                    item_enum.to_token_stream(),
+                )),
+                _ => None,
+            })
+            .filter(|(_, _, members, _)| *members >= MIN_TYPE_MEMBERS)
+            .map(|(ident, kind, members, tokens)| TypeAnalysis {
+                name: ident.to_string(),
+                kind: kind.to_string(),
+                line: Some(ident.span().start().line),
+                members,
+                definition: tokens.to_string(),
+                summary: None,
+            })
+            .collect()
+    }
+
     fn extract_exports(ast: &syn::File) -> Vec<String> {
         ast.items
             .iter()
@@ -71,8 +102,20 @@ impl RustAnalyzer {
 
 #[async_trait]
 impl LanguageAnalyzer for RustAnalyzer {
-    fn can_analyze(&self, file_path: &str) -> bool {
-        file_path.ends_with(".rs")
+    fn name(&self) -> &'static str {
+        "Rust"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".rs"]
+    }
+
+    fn parser_kind(&self) -> ParserKind {
+        ParserKind::Ast
+    }
+
+    fn fence_language(&self) -> &'static str {
+        "rust"
     }
 
     fn analyze(&self, content: &str) -> Result<CodeAnalysis, FolderSummaryError> {
@@ -99,61 +142,96 @@ impl LanguageAnalyzer for RustAnalyzer {
             imports,
             functions,
             types,
+            significant_types: Self::extract_significant_types(&ast),
             exports,
+            generated: None,
+            suppressions: find_suppressions(content),
+            warnings: Vec::new(),
+            commands: Vec::new(),
+            fence_language: None,
         })
     }
 
     async fn summarize(
         &self,
         analysis: &CodeAnalysis,
-        llm: &Box<dyn LLM>,
+        llm: &dyn LLM,
     ) -> Result<CodeAnalysis, FolderSummaryError> {
         let mut summarized = analysis.clone();
         for func in &mut summarized.functions {
-            let prompt = if func.lines_of_code > 200 {
-                generate_large_function_prompt(func)
+            let prompt = format!(
+                "Summarize the following Rust function:\n\nName: {}\nSignature: {}\nTypes: {}\nBody: {}",
+                func.name,
+                func.signature,
+                func.types,
+                func.body.as_deref().unwrap_or("(function body omitted)")
+            );
+            // Bodies too big for the model's window go in parts instead.
+            let prompt = if calculate_tokens(&prompt) + ANSWER_TOKENS > llm.context_window() {
+                generate_large_function_prompt(func, llm.context_window())
             } else {
-                format!(
-                    "Summarize the following Rust function:\n\nName: {}\nSignature: {}\nTypes: {}\nBody: {}",
-                    func.name,
-                    func.signature,
-                    func.types,
-                    func.body.as_deref().unwrap_or("(function body omitted)")
-                )
+                prompt
             };
-        
+
             func.summary = Some(llm.summarize(&prompt).await?);
         }
+        summarize_types(&mut summarized, "Rust", llm).await?;
         Ok(summarized)
     }
 }
 
-fn generate_large_function_prompt(func: &FunctionAnalysis) -> String {
+// Tokens of the context window left for the answer.
+const ANSWER_TOKENS: usize = 512;
+// Each part of the body gets about this share of what the window has room for.
+const PARTS_PER_WINDOW: usize = 8;
+
+// The body in numbered parts of roughly equal token counts, sized to the model's context
+// window, with the types given once up front. Parts end at line breaks where there are
+// any; a body printed from the syntax tree is one long line, which is split between
+// tokens. What doesn't fit in the window is left out and counted.
+fn generate_large_function_prompt(func: &FunctionAnalysis, context_window: usize) -> String {
     let mut prompt = format!(
         "Summarize this large Rust function:\n\nName: {}\nSignature: {}\nTypes: {}\n\nFunction body in parts:\n",
         func.name,
         func.signature,
         func.types
     );
+    let closing = "\nPlease provide a summary of the function's purpose and behavior based on these parts.";
 
-    let body = func.body.as_deref().unwrap_or("");
-    let lines: Vec<&str> = body.lines().collect();
-    let chunk_size = 5;
-
-    for (i, chunk) in lines.chunks(chunk_size).enumerate() {
-        prompt.push_str(&format!("\nPart {}:\n", i + 1));
-        for line in chunk {
-            prompt.push_str(&format!("{}\n", line));
+    let room = context_window
+        .saturating_sub(ANSWER_TOKENS + calculate_tokens(&prompt) + calculate_tokens(closing))
+        .max(PARTS_PER_WINDOW);
+    let part_tokens = room / PARTS_PER_WINDOW;
+    let mut segments = Vec::new();
+    for line in func.body.as_deref().unwrap_or("").lines() {
+        if calculate_tokens(line) <= part_tokens {
+            segments.push(line.to_string());
+        } else {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            segments.extend(words.chunks(part_tokens.max(1)).map(|chunk| chunk.join(" ")));
         }
-        prompt.push_str(&format!("\nTypes: {}\n", func.types));
+    }
+    let mut used = 0;
+    let mut part = 0;
+    let mut part_used = 0;
+    for (i, line) in segments.iter().enumerate() {
+        let tokens = calculate_tokens(line);
+        if used + tokens > room {
+            let omitted: usize = segments[i..].iter().map(|line| calculate_tokens(line)).sum();
+            prompt.push_str(&format!("\n(the remaining {} tokens are omitted)\n", omitted));
+            break;
+        }
+        if part == 0 || part_used + tokens > part_tokens {
+            part += 1;
+            part_used = 0;
+            prompt.push_str(&format!("\nPart {}:\n", part));
+        }
+        prompt.push_str(line);
+        prompt.push('\n');
+        used += tokens;
+        part_used += tokens;
     }
 
-    prompt.push_str("\nPlease provide a summary of the function's purpose and behavior based on these parts.");
+    prompt.push_str(closing);
     prompt
 }
-
-impl From<Box<dyn std::error::Error>> for FolderSummaryError {
-    fn from(err: Box<dyn std::error::Error>) -> Self {
-        FolderSummaryError::AnalysisError(err.to_string())
-    }
-}
\ No newline at end of file
@@ -0,0 +1,275 @@
+use super::{
+    find_suppressions,
+    static_analysis::{extract_block, line_of, unrecognized_declarations, FunctionAnalysis},
+    CodeAnalysis, LanguageAnalyzer, ParserKind,
+};
+use crate::error::FolderSummaryError;
+use crate::llm::LLM;
+use async_trait::async_trait;
+use regex::Regex;
+
+/// Keywords and builtins that can start a command; they run no external program.
+const BUILTINS: &[&str] = &[
+    "fi", "for", "done", "case", "esac", "in", "function", "select", "return", "local",
+    "export", "readonly", "declare", "typeset", "unset", "set", "shift", "read", "echo",
+    "printf", "cd", "pwd", "pushd", "popd", "exit", "eval", "source", ".", "test", "true",
+    "false", "trap", "wait", "break", "continue", "let", "alias", "unalias", "type", "getopts",
+    "hash", "ulimit", "umask", "shopt", "mapfile", "readarray", "kill", "jobs", "bg", "fg",
+    "disown", "caller", "complete", "compgen", "setopt", "unsetopt", "autoload", "zmodload",
+    "print",
+];
+
+/// Words followed by a command, which is the one worth listing.
+const PREFIXES: &[&str] = &[
+    "if", "elif", "then", "else", "while", "until", "do", "!", "{", "}", "time", "exec",
+    "command", "builtin", "sudo", "nohup", "nice",
+];
+
+pub struct ShellAnalyzer;
+
+impl ShellAnalyzer {
+    // Files read with `source` or `.`, as written; they often start with a variable such
+    // as `$SCRIPT_DIR`.
+    fn extract_imports(content: &str) -> Vec<String> {
+        let source_regex = Regex::new(r#"(?m)^[ \t]*(?:source|\.)\s+["']?([^\s;"']+)"#).unwrap();
+        source_regex
+            .captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .collect()
+    }
+
+    // `name() {` and `function name {` definitions. Also returns a warning for each
+    // function whose closing brace wasn't found.
+    fn extract_functions(content: &str) -> (Vec<FunctionAnalysis>, Vec<String>) {
+        let function_regex = Regex::new(
+            r"(?m)^[ \t]*(?:function\s+([\w.:-]+)\s*(?:\(\s*\))?|([\w.:-]+)\s*\(\s*\))\s*\{",
+        )
+        .unwrap();
+        let positional_regex = Regex::new(r"\$\{?([1-9])").unwrap();
+        let return_regex = Regex::new(r"\breturn\s+[^;\s]").unwrap();
+        let mut functions = Vec::new();
+        let mut warnings = Vec::new();
+
+        for caps in function_regex.captures_iter(content) {
+            let name_match = caps.get(1).or_else(|| caps.get(2)).unwrap();
+            let name = name_match.as_str().to_string();
+            let line = line_of(content, name_match.start());
+            let open = caps.get(0).unwrap().end() - 1;
+            let function_body = extract_block(content, open, true).unwrap_or_else(|| {
+                warnings.push(format!(
+                    "line {}: no closing brace found for `{}`; its body runs to the end of the file",
+                    line, name
+                ));
+                content[open..].to_string()
+            });
+            let signature = content[caps.get(0).unwrap().start()..open]
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            functions.push(FunctionAnalysis {
+                name,
+                line: Some(line),
+                signature,
+                types: String::new(),
+                body: Some(function_body.clone()),
+                lines_of_code: function_body.lines().count(),
+                cyclomatic_complexity: Self::calculate_cyclomatic_complexity(&function_body),
+                // Shell functions declare no parameters; count the positional ones they read.
+                parameters: positional_regex
+                    .captures_iter(&function_body)
+                    .filter_map(|caps| caps[1].parse().ok())
+                    .max()
+                    .unwrap_or(0),
+                returns: return_regex.is_match(&function_body),
+                summary: None,
+                is_async: false,
+                blocking_calls: Vec::new(),
+                calls: Self::find_commands(&function_body),
+                decorators: Vec::new(),
+                skip_reason: None,
+            });
+        }
+
+        (functions, warnings)
+    }
+
+    // The programs and functions `script` runs: the first word of each command, after
+    // variable assignments and prefixes like `sudo`, leaving out keywords and builtins.
+    // Heredoc bodies, array literals and `case` patterns are skipped.
+    fn find_commands(script: &str) -> Vec<String> {
+        // `$(( ... ))` is arithmetic, not a command substitution.
+        let separator_regex = Regex::new(r"\$\(\([^)]*\)\)|\|\|?|&&|;|&|\$\(|`").unwrap();
+        let heredoc_regex = Regex::new(r#"<<-?\s*['"]?(\w+)['"]?"#).unwrap();
+        let array_regex = Regex::new(r"\w+\+?=\(").unwrap();
+        let case_pattern_regex = Regex::new(r"^\s*\(?[^()$`]*\)").unwrap();
+        let word_regex = Regex::new(r"^[A-Za-z_./][\w./+-]*$").unwrap();
+        let assignment_regex = Regex::new(r"^\w+(?:\[[^\]]*\])?\+?=").unwrap();
+        let mut commands: Vec<String> = Vec::new();
+        let mut heredoc: Option<String> = None;
+        let mut in_array = false;
+        let mut case_depth = 0;
+
+        for line in script.lines() {
+            if let Some(tag) = &heredoc {
+                if line.trim() == tag {
+                    heredoc = None;
+                }
+                continue;
+            }
+            let mut code = Self::strip_comment(line);
+            if in_array {
+                in_array = !code.contains(')');
+                continue;
+            }
+            if let Some(caps) = heredoc_regex.captures(code) {
+                heredoc = Some(caps[1].to_string());
+            }
+            // An array literal continued on the next lines: `packages=(`.
+            if array_regex.is_match(code) && !code.contains(')') {
+                in_array = true;
+            }
+            let words: Vec<&str> = code.split_whitespace().collect();
+            if words.contains(&"case") {
+                case_depth += 1;
+            } else if case_depth > 0 {
+                if let Some(pattern) = case_pattern_regex.find(code) {
+                    code = &code[pattern.end()..];
+                }
+            }
+            if words.contains(&"esac") {
+                case_depth -= 1;
+            }
+
+            for segment in separator_regex.split(code) {
+                for word in segment.split_whitespace() {
+                    if PREFIXES.contains(&word) {
+                        continue;
+                    }
+                    // `arr=(a b)` assigns a list; `VAR=1 make` runs `make`.
+                    if assignment_regex.is_match(word) {
+                        if word.contains("=(") {
+                            break;
+                        }
+                        continue;
+                    }
+                    // Subshell parentheses and the quotes around a substitution.
+                    let word = word.trim_matches(['(', ')', '"', '\'']);
+                    if word_regex.is_match(word)
+                        && !BUILTINS.contains(&word)
+                        && !commands.iter().any(|command| command == word)
+                    {
+                        commands.push(word.to_string());
+                    }
+                    break;
+                }
+            }
+        }
+        commands
+    }
+
+    // `line` up to a `#` that starts a word; `$#` and `${#name}` aren't comments.
+    fn strip_comment(line: &str) -> &str {
+        let bytes = line.as_bytes();
+        let comment = (0..bytes.len()).find(|&i| {
+            bytes[i] == b'#' && (i == 0 || bytes[i - 1].is_ascii_whitespace() || bytes[i - 1] == b';')
+        });
+        &line[..comment.unwrap_or(line.len())]
+    }
+
+    // Variables and functions handed to child processes with `export`.
+    fn extract_exports(content: &str) -> Vec<String> {
+        let export_regex = Regex::new(r"(?m)^[ \t]*export\s+(.*)").unwrap();
+        let name_regex = Regex::new(r"^[A-Za-z_]\w*$").unwrap();
+        let mut exports = Vec::new();
+        for caps in export_regex.captures_iter(content) {
+            let names = Self::strip_comment(&caps[1])
+                .split_whitespace()
+                .filter(|word| !word.starts_with('-'))
+                .map(|word| word.split('=').next().unwrap_or_default())
+                .take_while(|name| name_regex.is_match(name));
+            for name in names {
+                if !exports.iter().any(|export| export == name) {
+                    exports.push(name.to_string());
+                }
+            }
+        }
+        exports
+    }
+
+    // `;;` ends each `case` branch.
+    fn calculate_cyclomatic_complexity(function_body: &str) -> usize {
+        let control_flow_regex =
+            Regex::new(r"\b(?:if|elif|for|while|until)\b|;;|&&|\|\|").unwrap();
+        1 + control_flow_regex.find_iter(function_body).count()
+    }
+}
+
+#[async_trait]
+impl LanguageAnalyzer for ShellAnalyzer {
+    fn name(&self) -> &'static str {
+        "Shell"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".sh", ".bash", ".zsh"]
+    }
+
+    fn parser_kind(&self) -> ParserKind {
+        ParserKind::Regex
+    }
+
+    fn fence_language(&self) -> &'static str {
+        "sh"
+    }
+
+    fn analyze(&self, content: &str) -> Result<CodeAnalysis, FolderSummaryError> {
+        let (functions, mut warnings) = Self::extract_functions(content);
+        // `function` definitions the pattern skips, such as ones with a `( ... )` subshell
+        // body.
+        let declaration_regex = Regex::new(r"(?m)^[ \t]*function\s+[\w.:-]+").unwrap();
+        warnings.extend(unrecognized_declarations(content, &declaration_regex, &functions));
+        // The script's own functions aren't external programs.
+        let commands = Self::find_commands(content)
+            .into_iter()
+            .filter(|command| !functions.iter().any(|func| &func.name == command))
+            .collect();
+        Ok(CodeAnalysis {
+            imports: Self::extract_imports(content),
+            functions,
+            types: Vec::new(),
+            significant_types: Vec::new(),
+            exports: Self::extract_exports(content),
+            generated: None,
+            suppressions: find_suppressions(content),
+            warnings,
+            commands,
+            fence_language: None,
+        })
+    }
+
+    async fn summarize(
+        &self,
+        analysis: &CodeAnalysis,
+        llm: &dyn LLM,
+    ) -> Result<CodeAnalysis, FolderSummaryError> {
+        let mut summarized = analysis.clone();
+        for func in &mut summarized.functions {
+            if self.needs_summary(func) {
+                let commands = if func.calls.is_empty() {
+                    String::new()
+                } else {
+                    format!("Commands: {}\n", func.calls.join(", "))
+                };
+                let prompt = format!(
+                    "Summarize the following shell function:\n\nName: {}\n{}Body: {}",
+                    func.name,
+                    commands,
+                    func.body.as_deref().unwrap_or("(Function body not available)")
+                );
+                func.summary = Some(llm.summarize(&prompt).await?);
+            }
+        }
+        Ok(summarized)
+    }
+}
@@ -10,6 +10,9 @@ use syn::{
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FunctionAnalysis {
     pub name: String,
+    /// 1-based line the function is declared on.
+    #[serde(default)]
+    pub line: Option<usize>,
     pub signature: String,
     pub types: String,
     pub body: Option<String>,
@@ -18,27 +21,60 @@ pub struct FunctionAnalysis {
     pub parameters: usize,
     pub returns: bool,
     pub summary: Option<String>,
+    #[serde(default)]
+    pub is_async: bool,
+    /// Blocking calls (sync file IO, sleeps, blocking HTTP) made inside an async function,
+    /// which stall the executor thread running it.
+    #[serde(default)]
+    pub blocking_calls: Vec<String>,
+    /// Names of the functions and methods this one calls, each listed once, for finding
+    /// its collaborators in the rest of the project.
+    #[serde(default)]
+    pub calls: Vec<String>,
+    /// Decorators applied to the function, such as `@staticmethod`, in source order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub decorators: Vec<String>,
+    /// Why the function was not sent to the LLM, when it was deliberately skipped.
+    #[serde(default)]
+    pub skip_reason: Option<String>,
 }
 
 pub fn extract_function_metrics(func: &ItemFn) -> FunctionAnalysis {
     let name = func.sig.ident.to_string();
+    let line = func.sig.ident.span().start().line;
     let (signature, types) = extract_signature_and_types(&func.sig);
     let body = extract_function_body(func);
     let lines_of_code = count_lines_of_code(func);
     let cyclomatic_complexity = calculate_cyclomatic_complexity(func);
     let parameters = func.sig.inputs.len();
     let returns = func.sig.output != syn::ReturnType::Default;
+    let is_async = func.sig.asyncness.is_some();
+    let blocking_calls = if is_async {
+        let mut visitor = BlockingVisitor::default();
+        visitor.visit_block(&func.block);
+        visitor.calls
+    } else {
+        Vec::new()
+    };
+    let mut call_visitor = CallVisitor::default();
+    call_visitor.visit_block(&func.block);
 
     FunctionAnalysis {
         name,
+        line: Some(line),
         signature,
         types,
-        body: if lines_of_code <= 20 { Some(body) } else { None },
+        body: Some(body),
         lines_of_code,
         cyclomatic_complexity,
         parameters,
         returns,
         summary: None,
+        is_async,
+        blocking_calls,
+        calls: call_visitor.calls,
+        decorators: Vec::new(),
+        skip_reason: None,
     }
 }
 
@@ -109,3 +145,206 @@ impl<'ast> Visit<'ast> for ComplexityVisitor {
         visit::visit_expr_loop(self, node);
     }
 }
+
+/// Standard library and blocking-client calls that hold up an async executor thread.
+const RUST_BLOCKING_CALLS: &[&str] = &[
+    "thread::sleep",
+    "fs::read",
+    "fs::read_to_string",
+    "fs::read_dir",
+    "fs::write",
+    "fs::copy",
+    "fs::rename",
+    "fs::remove_file",
+    "fs::remove_dir_all",
+    "fs::create_dir_all",
+    "fs::metadata",
+    "File::open",
+    "File::create",
+    "TcpStream::connect",
+    "blocking::get",
+];
+
+// Finds calls to `RUST_BLOCKING_CALLS` that aren't awaited, so `tokio::fs::read(..).await`
+// passes while `std::fs::read(..)` doesn't.
+#[derive(Default)]
+struct BlockingVisitor {
+    calls: Vec<String>,
+    awaited: bool,
+}
+
+impl<'ast> Visit<'ast> for BlockingVisitor {
+    fn visit_expr(&mut self, node: &'ast syn::Expr) {
+        let awaited = std::mem::take(&mut self.awaited);
+        if let syn::Expr::Call(call) = node {
+            if let syn::Expr::Path(path) = &*call.func {
+                let path = path.to_token_stream().to_string().replace(' ', "");
+                let async_crate = path.starts_with("tokio::") || path.starts_with("async_std::");
+                let blocking = RUST_BLOCKING_CALLS
+                    .iter()
+                    .any(|name| path == *name || path.ends_with(&format!("::{}", name)));
+                if blocking && !awaited && !async_crate && !self.calls.contains(&path) {
+                    self.calls.push(path);
+                }
+            }
+        }
+        visit::visit_expr(self, node);
+    }
+
+    fn visit_expr_await(&mut self, node: &'ast syn::ExprAwait) {
+        self.awaited = true;
+        visit::visit_expr_await(self, node);
+    }
+
+    // Closures and async blocks are analyzed as part of the function; nested items aren't.
+    fn visit_item(&mut self, _node: &'ast syn::Item) {}
+}
+
+#[derive(Default)]
+struct CallVisitor {
+    calls: Vec<String>,
+}
+
+impl CallVisitor {
+    fn add(&mut self, name: String) {
+        if !self.calls.contains(&name) {
+            self.calls.push(name);
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for CallVisitor {
+    fn visit_expr_call(&mut self, node: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(path) = &*node.func {
+            if let Some(segment) = path.path.segments.last() {
+                self.add(segment.ident.to_string());
+            }
+        }
+        visit::visit_expr_call(self, node);
+    }
+
+    fn visit_expr_method_call(&mut self, node: &'ast syn::ExprMethodCall) {
+        self.add(node.method.to_string());
+        visit::visit_expr_method_call(self, node);
+    }
+
+    fn visit_item(&mut self, _node: &'ast syn::Item) {}
+}
+
+// Words followed by `(` that aren't calls in the regex-analyzed languages.
+const CALL_KEYWORDS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "return", "function", "typeof", "await", "new",
+    "elif", "and", "or", "not", "in", "def", "lambda", "with", "assert", "except", "yield",
+    "super", "elseif", "foreach", "array", "isset", "empty", "unset", "list", "match", "fn",
+    "use",
+];
+
+/// A name followed by an opening parenthesis, for [`find_calls`].
+pub const CALL_PATTERN: &str = r"([A-Za-z_$][\w$]*)\s*\(";
+
+/// The names called in `body`, each listed once, for the regex-based analyzers; `name`
+/// itself is left out so a declaration isn't read as a call. `pattern` is
+/// [`CALL_PATTERN`], compiled once by the caller.
+pub fn find_calls(body: &str, name: &str, pattern: &Regex) -> Vec<String> {
+    let mut calls: Vec<String> = Vec::new();
+    for caps in pattern.captures_iter(body) {
+        let call = &caps[1];
+        if call != name && !CALL_KEYWORDS.contains(&call) && !calls.iter().any(|c| c == call) {
+            calls.push(call.to_string());
+        }
+    }
+    calls
+}
+
+/// The distinct matches of `pattern` in `body`, for the regex-based analyzers to list
+/// blocking calls inside async functions.
+pub fn find_blocking_calls(body: &str, pattern: &Regex) -> Vec<String> {
+    let mut calls: Vec<String> = Vec::new();
+    for found in pattern.find_iter(body) {
+        let call = found.as_str().trim_end_matches('(').to_string();
+        if !calls.contains(&call) {
+            calls.push(call);
+        }
+    }
+    calls
+}
+
+/// From the `{` at byte `start` to its matching `}`, for the regex-based analyzers of
+/// brace-delimited languages. Braces in quoted strings and in `//` and `/* */` comments
+/// don't count, nor in `#` comments when `hash_comments` is set, as in PHP and shell. Such
+/// a comment starts with a `#` at the start of a word, so shell's `${#name}` and PHP's `#[`
+/// attributes aren't comments. `None` when the block is never closed.
+pub fn extract_block(content: &str, start: usize, hash_comments: bool) -> Option<String> {
+    let bytes = content.as_bytes();
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' | b'\'' => {
+                let quote = bytes[i];
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote && bytes[i] != b'\n' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'#' if hash_comments
+                && bytes.get(i + 1) != Some(&b'[')
+                && (i == 0 || bytes[i - 1].is_ascii_whitespace() || bytes[i - 1] == b';') =>
+            {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i + 1 < bytes.len() && !(bytes[i] == b'*' && bytes[i + 1] == b'/') {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(content[start..=i].to_string());
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// 1-based line number of byte `offset` in `content`, for the regex-based analyzers.
+pub fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+/// Warnings for the lines matching `declaration` that none of `functions` is on: function
+/// declarations a regex analyzer skipped because its patterns don't recognize them.
+pub fn unrecognized_declarations(
+    content: &str,
+    declaration: &Regex,
+    functions: &[FunctionAnalysis],
+) -> Vec<String> {
+    declaration
+        .find_iter(content)
+        .filter_map(|m| {
+            let line = line_of(content, m.start());
+            if functions.iter().any(|f| f.line == Some(line)) {
+                return None;
+            }
+            let text = content[m.start()..].lines().next().unwrap_or_default().trim();
+            Some(format!("line {}: could not parse `{}`", line, text))
+        })
+        .collect()
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+/// Silences findings on its own line and the one after, e.g. above a function.
+const LINE_MARKER: &str = "folder-summary:ignore";
+/// Silences findings anywhere in the file.
+const FILE_MARKER: &str = "folder-summary:ignore-file";
+
+/// An inline `folder-summary:ignore` comment. It may name the rules it silences,
+/// `// folder-summary:ignore long-function, high-complexity`, and give a reason after
+/// ` -- `.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suppression {
+    /// 1-based line of the comment; `None` for `folder-summary:ignore-file`.
+    pub line: Option<usize>,
+    /// The rules it silences; empty for all of them.
+    pub rules: Vec<String>,
+}
+
+impl Suppression {
+    /// Whether a finding of `rule` at `line` (`None` for the whole file) is silenced.
+    pub fn covers(&self, rule: &str, line: Option<usize>) -> bool {
+        let applies = match self.line {
+            None => true,
+            Some(marker) => line.is_some_and(|line| line == marker || line == marker + 1),
+        };
+        applies && (self.rules.is_empty() || self.rules.iter().any(|r| r == rule))
+    }
+}
+
+/// The suppression comments in `content`, in whatever comment syntax the language uses.
+pub fn find_suppressions(content: &str) -> Vec<Suppression> {
+    let mut suppressions = Vec::new();
+    for (index, text) in content.lines().enumerate() {
+        let Some(start) = text.find(LINE_MARKER) else {
+            continue;
+        };
+        let rest = &text[start..];
+        let (line, rest) = match rest.strip_prefix(FILE_MARKER) {
+            Some(rest) => (None, rest),
+            None => (Some(index + 1), &rest[LINE_MARKER.len()..]),
+        };
+        // `ignore-files` or `ignored` are not markers.
+        if rest.starts_with(|c: char| c.is_alphanumeric() || c == '-' || c == '_') {
+            continue;
+        }
+        let rules = rest
+            .split(" -- ")
+            .next()
+            .unwrap_or("")
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|rule| {
+                !rule.is_empty() && rule.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+            })
+            .map(str::to_string)
+            .collect();
+        suppressions.push(Suppression { line, rules });
+    }
+    suppressions
+}
@@ -0,0 +1,292 @@
+use super::{
+    find_suppressions,
+    static_analysis::{
+        extract_block, find_calls, line_of, unrecognized_declarations, FunctionAnalysis,
+        CALL_PATTERN,
+    },
+    summarize_types, CodeAnalysis, LanguageAnalyzer, ParserKind, TypeAnalysis, MIN_TYPE_MEMBERS,
+};
+use crate::error::FolderSummaryError;
+use crate::llm::LLM;
+use async_trait::async_trait;
+use regex::Regex;
+
+const MODIFIERS: &str = r"(?:(?:public|private|fileprivate|internal|package|open|static|class|final|override|mutating|nonmutating|convenience|required|dynamic|nonisolated|indirect|lazy|weak|unowned)\s+)*";
+
+// Attributes such as `@MainActor` or `@available(iOS 15, *)`.
+const ATTRIBUTES: &str = r"(?:@\w+(?:\([^)]*\))?\s+)*";
+
+pub struct SwiftAnalyzer;
+
+impl SwiftAnalyzer {
+    fn extract_imports(content: &str) -> Vec<String> {
+        let import_regex = Regex::new(&format!(
+            r"(?m)^[ \t]*{}import\s+(?:(?:typealias|struct|class|enum|protocol|let|var|func)\s+)?([\w.]+)",
+            ATTRIBUTES
+        ))
+        .unwrap();
+        import_regex
+            .captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .collect()
+    }
+
+    // Functions and initializers; computed properties and closures are left out. Also
+    // returns a warning for each function whose closing brace wasn't found.
+    fn extract_functions(content: &str) -> (Vec<FunctionAnalysis>, Vec<String>) {
+        // Parameters may hold one level of parentheses, as closure types like
+        // `(Int) -> Void` do.
+        let function_regex = Regex::new(&format!(
+            r"(?m)^[ \t]*({}){}(?:func\s+(\w+|[-+*/%=<>!&|^~?.]+)|(init)[?!]?)\s*(<[^>]*>)?\s*\(((?:[^()]|\([^()]*\))*)\)\s*((?:async\s+)?(?:throws|rethrows)?)\s*(?:->\s*([^{{\n]+?))?\s*(?:where\s+[^{{]+)?\{{",
+            ATTRIBUTES, MODIFIERS
+        ))
+        .unwrap();
+        let attribute_regex = Regex::new(r"@\w+(?:\([^)]*\))?").unwrap();
+        let call_regex = Regex::new(CALL_PATTERN).unwrap();
+        let mut functions = Vec::new();
+        let mut warnings = Vec::new();
+
+        for caps in function_regex.captures_iter(content) {
+            let name_match = caps.get(2).or_else(|| caps.get(3)).unwrap();
+            let name = name_match.as_str().to_string();
+            let line = line_of(content, name_match.start());
+            let open = caps.get(0).unwrap().end() - 1;
+            let function_body = extract_block(content, open, false).unwrap_or_else(|| {
+                warnings.push(format!(
+                    "line {}: no closing brace found for `{}`; its body runs to the end of the file",
+                    line, name
+                ));
+                content[open..].to_string()
+            });
+            let params = caps[5].split_whitespace().collect::<Vec<_>>().join(" ");
+            let return_type = caps.get(7).map_or("", |m| m.as_str().trim());
+            // The declaration without its attributes, on one line.
+            let signature = content[caps.get(1).unwrap().end()..open]
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let calls = find_calls(&function_body, &name, &call_regex);
+
+            functions.push(FunctionAnalysis {
+                name,
+                line: Some(line),
+                signature,
+                types: return_type.to_string(),
+                body: Some(function_body.clone()),
+                lines_of_code: function_body.lines().count(),
+                cyclomatic_complexity: Self::calculate_cyclomatic_complexity(&function_body),
+                parameters: Self::count_parameters(&params),
+                returns: !matches!(return_type, "" | "Void" | "()" | "Never"),
+                summary: None,
+                is_async: caps[6].contains("async"),
+                blocking_calls: Vec::new(),
+                calls,
+                decorators: attribute_regex
+                    .find_iter(&caps[1])
+                    .map(|m| m.as_str().to_string())
+                    .collect(),
+                skip_reason: None,
+            });
+        }
+
+        (functions, warnings)
+    }
+
+    // Parameters separated by commas outside brackets, so `[String: Int]`, `Result<A, B>`
+    // and `(Int, Int) -> Void` each belong to one.
+    fn count_parameters(params: &str) -> usize {
+        if params.trim().is_empty() {
+            return 0;
+        }
+        let mut depth = 0;
+        let mut count = 1;
+        let mut previous = ' ';
+        for c in params.chars() {
+            match c {
+                '(' | '[' | '<' => depth += 1,
+                // The `>` of a `->` arrow closes nothing.
+                '>' if previous == '-' => {}
+                ')' | ']' | '>' => depth -= 1,
+                ',' if depth == 0 => count += 1,
+                _ => {}
+            }
+            previous = c;
+        }
+        count
+    }
+
+    // Extensions are listed as `extension Name`, since the type they extend is usually
+    // declared elsewhere.
+    fn extract_types(content: &str) -> Vec<String> {
+        let type_regex = Regex::new(&format!(
+            r"(?m)^[ \t]*{}{}(struct|class|protocol|enum|actor|extension)\s+([\w.]+)",
+            ATTRIBUTES, MODIFIERS
+        ))
+        .unwrap();
+        type_regex
+            .captures_iter(content)
+            // `class func` and `class var` are type members, not classes.
+            .filter(|caps| !matches!(&caps[2], "func" | "var" | "let" | "subscript" | "init"))
+            .map(|caps| match &caps[1] {
+                "extension" => format!("extension {}", &caps[2]),
+                _ => caps[2].to_string(),
+            })
+            .collect()
+    }
+
+    // Classes and actors with enough methods, protocols with enough requirements and
+    // structs with enough stored properties, outlined as their declaration and member
+    // declarations.
+    fn extract_significant_types(content: &str) -> Vec<TypeAnalysis> {
+        let type_regex = Regex::new(&format!(
+            r"(?m)^[ \t]*{}{}(struct|class|protocol|actor)\s+(\w+)[^{{]*\{{",
+            ATTRIBUTES, MODIFIERS
+        ))
+        .unwrap();
+        let method_regex = Regex::new(&format!(
+            r"^\s*{}{}(?:func\s|init\W|subscript\W)",
+            ATTRIBUTES, MODIFIERS
+        ))
+        .unwrap();
+        let property_regex =
+            Regex::new(&format!(r"^\s*{}{}(?:var|let)\s+\w+", ATTRIBUTES, MODIFIERS)).unwrap();
+        let mut types = Vec::new();
+        for caps in type_regex.captures_iter(content) {
+            if matches!(&caps[2], "func" | "var" | "let" | "subscript" | "init") {
+                continue;
+            }
+            let kind = &caps[1];
+            let header = caps.get(0).unwrap();
+            let open = header.end() - 1;
+            let body = extract_block(content, open, false).unwrap_or_else(|| content[open..].to_string());
+            let declaration = header.as_str().split_whitespace().collect::<Vec<_>>().join(" ");
+            let mut outline = vec![declaration.trim_end_matches('{').trim_end().to_string()];
+            let mut members = 0;
+            let mut depth = 0;
+            for line in body.lines() {
+                if depth == 1 {
+                    let method = method_regex.is_match(line);
+                    let property = property_regex.is_match(line);
+                    if method || property {
+                        let declaration = line.split('{').next().unwrap_or_default();
+                        outline.push(format!("  {}", declaration.trim()));
+                    }
+                    // A struct's size is its stored properties: not static, and not
+                    // computed with a `{ ... }` body.
+                    let counted = match kind {
+                        "struct" => property && !line.contains('{') && !line.contains("static "),
+                        "protocol" => method || property,
+                        _ => method,
+                    };
+                    if counted {
+                        members += 1;
+                    }
+                }
+                // Braces in a trailing `//` comment don't open or close anything.
+                let code = line.split("//").next().unwrap_or_default();
+                depth += code.matches('{').count() as i32 - code.matches('}').count() as i32;
+            }
+            if members >= MIN_TYPE_MEMBERS {
+                types.push(TypeAnalysis {
+                    name: caps[2].to_string(),
+                    kind: kind.to_string(),
+                    line: Some(line_of(content, caps.get(2).unwrap().start())),
+                    members,
+                    definition: outline.join("\n"),
+                    summary: None,
+                });
+            }
+        }
+        types
+    }
+
+    // Top-level declarations other modules can use: `public` and `open` ones.
+    fn extract_exports(content: &str) -> Vec<String> {
+        let public_regex = Regex::new(&format!(
+            r"(?m)^{}(?:public|open)\s+(?:(?:final|indirect)\s+)*(?:struct|class|protocol|enum|actor|func|typealias)\s+(\w+)",
+            ATTRIBUTES
+        ))
+        .unwrap();
+        public_regex
+            .captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .collect()
+    }
+
+    fn calculate_cyclomatic_complexity(function_body: &str) -> usize {
+        let control_flow_regex =
+            Regex::new(r"\b(?:if|guard|for|while|case|catch)\b|&&|\|\||\?\?").unwrap();
+        1 + control_flow_regex.find_iter(function_body).count()
+    }
+}
+
+#[async_trait]
+impl LanguageAnalyzer for SwiftAnalyzer {
+    fn name(&self) -> &'static str {
+        "Swift"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".swift"]
+    }
+
+    fn parser_kind(&self) -> ParserKind {
+        ParserKind::Regex
+    }
+
+    fn fence_language(&self) -> &'static str {
+        "swift"
+    }
+
+    // Getters, setters and other one-statement methods say what they do in their name.
+    fn needs_summary(&self, func: &FunctionAnalysis) -> bool {
+        func.lines_of_code > 3
+    }
+
+    fn analyze(&self, content: &str) -> Result<CodeAnalysis, FolderSummaryError> {
+        let (functions, mut warnings) = Self::extract_functions(content);
+        // Functions with a body that the pattern skips; protocol requirements have no body
+        // and are left alone.
+        let declaration_regex = Regex::new(r"(?m)^[^/\n]*\bfunc\s+[^{}\n;]*\{").unwrap();
+        warnings.extend(unrecognized_declarations(content, &declaration_regex, &functions));
+        Ok(CodeAnalysis {
+            imports: Self::extract_imports(content),
+            functions,
+            types: Self::extract_types(content),
+            significant_types: Self::extract_significant_types(content),
+            exports: Self::extract_exports(content),
+            generated: None,
+            suppressions: find_suppressions(content),
+            warnings,
+            commands: Vec::new(),
+            fence_language: None,
+        })
+    }
+
+    async fn summarize(
+        &self,
+        analysis: &CodeAnalysis,
+        llm: &dyn LLM,
+    ) -> Result<CodeAnalysis, FolderSummaryError> {
+        let mut summarized = analysis.clone();
+        for func in &mut summarized.functions {
+            if self.needs_summary(func) {
+                let attributes = if func.decorators.is_empty() {
+                    String::new()
+                } else {
+                    format!("Attributes: {}\n", func.decorators.join(" "))
+                };
+                let prompt = format!(
+                    "Summarize the following Swift function:\n\nName: {}\n{}Signature: {}\nBody: {}",
+                    func.name,
+                    attributes,
+                    func.signature,
+                    func.body.as_deref().unwrap_or("(Function body not available)")
+                );
+                func.summary = Some(llm.summarize(&prompt).await?);
+            }
+        }
+        summarize_types(&mut summarized, "Swift", llm).await?;
+        Ok(summarized)
+    }
+}
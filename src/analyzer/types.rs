@@ -0,0 +1,57 @@
+use super::CodeAnalysis;
+use crate::error::FolderSummaryError;
+use crate::llm::LLM;
+use serde::{Deserialize, Serialize};
+
+/// Fields, variants or methods a type needs before it gets a summary of its own.
+pub const MIN_TYPE_MEMBERS: usize = 5;
+
+/// A struct, enum or class large enough to be summarized like a function.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeAnalysis {
+    pub name: String,
+    /// `struct`, `enum`, `class` or `interface`.
+    pub kind: String,
+    /// 1-based line the type is declared on.
+    pub line: Option<usize>,
+    /// Fields of a struct, variants of an enum, methods of a class or members of an
+    /// interface.
+    pub members: usize,
+    /// What the LLM is shown: the whole struct or enum, or a class's declaration and the
+    /// signatures of its methods.
+    pub definition: String,
+    pub summary: Option<String>,
+}
+
+impl TypeAnalysis {
+    /// What [`members`](Self::members) counts, in the plural.
+    pub fn member_noun(&self) -> &'static str {
+        match self.kind.as_str() {
+            "struct" => "fields",
+            "enum" => "variants",
+            "class" => "methods",
+            _ => "members",
+        }
+    }
+}
+
+/// Asks `llm` for a summary of each of the file's significant types, as `language` code;
+/// each analyzer's `summarize` calls this after summarizing the functions.
+pub async fn summarize_types(
+    analysis: &mut CodeAnalysis,
+    language: &str,
+    llm: &dyn LLM,
+) -> Result<(), FolderSummaryError> {
+    for ty in &mut analysis.significant_types {
+        let prompt = format!(
+            "Summarize the following {} {}: what it represents and what it is used for, rather than each of its {}.\n\nName: {}\nDefinition: {}",
+            language,
+            ty.kind,
+            ty.member_noun(),
+            ty.name,
+            ty.definition
+        );
+        ty.summary = Some(llm.summarize(&prompt).await?);
+    }
+    Ok(())
+}
@@ -1,89 +1,263 @@
+use log::warn;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs::{File, OpenOptions};
-use std::io::{BufReader, BufWriter};
-use std::path::Path;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::task;
 
 use crate::analyzer::CodeAnalysis;
 use crate::error::FolderSummaryError;
+use crate::utils::write_atomic_with;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 struct CacheEntry {
     last_modified: u64,
     analysis: CodeAnalysis,
+    /// False for static-only results whose LLM phase hasn't completed yet.
+    #[serde(default = "default_summarized")]
+    summarized: bool,
+    /// The file's report section as last rendered from `analysis`.
+    #[serde(default)]
+    rendered: Option<String>,
+    /// LLM summary of a documentation file; such entries carry an empty `analysis`.
+    #[serde(default)]
+    doc_summary: Option<String>,
 }
 
-pub struct Cache {
-    cache_file: String,
-    cache: HashMap<String, CacheEntry>,
+// Entries written before static results were cached separately were always summarized.
+fn default_summarized() -> bool {
+    true
 }
 
-unsafe impl Send for Cache {}
-unsafe impl Sync for Cache {}
+// How often changes are written out during a run, so a crash loses at most this much.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+type Entries = HashMap<String, Arc<CacheEntry>>;
+
+/// Analyses and rendered sections by file, kept across runs.
+///
+/// Entries are looked up and stored against a modification time the caller reads with
+/// [`modified_secs`] beforehand, so nothing here waits on the disk. Changes are held in
+/// memory and written out from a snapshot on a blocking thread: at most every 30 seconds
+/// while they come in, by [`Cache::flush`], and when the cache is dropped.
+pub struct Cache {
+    /// Where the cache is persisted; `None` keeps it in memory for the current run only.
+    cache_file: Option<Arc<CacheFile>>,
+    cache: Entries,
+    /// Whether `cache` has changes not yet handed to `cache_file`.
+    dirty: bool,
+    last_flush: Instant,
+    /// Numbers snapshots, so an older one never overwrites a newer one on disk.
+    snapshots: u64,
+}
 
 impl Cache {
-    pub fn new(cache_file: &str) -> Result<Self, FolderSummaryError> {
-        let cache = if Path::new(cache_file).exists() {
-            let file = File::open(cache_file)?;
+    pub fn new(cache_file: impl AsRef<Path>) -> Result<Self, FolderSummaryError> {
+        let cache_file = cache_file.as_ref();
+        let cache = if cache_file.exists() {
+            let file = File::open(cache_file)
+                .map_err(|e| FolderSummaryError::file(cache_file.display().to_string(), e))?;
             let reader = BufReader::new(file);
             serde_json::from_reader(reader).unwrap_or_else(|_| HashMap::new())
         } else {
+            // The first run on a machine finds no cache directory yet.
+            let parent = cache_file.parent().filter(|parent| !parent.as_os_str().is_empty());
+            if let Some(parent) = parent {
+                fs::create_dir_all(parent)
+                    .map_err(|e| FolderSummaryError::file(parent.display().to_string(), e))?;
+            }
             HashMap::new()
         };
 
         Ok(Cache {
-            cache_file: cache_file.to_string(),
+            cache_file: Some(Arc::new(CacheFile {
+                path: cache_file.to_path_buf(),
+                written: Mutex::new(0),
+            })),
             cache,
+            dirty: false,
+            last_flush: Instant::now(),
+            snapshots: 0,
         })
     }
 
-    pub fn get(&self, file_path: &str) -> Option<&CodeAnalysis> {
-        let metadata = std::fs::metadata(file_path).ok()?;
-        let last_modified = metadata
-            .modified()
-            .ok()?
-            .duration_since(std::time::UNIX_EPOCH)
-            .ok()?
-            .as_secs();
-
-        self.cache.get(file_path).and_then(|entry| {
-            if entry.last_modified == last_modified {
-                Some(&entry.analysis)
-            } else {
-                None
+    /// A cache that starts empty and is never written to disk.
+    pub fn in_memory() -> Self {
+        Cache {
+            cache_file: None,
+            cache: HashMap::new(),
+            dirty: false,
+            last_flush: Instant::now(),
+            snapshots: 0,
+        }
+    }
+
+    /// Returns the fully summarized analysis of a file last modified at `last_modified`.
+    pub fn get(&self, file_path: &str, last_modified: u64) -> Option<&CodeAnalysis> {
+        self.get_entry(file_path, last_modified)
+            .filter(|entry| entry.summarized)
+            .map(|entry| &entry.analysis)
+    }
+
+    /// Returns the analysis of an unchanged file and whether it has been summarized yet.
+    pub fn get_any(&self, file_path: &str, last_modified: u64) -> Option<(&CodeAnalysis, bool)> {
+        self.get_entry(file_path, last_modified)
+            .map(|entry| (&entry.analysis, entry.summarized))
+    }
+
+    /// Every cached code analysis, including those of files changed since they were
+    /// cached; documentation summaries are left out.
+    pub fn analyses(&self) -> impl Iterator<Item = (&str, &CodeAnalysis)> {
+        self.cache
+            .iter()
+            .filter(|(_, entry)| entry.doc_summary.is_none())
+            .map(|(file_path, entry)| (file_path.as_str(), &entry.analysis))
+    }
+
+    /// Returns the report section rendered for an unchanged file by an earlier run.
+    pub fn get_rendered(&self, file_path: &str, last_modified: u64) -> Option<&str> {
+        self.get_entry(file_path, last_modified)
+            .and_then(|entry| entry.rendered.as_deref())
+    }
+
+    /// Stores freshly rendered report sections next to the analyses they came from.
+    pub fn set_rendered(&mut self, sections: HashMap<String, String>) {
+        for (file_path, section) in sections {
+            if let Some(entry) = self.cache.get_mut(&file_path) {
+                Arc::make_mut(entry).rendered = Some(section);
             }
-        })
+        }
+        self.changed();
+    }
+
+    fn get_entry(&self, file_path: &str, last_modified: u64) -> Option<&CacheEntry> {
+        self.cache
+            .get(file_path)
+            .filter(|entry| entry.last_modified == last_modified)
+            .map(Arc::as_ref)
     }
 
     pub fn set(
         &mut self,
         file_path: String,
+        last_modified: u64,
         analysis: CodeAnalysis,
-    ) -> Result<(), FolderSummaryError> {
-        let metadata = std::fs::metadata(&file_path)?;
-        let last_modified = metadata
-            .modified()?
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs();
-
+        summarized: bool,
+    ) {
         self.cache.insert(
             file_path,
-            CacheEntry {
+            Arc::new(CacheEntry {
                 last_modified,
                 analysis,
-            },
+                summarized,
+                rendered: None,
+                doc_summary: None,
+            }),
         );
-        self.save()
+        self.changed();
     }
 
-    fn save(&self) -> Result<(), FolderSummaryError> {
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&self.cache_file)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer(writer, &self.cache)?;
+    /// Returns the summary of a documentation file last modified at `last_modified`.
+    pub fn get_doc_summary(&self, file_path: &str, last_modified: u64) -> Option<&str> {
+        self.get_entry(file_path, last_modified)
+            .and_then(|entry| entry.doc_summary.as_deref())
+    }
+
+    pub fn set_doc_summary(&mut self, file_path: String, last_modified: u64, summary: String) {
+        self.cache.insert(
+            file_path,
+            Arc::new(CacheEntry {
+                last_modified,
+                analysis: CodeAnalysis::default(),
+                summarized: true,
+                rendered: None,
+                doc_summary: Some(summary),
+            }),
+        );
+        self.changed();
+    }
+
+    /// Writes pending changes to the cache file, once any earlier write has finished.
+    pub async fn flush(&mut self) -> Result<(), FolderSummaryError> {
+        self.last_flush = Instant::now();
+        let Some(write) = self.snapshot() else {
+            return Ok(());
+        };
+        task::spawn_blocking(write)
+            .await
+            .map_err(|e| FolderSummaryError::TaskJoinError(e.to_string()))?
+    }
+
+    fn changed(&mut self) {
+        self.dirty = true;
+        if self.last_flush.elapsed() < FLUSH_INTERVAL {
+            return;
+        }
+        self.last_flush = Instant::now();
+        if let Some(write) = self.snapshot() {
+            task::spawn_blocking(move || {
+                if let Err(e) = write() {
+                    warn!("Could not save the cache: {}", e);
+                }
+            });
+        }
+    }
+
+    // Takes the pending changes as a write that doesn't need the cache, or `None` if
+    // there is nothing to write. Entries are shared with the snapshot, not copied.
+    fn snapshot(
+        &mut self,
+    ) -> Option<impl FnOnce() -> Result<(), FolderSummaryError> + Send + 'static> {
+        let cache_file = self.cache_file.clone().filter(|_| self.dirty)?;
+        self.dirty = false;
+        self.snapshots += 1;
+        let (number, entries) = (self.snapshots, self.cache.clone());
+        Some(move || cache_file.save(number, &entries))
+    }
+}
+
+// A run that ends some other way than through `flush`, such as an error, still keeps
+// what it finished.
+impl Drop for Cache {
+    fn drop(&mut self) {
+        if let Some(write) = self.snapshot() {
+            if let Err(e) = write() {
+                warn!("Could not save the cache: {}", e);
+            }
+        }
+    }
+}
+
+struct CacheFile {
+    path: PathBuf,
+    /// The number of the snapshot on disk. Held while writing, so writes go one at a time.
+    written: Mutex<u64>,
+}
+
+impl CacheFile {
+    // Replaces the file through a temporary one, so a crash mid-write leaves the previous
+    // cache rather than a truncated one that `Cache::new` would throw away.
+    fn save(&self, number: u64, entries: &Entries) -> Result<(), FolderSummaryError> {
+        let mut written = self.written.lock().unwrap_or_else(PoisonError::into_inner);
+        if *written >= number {
+            return Ok(());
+        }
+        write_atomic_with(&self.path, |out| Ok(serde_json::to_writer(out, entries)?))?;
+        *written = number;
         Ok(())
     }
 }
+
+/// The modification time a cache entry for `file_path` is stored and looked up under.
+pub async fn modified_secs(file_path: &str) -> Result<u64, FolderSummaryError> {
+    let metadata = tokio::fs::metadata(file_path)
+        .await
+        .map_err(|e| FolderSummaryError::file(file_path, e))?;
+    Ok(metadata
+        .modified()?
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs())
+}
@@ -0,0 +1,193 @@
+use crate::analyzer::{get_analyzers, schema_kind};
+use crate::config::Config;
+use crate::error::FolderSummaryError;
+use crate::utils::{
+    add_line_stats, asset_kind, count_lines, decode_text, is_config_file, is_documentation_file,
+    language_name, line_ending, walk_project, ProjectFiles,
+};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+
+/// Where the files to analyze come from and how their contents are read.
+#[async_trait]
+pub trait FileCollector: Send + Sync + fmt::Debug {
+    /// Discovers the documentation, manifests and code files to analyze.
+    fn collect(
+        &self,
+        config: &Config,
+        cancel: &CancellationToken,
+    ) -> Result<ProjectFiles, FolderSummaryError>;
+
+    /// Reads one collected file. Defaults to the local filesystem.
+    async fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        tokio::fs::read(path).await
+    }
+
+    /// Whether collected paths are real files; the cache keys entries on their mtime,
+    /// so it is bypassed for sources that aren't.
+    fn on_disk(&self) -> bool {
+        true
+    }
+}
+
+/// Walks a directory on disk, honoring ignore files; the default collector.
+#[derive(Debug, Clone)]
+pub struct WalkCollector {
+    root: PathBuf,
+}
+
+impl WalkCollector {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        WalkCollector { root: root.into() }
+    }
+}
+
+#[async_trait]
+impl FileCollector for WalkCollector {
+    fn collect(
+        &self,
+        config: &Config,
+        cancel: &CancellationToken,
+    ) -> Result<ProjectFiles, FolderSummaryError> {
+        walk_project(&self.root, config, cancel)
+    }
+}
+
+/// A fixed list of files on disk, such as the output of `git diff --name-only`.
+///
+/// Unlike [`WalkCollector`], code files are kept whether or not a manifest was listed.
+#[derive(Debug, Clone)]
+pub struct ListCollector {
+    files: Vec<String>,
+}
+
+impl ListCollector {
+    pub fn new(files: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        ListCollector {
+            files: files.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl FileCollector for ListCollector {
+    fn collect(
+        &self,
+        config: &Config,
+        _cancel: &CancellationToken,
+    ) -> Result<ProjectFiles, FolderSummaryError> {
+        Ok(classify(self.files.iter().cloned(), config, |path| std::fs::read(path).ok()))
+    }
+}
+
+/// Files held in memory, for tests and callers that never touch the disk.
+#[derive(Default, Clone)]
+pub struct MemoryCollector {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemoryCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_file(mut self, path: impl Into<String>, contents: impl Into<Vec<u8>>) -> Self {
+        self.files.insert(path.into(), contents.into());
+        self
+    }
+}
+
+impl fmt::Debug for MemoryCollector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MemoryCollector")
+            .field("files", &self.files.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl FileCollector for MemoryCollector {
+    fn collect(
+        &self,
+        config: &Config,
+        _cancel: &CancellationToken,
+    ) -> Result<ProjectFiles, FolderSummaryError> {
+        Ok(classify(self.files.keys().cloned(), config, |path| self.files.get(path).cloned()))
+    }
+
+    async fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        self.files.get(path).cloned().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("{} is not in memory", path))
+        })
+    }
+
+    fn on_disk(&self) -> bool {
+        false
+    }
+}
+
+// Buckets an explicit list of paths the same way the directory walk does, reading each
+// with `read` for the line statistics.
+fn classify(
+    paths: impl Iterator<Item = String>,
+    config: &Config,
+    read: impl Fn(&str) -> Option<Vec<u8>>,
+) -> ProjectFiles {
+    let code_identifiers: HashSet<String> = config.get_code_identifiers().into_iter().collect();
+    let analyzers = get_analyzers();
+    let overrides = config.get_language_overrides();
+
+    let mut files = ProjectFiles::default();
+    for path in paths {
+        let mut size = None;
+        let file_name = Path::new(&path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if let Some(bytes) = read(&path) {
+            size = Some(bytes.len() as u64);
+            let text = language_name(Path::new(&path)).and_then(|_| decode_text(&bytes));
+            if let Some((contents, encoding)) = text {
+                files.text_files.push((path.clone(), encoding, line_ending(&contents)));
+                if let Some(stats) = count_lines(Path::new(&path), &contents) {
+                    add_line_stats(&mut files.line_stats, stats);
+                }
+            }
+            if let Some(kind) = asset_kind(Path::new(&path), Some(&bytes)) {
+                files.assets.push((path.clone(), kind.to_string(), bytes.len() as u64));
+            }
+        }
+        if is_documentation_file(&file_name) {
+            files.docs.push(path.clone());
+        }
+        if code_identifiers.contains(&file_name) {
+            files.manifests.push(path.clone());
+        }
+        if is_config_file(Path::new(&path)) {
+            files.config_files.push(path.clone());
+        }
+        if schema_kind(Path::new(&path)).is_some() {
+            files.schema_files.push(path.clone());
+        }
+        if analyzers.iter().any(|analyzer| analyzer.can_analyze(&path))
+            || overrides.iter().any(|(ext, _)| path.ends_with(ext.as_str()))
+        {
+            if let Some(size) = size {
+                files.code_sizes.insert(path.clone(), size);
+            }
+            files.code.push(path);
+        }
+    }
+    files.docs.sort();
+    files.manifests.sort();
+    files.code.sort();
+    files.config_files.sort();
+    files.schema_files.sort();
+    files.assets.sort();
+    files.text_files.sort_by(|a, b| a.0.cmp(&b.0));
+    files
+}
@@ -1,15 +1,21 @@
+use crate::error::FolderSummaryError;
+use crate::paths;
+use crate::summary::ExistingSummary;
+use crate::utils::clock;
+use regex::Regex;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
-use std::env;
-use chrono::{Local, DateTime};
-use std::time::SystemTime;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use toml;
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default, Clone)]
 pub struct Config {
     pub llm_provider: Option<String>,
     pub ollama_model: Option<String>,
+    /// Download the Ollama model with `/api/pull` when the server doesn't have it.
+    pub pull_missing_model: Option<bool>,
     pub gemini_model: Option<String>,
     pub openai_model: Option<String>,
     pub custom_openai_url: Option<String>,
@@ -18,67 +24,425 @@ pub struct Config {
     pub custom_openai_config: Option<OpenAIConfig>,
     pub summary_output_path: Option<String>,
     pub summary_filename_format: Option<String>,
+    /// What to do when a summary with the same name exists: `overwrite` (default),
+    /// `version`, `timestamp` or `error`.
+    pub on_existing_summary: Option<String>,
+    /// Write each project's reports to a subdirectory of the output path named after it.
+    pub project_subdirectory: Option<bool>,
+    /// Tell the LLM the project, file and imports each function comes from (default true).
+    pub prompt_context: Option<bool>,
+    /// Summarize functions a second time with the summaries of the functions they call
+    /// (default false).
+    pub callee_context: Option<bool>,
+    /// Write a `<report>.run.json` log of the run's files and LLM calls (default true).
+    pub write_run_log: Option<bool>,
+    /// Language of the CLI's messages, `en` or `es`; `--locale` and then `LANG` otherwise.
+    pub locale: Option<String>,
     pub custom_ignore_paths: Option<Vec<String>>,
+    /// `[language_overrides]`: file extensions mapped to the language analyzing them, for
+    /// extensions that are ambiguous or that no analyzer claims.
+    pub language_overrides: Option<BTreeMap<String, String>>,
     pub code_identifiers: Option<Vec<String>>,
+    pub max_depth: Option<usize>,
+    pub follow_symlinks: Option<bool>,
+    pub concurrency: Option<usize>,
+    pub fail_fast: Option<bool>,
+    pub file_timeout_secs: Option<u64>,
+    pub max_file_bytes: Option<u64>,
+    pub max_function_lines: Option<usize>,
+    pub summarize_generated_files: Option<bool>,
+    pub llm_concurrency: Option<usize>,
+    pub llm_requests_per_minute: Option<u32>,
+    pub summary_retries: Option<usize>,
+    /// Stop calling the LLM once this many prompt and completion tokens are spent.
+    pub max_total_tokens: Option<usize>,
+    /// Which files the LLM summarizes first: `priority` (default), `imports` or `path`.
+    pub summary_order: Option<String>,
+    pub route: Option<RouteConfig>,
+    pub no_llm: Option<bool>,
+    pub output_format: Option<String>,
+    /// Show absolute file paths in reports instead of paths relative to the analyzed
+    /// directory.
+    pub absolute_paths: Option<bool>,
+    /// A public API snapshot from an earlier run to compare the current API against.
+    pub api_baseline: Option<String>,
+    /// Intended dependency direction between directories, e.g. `"ui -> core -> db"`.
+    pub layers: Option<Vec<String>>,
+    pub exclude_config_from_llm: Option<bool>,
+    pub max_doc_summaries: Option<usize>,
+    pub max_listed_docs: Option<usize>,
+    pub skip_tests: Option<bool>,
+    pub tests_only: Option<bool>,
+    /// Analyze only a representative subset of the code files of projects above
+    /// `sample_threshold_lines` lines of code.
+    pub sample: Option<bool>,
+    pub sample_threshold_lines: Option<usize>,
+    pub sample_files: Option<usize>,
+    pub complexity_warning_threshold: Option<usize>,
+    pub length_warning_threshold: Option<usize>,
+    pub parameters_warning_threshold: Option<usize>,
+    pub rules: Option<RulesConfig>,
+    /// Findings recorded by `--write-baseline`; only findings not in it are reported.
+    pub findings_baseline: Option<String>,
+    /// Fail the run when a finding of at least this severity is reported.
+    pub fail_on: Option<String>,
+    pub webhooks: Option<Vec<WebhookConfig>>,
+    pub upload: Option<UploadConfig>,
+    pub daemon: Option<DaemonConfig>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct GeminiConfig {
     // Add Gemini-specific configuration options here
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct OpenAIConfig {
     // Add OpenAI-specific configuration options here
 }
 
+/// The `[route]` table: a second provider that summarizes only the large or complex
+/// functions, leaving the rest to `llm_provider`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct RouteConfig {
+    /// `ollama`, `gemini`, `openai` or `mock`.
+    pub large: String,
+    /// Functions with at least this many lines go to `large`; defaults to 50.
+    pub min_lines: Option<usize>,
+    /// Functions with at least this cyclomatic complexity go to `large`; defaults to 10.
+    pub min_complexity: Option<usize>,
+}
+
+/// The `[rules]` table: finding thresholds, which take precedence over the
+/// `*_warning_threshold` options, and per-rule severities, e.g.
+/// `long-function = "error"` or `text-hygiene = "off"`.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct RulesConfig {
+    pub max_complexity: Option<usize>,
+    pub max_lines: Option<usize>,
+    pub max_parameters: Option<usize>,
+    /// Rule name to `off`, `info`, `warning` or `error`.
+    #[serde(flatten)]
+    pub severities: BTreeMap<String, String>,
+}
+
+/// A `[[webhooks]]` entry notified when a run completes.
+#[derive(Deserialize, Debug, Clone)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// `json` (default), `slack` or `discord`.
+    pub kind: Option<String>,
+    /// Where the report can be viewed, with `{file}` replaced by the report's file name.
+    /// Without it notifications name the local path.
+    pub report_url: Option<String>,
+}
+
+/// The `[upload]` table: an S3-compatible bucket the written reports are copied to.
+#[derive(Deserialize, Debug, Clone)]
+pub struct UploadConfig {
+    pub bucket: String,
+    /// Defaults to AWS S3 in `region`; use `https://storage.googleapis.com` for GCS.
+    pub endpoint: Option<String>,
+    pub region: Option<String>,
+    /// Object key for each report; `{project}`, `{date}` and `{file}` are replaced.
+    pub key_template: Option<String>,
+}
+
+/// The `[daemon]` table: which repositories `folder-summary daemon` re-analyzes and when.
+#[derive(Deserialize, Debug, Clone)]
+pub struct DaemonConfig {
+    /// Five-field cron expression, in local time.
+    pub schedule: String,
+    pub repositories: Vec<String>,
+    /// Where report snapshots are kept; defaults to `snapshots` under the output path.
+    pub snapshot_dir: Option<String>,
+}
+
+impl RouteConfig {
+    pub fn get_min_lines(&self) -> usize {
+        self.min_lines.unwrap_or(50)
+    }
+
+    pub fn get_min_complexity(&self) -> usize {
+        self.min_complexity.unwrap_or(10)
+    }
+}
+
+impl UploadConfig {
+    pub fn get_region(&self) -> String {
+        self.region.clone().unwrap_or_else(|| "us-east-1".to_string())
+    }
+
+    pub fn get_endpoint(&self) -> String {
+        self.endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", self.get_region()))
+    }
+
+    pub fn get_key_template(&self) -> String {
+        self.key_template
+            .clone()
+            .unwrap_or_else(|| "{project}/{date}/{file}".to_string())
+    }
+}
+
+/// Placeholders `summary_filename_format` can use.
+pub const FILENAME_PLACEHOLDERS: &[&str] =
+    &["folder", "date", "time", "git_sha", "branch", "model", "format"];
+
+/// What the placeholders of `summary_filename_format` stand for in one report's name.
+#[derive(Debug)]
+pub struct FilenameVars<'a> {
+    pub folder: &'a str,
+    pub git_sha: Option<&'a str>,
+    pub branch: Option<&'a str>,
+    /// The LLM's model name; `None` for static analysis only.
+    pub model: Option<&'a str>,
+    /// The renderer's format, e.g. `markdown`.
+    pub format: &'a str,
+}
+
+// Branch and model names carry slashes, colons and spaces; `Ollama (gemma2:2b)` becomes
+// `Ollama-gemma2-2b`.
+fn filename_safe(value: &str) -> String {
+    value
+        .split(|c: char| !(c.is_alphanumeric() || c == '.' || c == '_' || c == '-'))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
 impl Config {
-    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let config_str = fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&config_str)?;
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FolderSummaryError> {
+        let path = path.as_ref();
+        let config_str = fs::read_to_string(path)
+            .map_err(|e| FolderSummaryError::file(path.display().to_string(), e))?;
+        let config: Config =
+            toml::from_str(&config_str).map_err(|e| FolderSummaryError::InvalidConfig {
+                path: path.display().to_string(),
+                message: e.to_string(),
+            })?;
+        config
+            .check_filename_format()
+            .map_err(|message| FolderSummaryError::InvalidConfig {
+                path: path.display().to_string(),
+                message,
+            })?;
         Ok(config)
     }
-    pub fn get_summary_output_path(&self) -> PathBuf {
-        self.summary_output_path
-            .as_ref()
-            .map(PathBuf::from)
-            .unwrap_or_else(|| {
-                env::var("HOME")
-                    .map(PathBuf::from)
-                    .expect("Could not find home directory")
-                    .join(".local")
-                    .join("share")
-                    .join("folder_summary")
-            })
+    pub fn get_summary_output_path(&self) -> Result<PathBuf, FolderSummaryError> {
+        if let Some(path) = &self.summary_output_path {
+            return Ok(PathBuf::from(path));
+        }
+        paths::data_dir()
     }
 
-    pub fn get_summary_filename(&self, folder_name: &str) -> String {
-        let now: DateTime<Local> = SystemTime::now().into();
+    pub fn get_summary_filename(&self, vars: &FilenameVars) -> String {
+        let now = clock::now();
         let date_str = now.format("%Y-%m-%d").to_string();
 
-        self.summary_filename_format
-            .as_ref()
-            .map(|format| format.replace("{folder}", folder_name).replace("{date}", &date_str))
-            .unwrap_or_else(|| format!("summary-{}-{}.md", folder_name, date_str))
+        let Some(format) = &self.summary_filename_format else {
+            return format!("summary-{}-{}.md", vars.folder, date_str);
+        };
+        let or_none = |value: Option<&str>| filename_safe(value.unwrap_or("none"));
+        format
+            .replace("{folder}", vars.folder)
+            .replace("{date}", &date_str)
+            .replace("{time}", &now.format("%H%M%S").to_string())
+            .replace("{git_sha}", &or_none(vars.git_sha.map(|sha| &sha[..sha.len().min(7)])))
+            .replace("{branch}", &or_none(vars.branch))
+            .replace("{model}", &or_none(vars.model))
+            .replace("{format}", vars.format)
+    }
+
+    // Placeholders in `summary_filename_format` that get_summary_filename doesn't fill in
+    // would otherwise end up in every file name.
+    fn check_filename_format(&self) -> Result<(), String> {
+        let Some(format) = &self.summary_filename_format else {
+            return Ok(());
+        };
+        let placeholder = Regex::new(r"\{([^{}]*)\}").unwrap();
+        for captures in placeholder.captures_iter(format) {
+            if !FILENAME_PLACEHOLDERS.contains(&&captures[1]) {
+                return Err(format!(
+                    "Unknown placeholder {} in summary_filename_format; available: {}",
+                    &captures[0],
+                    FILENAME_PLACEHOLDERS
+                        .iter()
+                        .map(|name| format!("{{{}}}", name))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+        Ok(())
     }
     
+    pub fn get_prompt_context(&self) -> bool {
+        self.prompt_context.unwrap_or(true)
+    }
+
+    pub fn get_write_run_log(&self) -> bool {
+        self.write_run_log.unwrap_or(true)
+    }
+
+    pub fn get_project_subdirectory(&self) -> bool {
+        self.project_subdirectory.unwrap_or(true)
+    }
+
+    pub fn get_on_existing_summary(&self) -> Result<ExistingSummary, FolderSummaryError> {
+        ExistingSummary::parse(self.on_existing_summary.as_deref().unwrap_or("overwrite"))
+    }
+
+    /// The default ignore patterns followed by `custom_ignore_paths`, so a `!pattern` in the
+    /// config can re-include a default.
     pub fn get_custom_ignore_paths(&self) -> Vec<String> {
-        let mut ignore_paths = self.custom_ignore_paths.clone().unwrap_or_else(Vec::new);
-        ignore_paths.extend(Self::default_ignore_patterns());
+        let mut ignore_paths = Self::default_ignore_patterns();
+        ignore_paths.extend(self.custom_ignore_paths.clone().unwrap_or_default());
         ignore_paths
     }
 
+    /// `[language_overrides]` with each extension given its leading dot, longest first so
+    /// `.d.ts` is matched before `.ts`.
+    pub fn get_language_overrides(&self) -> Vec<(String, String)> {
+        let mut overrides: Vec<(String, String)> = self
+            .language_overrides
+            .iter()
+            .flatten()
+            .map(|(extension, language)| {
+                let extension = format!(".{}", extension.trim_start_matches('.'));
+                (extension, language.clone())
+            })
+            .collect();
+        overrides.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        overrides
+    }
+
     pub fn get_code_identifiers(&self) -> Vec<String> {
         self.code_identifiers.clone().unwrap_or_else(|| {
             vec![
                 "Cargo.toml".to_string(),
                 "package.json".to_string(),
                 "setup.py".to_string(),
+                "pyproject.toml".to_string(),
                 "requirements.txt".to_string(),
+                "go.mod".to_string(),
+                "pom.xml".to_string(),
+                "build.gradle".to_string(),
+                "build.gradle.kts".to_string(),
+                "composer.json".to_string(),
+                "Package.swift".to_string(),
+                "Gemfile".to_string(),
+                "Makefile".to_string(),
             ]
         })
     }
 
+    pub fn get_follow_symlinks(&self) -> bool {
+        self.follow_symlinks.unwrap_or(false)
+    }
+
+    /// Number of files analyzed at once; defaults to the available CPU parallelism.
+    pub fn get_concurrency(&self) -> usize {
+        self.concurrency.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4)
+        })
+    }
+
+    /// Number of files summarized by the LLM at once.
+    pub fn get_llm_concurrency(&self) -> usize {
+        self.llm_concurrency.unwrap_or(4)
+    }
+
+    pub fn get_file_timeout(&self) -> Duration {
+        Duration::from_secs(self.file_timeout_secs.unwrap_or(30))
+    }
+
+    /// Files over this size aren't analyzed; 2 MiB by default.
+    pub fn get_max_file_bytes(&self) -> u64 {
+        self.max_file_bytes.unwrap_or(2 * 1024 * 1024)
+    }
+
+    pub fn get_max_function_lines(&self) -> usize {
+        self.max_function_lines.unwrap_or(1000)
+    }
+
+    /// How many times a rejected LLM summary is asked for again.
+    pub fn get_summary_retries(&self) -> usize {
+        self.summary_retries.unwrap_or(2)
+    }
+
+    /// Documentation files summarized by the LLM per run.
+    pub fn get_max_doc_summaries(&self) -> usize {
+        self.max_doc_summaries.unwrap_or(10)
+    }
+
+    /// Documentation files listed in the report, most relevant first.
+    pub fn get_max_listed_docs(&self) -> usize {
+        self.max_listed_docs.unwrap_or(30)
+    }
+
+    /// Lines of code above which `sample` analyzes only a subset of the files.
+    pub fn get_sample_threshold_lines(&self) -> usize {
+        self.sample_threshold_lines.unwrap_or(100_000)
+    }
+
+    /// Code files analyzed when a project is sampled.
+    pub fn get_sample_files(&self) -> usize {
+        self.sample_files.unwrap_or(300)
+    }
+
+    /// Which renderers write the report: `markdown`, `html`, `json` or registered ones,
+    /// separated by commas. Every one renders the same analysis.
+    pub fn get_output_formats(&self) -> Vec<String> {
+        let formats: Vec<String> = self
+            .output_format
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(|format| format.trim().to_string())
+            .filter(|format| !format.is_empty())
+            .collect();
+        if formats.is_empty() {
+            vec!["markdown".to_string()]
+        } else {
+            formats
+        }
+    }
+
+    /// Cyclomatic complexity above which a function is flagged.
+    pub fn get_complexity_warning_threshold(&self) -> usize {
+        self.rules
+            .as_ref()
+            .and_then(|rules| rules.max_complexity)
+            .or(self.complexity_warning_threshold)
+            .unwrap_or(10)
+    }
+
+    /// Lines of code above which a function is flagged.
+    pub fn get_length_warning_threshold(&self) -> usize {
+        self.rules
+            .as_ref()
+            .and_then(|rules| rules.max_lines)
+            .or(self.length_warning_threshold)
+            .unwrap_or(60)
+    }
+
+    /// Parameter count above which a function is flagged.
+    pub fn get_parameters_warning_threshold(&self) -> usize {
+        self.rules
+            .as_ref()
+            .and_then(|rules| rules.max_parameters)
+            .or(self.parameters_warning_threshold)
+            .unwrap_or(5)
+    }
+
+    pub fn get_webhooks(&self) -> Vec<WebhookConfig> {
+        self.webhooks.clone().unwrap_or_default()
+    }
+
     fn default_ignore_patterns() -> Vec<String> {
         vec![
             "node_modules".to_string(),
@@ -95,3 +459,35 @@ impl Config {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_format(format: &str) -> Config {
+        Config {
+            summary_filename_format: Some(format.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn filename_format_accepts_known_placeholders() {
+        assert!(Config::default().check_filename_format().is_ok());
+        let every = FILENAME_PLACEHOLDERS
+            .iter()
+            .map(|name| format!("{{{}}}", name))
+            .collect::<Vec<_>>()
+            .join("-");
+        assert!(with_format(&every).check_filename_format().is_ok());
+        assert!(with_format("summary").check_filename_format().is_ok());
+    }
+
+    #[test]
+    fn filename_format_rejects_unknown_placeholders() {
+        let error = with_format("summary-{folder}-{sha}").check_filename_format().unwrap_err();
+        assert!(error.starts_with("Unknown placeholder {sha}"), "{}", error);
+        assert!(error.contains("{git_sha}"), "{}", error);
+        assert!(with_format("summary-{}").check_filename_format().is_err());
+    }
+}
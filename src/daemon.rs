@@ -0,0 +1,199 @@
+use crate::analyzer::FunctionAnalysis;
+use crate::config::{Config, DaemonConfig};
+use crate::error::FolderSummaryError;
+use crate::llm::LLM;
+use crate::notify::notify_webhooks;
+use crate::paths;
+use crate::progress::NoProgress;
+use crate::render::Renderer;
+use crate::report::Report;
+use crate::schedule::Schedule;
+use crate::summarizer::FolderSummarizer;
+use crate::summary::generate_summary;
+use crate::upload::upload_reports;
+use chrono::{DateTime, Local};
+use log::{info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+
+/// Re-analyzes the `[daemon]` repositories on its schedule until `cancel` fires.
+///
+/// Each run renders the usual report, keeps a JSON snapshot of it, and notifies the
+/// configured webhooks when metrics got worse than in the repository's previous snapshot.
+/// A repository that fails is logged and retried at the next scheduled time. `scheduled`
+/// is told when each run will start, before the daemon waits for it.
+pub async fn run_daemon(
+    config: Config,
+    llm: Arc<dyn LLM>,
+    renderers: Vec<Arc<dyn Renderer>>,
+    cancel: CancellationToken,
+    scheduled: impl Fn(DateTime<Local>),
+) -> Result<(), FolderSummaryError> {
+    let daemon = config.daemon.clone().ok_or_else(|| {
+        FolderSummaryError::ConfigError(
+            "The daemon needs a [daemon] table with a schedule and repositories".to_string(),
+        )
+    })?;
+    let schedule: Schedule = daemon.schedule.parse()?;
+    let snapshot_dir = snapshot_dir(&config, &daemon)?;
+
+    loop {
+        let next = schedule.next_after(Local::now()).ok_or_else(|| {
+            FolderSummaryError::ConfigError(format!(
+                "Schedule {:?} never matches",
+                daemon.schedule
+            ))
+        })?;
+        info!("Next analysis at {}", next.format("%Y-%m-%d %H:%M"));
+        scheduled(next);
+        let wait = (next - Local::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = cancel.cancelled() => return Ok(()),
+        }
+
+        for repository in &daemon.repositories {
+            let run = analyze_repository(
+                &config,
+                &llm,
+                &renderers,
+                &snapshot_dir,
+                repository,
+                &cancel,
+            );
+            match run.await {
+                Ok(()) => {}
+                Err(FolderSummaryError::Cancelled) => return Ok(()),
+                Err(e) => warn!("Analysis of {} failed: {}", repository, e),
+            }
+        }
+    }
+}
+
+async fn analyze_repository(
+    config: &Config,
+    llm: &Arc<dyn LLM>,
+    renderers: &[Arc<dyn Renderer>],
+    snapshot_dir: &Path,
+    repository: &str,
+    cancel: &CancellationToken,
+) -> Result<(), FolderSummaryError> {
+    info!("Analyzing {}", repository);
+    let summarizer = FolderSummarizer::builder()
+        .directory(repository)
+        .config(config.clone())
+        .llm(llm.clone())
+        .cache_file(paths::cache_file())
+        .cancellation_token(cancel.clone())
+        .build()?;
+    let report = summarizer.run().await?;
+    let output = generate_summary(&report, config, renderers, &NoProgress)?;
+    {
+        let mut cache = summarizer.cache().lock().await;
+        cache.set_rendered(output.sections);
+        cache.flush().await?;
+    }
+    if let Some(upload) = &config.upload {
+        upload_reports(upload, &report, &output.files).await?;
+    }
+
+    let previous = latest_snapshot(&snapshot_dir.join(repository_name(repository)))?;
+    save_snapshot(snapshot_dir, repository, &report)?;
+
+    let Some(previous) = previous else {
+        return Ok(());
+    };
+    let regressions = regressions(config, &previous, &report);
+    if regressions.is_empty() {
+        info!("No regressions in {}", repository);
+    } else {
+        for regression in &regressions {
+            warn!("{}: {}", repository, regression);
+        }
+        notify_webhooks(config, &report, &output.files, &regressions).await;
+    }
+    Ok(())
+}
+
+/// Metrics that got worse between two reports of the same repository.
+pub fn regressions(config: &Config, previous: &Report, current: &Report) -> Vec<String> {
+    let max_complexity = config.get_complexity_warning_threshold();
+    let max_lines = config.get_length_warning_threshold();
+    let checks = [
+        ("files failing analysis", previous.metrics.failures, current.metrics.failures),
+        (
+            "functions over the complexity threshold",
+            count_functions(previous, |f| f.cyclomatic_complexity > max_complexity),
+            count_functions(current, |f| f.cyclomatic_complexity > max_complexity),
+        ),
+        (
+            "functions over the length threshold",
+            count_functions(previous, |f| f.lines_of_code > max_lines),
+            count_functions(current, |f| f.lines_of_code > max_lines),
+        ),
+    ];
+    checks
+        .iter()
+        .filter(|(_, before, after)| after > before)
+        .map(|(what, before, after)| format!("{} went from {} to {}", what, before, after))
+        .collect()
+}
+
+fn count_functions(report: &Report, flagged: impl Fn(&FunctionAnalysis) -> bool) -> usize {
+    report
+        .files
+        .iter()
+        .filter(|(file, _)| !report.duplicates.contains_key(*file))
+        .flat_map(|(_, analysis)| &analysis.functions)
+        .filter(|func| flagged(func))
+        .count()
+}
+
+fn snapshot_dir(config: &Config, daemon: &DaemonConfig) -> Result<PathBuf, FolderSummaryError> {
+    match &daemon.snapshot_dir {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(config.get_summary_output_path()?.join("snapshots")),
+    }
+}
+
+// Snapshots are named by timestamp, so the greatest name is the newest.
+fn latest_snapshot(dir: &Path) -> Result<Option<Report>, FolderSummaryError> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(None);
+    };
+    let latest = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .max();
+    let Some(path) = latest else {
+        return Ok(None);
+    };
+    let contents =
+        fs::read(&path).map_err(|e| FolderSummaryError::file(path.display().to_string(), e))?;
+    Ok(Some(serde_json::from_slice(&contents)?))
+}
+
+/// Saves `report` as the newest snapshot of `repository` under `snapshot_dir`, where the
+/// daemon compares each run with the previous one. Returns the snapshot's path.
+pub fn save_snapshot(
+    snapshot_dir: &Path,
+    repository: &str,
+    report: &Report,
+) -> Result<PathBuf, FolderSummaryError> {
+    let dir = snapshot_dir.join(repository_name(repository));
+    fs::create_dir_all(&dir).map_err(|e| FolderSummaryError::file(dir.display().to_string(), e))?;
+    let path = dir.join(format!("{}.json", Local::now().format("%Y%m%dT%H%M%S")));
+    fs::write(&path, serde_json::to_vec_pretty(report)?)
+        .map_err(|e| FolderSummaryError::file(path.display().to_string(), e))?;
+    Ok(path)
+}
+
+fn repository_name(repository: &str) -> String {
+    fs::canonicalize(repository)
+        .ok()
+        .and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| repository.replace(['/', '\\'], "_"))
+}
@@ -0,0 +1,201 @@
+use crate::analyzer::ThreadSafeCache;
+use crate::cache::modified_secs;
+use crate::collector::FileCollector;
+use crate::error::FolderSummaryError;
+use crate::llm::LLM;
+use crate::report::Report;
+use crate::utils::decode_text;
+use log::{debug, warn};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Longest piece of a document sent in one prompt; longer documents are summarized in
+/// chunks and the chunk summaries summarized again.
+const CHUNK_CHARS: usize = 6000;
+/// Source files listed in the overview prompt.
+const OVERVIEW_FILES: usize = 50;
+
+/// Orders documentation by how much it says about the project and keeps the first `limit`,
+/// returning them with the number left out.
+///
+/// The root README comes first, then anything under a top-level `docs` or `doc` directory,
+/// then other root-level documents and nested READMEs, then everything else; shallower
+/// files first within each group. Paths are ranked relative to `root`.
+pub fn rank_docs(mut docs: Vec<String>, root: &Path, limit: usize) -> (Vec<String>, usize) {
+    docs.sort_by_cached_key(|doc| {
+        let path = Path::new(doc);
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        (doc_tier(relative), relative.components().count(), doc.clone())
+    });
+    let omitted = docs.len().saturating_sub(limit);
+    docs.truncate(limit);
+    (docs, omitted)
+}
+
+fn doc_tier(relative: &Path) -> u8 {
+    let depth = relative.components().count();
+    let top_dir = relative
+        .components()
+        .next()
+        .map(|dir| dir.as_os_str().to_string_lossy().to_lowercase());
+    let readme = is_readme(&relative.to_string_lossy());
+    if depth == 1 && readme {
+        0
+    } else if depth > 1 && matches!(top_dir.as_deref(), Some("docs" | "doc")) {
+        1
+    } else if depth == 1 || readme {
+        2
+    } else {
+        3
+    }
+}
+
+/// Summarizes up to `limit` markdown and reStructuredText documents, in the order given
+/// (see [`rank_docs`]).
+///
+/// Summaries of unchanged documents come from the cache when `source` is on disk. A
+/// document that can't be read or summarized is logged and left out.
+pub async fn summarize_docs(
+    docs: &[String],
+    source: &dyn FileCollector,
+    llm: &dyn LLM,
+    cache: &ThreadSafeCache,
+    limit: usize,
+) -> BTreeMap<String, String> {
+    let mut summaries = BTreeMap::new();
+    for doc in docs.iter().filter(|doc| is_prose(doc)).take(limit) {
+        let modified = if source.on_disk() {
+            modified_secs(doc).await.ok()
+        } else {
+            None
+        };
+        if let Some(modified) = modified {
+            if let Some(summary) = cache.lock().await.get_doc_summary(doc, modified) {
+                summaries.insert(doc.clone(), summary.to_string());
+                continue;
+            }
+        }
+        let contents = match source.read(doc).await.map(|bytes| decode_text(&bytes)) {
+            Ok(Some((contents, _))) => contents,
+            Ok(None) => continue,
+            Err(e) => {
+                debug!("Skipping documentation file {}: {}", doc, e);
+                continue;
+            }
+        };
+        match summarize_document(doc, &contents, llm).await {
+            Ok(summary) => {
+                if let Some(modified) = modified {
+                    cache
+                        .lock()
+                        .await
+                        .set_doc_summary(doc.clone(), modified, summary.clone());
+                }
+                summaries.insert(doc.clone(), summary);
+            }
+            Err(e) => warn!("Could not summarize documentation file {}: {}", doc, e),
+        }
+    }
+    summaries
+}
+
+async fn summarize_document(
+    path: &str,
+    contents: &str,
+    llm: &dyn LLM,
+) -> Result<String, FolderSummaryError> {
+    let chunks = chunk(contents, CHUNK_CHARS);
+    if chunks.len() <= 1 {
+        let prompt = format!(
+            "Summarize the following documentation file:\n\nPath: {}\nContents:\n{}",
+            path, contents
+        );
+        return llm.summarize(&prompt).await;
+    }
+
+    let mut parts = Vec::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let prompt = format!(
+            "Summarize part {} of {} of the documentation file {}:\n\n{}",
+            index + 1,
+            chunks.len(),
+            path,
+            chunk
+        );
+        parts.push(llm.summarize(&prompt).await?);
+    }
+    let prompt = format!(
+        "Combine these summaries of consecutive parts of the documentation file {} into one summary:\n\n{}",
+        path,
+        parts.join("\n")
+    );
+    llm.summarize(&prompt).await
+}
+
+/// Writes a short description of the whole project from what the report found, led by
+/// the README's summary.
+pub async fn project_overview(report: &Report, llm: &dyn LLM) -> Result<String, FolderSummaryError> {
+    let mut prompt = format!(
+        "Write a short overview of the project {}: what it is for and how its code is organized.\n",
+        report.project_name
+    );
+    let readme = report
+        .docs
+        .iter()
+        .filter(|doc| is_readme(doc))
+        .find_map(|doc| report.doc_summaries.get(doc).map(|summary| (doc, summary)));
+    if let Some((doc, summary)) = readme {
+        prompt.push_str(&format!("\nREADME ({}): {}\n", doc, summary));
+    }
+    if !report.package_info.is_empty() {
+        let packages: Vec<String> = report
+            .package_info
+            .values()
+            .flatten()
+            .map(|(name, version)| format!("{} {}", name, version))
+            .collect();
+        prompt.push_str(&format!("\nPackages: {}\n", packages.join(", ")));
+    }
+    prompt.push_str("\nSource files:\n");
+    for (file, analysis) in report.files.iter().take(OVERVIEW_FILES) {
+        prompt.push_str(&format!("- {} ({} functions)\n", file, analysis.functions.len()));
+    }
+    if report.files.len() > OVERVIEW_FILES {
+        prompt.push_str(&format!("- ... and {} more\n", report.files.len() - OVERVIEW_FILES));
+    }
+    llm.summarize(&prompt).await
+}
+
+fn is_prose(path: &str) -> bool {
+    path.ends_with(".md") || path.ends_with(".markdown") || path.ends_with(".rst")
+}
+
+fn is_readme(path: &str) -> bool {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase().starts_with("readme"))
+        .unwrap_or(false)
+}
+
+// Splits at paragraph breaks into pieces of at most `max_chars` bytes where possible; a
+// single paragraph longer than that becomes its own piece.
+fn chunk(text: &str, max_chars: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut end = 0;
+    for (index, _) in text.match_indices("\n\n") {
+        if index - start > max_chars && end > start {
+            chunks.push(&text[start..end]);
+            start = end;
+        }
+        end = index;
+    }
+    if text.len() - start > max_chars && end > start {
+        chunks.push(&text[start..end]);
+        start = end;
+    }
+    if start < text.len() {
+        chunks.push(&text[start..]);
+    }
+    chunks
+}
@@ -1,34 +1,97 @@
+use crate::messages::Message;
+use crate::tr;
 use std::fmt;
 use std::time::SystemTimeError;
 
 #[derive(Debug)]
 pub enum FolderSummaryError {
     IoError(std::io::Error),
+    /// An IO failure on a specific file or directory.
+    FileError {
+        path: String,
+        source: std::io::Error,
+    },
     ConfigError(String),
+    /// The configuration file exists but could not be parsed.
+    InvalidConfig {
+        path: String,
+        message: String,
+    },
     LlmError(String),
+    /// A summarization request to `provider` failed.
+    ProviderError {
+        provider: String,
+        message: String,
+    },
     AnalysisError(String),
     CacheError(String),
+    SerializationError(serde_json::Error),
     TaskJoinError(String),
+    /// The run was stopped through its cancellation token.
+    Cancelled,
+    /// The run's `max_total_tokens` were spent; no more LLM calls are made.
+    TokenBudgetExhausted,
 }
 
-impl std::error::Error for FolderSummaryError {}
+impl FolderSummaryError {
+    /// Wraps an IO error with the path it happened on.
+    pub fn file(path: impl Into<String>, source: std::io::Error) -> Self {
+        FolderSummaryError::FileError {
+            path: path.into(),
+            source,
+        }
+    }
 
-impl fmt::Display for FolderSummaryError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    pub fn provider(provider: impl Into<String>, message: impl fmt::Display) -> Self {
+        FolderSummaryError::ProviderError {
+            provider: provider.into(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl std::error::Error for FolderSummaryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            FolderSummaryError::IoError(err) => write!(f, "IO error: {}", err),
-            FolderSummaryError::ConfigError(err) => write!(f, "Configuration error: {}", err),
-            FolderSummaryError::LlmError(err) => write!(f, "LLM error: {}", err),
-            FolderSummaryError::AnalysisError(err) => write!(f, "Analysis error: {}", err),
-            FolderSummaryError::CacheError(err) => write!(f, "Cache error: {}", err),
-            FolderSummaryError::TaskJoinError(err) => write!(f, "TaskJoin error: {}", err),
+            FolderSummaryError::IoError(err) => Some(err),
+            FolderSummaryError::FileError { source, .. } => Some(source),
+            FolderSummaryError::SerializationError(err) => Some(err),
+            _ => None,
         }
     }
 }
 
+impl fmt::Display for FolderSummaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            FolderSummaryError::IoError(err) => tr!(Message::IoError, err),
+            FolderSummaryError::FileError { path, source } => {
+                tr!(Message::IoErrorOn, path, source)
+            }
+            FolderSummaryError::ConfigError(err) => tr!(Message::ConfigError, err),
+            FolderSummaryError::InvalidConfig { path, message } => {
+                tr!(Message::ConfigErrorIn, path, message)
+            }
+            FolderSummaryError::LlmError(err) => tr!(Message::LlmError, err),
+            FolderSummaryError::ProviderError { provider, message } => {
+                tr!(Message::LlmErrorFrom, provider, message)
+            }
+            FolderSummaryError::AnalysisError(err) => tr!(Message::AnalysisError, err),
+            FolderSummaryError::CacheError(err) => tr!(Message::CacheError, err),
+            FolderSummaryError::SerializationError(err) => {
+                tr!(Message::SerializationError, err)
+            }
+            FolderSummaryError::TaskJoinError(err) => tr!(Message::TaskJoinError, err),
+            FolderSummaryError::Cancelled => tr!(Message::Cancelled),
+            FolderSummaryError::TokenBudgetExhausted => tr!(Message::TokenBudgetExhausted),
+        };
+        f.write_str(&text)
+    }
+}
+
 impl From<serde_json::Error> for FolderSummaryError {
     fn from(err: serde_json::Error) -> Self {
-        FolderSummaryError::TaskJoinError(err.to_string())
+        FolderSummaryError::SerializationError(err)
     }
 }
 
@@ -40,7 +103,7 @@ impl From<std::io::Error> for FolderSummaryError {
 
 impl From<SystemTimeError> for FolderSummaryError {
     fn from(error: SystemTimeError) -> Self {
-        FolderSummaryError::IoError(std::io::Error::new(std::io::ErrorKind::Other, error))
+        FolderSummaryError::IoError(std::io::Error::other(error))
     }
 }
 // Add more From implementations as needed
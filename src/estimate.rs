@@ -0,0 +1,134 @@
+use crate::analyzer::LanguageOverrides;
+use crate::cache::{modified_secs, Cache};
+use crate::collector::FileCollector;
+use crate::llm::{calculate_tokens, LLM};
+use crate::messages::Message;
+use crate::tr;
+use crate::utils::decode_text;
+use log::debug;
+use std::fmt;
+use std::time::Duration;
+
+// Tokens a one-line summary typically comes back with, plus the fixed prompt preamble.
+const COMPLETION_TOKENS_PER_CALL: usize = 30;
+const PROMPT_OVERHEAD_TOKENS: usize = 20;
+
+#[derive(Debug, Default, Clone)]
+pub struct RunEstimate {
+    pub files: usize,
+    pub cached_files: usize,
+    pub total_lines: usize,
+    pub llm_calls: usize,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    pub estimated_duration: Duration,
+    pub estimated_cost: f64,
+}
+
+/// Runs the static analyzers over uncached files to predict how much LLM work a run needs.
+///
+/// Pass `None` for `llm` when the run won't summarize; only file statistics are gathered.
+pub async fn estimate_run(
+    files: &[String],
+    source: &dyn FileCollector,
+    cache: &Cache,
+    languages: &LanguageOverrides,
+    llm: Option<&dyn LLM>,
+) -> RunEstimate {
+    let mut estimate = RunEstimate {
+        files: files.len(),
+        ..Default::default()
+    };
+
+    for file in files {
+        let content = match source.read(file).await.map(|bytes| decode_text(&bytes)) {
+            Ok(Some((content, _))) => content,
+            Ok(None) => continue,
+            Err(e) => {
+                debug!("Skipping {} in estimate: {}", file, e);
+                continue;
+            }
+        };
+        estimate.total_lines += content.lines().count();
+
+        let modified = if source.on_disk() {
+            modified_secs(file).await.ok()
+        } else {
+            None
+        };
+        if modified.is_some_and(|modified| cache.get(file, modified).is_some()) {
+            estimate.cached_files += 1;
+            continue;
+        }
+        if llm.is_none() {
+            continue;
+        }
+
+        let Some(analyzer) = languages.analyzer_for(file) else {
+            continue;
+        };
+        let Ok(analysis) = analyzer.analyze(&content) else {
+            continue;
+        };
+
+        for func in analysis.functions.iter().filter(|f| analyzer.needs_summary(f)) {
+            estimate.llm_calls += 1;
+            estimate.prompt_tokens += PROMPT_OVERHEAD_TOKENS
+                + calculate_tokens(&func.signature)
+                + calculate_tokens(func.body.as_deref().unwrap_or(""));
+        }
+        for ty in &analysis.significant_types {
+            estimate.llm_calls += 1;
+            estimate.prompt_tokens += PROMPT_OVERHEAD_TOKENS + calculate_tokens(&ty.definition);
+        }
+    }
+
+    if let Some(llm) = llm {
+        estimate.completion_tokens = estimate.llm_calls * COMPLETION_TOKENS_PER_CALL;
+        estimate.estimated_duration =
+            Duration::from_secs_f64(estimate.llm_calls as f64 * llm.estimated_seconds_per_call());
+        estimate.estimated_cost = (estimate.prompt_tokens + estimate.completion_tokens) as f64
+            / 1000.0
+            * llm.estimated_cost_per_1k_tokens();
+    }
+    estimate
+}
+
+impl fmt::Display for RunEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{}",
+            tr!(Message::EstimateFiles, self.files, self.total_lines, self.cached_files)
+        )?;
+        writeln!(
+            f,
+            "{}",
+            tr!(
+                Message::EstimateCalls,
+                self.llm_calls,
+                self.prompt_tokens + self.completion_tokens
+            )
+        )?;
+        write!(
+            f,
+            "{}",
+            tr!(
+                Message::EstimateCost,
+                format_duration(self.estimated_duration),
+                format!("{:.2}", self.estimated_cost)
+            )
+        )
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
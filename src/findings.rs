@@ -0,0 +1,356 @@
+use crate::config::Config;
+use crate::error::FolderSummaryError;
+use crate::report::Report;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Every rule a finding can come from, with its default severity.
+pub const RULES: &[(&str, Severity)] = &[
+    ("high-complexity", Severity::Warning),
+    ("long-function", Severity::Warning),
+    ("too-many-parameters", Severity::Warning),
+    ("blocking-in-async", Severity::Warning),
+    ("layering-violation", Severity::Warning),
+    ("swallowed-errors", Severity::Warning),
+    ("text-hygiene", Severity::Info),
+    ("analysis-failed", Severity::Error),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn parse(severity: &str) -> Option<Self> {
+        match severity {
+            "info" => Some(Severity::Info),
+            "warning" => Some(Severity::Warning),
+            "error" => Some(Severity::Error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// One problem the analysis turned up, in the shape every output format renders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    /// Stable identifier of the check, e.g. `high-complexity` or `layering-violation`.
+    pub rule: String,
+    pub severity: Severity,
+    pub file: String,
+    /// 1-based; `None` for findings about a whole file.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Findings listed in the markdown and HTML reports; the JSON report has them all.
+pub const MAX_LISTED_FINDINGS: usize = 50;
+
+/// Limits above which a function is flagged, and the severity of each rule.
+#[derive(Debug, Clone)]
+pub struct FindingRules {
+    pub max_complexity: usize,
+    pub max_lines: usize,
+    pub max_parameters: usize,
+    /// `None` turns a rule off.
+    severities: HashMap<&'static str, Option<Severity>>,
+}
+
+impl FindingRules {
+    /// Reads the thresholds and the severities set in `[rules]`, rejecting unknown rules
+    /// and severities.
+    pub fn from_config(config: &Config) -> Result<Self, FolderSummaryError> {
+        let mut severities: HashMap<_, _> = RULES
+            .iter()
+            .map(|(rule, severity)| (*rule, Some(*severity)))
+            .collect();
+        if let Some(rules) = &config.rules {
+            for (rule, severity) in &rules.severities {
+                let Some((name, _)) = RULES.iter().find(|(name, _)| name == rule) else {
+                    return Err(FolderSummaryError::ConfigError(format!(
+                        "Unknown rule \"{}\" in [rules]; known rules: {}",
+                        rule,
+                        RULES
+                            .iter()
+                            .map(|(name, _)| *name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )));
+                };
+                let severity = match severity.as_str() {
+                    "off" => None,
+                    other => Some(Severity::parse(other).ok_or_else(|| {
+                        FolderSummaryError::ConfigError(format!(
+                            "Invalid severity \"{}\" for rule {}: expected \"off\", \"info\", \"warning\" or \"error\"",
+                            other, rule
+                        ))
+                    })?),
+                };
+                severities.insert(name, severity);
+            }
+        }
+        Ok(FindingRules {
+            max_complexity: config.get_complexity_warning_threshold(),
+            max_lines: config.get_length_warning_threshold(),
+            max_parameters: config.get_parameters_warning_threshold(),
+            severities,
+        })
+    }
+
+    /// The severity findings of `rule` get, or `None` if it is turned off.
+    pub fn severity(&self, rule: &str) -> Option<Severity> {
+        self.severities.get(rule).copied().flatten()
+    }
+}
+
+/// Gathers the report's complexity breaches, blocking calls in async functions, layering
+/// violations, swallowed errors, text hygiene issues and analysis failures into one
+/// list, most severe first. Rules turned off in `rules` and findings silenced by a
+/// `folder-summary:ignore` comment are left out.
+pub fn collect_findings(report: &Report, rules: &FindingRules) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut add = |rule: &str, file: &str, line: Option<usize>, message| {
+        let Some(severity) = rules.severity(rule) else {
+            return;
+        };
+        let suppressed = report
+            .files
+            .get(file)
+            .is_some_and(|analysis| analysis.suppresses(rule, line));
+        if !suppressed {
+            findings.push(Finding {
+                rule: rule.to_string(),
+                severity,
+                file: file.to_string(),
+                line,
+                message,
+            })
+        }
+    };
+
+    for (file, analysis) in &report.files {
+        // Duplicates would repeat every finding of the file they copy.
+        if report.duplicates.contains_key(file) {
+            continue;
+        }
+        for func in &analysis.functions {
+            let line = func.line;
+            if func.cyclomatic_complexity > rules.max_complexity {
+                add(
+                    "high-complexity",
+                    file,
+                    line,
+                    format!(
+                        "{} has cyclomatic complexity {} (threshold {})",
+                        func.name, func.cyclomatic_complexity, rules.max_complexity
+                    ),
+                );
+            }
+            if func.lines_of_code > rules.max_lines {
+                add(
+                    "long-function",
+                    file,
+                    line,
+                    format!(
+                        "{} is {} lines long (threshold {})",
+                        func.name, func.lines_of_code, rules.max_lines
+                    ),
+                );
+            }
+            if func.parameters > rules.max_parameters {
+                add(
+                    "too-many-parameters",
+                    file,
+                    line,
+                    format!(
+                        "{} takes {} parameters (threshold {})",
+                        func.name, func.parameters, rules.max_parameters
+                    ),
+                );
+            }
+            if !func.blocking_calls.is_empty() {
+                add(
+                    "blocking-in-async",
+                    file,
+                    line,
+                    format!(
+                        "async {} calls {}",
+                        func.name,
+                        func.blocking_calls.join(", ")
+                    ),
+                );
+            }
+        }
+    }
+
+    for violation in &report.layer_violations {
+        add(
+            "layering-violation",
+            &violation.file,
+            None,
+            format!(
+                "{} imports from {} ({})",
+                violation.layer, violation.target, violation.import
+            ),
+        );
+    }
+    if let Some(error_handling) = &report.error_handling {
+        for file in error_handling
+            .files
+            .iter()
+            .filter(|file| file.swallowed > 0)
+        {
+            add(
+                "swallowed-errors",
+                &file.file,
+                None,
+                format!("{} error handlers drop the error", file.swallowed),
+            );
+        }
+    }
+    if let Some(hygiene) = &report.hygiene {
+        for issue in &hygiene.issues {
+            add("text-hygiene", &issue.file, None, issue.issue.clone());
+        }
+    }
+    for failure in &report.failures {
+        add(
+            "analysis-failed",
+            &failure.file,
+            None,
+            failure.reason.clone(),
+        );
+    }
+
+    findings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.file.cmp(&b.file))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    findings
+}
+
+/// Reads a baseline written by `--write-baseline`.
+pub fn load_baseline(path: &Path) -> Result<Vec<Finding>, FolderSummaryError> {
+    let contents =
+        fs::read(path).map_err(|e| FolderSummaryError::file(path.display().to_string(), e))?;
+    Ok(serde_json::from_slice(&contents)?)
+}
+
+/// Records `findings` as the baseline later runs compare against.
+pub fn write_baseline(path: &Path, findings: &[&Finding]) -> Result<(), FolderSummaryError> {
+    let contents = serde_json::to_vec_pretty(findings)?;
+    fs::write(path, contents).map_err(|e| FolderSummaryError::file(path.display().to_string(), e))
+}
+
+/// Drops the findings already recorded in `baseline` and returns how many were dropped.
+/// Findings match on rule, file and message but not line, so code moving around doesn't
+/// turn old findings into new ones; each baseline entry matches one finding.
+pub fn remove_baselined(findings: &mut Vec<Finding>, baseline: &[Finding]) -> usize {
+    let key = |finding: &Finding| {
+        (
+            finding.rule.clone(),
+            finding.file.clone(),
+            finding.message.clone(),
+        )
+    };
+    let mut known: HashMap<_, usize> = HashMap::new();
+    for finding in baseline {
+        *known.entry(key(finding)).or_default() += 1;
+    }
+    let before = findings.len();
+    findings.retain(|finding| match known.get_mut(&key(finding)) {
+        Some(count) if *count > 0 => {
+            *count -= 1;
+            false
+        }
+        _ => true,
+    });
+    before - findings.len()
+}
+
+/// `2 errors, 5 warnings, 1 info`, leaving out severities with no findings, or
+/// `No new findings`.
+pub fn finding_counts(findings: &[Finding]) -> String {
+    if findings.is_empty() {
+        return "No new findings".to_string();
+    }
+    [
+        (Severity::Error, "error", "errors"),
+        (Severity::Warning, "warning", "warnings"),
+        (Severity::Info, "info", "info"),
+    ]
+    .iter()
+    .filter_map(|(severity, one, many)| {
+        let count = findings.iter().filter(|f| f.severity == *severity).count();
+        (count > 0).then(|| format!("{} {}", count, if count == 1 { one } else { many }))
+    })
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(rule: &str, file: &str, line: usize, message: &str) -> Finding {
+        Finding {
+            rule: rule.to_string(),
+            severity: Severity::Warning,
+            file: file.to_string(),
+            line: Some(line),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn baselined_findings_are_suppressed_and_new_ones_reported() {
+        let baseline = vec![
+            finding("high-complexity", "src/lib.rs", 10, "parse has complexity 14"),
+            finding("long-function", "src/main.rs", 40, "run is 120 lines long"),
+        ];
+        let mut findings = vec![
+            // Moved down by an edit above it: still the same finding.
+            finding("high-complexity", "src/lib.rs", 25, "parse has complexity 14"),
+            // Same rule and file, different function: new.
+            finding("high-complexity", "src/lib.rs", 60, "load has complexity 12"),
+            finding("too-many-parameters", "src/main.rs", 5, "init takes 8 parameters"),
+        ];
+
+        let removed = remove_baselined(&mut findings, &baseline);
+
+        assert_eq!(removed, 1);
+        let remaining: Vec<&str> = findings.iter().map(|f| f.message.as_str()).collect();
+        assert_eq!(remaining, ["load has complexity 12", "init takes 8 parameters"]);
+    }
+
+    #[test]
+    fn each_baseline_entry_suppresses_one_finding() {
+        let baseline = vec![finding("swallowed-errors", "src/io.rs", 3, "error ignored")];
+        let mut findings = vec![
+            finding("swallowed-errors", "src/io.rs", 3, "error ignored"),
+            finding("swallowed-errors", "src/io.rs", 9, "error ignored"),
+        ];
+
+        assert_eq!(remove_baselined(&mut findings, &baseline), 1);
+        assert_eq!(findings.len(), 1);
+    }
+}
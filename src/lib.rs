@@ -1,12 +1,32 @@
 pub mod analyzer;
 pub mod cache;
+pub mod collector;
 pub mod config;
+pub mod daemon;
+pub mod docs;
 pub mod error;
+pub mod estimate;
+pub mod findings;
 pub mod llm;
+pub mod messages;
+pub mod notify;
+pub mod paths;
+pub mod progress;
+pub mod render;
+pub mod report;
+pub mod run_log;
+pub mod schedule;
+pub mod server;
+pub mod summarizer;
 pub mod summary;
+pub mod testing;
+pub mod timings;
+pub mod upload;
 pub mod utils;
 
 pub use analyzer::CodeAnalysis;
 pub use config::Config;
 pub use error::FolderSummaryError;
 pub use llm::LLM;
+pub use report::Report;
+pub use summarizer::FolderSummarizer;
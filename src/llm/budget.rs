@@ -0,0 +1,86 @@
+use super::{calculate_tokens, LLM};
+use crate::error::FolderSummaryError;
+use async_trait::async_trait;
+use log::warn;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A cap on the prompt and completion tokens of a run, shared by every provider and
+/// every directory in it.
+#[derive(Debug)]
+pub struct TokenBudget {
+    limit: usize,
+    used: AtomicUsize,
+    exhausted: AtomicBool,
+}
+
+impl TokenBudget {
+    pub fn new(limit: usize) -> Self {
+        TokenBudget {
+            limit,
+            used: AtomicUsize::new(0),
+            exhausted: AtomicBool::new(false),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Tokens spent so far, counted the same rough way as the run estimate.
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Whether a call has been turned away because the budget was spent.
+    pub fn exhausted(&self) -> bool {
+        self.exhausted.load(Ordering::Relaxed)
+    }
+}
+
+/// Wraps a provider so its calls draw on a shared [`TokenBudget`]. Once it is spent, calls
+/// fail with [`FolderSummaryError::TokenBudgetExhausted`] without reaching the provider;
+/// calls already in flight still finish, so a run can go slightly over.
+pub struct Budgeted {
+    inner: Arc<dyn LLM>,
+    budget: Arc<TokenBudget>,
+}
+
+impl Budgeted {
+    pub fn new(inner: Arc<dyn LLM>, budget: Arc<TokenBudget>) -> Self {
+        Budgeted { inner, budget }
+    }
+}
+
+#[async_trait]
+impl LLM for Budgeted {
+    async fn summarize(&self, text: &str) -> Result<String, FolderSummaryError> {
+        if self.budget.used() >= self.budget.limit {
+            if !self.budget.exhausted.swap(true, Ordering::Relaxed) {
+                warn!(
+                    "Token budget of {} spent; the remaining files get static analysis only",
+                    self.budget.limit
+                );
+            }
+            return Err(FolderSummaryError::TokenBudgetExhausted);
+        }
+        let summary = self.inner.summarize(text).await?;
+        self.budget.used.fetch_add(
+            calculate_tokens(text) + calculate_tokens(&summary),
+            Ordering::Relaxed,
+        );
+        Ok(summary)
+    }
+    fn model_name(&self) -> String {
+        self.inner.model_name()
+    }
+    fn estimated_seconds_per_call(&self) -> f64 {
+        self.inner.estimated_seconds_per_call()
+    }
+    fn estimated_cost_per_1k_tokens(&self) -> f64 {
+        self.inner.estimated_cost_per_1k_tokens()
+    }
+    fn context_window(&self) -> usize {
+        self.inner.context_window()
+    }
+}
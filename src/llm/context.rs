@@ -0,0 +1,38 @@
+use super::LLM;
+use crate::error::FolderSummaryError;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Wraps a provider and opens every prompt with `context`, such as the project and file
+/// the function being summarized comes from.
+pub struct WithContext {
+    inner: Arc<dyn LLM>,
+    context: String,
+}
+
+impl WithContext {
+    pub fn new(inner: Arc<dyn LLM>, context: String) -> Self {
+        WithContext { inner, context }
+    }
+}
+
+#[async_trait]
+impl LLM for WithContext {
+    async fn summarize(&self, text: &str) -> Result<String, FolderSummaryError> {
+        self.inner
+            .summarize(&format!("{}\n\n{}", self.context, text))
+            .await
+    }
+    fn model_name(&self) -> String {
+        self.inner.model_name()
+    }
+    fn estimated_seconds_per_call(&self) -> f64 {
+        self.inner.estimated_seconds_per_call()
+    }
+    fn estimated_cost_per_1k_tokens(&self) -> f64 {
+        self.inner.estimated_cost_per_1k_tokens()
+    }
+    fn context_window(&self) -> usize {
+        self.inner.context_window()
+    }
+}
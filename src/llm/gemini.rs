@@ -1,4 +1,5 @@
 use super::LLM;
+use crate::error::FolderSummaryError;
 use super::{calculate_tokens, log_performance};
 use async_trait::async_trait;
 use reqwest::Client;
@@ -36,10 +37,7 @@ impl Gemini {
 
 #[async_trait]
 impl LLM for Gemini {
-    fn clone_box(&self) -> Box<dyn LLM> {
-        Box::new(self.clone())
-    }
-    async fn summarize(&self, text: &str) -> Result<String, Box<dyn std::error::Error>> {
+    async fn summarize(&self, text: &str) -> Result<String, FolderSummaryError> {
         let start_time = Instant::now();
         let input_tokens = calculate_tokens(text);
 
@@ -52,9 +50,11 @@ impl LLM for Gemini {
                 "stream": false
             }))
             .send()
-            .await?
+            .await
+            .map_err(|e| FolderSummaryError::provider(self.model_name(), e))?
             .json::<serde_json::Value>()
-            .await?;
+            .await
+            .map_err(|e| FolderSummaryError::provider(self.model_name(), e))?;
 
         let output = response["response"].as_str().unwrap_or("").to_string();
         let output_tokens = calculate_tokens(&output);
@@ -66,4 +66,13 @@ impl LLM for Gemini {
     fn model_name(&self) -> String {
         self.model_name()
     }
+    fn estimated_seconds_per_call(&self) -> f64 {
+        2.0
+    }
+    fn estimated_cost_per_1k_tokens(&self) -> f64 {
+        0.00125
+    }
+    fn context_window(&self) -> usize {
+        1_000_000
+    }
 }
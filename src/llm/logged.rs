@@ -0,0 +1,47 @@
+use super::{calculate_tokens, LLM};
+use crate::error::FolderSummaryError;
+use crate::run_log::{LlmCall, RunLog, CURRENT_FILE};
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Wraps a provider and records each of its calls in a [`RunLog`].
+pub struct Logged {
+    inner: Arc<dyn LLM>,
+    log: Arc<RunLog>,
+}
+
+impl Logged {
+    pub fn new(inner: Arc<dyn LLM>, log: Arc<RunLog>) -> Self {
+        Logged { inner, log }
+    }
+}
+
+#[async_trait]
+impl LLM for Logged {
+    async fn summarize(&self, text: &str) -> Result<String, FolderSummaryError> {
+        let start = Instant::now();
+        let result = self.inner.summarize(text).await;
+        self.log.record_llm_call(LlmCall {
+            model: self.inner.model_name(),
+            file: CURRENT_FILE.try_with(Clone::clone).ok(),
+            duration_secs: start.elapsed().as_secs_f64(),
+            prompt_tokens: calculate_tokens(text),
+            completion_tokens: result.as_deref().map_or(0, calculate_tokens),
+            error: result.as_ref().err().map(ToString::to_string),
+        });
+        result
+    }
+    fn model_name(&self) -> String {
+        self.inner.model_name()
+    }
+    fn estimated_seconds_per_call(&self) -> f64 {
+        self.inner.estimated_seconds_per_call()
+    }
+    fn estimated_cost_per_1k_tokens(&self) -> f64 {
+        self.inner.estimated_cost_per_1k_tokens()
+    }
+    fn context_window(&self) -> usize {
+        self.inner.context_window()
+    }
+}
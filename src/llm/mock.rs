@@ -0,0 +1,28 @@
+use super::LLM;
+use crate::error::FolderSummaryError;
+use async_trait::async_trait;
+
+/// A provider that answers without a model, for end-to-end tests and `folder-summary
+/// selftest`: the same prompt always gets the same summary, naming the function when
+/// the prompt has a `Name:` line.
+pub struct Mock;
+
+#[async_trait]
+impl LLM for Mock {
+    async fn summarize(&self, text: &str) -> Result<String, FolderSummaryError> {
+        let name = text
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Name:"))
+            .map(str::trim);
+        Ok(match name {
+            Some(name) => format!("Mock summary of {}.", name),
+            None => format!("Mock summary of a {}-line prompt.", text.lines().count()),
+        })
+    }
+    fn model_name(&self) -> String {
+        "Mock".to_string()
+    }
+    fn estimated_seconds_per_call(&self) -> f64 {
+        0.0
+    }
+}
@@ -1,27 +1,52 @@
+mod budget;
+mod context;
 mod gemini;
+mod logged;
+mod mock;
 mod ollama;
 mod openai;
+mod rate_limit;
+mod timed;
+mod validate;
 
+pub use budget::{Budgeted, TokenBudget};
+pub use context::WithContext;
 pub use gemini::Gemini;
-pub use ollama::Ollama;
+pub use logged::Logged;
+pub use mock::Mock;
+pub use ollama::{Ollama, PullProgress};
 pub use openai::OpenAI;
+pub use rate_limit::{RateLimited, RateLimiter, RequestLimits};
+pub use timed::Timed;
+pub use validate::Validated;
 
 use crate::config::Config;
+use crate::error::FolderSummaryError;
 use async_trait::async_trait;
 use log::info;
 use std::env;
+use std::sync::Arc;
 use std::time::Instant;
 
 #[async_trait]
 pub trait LLM: Send + Sync {
-    async fn summarize(&self, text: &str) -> Result<String, Box<dyn std::error::Error>>;
+    async fn summarize(&self, text: &str) -> Result<String, FolderSummaryError>;
     fn model_name(&self) -> String;
-    fn clone_box(&self) -> Box<dyn LLM>;
-}
 
-impl Clone for Box<dyn LLM> {
-    fn clone(&self) -> Self {
-        self.clone_box()
+    /// Rough average latency of a single summarization call, used for run estimates.
+    fn estimated_seconds_per_call(&self) -> f64 {
+        5.0
+    }
+
+    /// Rough blended USD price per 1k tokens, used for run estimates.
+    fn estimated_cost_per_1k_tokens(&self) -> f64 {
+        0.0
+    }
+
+    /// Tokens the model reads per call, prompt and answer together; large functions are
+    /// cut to fit.
+    fn context_window(&self) -> usize {
+        8192
     }
 }
 
@@ -47,36 +72,49 @@ pub fn log_performance(
     );
 }
 
-pub fn get_llm(config: &Config) -> Result<Box<dyn LLM>, Box<dyn std::error::Error>> {
-    let llm_provider: Result<String, env::VarError> = env::var("LLM_PROVIDER").or_else(|_| {
-        Ok(config
+pub fn get_llm(config: &Config) -> Result<Arc<dyn LLM>, FolderSummaryError> {
+    provider_llm(config, &llm_provider(config))
+}
+
+/// The main provider: `LLM_PROVIDER`, else the config's `llm_provider`, else `ollama`.
+pub fn llm_provider(config: &Config) -> String {
+    env::var("LLM_PROVIDER").unwrap_or_else(|_| {
+        config
             .llm_provider
             .clone()
-            .unwrap_or_else(|| "ollama".to_string()))
-    });
+            .unwrap_or_else(|| "ollama".to_string())
+    })
+}
 
-    match llm_provider?.as_str() {
-        "ollama" => {
-            let model = env::var("OLLAMA_MODEL").unwrap_or_else(|_| {
-                config
-                    .ollama_model
-                    .clone()
-                    .unwrap_or_else(|| "mannix/gemma2-2b".to_string())
-            });
-            Ok(Box::new(Ollama::new(&model)))
-        }
+/// The Ollama model to use: `OLLAMA_MODEL`, else the config's `ollama_model`.
+pub fn ollama_model(config: &Config) -> String {
+    env::var("OLLAMA_MODEL").unwrap_or_else(|_| {
+        config
+            .ollama_model
+            .clone()
+            .unwrap_or_else(|| "mannix/gemma2-2b".to_string())
+    })
+}
+
+/// The client for `llm_provider` (`ollama`, `gemini`, `openai` or `mock`), with the model and
+/// endpoint the config and environment give for it. Its summaries aren't checked yet; see
+/// [`validated`].
+pub fn provider_llm(config: &Config, llm_provider: &str) -> Result<Arc<dyn LLM>, FolderSummaryError> {
+    let llm: Arc<dyn LLM> = match llm_provider {
+        "ollama" => Arc::new(Ollama::new(&ollama_model(config))),
+        "mock" => Arc::new(Mock),
         "gemini" => {
-            let api_key = env::var("GEMINI_API_KEY")?;
+            let api_key = require_env("GEMINI_API_KEY")?;
             let model = env::var("GEMINI_MODEL").unwrap_or_else(|_| {
                 config
                     .gemini_model
                     .clone()
                     .unwrap_or_else(|| "gemini-1.5-flash".to_string())
             });
-            Ok(Box::new(Gemini::new(&api_key, &model)))
+            Arc::new(Gemini::new(&api_key, &model))
         }
         "openai" => {
-            let api_key = env::var("OPENAI_API_KEY")?;
+            let api_key = require_env("OPENAI_API_KEY")?;
             let model = env::var("OPENAI_MODEL").unwrap_or_else(|_| {
                 config
                     .openai_model
@@ -89,8 +127,26 @@ pub fn get_llm(config: &Config) -> Result<Box<dyn LLM>, Box<dyn std::error::Erro
                     .clone()
                     .unwrap_or_else(|| "https://api.openai.com/v1".to_string())
             });
-            Ok(Box::new(OpenAI::new(&api_key, &model, &url)))
+            Arc::new(OpenAI::new(&api_key, &model, &url))
         }
-        _ => Err("Invalid LLM provider".into()),
-    }
+        other => {
+            return Err(FolderSummaryError::ConfigError(format!(
+                "Invalid LLM provider: {}",
+                other
+            )))
+        }
+    };
+    Ok(llm)
+}
+
+/// `llm` with its summaries [`Validated`], retried up to the config's `summary_retries`.
+/// Wrap it around any rate limit, log or budget so every retry goes through them too.
+pub fn validated(config: &Config, llm: Arc<dyn LLM>) -> Arc<dyn LLM> {
+    Arc::new(Validated::new(llm, config.get_summary_retries()))
+}
+
+fn require_env(name: &str) -> Result<String, FolderSummaryError> {
+    env::var(name).map_err(|_| {
+        FolderSummaryError::ConfigError(format!("{} must be set for this provider", name))
+    })
 }
@@ -1,10 +1,23 @@
 use super::LLM;
+use crate::error::FolderSummaryError;
 use super::{calculate_tokens, log_performance};
 use async_trait::async_trait;
 use reqwest::Client;
-use serde_json::json;
+use serde_json::{json, Value};
 use std::time::Instant;
 
+const OLLAMA_URL: &str = "http://localhost:11434";
+
+/// One status update of a model download.
+#[derive(Debug, Clone)]
+pub struct PullProgress {
+    /// What Ollama is doing, e.g. `pulling manifest` or `verifying sha256 digest`.
+    pub status: String,
+    /// Bytes of the current layer downloaded so far, and its size.
+    pub completed: Option<u64>,
+    pub total: Option<u64>,
+}
+
 pub struct Ollama {
     model: String,
     client: Client,
@@ -29,29 +42,99 @@ impl Ollama {
     pub fn model_name(&self) -> String {
         format!("Ollama ({})", self.model)
     }
+
+    /// Whether the local Ollama server has the model, according to `/api/tags`. A model
+    /// named without a tag means its `:latest` tag.
+    pub async fn has_model(&self) -> Result<bool, FolderSummaryError> {
+        let response = self
+            .client
+            .get(format!("{}/api/tags", OLLAMA_URL))
+            .send()
+            .await
+            .map_err(|e| self.unreachable(e))?
+            .json::<Value>()
+            .await
+            .map_err(|e| FolderSummaryError::provider(self.model_name(), e))?;
+        let wanted = if self.model.contains(':') {
+            self.model.clone()
+        } else {
+            format!("{}:latest", self.model)
+        };
+        Ok(response["models"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|model| model["name"].as_str())
+            .any(|name| name == wanted))
+    }
+
+    /// Downloads the model with `/api/pull`, passing each status update Ollama streams
+    /// back to `progress`.
+    pub async fn pull(&self, mut progress: impl FnMut(PullProgress)) -> Result<(), FolderSummaryError> {
+        let mut response = self
+            .client
+            .post(format!("{}/api/pull", OLLAMA_URL))
+            .json(&json!({ "name": self.model, "stream": true }))
+            .send()
+            .await
+            .map_err(|e| self.unreachable(e))?;
+        // Newline-delimited JSON; a line can be split across chunks.
+        let mut buffer = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| FolderSummaryError::provider(self.model_name(), e))?
+        {
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let Ok(update) = serde_json::from_slice::<Value>(&line) else {
+                    continue;
+                };
+                if let Some(error) = update["error"].as_str() {
+                    return Err(FolderSummaryError::provider(
+                        self.model_name(),
+                        format!("pull failed: {}", error),
+                    ));
+                }
+                progress(PullProgress {
+                    status: update["status"].as_str().unwrap_or("").to_string(),
+                    completed: update["completed"].as_u64(),
+                    total: update["total"].as_u64(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn unreachable(&self, e: reqwest::Error) -> FolderSummaryError {
+        FolderSummaryError::provider(
+            self.model_name(),
+            format!("can't reach Ollama at {} ({}); is `ollama serve` running?", OLLAMA_URL, e),
+        )
+    }
 }
 
 #[async_trait]
 impl LLM for Ollama {
-    fn clone_box(&self) -> Box<dyn LLM> {
-        Box::new(self.clone())
-    }
-    async fn summarize(&self, text: &str) -> Result<String, Box<dyn std::error::Error>> {
+    async fn summarize(&self, text: &str) -> Result<String, FolderSummaryError> {
         let start_time = Instant::now();
         let input_tokens = calculate_tokens(text);
 
         let response = self
             .client
-            .post("http://localhost:11434/api/generate")
+            .post(format!("{}/api/generate", OLLAMA_URL))
             .json(&json!({
                 "model": self.model,
                 "prompt": format!("Summarize this function in one line: {}", text),
                 "stream": false
             }))
             .send()
-            .await?
-            .json::<serde_json::Value>()
-            .await?;
+            .await
+            .map_err(|e| FolderSummaryError::provider(self.model_name(), e))?
+            .json::<Value>()
+            .await
+            .map_err(|e| FolderSummaryError::provider(self.model_name(), e))?;
 
         let output = response["response"].as_str().unwrap_or("").to_string();
         let output_tokens = calculate_tokens(&output);
@@ -63,4 +146,12 @@ impl LLM for Ollama {
     fn model_name(&self) -> String {
         self.model_name()
     }
+    fn estimated_seconds_per_call(&self) -> f64 {
+        // Local models are free but usually much slower than hosted APIs.
+        8.0
+    }
+    fn context_window(&self) -> usize {
+        // Ollama's default `num_ctx`, whatever the model supports.
+        2048
+    }
 }
@@ -1,4 +1,5 @@
 use super::LLM;
+use crate::error::FolderSummaryError;
 use super::{calculate_tokens, log_performance};
 use async_trait::async_trait;
 use reqwest::Client;
@@ -39,15 +40,14 @@ impl OpenAI {
 
 #[async_trait]
 impl LLM for OpenAI {
-    fn clone_box(&self) -> Box<dyn LLM> {
-        Box::new(self.clone())
-    }
-    async fn summarize(&self, text: &str) -> Result<String, Box<dyn std::error::Error>> {
+    async fn summarize(&self, text: &str) -> Result<String, FolderSummaryError> {
         let start_time = Instant::now();
         let input_tokens = calculate_tokens(text);
 
         let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert("Authorization", format!("Bearer {}", self.api_key).parse()?);
+        headers.insert("Authorization", format!("Bearer {}", self.api_key)
+                .parse()
+                .map_err(|e| FolderSummaryError::provider(self.model_name(), e))?);
 
         let response = self.client
             .post(format!("{}/chat/completions", self.url))
@@ -60,9 +60,11 @@ impl LLM for OpenAI {
                 ]
             }))
             .send()
-            .await?
+            .await
+            .map_err(|e| FolderSummaryError::provider(self.model_name(), e))?
             .json::<serde_json::Value>()
-            .await?;
+            .await
+            .map_err(|e| FolderSummaryError::provider(self.model_name(), e))?;
 
         let output = response["choices"][0]["message"]["content"]
             .as_str()
@@ -77,4 +79,17 @@ impl LLM for OpenAI {
     fn model_name(&self) -> String {
         self.model_name()
     }
+    fn estimated_seconds_per_call(&self) -> f64 {
+        2.0
+    }
+    fn estimated_cost_per_1k_tokens(&self) -> f64 {
+        if self.model.contains("mini") {
+            0.0003
+        } else {
+            0.005
+        }
+    }
+    fn context_window(&self) -> usize {
+        128_000
+    }
 }
@@ -0,0 +1,86 @@
+use super::LLM;
+use crate::error::FolderSummaryError;
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{sleep_until, Instant};
+
+/// Spaces calls evenly so no more than `requests_per_minute` start in any minute.
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: u32) -> Self {
+        RateLimiter {
+            interval: Duration::from_secs(60) / requests_per_minute.max(1),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn acquire(&self) {
+        let mut next_slot = self.next_slot.lock().await;
+        let now = Instant::now();
+        if *next_slot > now {
+            sleep_until(*next_slot).await;
+        }
+        *next_slot = (*next_slot).max(now) + self.interval;
+    }
+}
+
+/// One request budget for the main provider and one for the `[route]` provider, shared
+/// by every summarizer they are handed to, such as those of a batch.
+#[derive(Clone)]
+pub struct RequestLimits {
+    pub main: Arc<RateLimiter>,
+    pub route: Arc<RateLimiter>,
+}
+
+impl RequestLimits {
+    pub fn new(requests_per_minute: u32) -> Self {
+        RequestLimits {
+            main: Arc::new(RateLimiter::new(requests_per_minute)),
+            route: Arc::new(RateLimiter::new(requests_per_minute)),
+        }
+    }
+}
+
+/// Wraps a provider so every caller sharing the wrapper, or its limiter, shares one
+/// request budget.
+pub struct RateLimited {
+    inner: Arc<dyn LLM>,
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimited {
+    pub fn new(inner: Arc<dyn LLM>, requests_per_minute: u32) -> Self {
+        Self::sharing(inner, Arc::new(RateLimiter::new(requests_per_minute)))
+    }
+
+    /// Draws on `limiter`, which other wrappers may be drawing on too.
+    pub fn sharing(inner: Arc<dyn LLM>, limiter: Arc<RateLimiter>) -> Self {
+        RateLimited { inner, limiter }
+    }
+}
+
+#[async_trait]
+impl LLM for RateLimited {
+    async fn summarize(&self, text: &str) -> Result<String, FolderSummaryError> {
+        self.limiter.acquire().await;
+        self.inner.summarize(text).await
+    }
+    fn model_name(&self) -> String {
+        self.inner.model_name()
+    }
+    fn estimated_seconds_per_call(&self) -> f64 {
+        self.inner.estimated_seconds_per_call()
+    }
+    fn estimated_cost_per_1k_tokens(&self) -> f64 {
+        self.inner.estimated_cost_per_1k_tokens()
+    }
+    fn context_window(&self) -> usize {
+        self.inner.context_window()
+    }
+}
@@ -0,0 +1,41 @@
+use super::LLM;
+use crate::error::FolderSummaryError;
+use crate::timings::Timings;
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Wraps a provider and records the time spent in its calls under `LLM: <model>`.
+pub struct Timed {
+    inner: Arc<dyn LLM>,
+    timings: Arc<Timings>,
+}
+
+impl Timed {
+    pub fn new(inner: Arc<dyn LLM>, timings: Arc<Timings>) -> Self {
+        Timed { inner, timings }
+    }
+}
+
+#[async_trait]
+impl LLM for Timed {
+    async fn summarize(&self, text: &str) -> Result<String, FolderSummaryError> {
+        let start = Instant::now();
+        let result = self.inner.summarize(text).await;
+        self.timings
+            .record(&format!("LLM: {}", self.inner.model_name()), start.elapsed());
+        result
+    }
+    fn model_name(&self) -> String {
+        self.inner.model_name()
+    }
+    fn estimated_seconds_per_call(&self) -> f64 {
+        self.inner.estimated_seconds_per_call()
+    }
+    fn estimated_cost_per_1k_tokens(&self) -> f64 {
+        self.inner.estimated_cost_per_1k_tokens()
+    }
+    fn context_window(&self) -> usize {
+        self.inner.context_window()
+    }
+}
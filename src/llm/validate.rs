@@ -0,0 +1,152 @@
+use super::LLM;
+use crate::error::FolderSummaryError;
+use async_trait::async_trait;
+use log::{debug, warn};
+use std::sync::Arc;
+
+/// Phrases small models fall back on instead of answering.
+const BOILERPLATE: &[&str] = &[
+    "as an ai",
+    "language model",
+    "i'm sorry",
+    "i am sorry",
+    "i cannot",
+    "i can't",
+    "i'm unable",
+    "i am unable",
+];
+/// Words no finished sentence ends on.
+const DANGLING_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "by", "for", "from", "in", "of", "or", "that", "the", "to", "with",
+];
+
+/// Wraps a provider and rejects degenerate summaries: empty ones, "As an AI…" boilerplate,
+/// ones cut off mid-sentence and ones longer than the prompt they summarize. A rejected
+/// summary is asked for again, up to `retries` times, with a prompt saying what was wrong;
+/// if every attempt is rejected the summary is left empty rather than reported.
+pub struct Validated {
+    inner: Arc<dyn LLM>,
+    retries: usize,
+}
+
+impl Validated {
+    pub fn new(inner: Arc<dyn LLM>, retries: usize) -> Self {
+        Validated { inner, retries }
+    }
+}
+
+#[async_trait]
+impl LLM for Validated {
+    async fn summarize(&self, text: &str) -> Result<String, FolderSummaryError> {
+        let mut prompt = text.to_string();
+        for attempt in 0..=self.retries {
+            let summary = self.inner.summarize(&prompt).await?;
+            let Some(reason) = rejection(&summary, text) else {
+                return Ok(summary.trim().to_string());
+            };
+            debug!(
+                "{} summary rejected on attempt {} ({}): {:?}",
+                self.inner.model_name(),
+                attempt + 1,
+                reason,
+                summary
+            );
+            prompt = format!(
+                "{}\n\nA previous answer was rejected because {}. Reply with a short, complete summary in plain sentences.",
+                text, reason
+            );
+        }
+        warn!(
+            "{} gave no usable summary in {} attempts; leaving it out",
+            self.inner.model_name(),
+            self.retries + 1
+        );
+        Ok(String::new())
+    }
+    fn model_name(&self) -> String {
+        self.inner.model_name()
+    }
+    fn estimated_seconds_per_call(&self) -> f64 {
+        self.inner.estimated_seconds_per_call()
+    }
+    fn estimated_cost_per_1k_tokens(&self) -> f64 {
+        self.inner.estimated_cost_per_1k_tokens()
+    }
+    fn context_window(&self) -> usize {
+        self.inner.context_window()
+    }
+}
+
+// Why `summary` is unusable as a summary of `prompt`, or `None` if it looks fine.
+fn rejection(summary: &str, prompt: &str) -> Option<&'static str> {
+    let summary = summary.trim();
+    if summary.is_empty() {
+        return Some("it was empty");
+    }
+    let lower = summary.to_lowercase();
+    if BOILERPLATE.iter().any(|phrase| lower.contains(phrase)) {
+        return Some("it talked about being an AI instead of summarizing");
+    }
+    if summary.len() > prompt.len() {
+        return Some("it was longer than the code it summarizes");
+    }
+    let last_word = lower
+        .rsplit(|c: char| c.is_whitespace())
+        .next()
+        .unwrap_or("");
+    if summary.ends_with([',', ':', ';', '(', '[', '{', '-'])
+        || DANGLING_WORDS.contains(&last_word)
+        || summary.matches("```").count() % 2 == 1
+    {
+        return Some("it was cut off mid-sentence");
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROMPT: &str = "Summarize the following Rust function:\n\nName: load\nSignature: fn load(path: &Path) -> Result<Config, Error>\nBody: { let text = fs::read_to_string(path)?; toml::from_str(&text).map_err(Error::from) }";
+
+    // (name, summary, why it's rejected)
+    const CASES: &[(&str, &str, Option<&str>)] = &[
+        ("plain sentence", "Reads a TOML config file from disk.", None),
+        ("surrounding whitespace", "  Reads a TOML config file.\n", None),
+        ("closed code fence", "Parses `path` with ```toml``` rules.", None),
+        ("empty", "", Some("it was empty")),
+        ("only whitespace", " \n\t", Some("it was empty")),
+        (
+            "boilerplate",
+            "As an AI, I can't run this code.",
+            Some("it talked about being an AI instead of summarizing"),
+        ),
+        (
+            "boilerplate in any case",
+            "I'm Sorry, that is not possible.",
+            Some("it talked about being an AI instead of summarizing"),
+        ),
+        ("trailing comma", "Reads the file,", Some("it was cut off mid-sentence")),
+        ("trailing colon", "It does two things:", Some("it was cut off mid-sentence")),
+        ("dangling article", "Reads the config from the", Some("it was cut off mid-sentence")),
+        ("dangling conjunction", "Reads the file and", Some("it was cut off mid-sentence")),
+        ("open code fence", "Reads it like so: ```rust fs::read", Some("it was cut off mid-sentence")),
+    ];
+
+    #[test]
+    fn rejection_explains_degenerate_summaries() {
+        for (name, summary, expected) in CASES {
+            assert_eq!(rejection(summary, PROMPT), *expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn summaries_longer_than_the_prompt_are_rejected() {
+        let prompt = "fn id(x: u8) -> u8 { x }";
+        assert_eq!(
+            rejection("Returns its argument unchanged, whatever it is.", prompt),
+            Some("it was longer than the code it summarizes")
+        );
+        assert_eq!(rejection("Returns x.", prompt), None);
+    }
+}
@@ -1,99 +1,889 @@
-use clap::Parser;
+mod progress_bars;
+
+use chrono::{DateTime, Local};
+use clap::{Parser, Subcommand};
 use folder_summary::{
-    analyzer::analyze_code_files,
+    analyzer::{find_analyzer_by_language, get_analyzers, ThreadSafeCache},
     cache::Cache,
+    collector::MemoryCollector,
     config::Config,
-    llm::get_llm,
-    summary::generate_summary,
-    utils::{
-        collect_code_files, collect_documentation_files, parse_package_files,
+    daemon::{run_daemon, save_snapshot},
+    error::FolderSummaryError,
+    findings::{write_baseline, Finding, Severity},
+    llm::{
+        get_llm, llm_provider, ollama_model, provider_llm, Mock, Ollama, RequestLimits, TokenBudget,
+        LLM,
     },
+    messages::{is_yes, set_locale, Locale, Message},
+    notify::notify_webhooks,
+    paths,
+    progress::ProgressReporter,
+    render::{Renderer, RendererRegistry},
+    report::Report,
+    server,
+    tr,
+    summary::generate_summary,
+    timings::Timings,
+    upload::upload_reports,
+    utils::{decode_text, is_archive, is_image_layout, ExtractedArchive},
+    FolderSummarizer,
 };
 
-use indicatif::{ProgressBar, ProgressStyle};
 use log::{info, warn, error};
-use std::io::Write;
-use std::sync::{Arc, Mutex};
+use progress_bars::{download_bar, PipelineProgress};
+use std::fs;
+use std::io::{IsTerminal, Read, Write};
+use std::sync::Arc;
+use std::time::Instant;
 use std::{io, path::PathBuf};
 use std::path::Path;
-
-pub type ThreadSafeCache = Arc<Mutex<Cache>>;
+use std::process::ExitCode;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
+    /// Directory to analyze, or a .zip, .tar.gz or .tar archive of one; repeat to analyze
+    /// several repositories in one batch
     #[clap(short, long, default_value = ".")]
-    directory: PathBuf,
+    directory: Vec<PathBuf>,
+
+    /// Image in the local Docker or Podman image store to analyze the source code of,
+    /// such as `vendor/app:1.2`; repeatable
+    #[clap(long)]
+    image: Vec<String>,
 
-    #[clap(short, long, default_value = "config.toml")]
-    config: PathBuf,
+    /// File listing more directories to analyze, one per line (`#` starts a comment)
+    #[clap(long)]
+    repos: Option<PathBuf>,
+
+    /// Write one report with a section per directory instead of one report per directory
+    #[clap(long)]
+    combined: bool,
+
+    /// Config file; defaults to ./config.toml, or the one in the config directory (see `paths`)
+    #[clap(short, long)]
+    config: Option<PathBuf>,
 
     #[clap(short, long)]
     llm_provider: Option<String>,
 
     #[clap(long)]
     file_types: Option<Vec<String>>,
+
+    /// Maximum directory depth to descend into, relative to the analyzed directory
+    #[clap(long)]
+    max_depth: Option<usize>,
+
+    /// Follow symbolic links while collecting files
+    #[clap(long)]
+    follow_symlinks: bool,
+
+    /// Only run static analysis; don't call the LLM
+    #[clap(long)]
+    no_llm: bool,
+
+    /// Leave test files (tests/ directories, *_test.*, *.spec.*, ...) out of the analysis
+    #[clap(long, conflicts_with = "tests_only")]
+    skip_tests: bool,
+
+    /// Analyze only test files
+    #[clap(long)]
+    tests_only: bool,
+
+    /// In projects above sample_threshold_lines lines of code, analyze only a
+    /// representative subset of the files: entry points, public API files and the largest
+    #[clap(long)]
+    sample: bool,
+
+    /// Abort on the first file that fails to analyze instead of listing it in the report
+    #[clap(long)]
+    fail_fast: bool,
+
+    /// Report formats, comma-separated or repeated: markdown (default), html, json or
+    /// github-annotations; all of them are rendered from the same analysis
+    #[clap(long, value_delimiter = ',')]
+    format: Vec<String>,
+
+    /// Show absolute file paths in the report instead of paths relative to the analyzed directory
+    #[clap(long)]
+    absolute_paths: bool,
+
+    /// Public API snapshot from an earlier run; report API changes since it and the semver bump they need
+    #[clap(long)]
+    api_baseline: Option<PathBuf>,
+
+    /// Findings file written by --write-baseline; only findings not in it are reported
+    #[clap(long, conflicts_with = "write_baseline")]
+    baseline: Option<PathBuf>,
+
+    /// Record the run's findings to this file, to pass as --baseline later
+    #[clap(long)]
+    write_baseline: Option<PathBuf>,
+
+    /// Exit with an error when a finding of at least this severity (info, warning or error) is reported
+    #[clap(long)]
+    fail_on: Option<String>,
+
+    /// Also save each report as a JSON snapshot under this directory, as the daemon does, to
+    /// track its metrics over time
+    #[clap(long)]
+    snapshot_dir: Option<PathBuf>,
+
+    /// Print how long file walking, static analysis, LLM calls, cache IO and rendering took
+    #[clap(long)]
+    timings: bool,
+
+    /// Download the Ollama model first if the local server doesn't have it
+    #[clap(long)]
+    pull_model: bool,
+
+    /// Analyze files with an extension as the given language, as EXT=LANGUAGE (`h=cpp`,
+    /// `.mjs=javascript`); repeatable, and added to the config's [language_overrides]
+    #[clap(long = "language", value_name = "EXT=LANGUAGE")]
+    languages: Vec<String>,
+
+    /// Language of the CLI's messages (en or es); defaults to the config's `locale`, then LANG
+    #[clap(long)]
+    locale: Option<String>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List the supported languages, their file extensions and how they are parsed
+    Languages,
+    /// Statically analyze a single file (or `-` for stdin) and print the analysis as JSON
+    File {
+        /// Language of the input; required when reading from stdin
+        #[clap(long)]
+        lang: Option<String>,
+
+        path: PathBuf,
+    },
+    /// Serve the analyzer as a Model Context Protocol server over stdio
+    Mcp,
+    /// Serve file and function summaries to editor plugins over stdio (Content-Length framed JSON-RPC)
+    Editor,
+    /// Re-analyze the repositories in the config's [daemon] table on its schedule
+    Daemon,
+    /// Statically analyze the directories into the cache without calling the LLM, so the next run only summarizes
+    Warm,
+    /// Search function names, signatures and summaries in the analysis cache
+    Search {
+        /// Text to look for, or a regular expression with --regex
+        pattern: String,
+
+        /// Treat the pattern as a regular expression
+        #[clap(long)]
+        regex: bool,
+
+        /// Match regardless of case
+        #[clap(short, long)]
+        ignore_case: bool,
+
+        /// Cache file to search; defaults to the working directory's cache (see `paths`)
+        #[clap(long)]
+        cache: Option<PathBuf>,
+    },
+    /// Show where the config file, analysis cache and reports are
+    Paths,
+    /// Analyze a built-in sample project with the mock provider and check every report format renders it
+    Selftest,
+    /// Check the configured LLM providers
+    Llm {
+        #[clap(subcommand)]
+        command: LlmCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum LlmCommand {
+    /// Send a tiny prompt to each configured provider and report latency and response
+    Test,
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
     env_logger::init();
     let args = Args::parse();
 
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", tr!(Message::Error, e));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<(), FolderSummaryError> {
+    set_locale(Locale::from_env());
+    if let Some(locale) = &args.locale {
+        set_locale(parse_locale(locale)?);
+    }
+    match &args.command {
+        Some(Command::Languages) => {
+            print_languages();
+            return Ok(());
+        }
+        Some(Command::File { lang, path }) => return analyze_single_file(lang.as_deref(), path),
+        Some(Command::Search {
+            pattern,
+            regex,
+            ignore_case,
+            cache,
+        }) => {
+            let cache = cache.clone().unwrap_or_else(paths::cache_file);
+            return search_cache(&cache, pattern, *regex, *ignore_case);
+        }
+        Some(Command::Paths) => return print_paths(args.config.as_deref()),
+        Some(Command::Selftest) => return selftest().await,
+        Some(Command::Mcp)
+        | Some(Command::Editor)
+        | Some(Command::Daemon)
+        | Some(Command::Warm)
+        | Some(Command::Llm { .. })
+        | None => {}
+    }
+
     // Load config file
-    let mut config = Config::load(args.config.to_str().unwrap())?;
+    let mut config = Config::load(args.config.clone().unwrap_or_else(paths::config_file))?;
+
+    if let (None, Some(locale)) = (&args.locale, &config.locale) {
+        set_locale(parse_locale(locale)?);
+    }
 
     // Override config with CLI arguments
     if let Some(llm_provider) = args.llm_provider {
         config.llm_provider = Some(llm_provider);
     }
-
+    if args.max_depth.is_some() {
+        config.max_depth = args.max_depth;
+    }
+    if args.follow_symlinks {
+        config.follow_symlinks = Some(true);
+    }
+    if args.no_llm {
+        config.no_llm = Some(true);
+    }
+    if args.fail_fast {
+        config.fail_fast = Some(true);
+    }
+    if args.skip_tests {
+        config.skip_tests = Some(true);
+    }
+    if args.tests_only {
+        config.tests_only = Some(true);
+    }
+    if args.sample {
+        config.sample = Some(true);
+    }
+    if !args.format.is_empty() {
+        config.output_format = Some(args.format.join(","));
+    }
+    if args.absolute_paths {
+        config.absolute_paths = Some(true);
+    }
+    if let Some(baseline) = &args.api_baseline {
+        config.api_baseline = Some(baseline.display().to_string());
+    }
+    if let Some(baseline) = &args.baseline {
+        config.findings_baseline = Some(baseline.display().to_string());
+    }
+    if args.fail_on.is_some() {
+        config.fail_on = args.fail_on.clone();
+    }
+    for language in &args.languages {
+        let Some((extension, language)) = language.split_once('=') else {
+            return Err(FolderSummaryError::ConfigError(tr!(Message::InvalidLanguageFlag, language)));
+        };
+        let extension = extension.trim().trim_start_matches('.');
+        let overrides = config.language_overrides.get_or_insert_with(Default::default);
+        // Replaces the config's entry whether it was written with the dot or without.
+        overrides.remove(extension);
+        overrides.insert(format!(".{}", extension), language.trim().to_string());
+    }
+    let fail_on = match &config.fail_on {
+        Some(severity) => Some(Severity::parse(severity).ok_or_else(|| {
+            FolderSummaryError::ConfigError(tr!(Message::InvalidFailOn, severity))
+        })?),
+        None => None,
+    };
+    if let Some(Command::Warm) = args.command {
+        let directories = batch_directories(&args.directory, args.repos.as_deref())?;
+        return warm(config, &directories).await;
+    }
+    if args.pull_model {
+        config.pull_missing_model = Some(true);
+    }
+    if !config.no_llm.unwrap_or(false) {
+        prepare_ollama(&config).await?;
+    }
     let llm = get_llm(&config)?;
+    match args.command {
+        Some(Command::Mcp) => return server::mcp::serve(config, llm).await,
+        Some(Command::Editor) => {
+            let cache = Cache::new(paths::cache_file())?;
+            return server::editor::serve(config, llm, cache).await;
+        }
+        Some(Command::Llm {
+            command: LlmCommand::Test,
+        }) => return test_llm(&config, llm).await,
+        _ => {}
+    }
+    let registry = RendererRegistry::default();
+    let renderers = config
+        .get_output_formats()
+        .iter()
+        .map(|format| registry.get(format))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(Command::Daemon) = args.command {
+        let cancel = CancellationToken::new();
+        cancel_on_ctrl_c(cancel.clone());
+        let scheduled = |next: DateTime<Local>| {
+            println!("{}", tr!(Message::NextAnalysis, next.format("%Y-%m-%d %H:%M")));
+        };
+        return run_daemon(config, llm, renderers, cancel, scheduled).await;
+    }
+
+    let mut directories = batch_directories(&args.directory, args.repos.as_deref())?;
+    // Images on their own replace the default `.`, as a --repos list does.
+    if !args.image.is_empty() && directories == [PathBuf::from(".")] {
+        directories.clear();
+    }
+    println!("{}", tr!(Message::Starting));
+    println!("{}", tr!(Message::UsingModel, llm.model_name()));
+    for directory in &directories {
+        println!("{}", tr!(Message::FolderToAnalyze, directory.display()));
+    }
+    for image in &args.image {
+        println!("{}", tr!(Message::FolderToAnalyze, image));
+    }
 
-    println!("Starting folder summary task");
-    println!("Using LLM model: {}", llm.model_name());
-    println!("Folder to analyze: {}", args.directory.display());
+    // Every directory of a batch shares one cache, one token budget, one set of request
+    // limits and one set of timings.
+    let cache: ThreadSafeCache = Arc::new(Mutex::new(Cache::new(paths::cache_file())?));
+    let timings = Arc::new(Timings::new());
+    let budget = config.max_total_tokens.map(|limit| Arc::new(TokenBudget::new(limit)));
+    let cancel = CancellationToken::new();
+    let limits = config.llm_requests_per_minute.map(RequestLimits::new);
+
+    info!("Collecting files...");
+    let progress = Arc::new(PipelineProgress::new(&llm.model_name()));
+    // Unpacked archives and images are removed when this goes out of scope, however the
+    // run ends.
+    let mut archives = Vec::new();
+    let mut sources = Vec::new();
+    for directory in &directories {
+        let label = directory.display().to_string();
+        let archive = if is_archive(directory) {
+            ExtractedArchive::extract(directory)?
+        } else if directory.is_dir() && is_image_layout(directory) {
+            ExtractedArchive::from_image_layout(directory)?
+        } else {
+            sources.push((label, directory.clone(), cache.clone()));
+            continue;
+        };
+        // An unpacked archive's temp path differs on every run, so caching its files
+        // would only grow the cache.
+        let root = archive.root().to_path_buf();
+        archives.push(archive);
+        sources.push((label, root, Arc::new(Mutex::new(Cache::in_memory()))));
+    }
+    for image in &args.image {
+        info!("Saving and unpacking image {}...", image);
+        let archive = ExtractedArchive::from_image(image)?;
+        let root = archive.root().to_path_buf();
+        archives.push(archive);
+        sources.push((image.clone(), root, Arc::new(Mutex::new(Cache::in_memory()))));
+    }
+    let batch = sources.len() > 1;
+    let mut runs = Vec::new();
+    for (label, root, cache) in sources {
+        let mut builder = FolderSummarizer::builder()
+            .directory(root)
+            .config(config.clone())
+            .llm(llm.clone())
+            .progress(progress.clone())
+            .cache(cache)
+            .timings(timings.clone())
+            .cancellation_token(cancel.clone());
+        if let Some(budget) = &budget {
+            builder = builder.token_budget(budget.clone());
+        }
+        if let Some(limits) = &limits {
+            builder = builder.request_limits(limits.clone());
+        }
+        let summarizer = builder.build()?;
+        let project_files = summarizer.collect()?;
+        if project_files.code.is_empty() {
+            error!("{}", tr!(Message::NoCodeFiles, label));
+            continue;
+        }
+        if batch {
+            println!("{}:", label);
+        }
+        println!("{}", summarizer.estimate(&project_files).await);
+        runs.push((label, summarizer, project_files));
+    }
+    if runs.is_empty() {
+        return Ok(());
+    }
 
-    print!("Do you want to proceed? (y/n): ");
+    print!("{}", tr!(Message::Proceed));
     io::stdout().flush()?;
 
     let mut input = String::new();
     io::stdin().read_line(&mut input)?;
+    // A piped answer isn't echoed; end the prompt line so later output, such as
+    // workflow commands, starts on a line of its own.
+    if !io::stdin().is_terminal() {
+        println!();
+    }
 
-    if input.trim().to_lowercase() != "y" {
-        warn!("User aborted the operation");
+    if !is_yes(&input) {
+        warn!("{}", tr!(Message::Aborted));
         return Ok(());
     }
 
-    info!("Collecting files...");
-    let docs = collect_documentation_files(&args.directory);
-    let package_info = parse_package_files(&args.directory);
-    let code_files = collect_code_files(&args.directory, &config);
-    if code_files.is_empty() {
-        error!("No code files found to analyze. Please check your configuration and directory path.");
+    cancel_on_ctrl_c(cancel);
+    let combined = args.combined && runs.len() > 1;
+    let mut reports = Vec::new();
+    for (label, summarizer, project_files) in runs {
+        let mut report = summarizer.summarize(project_files).await?;
+        // An archive or image is reported under its own name rather than where it was
+        // unpacked.
+        report.metadata.directory = label;
+        if let Some(snapshot_dir) = &args.snapshot_dir {
+            let path = save_snapshot(snapshot_dir, &report.metadata.directory, &report)?;
+            info!("Snapshot saved as {}", path.display());
+        }
+        // Published as soon as it is ready, so only one repository's analyses are held
+        // at a time.
+        if !combined {
+            publish(&mut report, &config, &renderers, progress.as_ref(), &cache, &timings).await?;
+        }
+        reports.push(report);
+    }
+    if combined {
+        let mut report = Report::combine("batch".to_string(), reports);
+        publish(&mut report, &config, &renderers, progress.as_ref(), &cache, &timings).await?;
+        reports = vec![report];
+    }
+
+    let failure_count: usize = reports.iter().map(|report| report.metrics.failures).sum();
+    if failure_count > 0 {
+        warn!("{}", tr!(Message::FilesFailed, failure_count));
+    }
+    let findings: Vec<&Finding> = reports
+        .iter()
+        .flat_map(|report| report.parts())
+        .flat_map(|part| &part.findings)
+        .collect();
+    if let Some(path) = &args.write_baseline {
+        write_baseline(path, &findings)?;
+        println!("{}", tr!(Message::BaselineRecorded, findings.len(), path.display()));
+    }
+    if let Some(budget) = budget.filter(|budget| budget.exhausted()) {
+        println!("{}", tr!(Message::BudgetSpent, budget.used(), budget.limit()));
+    }
+    if args.timings {
+        print!("{}", timings);
+    }
+
+    println!("{}", tr!(Message::Complete));
+    info!("Congratulations! Your folder summary is ready.");
+    println!("{}", tr!(Message::SummaryLocation, config.get_summary_output_path()?.display()));
+
+    if let Some(severity) = fail_on {
+        let failing = findings.iter().filter(|finding| finding.severity >= severity).count();
+        if failing > 0 {
+            return Err(FolderSummaryError::AnalysisError(tr!(
+                Message::FindingsAtSeverity,
+                failing,
+                severity
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Static analysis of each directory into the shared cache, for `warm`. No provider is
+// needed, so one that isn't reachable or has no API key doesn't get in the way.
+async fn warm(config: Config, directories: &[PathBuf]) -> Result<(), FolderSummaryError> {
+    let cache: ThreadSafeCache = Arc::new(Mutex::new(Cache::new(paths::cache_file())?));
+    let cancel = CancellationToken::new();
+    cancel_on_ctrl_c(cancel.clone());
+    for directory in directories {
+        let summarizer = FolderSummarizer::builder()
+            .directory(directory)
+            .config(config.clone())
+            .llm(Arc::new(Mock))
+            .cache(cache.clone())
+            .cancellation_token(cancel.clone())
+            .build()?;
+        let files = summarizer.collect()?;
+        let results = summarizer.warm(&files).await?;
+        println!(
+            "{}",
+            tr!(
+                Message::CacheWarmed,
+                directory.display(),
+                results.analyzed,
+                results.cached,
+                results.failures.len()
+            )
+        );
+    }
+    Ok(())
+}
+
+// Everything a finished report feeds: each requested format, the cache of rendered
+// sections, the upload bucket and the webhooks. The per-file analyses are dropped
+// afterwards unless JSON output was requested.
+async fn publish(
+    report: &mut Report,
+    config: &Config,
+    renderers: &[Arc<dyn Renderer>],
+    progress: &dyn ProgressReporter,
+    cache: &ThreadSafeCache,
+    timings: &Timings,
+) -> Result<(), FolderSummaryError> {
+    let output = timings.time("Rendering", || {
+        generate_summary(report, config, renderers, progress)
+    })?;
+    info!("Rendered {} sections", output.sections.len());
+    for path in &output.files {
+        if output.public_api.as_ref() == Some(path) {
+            println!("{}", tr!(Message::PublicApiSaved, path.display()));
+        } else if output.run_log.as_ref() != Some(path) {
+            println!("{}", tr!(Message::SummarySaved, path.display()));
+        }
+    }
+    {
+        let mut cache = cache.lock().await;
+        cache.set_rendered(output.sections);
+        cache.flush().await?;
+    }
+    if let Some(upload) = &config.upload {
+        for url in upload_reports(upload, report, &output.files).await? {
+            println!("{}", tr!(Message::ReportUploaded, url));
+        }
+    }
+    notify_webhooks(config, report, &output.files, &[]).await;
+    if !renderers.iter().any(|renderer| renderer.format() == "json") {
+        report.drop_analyses();
+    }
+    Ok(())
+}
+
+fn parse_locale(locale: &str) -> Result<Locale, FolderSummaryError> {
+    Locale::parse(locale).ok_or_else(|| {
+        FolderSummaryError::ConfigError(tr!(Message::UnsupportedLocale, locale))
+    })
+}
+
+// The `--directory` values followed by the directories listed in the `--repos` file.
+fn batch_directories(
+    directory: &[PathBuf],
+    repos: Option<&Path>,
+) -> Result<Vec<PathBuf>, FolderSummaryError> {
+    let mut directories = directory.to_vec();
+    if let Some(repos) = repos {
+        let listing = fs::read_to_string(repos)
+            .map_err(|e| FolderSummaryError::file(repos.display().to_string(), e))?;
+        // An explicit list replaces the default `.` rather than adding to it.
+        if directories == [PathBuf::from(".")] {
+            directories.clear();
+        }
+        directories.extend(
+            listing
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or("").trim())
+                .filter(|line| !line.is_empty())
+                .map(PathBuf::from),
+        );
+    }
+    Ok(directories)
+}
+
+// Ctrl-C stops the run cleanly; files finished so far stay in the cache.
+fn cancel_on_ctrl_c(cancel: CancellationToken) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            warn!("{}", tr!(Message::Cancelling));
+            cancel.cancel();
+        }
+    });
+}
+
+// Checks that Ollama has the configured model before any file is analyzed, pulling it
+// when `pull_missing_model` is set, so a missing model doesn't fail every request.
+async fn prepare_ollama(config: &Config) -> Result<(), FolderSummaryError> {
+    let routed = config.route.as_ref().map(|route| route.large.as_str());
+    if llm_provider(config) != "ollama" && routed != Some("ollama") {
+        return Ok(());
+    }
+    let model = ollama_model(config);
+    let ollama = Ollama::new(&model);
+    if ollama.has_model().await? {
         return Ok(());
     }
+    if !config.pull_missing_model.unwrap_or(false) {
+        return Err(FolderSummaryError::ConfigError(tr!(Message::MissingOllamaModel, model, model)));
+    }
+
+    let bar = download_bar(&model);
+    ollama
+        .pull(|update| {
+            if let (Some(completed), Some(total)) = (update.completed, update.total) {
+                bar.set_length(total);
+                bar.set_position(completed);
+            }
+            bar.set_message(update.status);
+        })
+        .await?;
+    bar.finish_with_message("done");
+    Ok(())
+}
+
+// Sends a tiny prompt to the main provider and, if one is configured, the `[route]` one.
+// An empty answer counts as a failure: providers answer a bad key or model with an
+// error body that carries no summary.
+async fn test_llm(config: &Config, llm: Arc<dyn LLM>) -> Result<(), FolderSummaryError> {
+    const TEST_PROMPT: &str = "Summarize the following Rust function:\n\nName: add\nSignature: fn add(a: i32, b: i32) -> i32\nBody: { a + b }";
+    let mut providers = vec![llm];
+    if let Some(route) = &config.route {
+        providers.push(provider_llm(config, &route.large)?);
+    }
+
+    let mut failures = Vec::new();
+    for provider in providers {
+        let name = provider.model_name();
+        println!("{}", name);
+        let start = Instant::now();
+        let result = provider.summarize(TEST_PROMPT).await;
+        let latency = format!("{:.2}", start.elapsed().as_secs_f64());
+        println!("{}", tr!(Message::ProviderLatency, latency));
+        match result {
+            Ok(response) if !response.is_empty() => {
+                println!("{}", tr!(Message::ProviderResponse, response))
+            }
+            Ok(_) => {
+                println!("{}", tr!(Message::ProviderEmptyResponse));
+                failures.push(name);
+            }
+            Err(e) => {
+                println!("{}", tr!(Message::ProviderFailed, e));
+                failures.push(name);
+            }
+        }
+    }
+    if failures.is_empty() {
+        println!("{}", tr!(Message::ProvidersAnswered));
+        Ok(())
+    } else {
+        Err(FolderSummaryError::ConfigError(tr!(Message::LlmTestFailed, failures.join(", "))))
+    }
+}
+
+// The project `selftest` analyzes: something for every analyzer, with functions long
+// enough that each gets summarized.
+const SELFTEST_FILES: &[(&str, &str)] = &[
+    ("Cargo.toml", "[package]\nname = \"selftest\"\nversion = \"0.1.0\"\n"),
+    (
+        "src/main.rs",
+        "fn main() {\n    println!(\"{}\", add(1, 2));\n}\n\nfn add(a: u32, b: u32) -> u32 {\n    a + b\n}\n",
+    ),
+    (
+        "web/greet.js",
+        "export function greet(name) {\n  if (!name) {\n    return 'Hello';\n  }\n  const greeting = 'Hello ' + name;\n  console.log(greeting);\n  return greeting;\n}\n",
+    ),
+    (
+        "tools/total.py",
+        "def total(values):\n    result = 0\n    for value in values:\n        if value is None:\n            continue\n        result += value\n    print(result)\n    return result\n",
+    ),
+];
+
+// Runs the whole pipeline on `SELFTEST_FILES` in memory, with the mock provider, and
+// checks that every file was analyzed and summarized and every format renders. Needs no
+// config, network or model, so it tells whether the installation itself works.
+async fn selftest() -> Result<(), FolderSummaryError> {
+    let collector = SELFTEST_FILES
+        .iter()
+        .fold(MemoryCollector::new(), |collector, (path, contents)| {
+            collector.with_file(*path, *contents)
+        });
+    let report = FolderSummarizer::builder()
+        .directory("selftest")
+        .llm(Arc::new(Mock))
+        .collector(Arc::new(collector))
+        .build()?
+        .run()
+        .await?;
+
+    let mut checks = Vec::new();
+    let code_files = SELFTEST_FILES.len() - 1;
+    checks.push((
+        tr!(Message::SelftestFilesAnalyzed, code_files),
+        report.files.len() == code_files && report.failures.is_empty(),
+    ));
+    let functions: Vec<_> = report.files.values().flat_map(|analysis| &analysis.functions).collect();
+    checks.push((
+        tr!(Message::SelftestFunctionsSummarized, functions.len()),
+        !functions.is_empty()
+            && functions
+                .iter()
+                .all(|f| f.summary.as_deref() == Some(format!("Mock summary of {}.", f.name).as_str())),
+    ));
+    let renderers = RendererRegistry::default();
+    for format in renderers.formats() {
+        let rendered = renderers.get(format).and_then(|renderer| renderer.render(&report));
+        let passed = match rendered {
+            Ok(artifacts) => artifacts.into_iter().filter(|artifact| !artifact.to_stdout).all(|artifact| {
+                let mut contents = Vec::new();
+                artifact.contents.write_to(&mut contents).is_ok()
+                    && String::from_utf8_lossy(&contents).contains("Mock summary of add")
+            }),
+            Err(_) => false,
+        };
+        checks.push((tr!(Message::SelftestRendered, format), passed));
+    }
+
+    for (check, passed) in &checks {
+        let status = if *passed { Message::CheckPassed } else { Message::CheckFailed };
+        println!("{:<6} {}", tr!(status), check);
+    }
+    let failed = checks.iter().filter(|(_, passed)| !passed).count();
+    if failed > 0 {
+        return Err(FolderSummaryError::AnalysisError(tr!(
+            Message::SelftestFailed,
+            failed,
+            checks.len()
+        )));
+    }
+    println!("{}", tr!(Message::SelftestPassed));
+    Ok(())
+}
 
-    let pb = ProgressBar::new(code_files.len() as u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg}")
-            .unwrap()
-            .progress_chars("##-"),
+fn print_languages() {
+    println!(
+        "{:<24} {:<16} {}",
+        tr!(Message::ColumnLanguage),
+        tr!(Message::ColumnExtensions),
+        tr!(Message::ColumnParser)
     );
+    for analyzer in get_analyzers() {
+        println!(
+            "{:<24} {:<16} {}",
+            analyzer.name(),
+            analyzer.extensions().join(", "),
+            analyzer.parser_kind()
+        );
+    }
+}
+
+fn analyze_single_file(lang: Option<&str>, path: &Path) -> Result<(), FolderSummaryError> {
+    let from_stdin = path.as_os_str() == "-";
+    let analyzer = match lang {
+        Some(lang) => find_analyzer_by_language(lang)
+            .ok_or_else(|| FolderSummaryError::AnalysisError(tr!(Message::UnsupportedLanguage, lang)))?,
+        None if from_stdin => {
+            return Err(FolderSummaryError::AnalysisError(tr!(Message::LangRequiredForStdin)))
+        }
+        None => get_analyzers()
+            .into_iter()
+            .find(|analyzer| analyzer.can_analyze(&path.to_string_lossy()))
+            .ok_or_else(|| {
+                FolderSummaryError::AnalysisError(tr!(Message::NoAnalyzerFor, path.display()))
+            })?,
+    };
 
-    let cache = Arc::new(Mutex::new(Cache::new("analysis_cache.json")?));
-    let code_analysis = analyze_code_files(&code_files, &llm, &pb, &cache).await?;
+    let bytes = if from_stdin {
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes)?;
+        bytes
+    } else {
+        fs::read(path).map_err(|e| FolderSummaryError::file(path.display().to_string(), e))?
+    };
+    let (content, _) = decode_text(&bytes).ok_or_else(|| {
+        FolderSummaryError::AnalysisError(tr!(Message::BinaryFile, path.display()))
+    })?;
 
-    pb.finish_with_message("Analysis complete");
+    let analysis = analyzer.analyze(&content)?;
+    println!("{}", serde_json::to_string_pretty(&analysis)?);
+    Ok(())
+}
 
-    println!("Generating summary...");
-    let analyzed_folder = Path::new(&args.directory);
-    generate_summary(docs, package_info, code_analysis, &config, analyzed_folder);
+// Prints where the config, the cache for this working directory and the reports are, and
+// whether each exists yet.
+fn print_paths(config_file: Option<&Path>) -> Result<(), FolderSummaryError> {
+    let config_file = config_file.map_or_else(paths::config_file, Path::to_path_buf);
+    let config = if config_file.exists() {
+        Config::load(&config_file)?
+    } else {
+        Config::default()
+    };
+    let state = |path: &Path| if path.exists() { String::new() } else { tr!(Message::NotCreatedYet) };
+    let cache_file = paths::cache_file();
+    let output = config.get_summary_output_path()?;
+    println!("{}", tr!(Message::PathConfig, config_file.display(), state(&config_file)));
+    println!("{}", tr!(Message::PathCache, cache_file.display(), state(&cache_file)));
+    println!("{}", tr!(Message::PathOutput, output.display(), state(&output)));
+    Ok(())
+}
 
-    println!("Summary generation complete!");
-    info!("Congratulations! Your folder summary is ready.");
-    println!("You can find the summary at: {}", config.get_summary_output_path().display());
+// Prints the cached functions whose name, signature or summary matches `pattern`, as
+// `file:line  signature` followed by the summary.
+fn search_cache(
+    cache_file: &Path,
+    pattern: &str,
+    regex: bool,
+    ignore_case: bool,
+) -> Result<(), FolderSummaryError> {
+    if !cache_file.exists() {
+        return Err(FolderSummaryError::AnalysisError(tr!(Message::NoCacheAt, cache_file.display())));
+    }
+    let pattern = if regex { pattern.to_string() } else { regex::escape(pattern) };
+    let matcher = regex::RegexBuilder::new(&pattern)
+        .case_insensitive(ignore_case)
+        .build()
+        .map_err(|e| FolderSummaryError::AnalysisError(tr!(Message::InvalidPattern, e)))?;
+
+    let cache = Cache::new(cache_file)?;
+    let mut matches = Vec::new();
+    for (file, analysis) in cache.analyses() {
+        // Files deleted since they were cached.
+        if !Path::new(file).exists() {
+            continue;
+        }
+        for function in &analysis.functions {
+            let summary = function.summary.as_deref().unwrap_or("");
+            if [function.name.as_str(), &function.signature, summary]
+                .iter()
+                .any(|text| matcher.is_match(text))
+            {
+                matches.push((file, function.line, &function.signature, summary));
+            }
+        }
+    }
+    matches.sort();
 
+    for (file, line, signature, summary) in &matches {
+        match line {
+            Some(line) => println!("{}:{}  {}", file, line, signature),
+            None => println!("{}  {}", file, signature),
+        }
+        for summary_line in summary.lines() {
+            println!("    {}", summary_line);
+        }
+    }
+    if matches.is_empty() {
+        println!("{}", tr!(Message::NoSearchMatches));
+    }
     Ok(())
 }
@@ -0,0 +1,298 @@
+//! The CLI's user-facing text in each supported language. Messages are looked up by
+//! [`Message`] and filled in with [`tr!`](crate::tr); the language is English until the
+//! application calls [`set_locale`], as the CLI does from `--locale`, the config's
+//! `locale` or the environment.
+
+use std::env;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Es,
+}
+
+impl Locale {
+    /// Reads a locale such as `es`, `es_MX` or `es_ES.UTF-8`; `None` for unsupported
+    /// languages.
+    pub fn parse(locale: &str) -> Option<Self> {
+        let language = locale.split(['_', '-', '.']).next().unwrap_or("");
+        match language.to_lowercase().as_str() {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "es" => Some(Locale::Es),
+            _ => None,
+        }
+    }
+
+    /// The locale named by `LC_ALL`, `LC_MESSAGES` or `LANG`, whichever is set first,
+    /// falling back to English.
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()))
+            .and_then(|locale| Locale::parse(&locale))
+            .unwrap_or(Locale::En)
+    }
+}
+
+static LOCALE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_locale(locale: Locale) {
+    LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+pub fn locale() -> Locale {
+    match LOCALE.load(Ordering::Relaxed) {
+        1 => Locale::Es,
+        _ => Locale::En,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    Error,
+    IoError,
+    IoErrorOn,
+    ConfigError,
+    ConfigErrorIn,
+    LlmError,
+    LlmErrorFrom,
+    AnalysisError,
+    CacheError,
+    SerializationError,
+    TaskJoinError,
+    Cancelled,
+    TokenBudgetExhausted,
+    Starting,
+    UsingModel,
+    FolderToAnalyze,
+    EstimateFiles,
+    EstimateCalls,
+    EstimateCost,
+    Proceed,
+    Aborted,
+    SummarySaved,
+    PublicApiSaved,
+    ReportUploaded,
+    NextAnalysis,
+    BaselineRecorded,
+    BudgetSpent,
+    Complete,
+    SummaryLocation,
+    FindingsAtSeverity,
+    PathConfig,
+    PathCache,
+    PathOutput,
+    NotCreatedYet,
+    NoSearchMatches,
+    CacheWarmed,
+    NoCodeFiles,
+    FilesFailed,
+    Cancelling,
+    InvalidLanguageFlag,
+    InvalidFailOn,
+    UnsupportedLocale,
+    MissingOllamaModel,
+    ProviderLatency,
+    ProviderResponse,
+    ProviderEmptyResponse,
+    ProviderFailed,
+    ProvidersAnswered,
+    LlmTestFailed,
+    CheckPassed,
+    CheckFailed,
+    SelftestFilesAnalyzed,
+    SelftestFunctionsSummarized,
+    SelftestRendered,
+    SelftestFailed,
+    SelftestPassed,
+    ColumnLanguage,
+    ColumnExtensions,
+    ColumnParser,
+    UnsupportedLanguage,
+    LangRequiredForStdin,
+    NoAnalyzerFor,
+    BinaryFile,
+    NoCacheAt,
+    InvalidPattern,
+}
+
+impl Message {
+    fn text(self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::En => self.en(),
+            Locale::Es => self.es(),
+        }
+    }
+
+    fn en(self) -> &'static str {
+        match self {
+            Message::Error => "Error: {}",
+            Message::IoError => "IO error: {}",
+            Message::IoErrorOn => "IO error on {}: {}",
+            Message::ConfigError => "Configuration error: {}",
+            Message::ConfigErrorIn => "Configuration error in {}: {}",
+            Message::LlmError => "LLM error: {}",
+            Message::LlmErrorFrom => "LLM error from {}: {}",
+            Message::AnalysisError => "Analysis error: {}",
+            Message::CacheError => "Cache error: {}",
+            Message::SerializationError => "Serialization error: {}",
+            Message::TaskJoinError => "TaskJoin error: {}",
+            Message::Cancelled => "Run cancelled",
+            Message::TokenBudgetExhausted => "Token budget exhausted",
+            Message::Starting => "Starting folder summary task",
+            Message::UsingModel => "Using LLM model: {}",
+            Message::FolderToAnalyze => "Folder to analyze: {}",
+            Message::EstimateFiles => "Code files found: {} ({} lines, {} already cached)",
+            Message::EstimateCalls => "Expected LLM calls: {} (~{} tokens)",
+            Message::EstimateCost => "Estimated time: ~{}, estimated cost: ~${}",
+            Message::Proceed => "Do you want to proceed? (y/n): ",
+            Message::Aborted => "User aborted the operation",
+            Message::SummarySaved => "Summary generated and saved as {}",
+            Message::PublicApiSaved => "Public API snapshot saved as {}",
+            Message::ReportUploaded => "Report uploaded to {}",
+            Message::NextAnalysis => "Next analysis at {}",
+            Message::BaselineRecorded => "Recorded {} findings as the baseline in {}",
+            Message::BudgetSpent => "Stopped summarizing after ~{} of the {} allowed tokens; the remaining files have static analysis only and are summarized on the next run",
+            Message::Complete => "Summary generation complete!",
+            Message::SummaryLocation => "You can find the summary at: {}",
+            Message::FindingsAtSeverity => "{} findings at {} severity or above",
+            Message::PathConfig => "Config: {}{}",
+            Message::PathCache => "Cache:  {}{}",
+            Message::PathOutput => "Output: {}{}",
+            Message::NotCreatedYet => " (not created yet)",
+            Message::NoSearchMatches => "No cached functions match",
+            Message::CacheWarmed => "{}: {} files analyzed, {} already cached, {} failed",
+            Message::NoCodeFiles => "No code files found to analyze in {}. Please check your configuration and directory path.",
+            Message::FilesFailed => "{} files could not be analyzed; see the report's error appendix",
+            Message::Cancelling => "Cancelling; finished files are kept in the cache",
+            Message::InvalidLanguageFlag => "Invalid --language \"{}\": expected EXT=LANGUAGE, e.g. h=cpp",
+            Message::InvalidFailOn => "Invalid fail_on \"{}\": expected \"info\", \"warning\" or \"error\"",
+            Message::UnsupportedLocale => "Unsupported locale \"{}\": expected \"en\" or \"es\"",
+            Message::MissingOllamaModel => "Ollama has no model named {}; run `ollama pull {}`, pass --pull-model or set pull_missing_model = true",
+            Message::ProviderLatency => "  Latency: {}s",
+            Message::ProviderResponse => "  Response: {}",
+            Message::ProviderEmptyResponse => "  Failed: empty response; check the API key and model name",
+            Message::ProviderFailed => "  Failed: {}",
+            Message::ProvidersAnswered => "All providers answered",
+            Message::LlmTestFailed => "LLM self-test failed for {}",
+            Message::CheckPassed => "ok",
+            Message::CheckFailed => "FAILED",
+            Message::SelftestFilesAnalyzed => "{} files analyzed",
+            Message::SelftestFunctionsSummarized => "{} functions summarized",
+            Message::SelftestRendered => "{} report rendered",
+            Message::SelftestFailed => "Self-test failed: {} of {} checks",
+            Message::SelftestPassed => "Self-test passed",
+            Message::ColumnLanguage => "Language",
+            Message::ColumnExtensions => "Extensions",
+            Message::ColumnParser => "Parser",
+            Message::UnsupportedLanguage => "Unsupported language: {}",
+            Message::LangRequiredForStdin => "--lang is required when reading from stdin",
+            Message::NoAnalyzerFor => "No suitable analyzer found for file: {}",
+            Message::BinaryFile => "{} looks like a binary file",
+            Message::NoCacheAt => "No analysis cache at {}; run an analysis first",
+            Message::InvalidPattern => "Invalid pattern: {}",
+        }
+    }
+
+    fn es(self) -> &'static str {
+        match self {
+            Message::Error => "Error: {}",
+            Message::IoError => "Error de E/S: {}",
+            Message::IoErrorOn => "Error de E/S en {}: {}",
+            Message::ConfigError => "Error de configuración: {}",
+            Message::ConfigErrorIn => "Error de configuración en {}: {}",
+            Message::LlmError => "Error del LLM: {}",
+            Message::LlmErrorFrom => "Error del LLM de {}: {}",
+            Message::AnalysisError => "Error de análisis: {}",
+            Message::CacheError => "Error de caché: {}",
+            Message::SerializationError => "Error de serialización: {}",
+            Message::TaskJoinError => "Error al esperar una tarea: {}",
+            Message::Cancelled => "Ejecución cancelada",
+            Message::TokenBudgetExhausted => "Presupuesto de tokens agotado",
+            Message::Starting => "Iniciando el resumen de la carpeta",
+            Message::UsingModel => "Modelo LLM: {}",
+            Message::FolderToAnalyze => "Carpeta a analizar: {}",
+            Message::EstimateFiles => "Archivos de código encontrados: {} ({} líneas, {} ya en caché)",
+            Message::EstimateCalls => "Llamadas al LLM previstas: {} (~{} tokens)",
+            Message::EstimateCost => "Tiempo estimado: ~{}, costo estimado: ~${}",
+            Message::Proceed => "¿Desea continuar? (s/n): ",
+            Message::Aborted => "Operación cancelada por el usuario",
+            Message::SummarySaved => "Resumen generado y guardado en {}",
+            Message::PublicApiSaved => "Instantánea de la API pública guardada en {}",
+            Message::ReportUploaded => "Informe subido a {}",
+            Message::NextAnalysis => "Próximo análisis: {}",
+            Message::BaselineRecorded => "{} hallazgos guardados como línea base en {}",
+            Message::BudgetSpent => "Se dejó de resumir tras ~{} de los {} tokens permitidos; los archivos restantes solo tienen análisis estático y se resumirán en la próxima ejecución",
+            Message::Complete => "¡Resumen completado!",
+            Message::SummaryLocation => "El resumen está en: {}",
+            Message::FindingsAtSeverity => "{} hallazgos de severidad {} o superior",
+            Message::PathConfig => "Configuración: {}{}",
+            Message::PathCache => "Caché:         {}{}",
+            Message::PathOutput => "Informes:      {}{}",
+            Message::NotCreatedYet => " (aún no existe)",
+            Message::NoSearchMatches => "Ninguna función en caché coincide",
+            Message::CacheWarmed => "{}: {} archivos analizados, {} ya en caché, {} con errores",
+            Message::NoCodeFiles => "No se encontraron archivos de código para analizar en {}. Revise la configuración y la ruta del directorio.",
+            Message::FilesFailed => "No se pudieron analizar {} archivos; consulte el apéndice de errores del informe",
+            Message::Cancelling => "Cancelando; los archivos terminados se conservan en la caché",
+            Message::InvalidLanguageFlag => "--language \"{}\" no es válido: se esperaba EXT=LENGUAJE, p. ej. h=cpp",
+            Message::InvalidFailOn => "fail_on \"{}\" no es válido: se esperaba \"info\", \"warning\" o \"error\"",
+            Message::UnsupportedLocale => "Idioma \"{}\" no admitido: se esperaba \"en\" o \"es\"",
+            Message::MissingOllamaModel => "Ollama no tiene ningún modelo llamado {}; ejecute `ollama pull {}`, use --pull-model o configure pull_missing_model = true",
+            Message::ProviderLatency => "  Latencia: {}s",
+            Message::ProviderResponse => "  Respuesta: {}",
+            Message::ProviderEmptyResponse => "  Error: respuesta vacía; revise la clave de API y el nombre del modelo",
+            Message::ProviderFailed => "  Error: {}",
+            Message::ProvidersAnswered => "Todos los proveedores respondieron",
+            Message::LlmTestFailed => "La prueba del LLM falló para {}",
+            Message::CheckPassed => "ok",
+            Message::CheckFailed => "FALLÓ",
+            Message::SelftestFilesAnalyzed => "{} archivos analizados",
+            Message::SelftestFunctionsSummarized => "{} funciones resumidas",
+            Message::SelftestRendered => "informe {} generado",
+            Message::SelftestFailed => "La autoprueba falló: {} de {} comprobaciones",
+            Message::SelftestPassed => "Autoprueba superada",
+            Message::ColumnLanguage => "Lenguaje",
+            Message::ColumnExtensions => "Extensiones",
+            Message::ColumnParser => "Analizador",
+            Message::UnsupportedLanguage => "Lenguaje no admitido: {}",
+            Message::LangRequiredForStdin => "--lang es obligatorio al leer de la entrada estándar",
+            Message::NoAnalyzerFor => "Ningún analizador admite el archivo: {}",
+            Message::BinaryFile => "{} parece un archivo binario",
+            Message::NoCacheAt => "No hay caché de análisis en {}; ejecute primero un análisis",
+            Message::InvalidPattern => "Patrón no válido: {}",
+        }
+    }
+}
+
+/// `message` in the current locale with each `{}` replaced by the next of `args`.
+/// Use [`tr!`](crate::tr) rather than calling this directly.
+pub fn format(message: Message, args: &[&dyn Display]) -> String {
+    let mut parts = message.text(locale()).split("{}");
+    let mut args = args.iter();
+    let mut text = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        if let Some(arg) = args.next() {
+            text.push_str(&arg.to_string());
+        }
+        text.push_str(part);
+    }
+    text
+}
+
+/// Whether `answer` to a yes/no prompt means yes in the current locale; English `y`
+/// is always accepted.
+pub fn is_yes(answer: &str) -> bool {
+    let answer = answer.trim().to_lowercase();
+    answer == "y" || (locale() == Locale::Es && answer == "s")
+}
+
+/// Formats a [`Message`] in the current locale: `tr!(Message::UsingModel, model)`.
+#[macro_export]
+macro_rules! tr {
+    ($message:expr $(, $arg:expr)* $(,)?) => {
+        $crate::messages::format($message, &[$(&$arg as &dyn std::fmt::Display),*])
+    };
+}
@@ -0,0 +1,119 @@
+use crate::config::{Config, WebhookConfig};
+use crate::error::FolderSummaryError;
+use crate::report::Report;
+use log::{info, warn};
+use reqwest::multipart::{Form, Part};
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Posts a completion notice for `report` to every configured webhook.
+///
+/// `files` are the written reports, main report first; `regressions` describe metrics that
+/// got worse since the previous run, if anything compared them. A failing webhook is
+/// logged and skipped; notifications never fail the run.
+pub async fn notify_webhooks(
+    config: &Config,
+    report: &Report,
+    files: &[PathBuf],
+    regressions: &[String],
+) {
+    let client = Client::new();
+    for webhook in config.get_webhooks() {
+        match send(&client, &webhook, report, files, regressions).await {
+            Ok(()) => info!("Notified webhook {}", webhook.url),
+            Err(e) => warn!("Webhook {} failed: {}", webhook.url, e),
+        }
+    }
+}
+
+async fn send(
+    client: &Client,
+    webhook: &WebhookConfig,
+    report: &Report,
+    files: &[PathBuf],
+    regressions: &[String],
+) -> Result<(), FolderSummaryError> {
+    let kind = webhook.kind.as_deref().unwrap_or("json").to_lowercase();
+    let link = files.first().map(|file| report_link(webhook, file));
+    let text = headline(report, link.as_deref(), regressions);
+
+    let request = match kind.as_str() {
+        "json" => client.post(&webhook.url).json(&json!({
+            "event": if regressions.is_empty() { "run_completed" } else { "metrics_regressed" },
+            "project": report.project_name,
+            "regressions": regressions,
+            "report_url": link,
+            "metrics": report.metrics,
+            "report": report,
+        })),
+        "slack" => client.post(&webhook.url).json(&json!({ "text": text })),
+        "discord" => {
+            let payload = json!({ "content": text });
+            match files.first() {
+                Some(file) => client
+                    .post(&webhook.url)
+                    .multipart(discord_form(&payload, file).await?),
+                None => client.post(&webhook.url).json(&payload),
+            }
+        }
+        other => {
+            return Err(FolderSummaryError::ConfigError(format!(
+                "Unknown webhook kind {:?}; expected json, slack or discord",
+                other
+            )))
+        }
+    };
+
+    request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| FolderSummaryError::provider(&webhook.url, e))?;
+    Ok(())
+}
+
+// Discord takes the message and the report as one multipart upload.
+async fn discord_form(payload: &Value, file: &Path) -> Result<Form, FolderSummaryError> {
+    let contents = tokio::fs::read(file)
+        .await
+        .map_err(|e| FolderSummaryError::file(file.display().to_string(), e))?;
+    Ok(Form::new()
+        .text("payload_json", payload.to_string())
+        .part("files[0]", Part::bytes(contents).file_name(file_name(file))))
+}
+
+fn headline(report: &Report, link: Option<&str>, regressions: &[String]) -> String {
+    let metrics = &report.metrics;
+    let mut text = format!(
+        "Folder summary for {} finished: {} files, {} functions ({} summarized), {} lines of code",
+        report.project_name,
+        metrics.files,
+        metrics.functions,
+        metrics.summarized_functions,
+        metrics.lines_of_code
+    );
+    if metrics.failures > 0 {
+        text.push_str(&format!(", {} files failed to analyze", metrics.failures));
+    }
+    for regression in regressions {
+        text.push_str(&format!("\nRegression: {}", regression));
+    }
+    if let Some(link) = link {
+        text.push_str(&format!("\nReport: {}", link));
+    }
+    text
+}
+
+fn report_link(webhook: &WebhookConfig, file: &Path) -> String {
+    match &webhook.report_url {
+        Some(template) => template.replace("{file}", &file_name(file)),
+        None => file.display().to_string(),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,62 @@
+//! Where folder-summary keeps its files unless told otherwise, following the XDG base
+//! directories on Linux and the platform conventions elsewhere.
+
+use crate::error::FolderSummaryError;
+use directories::ProjectDirs;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::path::PathBuf;
+
+const CONFIG_FILE: &str = "config.toml";
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "folder_summary")
+}
+
+/// The config file used without `--config`: `config.toml` in the working directory if
+/// there is one, otherwise the one in the config directory
+/// (`~/.config/folder_summary/config.toml` on Linux).
+pub fn config_file() -> PathBuf {
+    let local = PathBuf::from(CONFIG_FILE);
+    if local.exists() {
+        return local;
+    }
+    match project_dirs() {
+        Some(dirs) => dirs.config_dir().join(CONFIG_FILE),
+        None => local,
+    }
+}
+
+/// The analysis cache for runs started in the working directory, in the cache directory
+/// (`~/.cache/folder_summary` on Linux). Cached files are keyed by their path relative to
+/// the working directory, so each working directory gets a cache of its own, named after
+/// it and a hash of its full path.
+pub fn cache_file() -> PathBuf {
+    let Some(dirs) = project_dirs() else {
+        return PathBuf::from("analysis_cache.json");
+    };
+    let cwd = env::current_dir().unwrap_or_default();
+    let cwd = cwd.canonicalize().unwrap_or(cwd);
+    let name = cwd
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "root".to_string());
+    let hash: String = Sha256::digest(cwd.to_string_lossy().as_bytes())[..4]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    dirs.cache_dir().join(format!("{}-{}.json", name, hash))
+}
+
+/// Where reports go without `summary_output_path` (`~/.local/share/folder_summary` on
+/// Linux, `%APPDATA%\folder_summary\data` on Windows).
+pub fn data_dir() -> Result<PathBuf, FolderSummaryError> {
+    project_dirs()
+        .map(|dirs| dirs.data_dir().to_path_buf())
+        .ok_or_else(|| {
+            FolderSummaryError::ConfigError(
+                "No home directory found; set summary_output_path in the config file".to_string(),
+            )
+        })
+}
+
@@ -0,0 +1,36 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    Collection,
+    Analysis,
+    Summarization,
+    Rendering,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Phase::Collection => write!(f, "Collecting files"),
+            Phase::Analysis => write!(f, "Static analysis"),
+            Phase::Summarization => write!(f, "LLM summarization"),
+            Phase::Rendering => write!(f, "Rendering report"),
+        }
+    }
+}
+
+/// Receives pipeline progress; every method defaults to doing nothing.
+///
+/// Workers call these concurrently, so implementations must be cheap and thread-safe.
+pub trait ProgressReporter: Send + Sync {
+    /// `phase` is about to process `total` items. May be called again to resize it.
+    fn start(&self, _phase: Phase, _total: u64) {}
+    fn advance(&self, _phase: Phase, _delta: u64) {}
+    fn finish(&self, _phase: Phase, _message: &str) {}
+}
+
+/// Discards all progress; the default for library use.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoProgress;
+
+impl ProgressReporter for NoProgress {}
@@ -0,0 +1,104 @@
+use folder_summary::progress::{Phase, ProgressReporter};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::time::Duration;
+
+// Bars render the ETA from indicatif's estimator, which is a moving average of
+// recent throughput, so a slow LLM phase doesn't inherit the fast static phase's rate.
+const BAR_TEMPLATE: &str =
+    "{prefix:>22} [{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} ({per_sec}, ETA {eta}) {msg}";
+const SPINNER_TEMPLATE: &str = "{prefix:>22} [{elapsed_precise}] {spinner} {msg}";
+const DOWNLOAD_TEMPLATE: &str =
+    "{prefix:>22} [{elapsed_precise}] {bar:40.cyan/blue} {bytes}/{total_bytes} ({bytes_per_sec}) {msg}";
+
+/// Draws one terminal bar per pipeline phase.
+pub struct PipelineProgress {
+    multi: MultiProgress,
+    collection: ProgressBar,
+    analysis: ProgressBar,
+    summarization: ProgressBar,
+    rendering: ProgressBar,
+}
+
+impl PipelineProgress {
+    pub fn new(provider: &str) -> Self {
+        let multi = MultiProgress::new();
+
+        let collection = multi.add(ProgressBar::new_spinner());
+        collection.set_style(spinner_style());
+        collection.set_prefix(Phase::Collection.to_string());
+        collection.enable_steady_tick(Duration::from_millis(100));
+
+        let analysis = Self::bar(&Phase::Analysis.to_string());
+        let summarization = Self::bar(&format!("LLM: {}", provider));
+        let rendering = Self::bar(&Phase::Rendering.to_string());
+
+        PipelineProgress {
+            multi,
+            collection,
+            analysis,
+            summarization,
+            rendering,
+        }
+    }
+
+    fn bar_for(&self, phase: Phase) -> &ProgressBar {
+        match phase {
+            Phase::Collection => &self.collection,
+            Phase::Analysis => &self.analysis,
+            Phase::Summarization => &self.summarization,
+            Phase::Rendering => &self.rendering,
+        }
+    }
+
+    // Bars stay hidden until their phase starts and they are attached to the multi-progress.
+    fn bar(prefix: &str) -> ProgressBar {
+        let pb = ProgressBar::with_draw_target(Some(0), ProgressDrawTarget::hidden());
+        pb.set_style(bar_style());
+        pb.set_prefix(prefix.to_string());
+        pb
+    }
+}
+
+impl ProgressReporter for PipelineProgress {
+    fn start(&self, phase: Phase, total: u64) {
+        let pb = self.bar_for(phase);
+        if phase != Phase::Collection && pb.is_hidden() {
+            self.multi.add(pb.clone());
+        }
+        pb.set_length(total);
+    }
+
+    fn advance(&self, phase: Phase, delta: u64) {
+        self.bar_for(phase).inc(delta);
+    }
+
+    fn finish(&self, phase: Phase, message: &str) {
+        self.bar_for(phase).finish_with_message(message.to_string());
+    }
+}
+
+/// A byte-sized bar for downloading an Ollama model.
+pub fn download_bar(model: &str) -> ProgressBar {
+    let pb = ProgressBar::new(0);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template(DOWNLOAD_TEMPLATE)
+            .unwrap()
+            .progress_chars("##-"),
+    );
+    pb.set_prefix(format!("Pull: {}", model));
+    pb
+}
+
+fn bar_style() -> ProgressStyle {
+    ProgressStyle::default_bar()
+        .template(BAR_TEMPLATE)
+        .unwrap()
+        .progress_chars("##-")
+}
+
+fn spinner_style() -> ProgressStyle {
+    ProgressStyle::default_spinner()
+        .template(SPINNER_TEMPLATE)
+        .unwrap()
+}
@@ -0,0 +1,69 @@
+use super::{Artifact, Renderer};
+use crate::error::FolderSummaryError;
+use crate::findings::Severity;
+use crate::report::Report;
+use std::fmt::Write;
+
+/// GitHub Actions workflow commands (`::warning file=…,line=…::`) for the report's
+/// [findings](crate::findings), so they show up inline on pull request diffs: errors as
+/// `::error`, warnings as `::warning` and info as `::notice`. Printed to stdout, where the
+/// runner reads them.
+pub struct GithubAnnotationsRenderer;
+
+impl GithubAnnotationsRenderer {
+    fn write_annotations(&self, out: &mut String, report: &Report) -> std::fmt::Result {
+        for part in report.parts() {
+            for finding in &part.findings {
+                let command = match finding.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Info => "notice",
+                };
+                write!(
+                    out,
+                    "::{} file={}",
+                    command,
+                    escape_property(&annotation_path(&finding.file))
+                )?;
+                if let Some(line) = finding.line {
+                    write!(out, ",line={}", line)?;
+                }
+                writeln!(
+                    out,
+                    ",title={}::{}",
+                    escape_property(&finding.rule),
+                    escape_data(&finding.message)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Renderer for GithubAnnotationsRenderer {
+    fn format(&self) -> &'static str {
+        "github-annotations"
+    }
+
+    fn render<'r>(&self, report: &'r Report) -> Result<Vec<Artifact<'r>>, FolderSummaryError> {
+        let mut out = String::new();
+        self.write_annotations(&mut out, report)
+            .map_err(|e| FolderSummaryError::AnalysisError(e.to_string()))?;
+        Ok(vec![Artifact::stdout(out.into_bytes())])
+    }
+}
+
+// Annotations resolve paths against the repository root, so drop a leading `./`.
+fn annotation_path(path: &str) -> String {
+    path.trim_start_matches("./").to_string()
+}
+
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
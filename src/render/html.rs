@@ -0,0 +1,461 @@
+use super::{Artifact, Renderer};
+use crate::analyzer::CodeAnalysis;
+use crate::error::FolderSummaryError;
+use crate::findings::{finding_counts, MAX_LISTED_FINDINGS};
+use crate::report::Report;
+use crate::utils::format_bytes;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// A single self-contained HTML page.
+pub struct HtmlRenderer;
+
+impl Renderer for HtmlRenderer {
+    fn format(&self) -> &'static str {
+        "html"
+    }
+
+    fn render<'r>(&self, report: &'r Report) -> Result<Vec<Artifact<'r>>, FolderSummaryError> {
+        Ok(vec![Artifact::main("html", move |out| {
+            write_page(out, report)?;
+            Ok(HashMap::new())
+        })])
+    }
+}
+
+fn write_page(out: &mut dyn Write, report: &Report) -> io::Result<()> {
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html><head><meta charset=\"utf-8\"><title>{} - Code Summary</title></head><body>", escape(&report.project_name))?;
+    for part in report.parts() {
+        writeln!(out, "<h1>Code Summary: {}</h1>", escape(&part.project_name))?;
+        write_repository(out, part)?;
+    }
+    writeln!(out, "</body></html>")
+}
+
+fn write_metadata(out: &mut dyn Write, report: &Report) -> io::Result<()> {
+    let metadata = &report.metadata;
+    writeln!(out, "<ul>\n<li>Generated by folder-summary {} at {}</li>", escape(&report.tool_version), escape(&metadata.generated_at))?;
+    writeln!(out, "<li>Directory: {}</li>", escape(&metadata.directory))?;
+    if let Some(commit) = &metadata.git_commit {
+        let branch = metadata.git_branch.as_deref().unwrap_or("detached HEAD");
+        writeln!(out, "<li>Git: {} ({})</li>", escape(commit), escape(branch))?;
+    }
+    writeln!(out, "<li>LLM: {}</li>", escape(metadata.llm_model.as_deref().unwrap_or("none, static analysis only")))?;
+    writeln!(
+        out,
+        "<li>Files: {} analyzed, {} failed; {} functions, {} summarized</li>",
+        report.metrics.files, report.metrics.failures, report.metrics.functions, report.metrics.summarized_functions
+    )?;
+    if let Some(total) = report.sampled_from {
+        writeln!(
+            out,
+            "<li><strong>Sampled:</strong> only part of the project's {} code files was analyzed: entry points, public API files and the largest files</li>",
+            total
+        )?;
+    }
+    writeln!(out, "<li>Duration: {:.1}s</li>\n</ul>", metadata.duration_secs)
+}
+
+fn write_repository(out: &mut dyn Write, report: &Report) -> io::Result<()> {
+    write_metadata(out, report)?;
+
+    if !report.languages.is_empty() {
+        writeln!(out, "<h2>Languages</h2>\n<table>\n<tr><th>Language</th><th>Files</th><th>Code</th><th>Comments</th><th>Blank</th></tr>")?;
+        for stats in &report.languages {
+            writeln!(out, "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>", escape(&stats.language), stats.files, stats.code, stats.comments, stats.blank)?;
+        }
+        writeln!(out, "</table>")?;
+    }
+
+    if let Some(assets) = &report.assets {
+        let kinds: Vec<String> = assets
+            .kinds
+            .iter()
+            .map(|total| format!("{} {} ({})", total.files, total.kind, format_bytes(total.bytes)))
+            .collect();
+        writeln!(out, "<h2>Assets</h2>\n<p>{}</p>\n<table>\n<tr><th>Directory</th><th>Files</th><th>Size</th><th>Kinds</th></tr>", escape(&kinds.join(", ")))?;
+        for directory in &assets.directories {
+            let kinds: Vec<String> = directory.kinds.iter().map(|(kind, count)| format!("{} {}", count, kind)).collect();
+            writeln!(out, "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>", escape(&directory.directory), directory.files, format_bytes(directory.bytes), escape(&kinds.join(", ")))?;
+        }
+        writeln!(out, "</table>")?;
+        if assets.omitted_directories > 0 {
+            writeln!(out, "<p>... and {} more directories</p>", assets.omitted_directories)?;
+        }
+    }
+
+    if let Some(overview) = &report.overview {
+        writeln!(out, "<h2>Overview</h2>\n<p>{}</p>", escape(overview))?;
+    }
+
+    writeln!(out, "<h2>Documentation Files</h2>\n<ul>")?;
+    for doc in &report.docs {
+        match report.doc_summaries.get(doc) {
+            Some(summary) => writeln!(out, "<li>{}: {}</li>", escape(doc), escape(summary))?,
+            None => writeln!(out, "<li>{}</li>", escape(doc))?,
+        }
+    }
+    if report.omitted_docs > 0 {
+        writeln!(out, "<li>... and {} more</li>", report.omitted_docs)?;
+    }
+    writeln!(out, "</ul>")?;
+
+    writeln!(out, "<h2>Package Information</h2>\n<ul>")?;
+    for (dir, packages) in &report.package_info {
+        let items: Vec<String> = packages
+            .iter()
+            .map(|(package, version)| format!("<li>{}: {}</li>", escape(package), escape(version)))
+            .collect();
+        if dir == "." {
+            writeln!(out, "{}", items.join("\n"))?;
+        } else {
+            writeln!(out, "<li><code>{}</code>\n<ul>\n{}\n</ul></li>", escape(dir), items.join("\n"))?;
+        }
+    }
+    writeln!(out, "</ul>")?;
+
+    if let Some(changelog) = &report.changelog {
+        writeln!(out, "<h2>Recent Changes</h2>\n<p>From <code>{}</code></p>", escape(&changelog.path))?;
+        for entry in &changelog.entries {
+            writeln!(out, "<h3>{}</h3>\n<ul>", escape(&entry.version))?;
+            for change in &entry.changes {
+                writeln!(out, "<li>{}</li>", escape(change))?;
+            }
+            writeln!(out, "</ul>")?;
+        }
+    }
+
+    if !report.configuration.is_empty() {
+        writeln!(out, "<h2>Configuration</h2>\n<dl>")?;
+        for config_file in &report.configuration {
+            writeln!(out, "<dt><code>{}</code> ({})</dt>", escape(&config_file.path), escape(&config_file.kind))?;
+            if !config_file.outline.is_empty() {
+                writeln!(out, "<dd>Keys: {}</dd>", escape(&config_file.outline.join(", ")))?;
+            }
+            if let Some(summary) = &config_file.summary {
+                writeln!(out, "<dd>{}</dd>", escape(summary))?;
+            }
+        }
+        writeln!(out, "</dl>")?;
+    }
+
+    if let Some(data_model) = &report.data_model {
+        writeln!(out, "<h2>Data Model</h2>\n<p>From {}</p>\n<dl>", escape(&data_model.sources.join(", ")))?;
+        for table in &data_model.tables {
+            writeln!(out, "<dt><code>{}</code></dt>", escape(&table.name))?;
+            if !table.fields.is_empty() {
+                writeln!(out, "<dd>Fields: {}</dd>", escape(&table.fields.join(", ")))?;
+            }
+            if !table.relations.is_empty() {
+                writeln!(out, "<dd>Relations: {}</dd>", escape(&table.relations.join(", ")))?;
+            }
+        }
+        if data_model.omitted_tables > 0 {
+            writeln!(out, "<dt>... and {} more tables</dt>", data_model.omitted_tables)?;
+        }
+        writeln!(out, "</dl>")?;
+    }
+
+    if !report.ci_pipelines.is_empty() {
+        writeln!(out, "<h2>Build &amp; CI</h2>")?;
+        for pipeline in &report.ci_pipelines {
+            writeln!(out, "<h3><code>{}</code> ({})</h3>", escape(&pipeline.path), escape(&pipeline.provider))?;
+            if !pipeline.triggers.is_empty() {
+                writeln!(out, "<p>Runs on {}</p>", escape(&pipeline.triggers.join(", ")))?;
+            }
+            writeln!(out, "<dl>")?;
+            for job in &pipeline.jobs {
+                writeln!(out, "<dt>{}</dt>", escape(&job.name))?;
+                if !job.activities.is_empty() {
+                    writeln!(out, "<dd>{}</dd>", escape(&job.activities.join(", ")))?;
+                }
+                if !job.commands.is_empty() {
+                    writeln!(out, "<dd><code>{}</code></dd>", escape(&job.commands.join("; ")))?;
+                }
+            }
+            writeln!(out, "</dl>")?;
+        }
+    }
+
+    if let Some(infrastructure) = &report.infrastructure {
+        writeln!(out, "<h2>Infrastructure</h2>")?;
+        for dockerfile in &infrastructure.dockerfiles {
+            writeln!(out, "<h3><code>{}</code></h3>\n<ul>", escape(&dockerfile.path))?;
+            for stage in &dockerfile.stages {
+                match &stage.name {
+                    Some(name) => writeln!(out, "<li>Stage {}: from <code>{}</code></li>", escape(name), escape(&stage.base_image))?,
+                    None => writeln!(out, "<li>From <code>{}</code></li>", escape(&stage.base_image))?,
+                }
+            }
+            writeln!(out, "</ul>")?;
+            if !dockerfile.exposed_ports.is_empty() {
+                writeln!(out, "<p>Exposes {}</p>", escape(&dockerfile.exposed_ports.join(", ")))?;
+            }
+        }
+        for compose in &infrastructure.compose_files {
+            writeln!(out, "<h3><code>{}</code></h3>\n<dl>", escape(&compose.path))?;
+            for service in &compose.services {
+                writeln!(out, "<dt>{}</dt>", escape(&service.name))?;
+                writeln!(out, "<dd>{}</dd>", escape(&service.details()))?;
+            }
+            writeln!(out, "</dl>")?;
+        }
+    }
+
+    if !report.public_api.is_empty() {
+        writeln!(out, "<h2>Public API</h2>\n<ul>")?;
+        for krate in &report.public_api {
+            writeln!(out, "<li><code>{}</code>: {} items</li>", escape(&krate.name), krate.items.len())?;
+        }
+        writeln!(out, "</ul>")?;
+    }
+    if let Some(diff) = &report.api_diff {
+        writeln!(out, "<p>Changes since <code>{}</code> (suggested bump: {}):</p>\n<ul>", escape(&diff.baseline), escape(&diff.bump))?;
+        for change in &diff.changes {
+            let signature = change.after.as_ref().or(change.before.as_ref()).map_or("", String::as_str);
+            writeln!(out, "<li>{} <code>{}</code>: <code>{}</code></li>", escape(&change.change), escape(&change.path), escape(signature))?;
+        }
+        writeln!(out, "</ul>")?;
+    }
+
+    if !report.findings.is_empty() || report.baselined_findings > 0 {
+        writeln!(out, "<h2>Findings</h2>\n<p>{}</p>", finding_counts(&report.findings))?;
+        if report.baselined_findings > 0 {
+            writeln!(out, "<p>{} findings already in the baseline are not listed.</p>", report.baselined_findings)?;
+        }
+    }
+    if !report.findings.is_empty() {
+        writeln!(out, "<table>\n<tr><th>Severity</th><th>Rule</th><th>Location</th><th>Message</th></tr>")?;
+        for finding in report.findings.iter().take(MAX_LISTED_FINDINGS) {
+            let location = match finding.line {
+                Some(line) => format!("{}:{}", finding.file, line),
+                None => finding.file.clone(),
+            };
+            writeln!(out, "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>", finding.severity, escape(&finding.rule), escape(&location), escape(&finding.message))?;
+        }
+        writeln!(out, "</table>")?;
+        if report.findings.len() > MAX_LISTED_FINDINGS {
+            writeln!(out, "<p>... and {} more</p>", report.findings.len() - MAX_LISTED_FINDINGS)?;
+        }
+    }
+
+    if !report.layer_violations.is_empty() {
+        writeln!(out, "<h2>Architecture</h2>\n<p>Imports against the declared layering:</p>\n<ul>")?;
+        for violation in &report.layer_violations {
+            writeln!(out, "<li>{} ({} -&gt; {}): <code>{}</code></li>", escape(&violation.file), escape(&violation.layer), escape(&violation.target), escape(&violation.import))?;
+        }
+        writeln!(out, "</ul>")?;
+    }
+
+    if !report.generics.is_empty() {
+        writeln!(out, "<h2>Generics-Heavy Items</h2>\n<table>\n<tr><th>Item</th><th>Type params</th><th>Lifetimes</th><th>Const params</th><th>Bounds</th><th>Defined in</th></tr>")?;
+        for item in &report.generics {
+            writeln!(out, "<tr><td>{} <code>{}</code></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}:{}</td></tr>", escape(&item.kind), escape(&item.name), item.type_params, item.lifetimes, item.const_params, item.bounds, escape(&item.file), item.line)?;
+        }
+        writeln!(out, "</table>")?;
+    }
+
+    if !report.markers.is_empty() {
+        writeln!(out, "<h2>Debt Heatmap</h2>\n<table>\n<tr><th>Directory</th><th>TODOs</th><th>Feature flags</th><th>Deprecations</th></tr>")?;
+        let densest = report.markers.iter().flat_map(|m| [m.todos, m.feature_flags, m.deprecations]).max().unwrap_or(1).max(1);
+        let cell = |count: usize| format!("<td style=\"background: rgba(220, 50, 47, {:.2})\">{}</td>", count as f64 / densest as f64 * 0.8, count);
+        for markers in &report.markers {
+            writeln!(out, "<tr><td>{}</td>{}{}{}</tr>", escape(&markers.directory), cell(markers.todos), cell(markers.feature_flags), cell(markers.deprecations))?;
+        }
+        writeln!(out, "</table>")?;
+    }
+
+    if let Some(errors) = &report.error_handling {
+        writeln!(out, "<h2>Error Handling</h2>\n<ul>")?;
+        for posture in &errors.posture {
+            writeln!(out, "<li>{}</li>", escape(posture))?;
+        }
+        writeln!(out, "</ul>")?;
+        if !errors.files.is_empty() {
+            writeln!(out, "<table>\n<tr><th>File</th><th>Propagated</th><th>Handled</th><th>Panics/exits</th><th>Broad</th><th>Swallowed</th><th>Unhandled promises</th></tr>")?;
+            for file in &errors.files {
+                writeln!(out, "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>", escape(&file.file), file.propagated, file.handled, file.panics, file.broad_handlers, file.swallowed, file.unhandled_promises)?;
+            }
+            writeln!(out, "</table>")?;
+            if errors.omitted_files > 0 {
+                writeln!(out, "<p>... and {} more files</p>", errors.omitted_files)?;
+            }
+        }
+    }
+
+    if report.metrics.async_functions > 0 {
+        writeln!(out, "<h2>Async Usage</h2>\n<p>{} async functions, {} of which make blocking calls.</p>\n<ul>", report.metrics.async_functions, report.metrics.blocking_in_async)?;
+        for (file_path, analysis) in report.files.iter().filter(|(path, _)| !report.duplicates.contains_key(*path)) {
+            for func in analysis.functions.iter().filter(|f| !f.blocking_calls.is_empty()) {
+                let line = func.line.map(|line| format!(":{}", line)).unwrap_or_default();
+                writeln!(out, "<li>{}{} <code>{}</code>: <code>{}</code></li>", escape(file_path), line, escape(&func.name), escape(&func.blocking_calls.join(", ")))?;
+            }
+        }
+        writeln!(out, "</ul>")?;
+    }
+
+    if !report.routes.is_empty() {
+        writeln!(out, "<h2>HTTP Routes</h2>\n<table>\n<tr><th>Method</th><th>Path</th><th>Handler</th><th>Defined in</th></tr>")?;
+        for route in &report.routes {
+            let handler = route.handler.as_deref().unwrap_or("");
+            writeln!(out, "<tr><td>{}</td><td><code>{}</code></td><td><code>{}</code></td><td>{}:{}</td></tr>", escape(&route.method), escape(&route.path), escape(handler), escape(&route.file), route.line)?;
+        }
+        writeln!(out, "</table>")?;
+    }
+
+    if !report.api_specs.is_empty() {
+        writeln!(out, "<h2>REST API</h2>")?;
+        for spec in &report.api_specs {
+            let title = [spec.title.as_deref(), spec.version.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(out, "<h3><code>{}</code> ({}) {}</h3>\n<ul>", escape(&spec.path), escape(&spec.spec_version), escape(&title))?;
+            for endpoint in &spec.endpoints {
+                let summary = endpoint.summary.as_deref().unwrap_or("");
+                writeln!(out, "<li><code>{} {}</code> {}</li>", endpoint.method, escape(&endpoint.path), escape(summary))?;
+            }
+            if spec.omitted_endpoints > 0 {
+                writeln!(out, "<li>... and {} more endpoints</li>", spec.omitted_endpoints)?;
+            }
+            writeln!(out, "</ul>")?;
+            if !spec.schemas.is_empty() {
+                writeln!(out, "<p>Schemas: {}</p>", escape(&spec.schemas.join(", ")))?;
+            }
+        }
+    }
+
+    let generated: Vec<(&String, &String)> = report
+        .files
+        .iter()
+        .filter(|(file, _)| !report.duplicates.contains_key(*file))
+        .filter_map(|(file, analysis)| analysis.generated.as_ref().map(|reason| (file, reason)))
+        .collect();
+    if !generated.is_empty() {
+        writeln!(out, "<h2>Generated Files</h2>\n<ul>")?;
+        for (file, reason) in generated {
+            writeln!(out, "<li>{}: {}</li>", escape(file), escape(reason))?;
+        }
+        writeln!(out, "</ul>")?;
+    }
+
+    if let Some(hygiene) = &report.hygiene {
+        let counts = |counts: &std::collections::BTreeMap<String, usize>| {
+            counts
+                .iter()
+                .map(|(name, count)| format!("{} {}", count, name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(out, "<h2>Hygiene</h2>\n<p>Encodings: {}<br>Line endings: {}</p>", escape(&counts(&hygiene.encodings)), escape(&counts(&hygiene.line_endings)))?;
+        if !hygiene.issues.is_empty() {
+            writeln!(out, "<ul>")?;
+            for issue in &hygiene.issues {
+                writeln!(out, "<li>{}: {}</li>", escape(&issue.file), escape(&issue.issue))?;
+            }
+            if hygiene.omitted_issues > 0 {
+                writeln!(out, "<li>... and {} more</li>", hygiene.omitted_issues)?;
+            }
+            writeln!(out, "</ul>")?;
+        }
+    }
+
+    writeln!(out, "<h2>Code Analysis</h2>")?;
+    for (file_path, analysis) in &report.files {
+        writeln!(out, "<section>\n<h3>{}</h3>", escape(file_path))?;
+        match report.duplicates.get(file_path) {
+            Some(original) => writeln!(out, "<p>Identical to <code>{}</code>.</p>", escape(original))?,
+            None => write_file_section(out, analysis)?,
+        }
+        writeln!(out, "</section>")?;
+    }
+
+    let partial: Vec<_> = report.files.iter().filter(|(_, analysis)| !analysis.warnings.is_empty()).collect();
+    if !report.failures.is_empty() || !partial.is_empty() {
+        writeln!(out, "<h2>Analysis errors</h2>\n<ul>")?;
+        for failure in &report.failures {
+            writeln!(out, "<li><code>{}</code>: {}</li>", escape(&failure.file), escape(&failure.reason))?;
+        }
+        for (file_path, analysis) in partial {
+            for warning in &analysis.warnings {
+                writeln!(out, "<li><code>{}</code> (partially analyzed): {}</li>", escape(file_path), escape(warning))?;
+            }
+        }
+        writeln!(out, "</ul>")?;
+    }
+    Ok(())
+}
+
+fn write_file_section(out: &mut dyn Write, analysis: &CodeAnalysis) -> io::Result<()> {
+    write_list(out, "Imports", &analysis.imports)?;
+    write_list(out, "Commands", &analysis.commands)?;
+
+    if !analysis.functions.is_empty() {
+        writeln!(out, "<h4>Functions</h4>\n<dl>")?;
+        for func in &analysis.functions {
+            for decorator in &func.decorators {
+                writeln!(out, "<dt><code>{}</code></dt>", escape(decorator))?;
+            }
+            writeln!(out, "<dt><code>{}</code></dt>", escape(&func.signature))?;
+            writeln!(
+                out,
+                "<dd>Lines of code: {}, cyclomatic complexity: {}, parameters: {}, returns: {}</dd>",
+                func.lines_of_code, func.cyclomatic_complexity, func.parameters, func.returns
+            )?;
+            if let Some(summary) = func.summary.as_deref().filter(|summary| !summary.is_empty()) {
+                writeln!(out, "<dd>{}</dd>", escape(summary))?;
+            } else if let Some(reason) = &func.skip_reason {
+                writeln!(out, "<dd>Summary skipped ({})</dd>", escape(reason))?;
+            }
+        }
+        writeln!(out, "</dl>")?;
+    }
+
+    if !analysis.types.is_empty() {
+        writeln!(out, "<h4>Types</h4>")?;
+        let class = match &analysis.fence_language {
+            Some(language) => format!(" class=\"language-{}\"", escape(language)),
+            None => String::new(),
+        };
+        for type_def in &analysis.types {
+            writeln!(out, "<pre><code{}>{}</code></pre>", class, escape(type_def))?;
+        }
+    }
+
+    let summarized_types: Vec<_> = analysis
+        .significant_types
+        .iter()
+        .filter_map(|ty| ty.summary.as_deref().filter(|summary| !summary.is_empty()).map(|summary| (ty, summary)))
+        .collect();
+    if !summarized_types.is_empty() {
+        writeln!(out, "<h4>Type summaries</h4>\n<dl>")?;
+        for (ty, summary) in summarized_types {
+            writeln!(out, "<dt><code>{} {}</code></dt>", ty.kind, escape(&ty.name))?;
+            writeln!(out, "<dd>{} {}</dd>", ty.members, ty.member_noun())?;
+            writeln!(out, "<dd>{}</dd>", escape(summary))?;
+        }
+        writeln!(out, "</dl>")?;
+    }
+
+    write_list(out, "Exports", &analysis.exports)
+}
+
+fn write_list(out: &mut dyn Write, heading: &str, items: &[String]) -> io::Result<()> {
+    if items.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "<h4>{}</h4>\n<ul>", heading)?;
+    for item in items {
+        writeln!(out, "<li><code>{}</code></li>", escape(item))?;
+    }
+    writeln!(out, "</ul>")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
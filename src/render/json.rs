@@ -0,0 +1,20 @@
+use super::{Artifact, Renderer};
+use crate::error::FolderSummaryError;
+use crate::report::Report;
+use std::collections::HashMap;
+
+/// The whole [`Report`] as pretty-printed JSON.
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn format(&self) -> &'static str {
+        "json"
+    }
+
+    fn render<'r>(&self, report: &'r Report) -> Result<Vec<Artifact<'r>>, FolderSummaryError> {
+        Ok(vec![Artifact::main("json", move |out| {
+            serde_json::to_writer_pretty(out, report)?;
+            Ok(HashMap::new())
+        })])
+    }
+}
@@ -0,0 +1,529 @@
+use super::{Artifact, Renderer};
+use crate::analyzer::{ApiSpec, CodeAnalysis};
+use crate::error::FolderSummaryError;
+use crate::findings::{finding_counts, MAX_LISTED_FINDINGS};
+use crate::report::Report;
+use crate::utils::format_bytes;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+
+/// The default report format; reuses sections cached from earlier runs.
+pub struct MarkdownRenderer;
+
+impl Renderer for MarkdownRenderer {
+    fn format(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn render<'r>(&self, report: &'r Report) -> Result<Vec<Artifact<'r>>, FolderSummaryError> {
+        Ok(vec![Artifact::main("md", move |mut out| Ok(write_summary(&mut out, report)?))])
+    }
+}
+
+// What produced the report, so a months-old summary still says what it describes.
+fn write_metadata<W: Write>(out: &mut W, report: &Report) -> io::Result<()> {
+    let metadata = &report.metadata;
+    writeln!(out, "- Generated by folder-summary {} at {}", report.tool_version, metadata.generated_at)?;
+    writeln!(out, "- Directory: {}", metadata.directory)?;
+    if let Some(commit) = &metadata.git_commit {
+        let branch = metadata.git_branch.as_deref().unwrap_or("detached HEAD");
+        writeln!(out, "- Git: {} ({})", commit, branch)?;
+    }
+    writeln!(out, "- LLM: {}", metadata.llm_model.as_deref().unwrap_or("none, static analysis only"))?;
+    writeln!(
+        out,
+        "- Files: {} analyzed, {} failed; {} functions, {} summarized",
+        report.metrics.files, report.metrics.failures, report.metrics.functions, report.metrics.summarized_functions
+    )?;
+    if let Some(total) = report.sampled_from {
+        writeln!(
+            out,
+            "- **Sampled:** only part of the project's {} code files was analyzed: entry points, public API files and the largest files",
+            total
+        )?;
+    }
+    writeln!(out, "- Duration: {:.1}s\n", metadata.duration_secs)
+}
+
+/// Streams the report to `out` as markdown, one section at a time.
+///
+/// Sections in [`Report::rendered`] are copied as-is; the ones rendered now are returned
+/// so the caller can cache them for the next run.
+pub fn write_summary<W: Write>(out: &mut W, report: &Report) -> io::Result<HashMap<String, String>> {
+    if report.repositories.is_empty() {
+        writeln!(out, "# Code Summary\n")?;
+        return write_repository(out, report);
+    }
+
+    let mut rendered = HashMap::new();
+    for repository in &report.repositories {
+        writeln!(out, "# Code Summary: {}\n", repository.project_name)?;
+        rendered.extend(write_repository(out, repository)?);
+    }
+    Ok(rendered)
+}
+
+fn write_repository<W: Write>(out: &mut W, report: &Report) -> io::Result<HashMap<String, String>> {
+    write_metadata(out, report)?;
+
+    if !report.languages.is_empty() {
+        writeln!(out, "## Languages\n")?;
+        writeln!(out, "| Language | Files | Code | Comments | Blank |")?;
+        writeln!(out, "|---|---:|---:|---:|---:|")?;
+        for stats in &report.languages {
+            writeln!(out, "| {} | {} | {} | {} | {} |", stats.language, stats.files, stats.code, stats.comments, stats.blank)?;
+        }
+        writeln!(out)?;
+    }
+
+    if let Some(assets) = &report.assets {
+        writeln!(out, "## Assets\n")?;
+        let kinds: Vec<String> = assets
+            .kinds
+            .iter()
+            .map(|total| format!("{} {} ({})", total.files, total.kind, format_bytes(total.bytes)))
+            .collect();
+        writeln!(out, "{}\n", kinds.join(", "))?;
+        writeln!(out, "| Directory | Files | Size | Kinds |")?;
+        writeln!(out, "|---|---:|---:|---|")?;
+        for directory in &assets.directories {
+            let kinds: Vec<String> = directory.kinds.iter().map(|(kind, count)| format!("{} {}", count, kind)).collect();
+            writeln!(out, "| {} | {} | {} | {} |", directory.directory, directory.files, format_bytes(directory.bytes), kinds.join(", "))?;
+        }
+        if assets.omitted_directories > 0 {
+            writeln!(out, "\n... and {} more directories", assets.omitted_directories)?;
+        }
+        writeln!(out)?;
+    }
+
+    if let Some(overview) = &report.overview {
+        writeln!(out, "## Overview\n\n{}\n", overview)?;
+    }
+
+    writeln!(out, "## Documentation Files")?;
+    for doc in &report.docs {
+        writeln!(out, "- {}", doc)?;
+        if let Some(summary) = report.doc_summaries.get(doc) {
+            writeln!(out, "  Summary: {}", summary)?;
+        }
+    }
+    if report.omitted_docs > 0 {
+        writeln!(out, "- ... and {} more", report.omitted_docs)?;
+    }
+
+    writeln!(out, "\n## Package Information")?;
+    // The root's packages are listed as they are, each nested directory's under its path.
+    for (dir, packages) in &report.package_info {
+        let indent = if dir == "." {
+            ""
+        } else {
+            writeln!(out, "- {}", dir)?;
+            "  "
+        };
+        for (package, version) in packages {
+            writeln!(out, "{}- {}: {}", indent, package, version)?;
+        }
+    }
+
+    if let Some(changelog) = &report.changelog {
+        writeln!(out, "\n## Recent Changes\nFrom {}", changelog.path)?;
+        for entry in &changelog.entries {
+            writeln!(out, "\n### {}", entry.version)?;
+            for change in &entry.changes {
+                writeln!(out, "- {}", change)?;
+            }
+        }
+    }
+
+    if !report.configuration.is_empty() {
+        writeln!(out, "\n## Configuration")?;
+        for config_file in &report.configuration {
+            writeln!(out, "- {} ({})", config_file.path, config_file.kind)?;
+            if !config_file.outline.is_empty() {
+                writeln!(out, "  Keys: {}", config_file.outline.join(", "))?;
+            }
+            if let Some(summary) = &config_file.summary {
+                writeln!(out, "  Summary: {}", summary)?;
+            }
+        }
+    }
+
+    if let Some(data_model) = &report.data_model {
+        writeln!(out, "\n## Data Model\nFrom {}", data_model.sources.join(", "))?;
+        for table in &data_model.tables {
+            writeln!(out, "- {}", table.name)?;
+            if !table.fields.is_empty() {
+                writeln!(out, "  Fields: {}", table.fields.join(", "))?;
+            }
+            if !table.relations.is_empty() {
+                writeln!(out, "  Relations: {}", table.relations.join(", "))?;
+            }
+        }
+        if data_model.omitted_tables > 0 {
+            writeln!(out, "- ... and {} more tables", data_model.omitted_tables)?;
+        }
+    }
+
+    if !report.ci_pipelines.is_empty() {
+        writeln!(out, "\n## Build & CI")?;
+        for pipeline in &report.ci_pipelines {
+            write!(out, "- {} ({})", pipeline.path, pipeline.provider)?;
+            if pipeline.triggers.is_empty() {
+                writeln!(out)?;
+            } else {
+                writeln!(out, ", on {}", pipeline.triggers.join(", "))?;
+            }
+            for job in &pipeline.jobs {
+                write!(out, "  - {}", job.name)?;
+                if job.activities.is_empty() {
+                    writeln!(out)?;
+                } else {
+                    writeln!(out, ": {}", job.activities.join(", "))?;
+                }
+                if !job.commands.is_empty() {
+                    writeln!(out, "    Runs: {}", job.commands.join("; "))?;
+                }
+            }
+        }
+    }
+
+    if let Some(infrastructure) = &report.infrastructure {
+        writeln!(out, "\n## Infrastructure")?;
+        for dockerfile in &infrastructure.dockerfiles {
+            writeln!(out, "- {}", dockerfile.path)?;
+            for stage in &dockerfile.stages {
+                match &stage.name {
+                    Some(name) => writeln!(out, "  - Stage {}: from {}", name, stage.base_image)?,
+                    None => writeln!(out, "  - From {}", stage.base_image)?,
+                }
+            }
+            if !dockerfile.exposed_ports.is_empty() {
+                writeln!(out, "  Exposes: {}", dockerfile.exposed_ports.join(", "))?;
+            }
+        }
+        for compose in &infrastructure.compose_files {
+            writeln!(out, "- {}", compose.path)?;
+            for service in &compose.services {
+                writeln!(out, "  - {}: {}", service.name, service.details())?;
+            }
+        }
+    }
+
+    if !report.public_api.is_empty() {
+        writeln!(out, "\n## Public API")?;
+        for krate in &report.public_api {
+            let mut kinds: BTreeMap<&str, usize> = BTreeMap::new();
+            for item in &krate.items {
+                *kinds.entry(item.kind.as_str()).or_default() += 1;
+            }
+            let kinds: Vec<String> = kinds.iter().map(|(kind, count)| format!("{} {}", count, kind)).collect();
+            writeln!(out, "- {}: {} items ({})", krate.name, krate.items.len(), kinds.join(", "))?;
+        }
+    }
+    if let Some(diff) = &report.api_diff {
+        writeln!(out, "\nChanges since {} (suggested bump: {}):", diff.baseline, diff.bump)?;
+        if diff.changes.is_empty() {
+            writeln!(out, "- none")?;
+        }
+        for change in &diff.changes {
+            match (&change.before, &change.after) {
+                (Some(before), Some(after)) => writeln!(out, "- changed `{}`: `{}` -> `{}`", change.path, before, after)?,
+                (Some(before), None) => writeln!(out, "- removed `{}`: `{}`", change.path, before)?,
+                (None, Some(after)) => writeln!(out, "- added `{}`: `{}`", change.path, after)?,
+                (None, None) => {}
+            }
+        }
+    }
+
+    if !report.findings.is_empty() || report.baselined_findings > 0 {
+        writeln!(out, "\n## Findings\n")?;
+        writeln!(out, "{}\n", finding_counts(&report.findings))?;
+        if report.baselined_findings > 0 {
+            writeln!(out, "{} findings already in the baseline are not listed.\n", report.baselined_findings)?;
+        }
+    }
+    if !report.findings.is_empty() {
+        writeln!(out, "| Severity | Rule | Location | Message |")?;
+        writeln!(out, "|---|---|---|---|")?;
+        for finding in report.findings.iter().take(MAX_LISTED_FINDINGS) {
+            let location = match finding.line {
+                Some(line) => format!("{}:{}", finding.file, line),
+                None => finding.file.clone(),
+            };
+            writeln!(out, "| {} | {} | {} | {} |", finding.severity, finding.rule, location, finding.message.replace('|', "\\|"))?;
+        }
+        if report.findings.len() > MAX_LISTED_FINDINGS {
+            writeln!(out, "\n... and {} more", report.findings.len() - MAX_LISTED_FINDINGS)?;
+        }
+    }
+
+    if !report.layer_violations.is_empty() {
+        writeln!(out, "\n## Architecture\n")?;
+        writeln!(out, "Imports against the declared layering:")?;
+        for violation in &report.layer_violations {
+            writeln!(
+                out,
+                "- {} ({} -> {}): `{}`",
+                violation.file, violation.layer, violation.target, violation.import
+            )?;
+        }
+    }
+
+    if !report.generics.is_empty() {
+        writeln!(out, "\n## Generics-Heavy Items\n")?;
+        writeln!(out, "| Item | Type params | Lifetimes | Const params | Bounds | Defined in |")?;
+        writeln!(out, "|---|---:|---:|---:|---:|---|")?;
+        for item in &report.generics {
+            writeln!(
+                out,
+                "| {} `{}` | {} | {} | {} | {} | {}:{} |",
+                item.kind, item.name, item.type_params, item.lifetimes, item.const_params, item.bounds, item.file, item.line
+            )?;
+        }
+    }
+
+    if !report.markers.is_empty() {
+        writeln!(out, "\n## Debt Heatmap\n")?;
+        writeln!(out, "| Directory | TODOs | Feature flags | Deprecations | |")?;
+        writeln!(out, "|---|---:|---:|---:|---|")?;
+        let densest = report.markers[0].total();
+        for markers in &report.markers {
+            // Up to ten blocks, relative to the densest directory.
+            let bar = "█".repeat((markers.total() * 10).div_ceil(densest));
+            writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                markers.directory, markers.todos, markers.feature_flags, markers.deprecations, bar
+            )?;
+        }
+    }
+
+    if let Some(errors) = &report.error_handling {
+        writeln!(out, "\n## Error Handling\n")?;
+        for posture in &errors.posture {
+            writeln!(out, "- {}", posture)?;
+        }
+        if !errors.files.is_empty() {
+            writeln!(out, "\n| File | Propagated | Handled | Panics/exits | Broad | Swallowed | Unhandled promises |")?;
+            writeln!(out, "|---|---:|---:|---:|---:|---:|---:|")?;
+            for file in &errors.files {
+                writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {} | {} | {} |",
+                    file.file, file.propagated, file.handled, file.panics, file.broad_handlers, file.swallowed, file.unhandled_promises
+                )?;
+            }
+            if errors.omitted_files > 0 {
+                writeln!(out, "\n... and {} more files", errors.omitted_files)?;
+            }
+        }
+    }
+
+    if report.metrics.async_functions > 0 {
+        writeln!(out, "\n## Async Usage\n")?;
+        writeln!(
+            out,
+            "{} async functions, {} of which make blocking calls.",
+            report.metrics.async_functions, report.metrics.blocking_in_async
+        )?;
+        for (file_path, analysis) in report.files.iter().filter(|(path, _)| !report.duplicates.contains_key(*path)) {
+            for func in analysis.functions.iter().filter(|f| !f.blocking_calls.is_empty()) {
+                let line = func.line.map(|line| format!(":{}", line)).unwrap_or_default();
+                writeln!(out, "- {}{} `{}`: {}", file_path, line, func.name, func.blocking_calls.join(", "))?;
+            }
+        }
+    }
+
+    if !report.routes.is_empty() {
+        writeln!(out, "\n## HTTP Routes")?;
+        for route in &report.routes {
+            write!(out, "- {} {}", route.method, route.path)?;
+            if let Some(handler) = &route.handler {
+                write!(out, " -> {}", handler)?;
+            }
+            writeln!(out, " ({}:{})", route.file, route.line)?;
+        }
+    }
+
+    if !report.api_specs.is_empty() {
+        writeln!(out, "\n## REST API")?;
+        for spec in &report.api_specs {
+            write_api_spec(out, spec)?;
+        }
+    }
+
+    let generated: Vec<(&String, &String)> = report
+        .files
+        .iter()
+        .filter(|(file, _)| !report.duplicates.contains_key(*file))
+        .filter_map(|(file, analysis)| analysis.generated.as_ref().map(|reason| (file, reason)))
+        .collect();
+    if !generated.is_empty() {
+        writeln!(out, "\n## Generated Files\n")?;
+        for (file, reason) in generated {
+            writeln!(out, "- {}: {}", file, reason)?;
+        }
+    }
+
+    if let Some(hygiene) = &report.hygiene {
+        let counts = |counts: &BTreeMap<String, usize>| {
+            counts
+                .iter()
+                .map(|(name, count)| format!("{} {}", count, name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(out, "\n## Hygiene\n")?;
+        writeln!(out, "Encodings: {}", counts(&hygiene.encodings))?;
+        writeln!(out, "Line endings: {}", counts(&hygiene.line_endings))?;
+        if !hygiene.issues.is_empty() {
+            writeln!(out)?;
+        }
+        for issue in &hygiene.issues {
+            writeln!(out, "- {}: {}", issue.file, issue.issue)?;
+        }
+        if hygiene.omitted_issues > 0 {
+            writeln!(out, "- ... and {} more", hygiene.omitted_issues)?;
+        }
+    }
+
+    writeln!(out, "\n## Code Analysis")?;
+    let mut rendered = HashMap::new();
+    for (file_path, code_analysis) in &report.files {
+        writeln!(out, "## {}\n", file_path)?;
+
+        if let Some(original) = report.duplicates.get(file_path) {
+            writeln!(out, "Identical to `{}`.\n", original)?;
+        } else if let Some(section) = report.rendered.get(report.source_path(file_path)) {
+            out.write_all(section.as_bytes())?;
+        } else {
+            let mut section = Vec::new();
+            write_file_section(&mut section, code_analysis)?;
+            writeln!(section, "\n")?;
+            out.write_all(&section)?;
+            rendered.insert(
+                report.source_path(file_path).to_string(),
+                String::from_utf8_lossy(&section).into_owned(),
+            );
+        }
+    }
+
+    let partial: Vec<_> = report.files.iter().filter(|(_, analysis)| !analysis.warnings.is_empty()).collect();
+    if !report.failures.is_empty() || !partial.is_empty() {
+        writeln!(out, "## Analysis errors\n")?;
+        for failure in &report.failures {
+            writeln!(out, "- `{}`: {}", failure.file, failure.reason)?;
+        }
+        for (file_path, analysis) in partial {
+            for warning in &analysis.warnings {
+                writeln!(out, "- `{}` (partially analyzed): {}", file_path, warning)?;
+            }
+        }
+        writeln!(out)?;
+    }
+
+    Ok(rendered)
+}
+
+fn write_api_spec<W: Write>(out: &mut W, spec: &ApiSpec) -> io::Result<()> {
+    write!(out, "- {} ({})", spec.path, spec.spec_version)?;
+    match (&spec.title, &spec.version) {
+        (Some(title), Some(version)) => writeln!(out, ": {} {}", title, version)?,
+        (Some(title), None) => writeln!(out, ": {}", title)?,
+        _ => writeln!(out)?,
+    }
+    for endpoint in &spec.endpoints {
+        write!(out, "  - {} {}", endpoint.method, endpoint.path)?;
+        match &endpoint.summary {
+            Some(summary) => writeln!(out, ": {}", summary)?,
+            None => writeln!(out)?,
+        }
+    }
+    if spec.omitted_endpoints > 0 {
+        writeln!(out, "  - ... and {} more endpoints", spec.omitted_endpoints)?;
+    }
+    if !spec.schemas.is_empty() {
+        write!(out, "  Schemas: {}", spec.schemas.join(", "))?;
+        if spec.omitted_schemas > 0 {
+            write!(out, " and {} more", spec.omitted_schemas)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn write_file_section<W: Write>(out: &mut W, code_analysis: &CodeAnalysis) -> io::Result<()> {
+    if !code_analysis.imports.is_empty() {
+        writeln!(out, "**Imports:**")?;
+        for import in &code_analysis.imports {
+            writeln!(out, "- {}", import)?;
+        }
+        writeln!(out)?;
+    }
+
+    if !code_analysis.commands.is_empty() {
+        writeln!(out, "**Commands:** {}", code_analysis.commands.join(", "))?;
+        writeln!(out)?;
+    }
+
+    if !code_analysis.functions.is_empty() {
+        writeln!(out, "**Functions:**")?;
+        for func in &code_analysis.functions {
+            writeln!(out, "- {}", func.name)?;
+            if !func.decorators.is_empty() {
+                writeln!(out, "  Decorators: {}", func.decorators.join(" "))?;
+            }
+            writeln!(out, "  Signature: {}", func.signature)?;
+            writeln!(out, "  Lines of code: {}", func.lines_of_code)?;
+            writeln!(out, "  Cyclomatic complexity: {}", func.cyclomatic_complexity)?;
+            writeln!(out, "  Parameters: {}", func.parameters)?;
+            writeln!(out, "  Returns: {}", func.returns)?;
+            // Empty when every answer the LLM gave was rejected.
+            if let Some(sum) = func.summary.as_deref().filter(|sum| !sum.is_empty()) {
+                writeln!(out, "  Summary: {}", sum)?;
+            } else if let Some(reason) = &func.skip_reason {
+                writeln!(out, "  Summary: skipped ({})", reason)?;
+            }
+            writeln!(out)?;
+        }
+    }
+
+    if !code_analysis.types.is_empty() {
+        writeln!(out, "**Types:**")?;
+        let fence = code_analysis.fence_language.as_deref().unwrap_or_default();
+        for type_def in &code_analysis.types {
+            writeln!(out, "```{}\n{}\n```\n", fence, type_def)?;
+        }
+    }
+
+    let summarized_types: Vec<_> = code_analysis
+        .significant_types
+        .iter()
+        .filter_map(|ty| ty.summary.as_deref().filter(|sum| !sum.is_empty()).map(|sum| (ty, sum)))
+        .collect();
+    if !summarized_types.is_empty() {
+        writeln!(out, "**Type summaries:**")?;
+        for (ty, sum) in summarized_types {
+            writeln!(
+                out,
+                "- {} {} ({} {}): {}",
+                ty.kind,
+                ty.name,
+                ty.members,
+                ty.member_noun(),
+                sum
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    if !code_analysis.exports.is_empty() {
+        writeln!(out, "**Exports:**")?;
+        for export in &code_analysis.exports {
+            writeln!(out, "- {}", export)?;
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
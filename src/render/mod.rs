@@ -0,0 +1,150 @@
+mod github;
+mod html;
+mod json;
+mod markdown;
+
+pub use github::GithubAnnotationsRenderer;
+pub use html::HtmlRenderer;
+pub use json::JsonRenderer;
+pub use markdown::{write_summary, MarkdownRenderer};
+
+use crate::error::FolderSummaryError;
+use crate::report::Report;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Writes a document to its destination and returns the per-file sections it rendered
+/// from scratch, for renderers that honor [`Report::rendered`].
+pub type WriteDocument<'r> =
+    Box<dyn FnOnce(&mut dyn Write) -> Result<HashMap<String, String>, FolderSummaryError> + 'r>;
+
+/// What goes into an [`Artifact`]'s file.
+pub enum Contents<'r> {
+    /// Small output, already rendered.
+    Bytes(Vec<u8>),
+    /// A document written straight to the file it ends up in, so a large report never
+    /// sits in memory whole.
+    Streamed(WriteDocument<'r>),
+}
+
+impl Contents<'_> {
+    /// Writes the contents to `out`; returns the sections a streamed document rendered.
+    pub fn write_to(self, out: &mut dyn Write) -> Result<HashMap<String, String>, FolderSummaryError> {
+        match self {
+            Contents::Bytes(bytes) => {
+                out.write_all(&bytes)?;
+                Ok(HashMap::new())
+            }
+            Contents::Streamed(write) => write(out),
+        }
+    }
+}
+
+impl Default for Contents<'_> {
+    fn default() -> Self {
+        Contents::Bytes(Vec::new())
+    }
+}
+
+impl fmt::Debug for Contents<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Contents::Bytes(bytes) => write!(f, "Bytes({} bytes)", bytes.len()),
+            Contents::Streamed(_) => write!(f, "Streamed"),
+        }
+    }
+}
+
+/// One file produced by a [`Renderer`].
+#[derive(Debug, Default)]
+pub struct Artifact<'r> {
+    /// Where the file goes, relative to the output directory. `None` marks the main
+    /// report, which is named by `summary_filename_format` with `extension` swapped in.
+    pub path: Option<PathBuf>,
+    pub extension: String,
+    pub contents: Contents<'r>,
+    /// Printed to stdout instead of written to the output directory, for output meant
+    /// for whatever runs the tool, such as CI workflow commands.
+    pub to_stdout: bool,
+}
+
+impl<'r> Artifact<'r> {
+    /// The main report file, written as it is rendered.
+    pub fn main(
+        extension: &str,
+        write: impl FnOnce(&mut dyn Write) -> Result<HashMap<String, String>, FolderSummaryError> + 'r,
+    ) -> Self {
+        Artifact {
+            extension: extension.to_string(),
+            contents: Contents::Streamed(Box::new(write)),
+            ..Default::default()
+        }
+    }
+
+    /// Output printed to stdout rather than saved.
+    pub fn stdout(contents: Vec<u8>) -> Self {
+        Artifact {
+            contents: Contents::Bytes(contents),
+            to_stdout: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Turns a [`Report`] into one or more output files.
+pub trait Renderer: Send + Sync {
+    /// The name `--format` selects this renderer by.
+    fn format(&self) -> &'static str;
+    /// The files for `report`. Streamed contents borrow the report and are written only
+    /// once the caller knows where each file goes.
+    fn render<'r>(&self, report: &'r Report) -> Result<Vec<Artifact<'r>>, FolderSummaryError>;
+}
+
+/// The renderers available by format name; starts with the built-in ones.
+#[derive(Clone)]
+pub struct RendererRegistry {
+    renderers: Vec<Arc<dyn Renderer>>,
+}
+
+impl Default for RendererRegistry {
+    fn default() -> Self {
+        RendererRegistry {
+            renderers: vec![
+                Arc::new(MarkdownRenderer),
+                Arc::new(HtmlRenderer),
+                Arc::new(JsonRenderer),
+                Arc::new(GithubAnnotationsRenderer),
+            ],
+        }
+    }
+}
+
+impl RendererRegistry {
+    /// Adds `renderer`, replacing any existing renderer for the same format.
+    pub fn register(&mut self, renderer: Arc<dyn Renderer>) {
+        self.renderers.retain(|r| r.format() != renderer.format());
+        self.renderers.push(renderer);
+    }
+
+    pub fn get(&self, format: &str) -> Result<Arc<dyn Renderer>, FolderSummaryError> {
+        let format = format.to_lowercase();
+        self.renderers
+            .iter()
+            .find(|r| r.format() == format)
+            .cloned()
+            .ok_or_else(|| {
+                FolderSummaryError::ConfigError(format!(
+                    "Unknown output format {:?}; expected one of: {}",
+                    format,
+                    self.formats().join(", ")
+                ))
+            })
+    }
+
+    pub fn formats(&self) -> Vec<&'static str> {
+        self.renderers.iter().map(|r| r.format()).collect()
+    }
+}
@@ -0,0 +1,421 @@
+use crate::analyzer::{
+    AnalysisFailure, AnalysisResults, ApiDiff, ApiSpec, Changelog, CiPipeline, CodeAnalysis,
+    ConfigFileSummary, CrateApi, DataModel, DirectoryMarkers, ErrorHandling, GenericItem,
+    Infrastructure, LayerViolation, Route,
+};
+use crate::findings::Finding;
+use crate::run_log::RunRecord;
+use crate::utils::{clock, AssetInventory, Hygiene, LanguageStats};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Everything a run produced, in the form every output format is rendered from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Report {
+    /// Version of folder-summary that produced the report.
+    pub tool_version: String,
+    pub project_name: String,
+    pub metadata: RunMetadata,
+    /// How many code files the project has, when `--sample` analyzed only some of them.
+    #[serde(default)]
+    pub sampled_from: Option<usize>,
+    /// Files and lines per language across the whole directory, most code first.
+    #[serde(default)]
+    pub languages: Vec<LanguageStats>,
+    /// Images, fonts, datasets, binaries and other non-code files.
+    #[serde(default)]
+    pub assets: Option<AssetInventory>,
+    /// Encodings and line endings of text files, and the files that stand out.
+    #[serde(default)]
+    pub hygiene: Option<Hygiene>,
+    pub docs: Vec<String>,
+    /// The newest releases from the project's changelog, if it has one.
+    #[serde(default)]
+    pub changelog: Option<Changelog>,
+    /// Documentation files left out of `docs` by the listing cap.
+    #[serde(default)]
+    pub omitted_docs: usize,
+    /// LLM summaries of documentation files, by path.
+    #[serde(default)]
+    pub doc_summaries: BTreeMap<String, String>,
+    /// LLM-written description of the whole project.
+    #[serde(default)]
+    pub overview: Option<String>,
+    /// Package names and versions by directory: `.` for the project root, then each
+    /// nested code directory with a manifest.
+    pub package_info: BTreeMap<String, BTreeMap<String, String>>,
+    /// Outlines of the project's configuration files.
+    #[serde(default)]
+    pub configuration: Vec<ConfigFileSummary>,
+    /// Tables and relations from schema files and migrations.
+    #[serde(default)]
+    pub data_model: Option<DataModel>,
+    /// CI pipeline definitions, kept out of `configuration`.
+    #[serde(default)]
+    pub ci_pipelines: Vec<CiPipeline>,
+    /// Build stages and services from Dockerfiles and Compose files.
+    #[serde(default)]
+    pub infrastructure: Option<Infrastructure>,
+    /// Public items of the project's Rust library crates.
+    #[serde(default)]
+    pub public_api: Vec<CrateApi>,
+    /// Changes to `public_api` since the configured baseline snapshot.
+    #[serde(default)]
+    pub api_diff: Option<ApiDiff>,
+    /// The Rust functions and types with the most generic parameters and bounds.
+    #[serde(default)]
+    pub generics: Vec<GenericItem>,
+    /// Imports that go against the configured `layers`.
+    #[serde(default)]
+    pub layer_violations: Vec<LayerViolation>,
+    /// Directories with the most TODOs, feature flags and deprecation markers.
+    #[serde(default)]
+    pub markers: Vec<DirectoryMarkers>,
+    /// How the code deals with errors, per language and for the riskiest files.
+    #[serde(default)]
+    pub error_handling: Option<ErrorHandling>,
+    /// HTTP endpoints defined in the code, in file order.
+    #[serde(default)]
+    pub routes: Vec<Route>,
+    /// OpenAPI and Swagger documents, kept out of `configuration`.
+    #[serde(default)]
+    pub api_specs: Vec<ApiSpec>,
+    /// Everything flagged above, in one severity-tagged list; see
+    /// [`collect_findings`](crate::findings::collect_findings).
+    #[serde(default)]
+    pub findings: Vec<Finding>,
+    /// Findings left out of `findings` because the `findings_baseline` has them.
+    #[serde(default)]
+    pub baselined_findings: usize,
+    pub files: BTreeMap<String, CodeAnalysis>,
+    /// Files byte-identical to another analyzed file, mapped to the file that was analyzed.
+    pub duplicates: BTreeMap<String, String>,
+    pub failures: Vec<AnalysisFailure>,
+    pub metrics: ReportMetrics,
+    /// The per-repository reports a batch run was combined from; see [`Report::combine`].
+    /// Empty for a single directory.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repositories: Vec<Report>,
+    /// Sections a previous run already rendered for unchanged files; see
+    /// [`AnalysisResults::rendered`]. Keyed by the path each file was read from, like the
+    /// cache; see [`Report::source_path`]. Not part of the serialized report.
+    #[serde(skip)]
+    pub rendered: HashMap<String, String>,
+    /// The path each file in `files` was read from, where it differs from the key; see
+    /// [`Report::relativize_paths`]. Not part of the serialized report.
+    #[serde(skip)]
+    pub source_paths: HashMap<String, String>,
+    /// What happened to each file and LLM call; written next to the report rather than
+    /// into it.
+    #[serde(skip)]
+    pub run_log: Option<RunRecord>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunMetadata {
+    /// RFC 3339 timestamp of when the run finished.
+    pub generated_at: String,
+    pub directory: String,
+    /// The model that wrote the summaries, or `None` for static-only runs.
+    pub llm_model: Option<String>,
+    /// The commit the analyzed directory was checked out at, if it is a git repository.
+    #[serde(default)]
+    pub git_commit: Option<String>,
+    #[serde(default)]
+    pub git_branch: Option<String>,
+    /// Seconds spent analyzing and summarizing.
+    #[serde(default)]
+    pub duration_secs: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportMetrics {
+    pub files: usize,
+    pub functions: usize,
+    pub summarized_functions: usize,
+    /// Lines inside functions, summed over every file.
+    pub lines_of_code: usize,
+    pub failures: usize,
+    #[serde(default)]
+    pub async_functions: usize,
+    /// Async functions that make at least one blocking call.
+    #[serde(default)]
+    pub blocking_in_async: usize,
+}
+
+impl Report {
+    pub fn new(
+        project_name: String,
+        metadata: RunMetadata,
+        docs: Vec<String>,
+        package_info: BTreeMap<String, BTreeMap<String, String>>,
+        results: AnalysisResults,
+    ) -> Self {
+        let files: BTreeMap<String, CodeAnalysis> = results.analyses.into_iter().collect();
+        let metrics = ReportMetrics::from_files(&files, results.failures.len());
+        Report {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            project_name,
+            metadata,
+            languages: Vec::new(),
+            assets: None,
+            hygiene: None,
+            docs,
+            omitted_docs: 0,
+            sampled_from: None,
+            changelog: None,
+            doc_summaries: BTreeMap::new(),
+            overview: None,
+            package_info,
+            configuration: Vec::new(),
+            data_model: None,
+            ci_pipelines: Vec::new(),
+            infrastructure: None,
+            public_api: Vec::new(),
+            api_diff: None,
+            generics: Vec::new(),
+            layer_violations: Vec::new(),
+            markers: Vec::new(),
+            error_handling: None,
+            routes: Vec::new(),
+            api_specs: Vec::new(),
+            findings: Vec::new(),
+            baselined_findings: 0,
+            files,
+            duplicates: results.duplicates,
+            failures: results.failures,
+            metrics,
+            repositories: Vec::new(),
+            rendered: results.rendered,
+            source_paths: HashMap::new(),
+            run_log: None,
+        }
+    }
+
+    /// Rewrites every file path in the report, which starts out as the path the file was
+    /// read from (`./src/main.rs`, `/home/me/project/src/main.rs`), relative to `root`,
+    /// the analyzed directory: `src/main.rs`. With `absolute`, paths are made absolute
+    /// instead. Paths outside `root` are left as they are.
+    pub fn relativize_paths(&mut self, root: &Path, absolute: bool) {
+        let absolute_root =
+            absolute.then(|| fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf()));
+        let display = |path: &str| -> String {
+            let Ok(relative) = Path::new(path).strip_prefix(root) else {
+                return path.to_string();
+            };
+            let path: PathBuf = match &absolute_root {
+                Some(absolute_root) => absolute_root.join(relative),
+                None if relative.as_os_str().is_empty() => PathBuf::from("."),
+                None => relative.to_path_buf(),
+            };
+            path.to_string_lossy().into_owned()
+        };
+        let fix = |path: &mut String| *path = display(path);
+
+        self.source_paths = self
+            .files
+            .keys()
+            .map(|source| (display(source), source.clone()))
+            .filter(|(shown, source)| shown != source)
+            .collect();
+        self.files = std::mem::take(&mut self.files)
+            .into_iter()
+            .map(|(path, analysis)| (display(&path), analysis))
+            .collect();
+        self.duplicates = std::mem::take(&mut self.duplicates)
+            .into_iter()
+            .map(|(duplicate, original)| (display(&duplicate), display(&original)))
+            .collect();
+        self.doc_summaries = std::mem::take(&mut self.doc_summaries)
+            .into_iter()
+            .map(|(path, summary)| (display(&path), summary))
+            .collect();
+        self.docs.iter_mut().for_each(fix);
+        self.failures.iter_mut().for_each(|failure| fix(&mut failure.file));
+        self.findings.iter_mut().for_each(|finding| fix(&mut finding.file));
+        self.layer_violations.iter_mut().for_each(|violation| fix(&mut violation.file));
+        self.routes.iter_mut().for_each(|route| fix(&mut route.file));
+        self.generics.iter_mut().for_each(|item| fix(&mut item.file));
+        self.configuration.iter_mut().for_each(|config| fix(&mut config.path));
+        self.ci_pipelines.iter_mut().for_each(|pipeline| fix(&mut pipeline.path));
+        self.api_specs.iter_mut().for_each(|spec| fix(&mut spec.path));
+        if let Some(changelog) = &mut self.changelog {
+            fix(&mut changelog.path);
+        }
+        if let Some(infrastructure) = &mut self.infrastructure {
+            infrastructure.dockerfiles.iter_mut().for_each(|file| fix(&mut file.path));
+            infrastructure.compose_files.iter_mut().for_each(|file| fix(&mut file.path));
+        }
+        if let Some(error_handling) = &mut self.error_handling {
+            error_handling.files.iter_mut().for_each(|file| fix(&mut file.file));
+        }
+        if let Some(hygiene) = &mut self.hygiene {
+            hygiene.issues.iter_mut().for_each(|issue| fix(&mut issue.file));
+        }
+    }
+
+    /// The path `file`, a key of `files`, was read from.
+    pub fn source_path<'a>(&'a self, file: &'a str) -> &'a str {
+        self.source_paths.get(file).map_or(file, String::as_str)
+    }
+
+    /// One report with a section per repository, for batch runs. Metrics are summed;
+    /// everything else stays in the individual reports.
+    pub fn combine(project_name: String, repositories: Vec<Report>) -> Self {
+        let directories: Vec<&str> = repositories
+            .iter()
+            .map(|report| report.metadata.directory.as_str())
+            .collect();
+        let llm_model = repositories
+            .iter()
+            .find_map(|report| report.metadata.llm_model.clone());
+        let mut metadata = RunMetadata::now(directories.join(", "), llm_model);
+        metadata.duration_secs =
+            repositories.iter().map(|report| report.metadata.duration_secs).sum();
+        let mut metrics = ReportMetrics::default();
+        for report in &repositories {
+            metrics.files += report.metrics.files;
+            metrics.functions += report.metrics.functions;
+            metrics.summarized_functions += report.metrics.summarized_functions;
+            metrics.lines_of_code += report.metrics.lines_of_code;
+            metrics.failures += report.metrics.failures;
+            metrics.async_functions += report.metrics.async_functions;
+            metrics.blocking_in_async += report.metrics.blocking_in_async;
+        }
+        Report {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            project_name,
+            metadata,
+            metrics,
+            repositories,
+            ..Default::default()
+        }
+    }
+
+    /// Frees the per-file analyses and rendered sections, of this report and the ones it
+    /// combines, once every output has been written from them. Metrics, findings and
+    /// failures stay.
+    pub fn drop_analyses(&mut self) {
+        self.files = BTreeMap::new();
+        self.rendered = HashMap::new();
+        self.source_paths = HashMap::new();
+        self.repositories.iter_mut().for_each(Report::drop_analyses);
+    }
+
+    /// The reports renderers lay out one after another: the combined repositories, or
+    /// just this report.
+    pub fn parts(&self) -> Vec<&Report> {
+        if self.repositories.is_empty() {
+            vec![self]
+        } else {
+            self.repositories.iter().collect()
+        }
+    }
+}
+
+impl RunMetadata {
+    pub fn now(directory: String, llm_model: Option<String>) -> Self {
+        RunMetadata {
+            generated_at: clock::now().to_rfc3339(),
+            directory,
+            llm_model,
+            git_commit: None,
+            git_branch: None,
+            duration_secs: 0.0,
+        }
+    }
+}
+
+impl ReportMetrics {
+    // Duplicates share their original's analysis, so they count like any other file.
+    fn from_files(files: &BTreeMap<String, CodeAnalysis>, failures: usize) -> Self {
+        let functions = files.values().flat_map(|analysis| &analysis.functions);
+        ReportMetrics {
+            files: files.len(),
+            functions: functions.clone().count(),
+            summarized_functions: functions.clone().filter(|f| f.summary.is_some()).count(),
+            lines_of_code: functions.clone().map(|f| f.lines_of_code).sum(),
+            failures,
+            async_functions: functions.clone().filter(|f| f.is_async).count(),
+            blocking_in_async: functions.filter(|f| !f.blocking_calls.is_empty()).count(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::findings::Severity;
+
+    fn report() -> Report {
+        Report {
+            files: BTreeMap::from([
+                ("./project/src/main.rs".to_string(), CodeAnalysis::default()),
+                ("./project/src/copy.rs".to_string(), CodeAnalysis::default()),
+                ("/elsewhere/lib.rs".to_string(), CodeAnalysis::default()),
+            ]),
+            duplicates: BTreeMap::from([(
+                "./project/src/copy.rs".to_string(),
+                "./project/src/main.rs".to_string(),
+            )]),
+            failures: vec![AnalysisFailure {
+                file: "./project/src/broken.rs".to_string(),
+                reason: "parse error".to_string(),
+            }],
+            findings: vec![Finding {
+                rule: "long-function".to_string(),
+                severity: Severity::Warning,
+                file: "./project/src/main.rs".to_string(),
+                line: Some(3),
+                message: "main is 200 lines long".to_string(),
+            }],
+            docs: vec!["./project".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn paths_become_relative_to_the_root() {
+        let mut report = report();
+        report.relativize_paths(Path::new("./project"), false);
+
+        let files: Vec<&str> = report.files.keys().map(String::as_str).collect();
+        assert_eq!(files, ["/elsewhere/lib.rs", "src/copy.rs", "src/main.rs"]);
+        assert_eq!(report.duplicates["src/copy.rs"], "src/main.rs");
+        assert_eq!(report.failures[0].file, "src/broken.rs");
+        assert_eq!(report.findings[0].file, "src/main.rs");
+        // The root itself.
+        assert_eq!(report.docs, ["."]);
+    }
+
+    #[test]
+    fn source_paths_remember_where_files_were_read() {
+        let mut report = report();
+        report.relativize_paths(Path::new("./project"), false);
+
+        assert_eq!(report.source_path("src/main.rs"), "./project/src/main.rs");
+        // Paths left as they were aren't recorded.
+        assert_eq!(report.source_paths.get("/elsewhere/lib.rs"), None);
+        assert_eq!(report.source_path("/elsewhere/lib.rs"), "/elsewhere/lib.rs");
+    }
+
+    #[test]
+    fn absolute_paths_start_at_the_canonical_root() {
+        let root = std::env::temp_dir().join(format!("folder-summary-report-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let mut report = Report {
+            files: BTreeMap::from([(
+                root.join("src/main.rs").display().to_string(),
+                CodeAnalysis::default(),
+            )]),
+            ..Default::default()
+        };
+        report.relativize_paths(&root, true);
+
+        let expected = fs::canonicalize(&root).unwrap().join("src/main.rs");
+        assert_eq!(report.files.keys().next().map(String::as_str), Some(expected.to_str().unwrap()));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
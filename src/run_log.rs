@@ -0,0 +1,108 @@
+use crate::analyzer::AnalysisFailure;
+use crate::report::RunMetadata;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+tokio::task_local! {
+    /// The file whose functions are being summarized, for attributing LLM calls to it.
+    pub static CURRENT_FILE: String;
+}
+
+/// Where a file's analysis came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheOutcome {
+    /// Fully summarized analysis from the cache; nothing was redone.
+    Hit,
+    /// Static analysis from the cache, still to be summarized.
+    Partial,
+    /// Analyzed from scratch.
+    Miss,
+    /// The collector doesn't read from disk, so the cache wasn't consulted.
+    Unused,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileRecord {
+    pub file: String,
+    pub cache: CacheOutcome,
+    /// Seconds of static analysis; 0 when it came from the cache.
+    pub analysis_secs: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmCall {
+    pub model: String,
+    /// The file being summarized; `None` for documentation, configuration and overview
+    /// prompts.
+    pub file: Option<String>,
+    pub duration_secs: f64,
+    /// Counted the same rough way as the run estimate.
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    pub error: Option<String>,
+}
+
+/// Collects what happens to each file and LLM call while a run is in progress.
+#[derive(Debug, Default)]
+pub struct RunLog {
+    files: Mutex<Vec<FileRecord>>,
+    llm_calls: Mutex<Vec<LlmCall>>,
+}
+
+impl RunLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_file(&self, record: FileRecord) {
+        self.files.lock().unwrap().push(record);
+    }
+
+    pub fn record_llm_call(&self, call: LlmCall) {
+        self.llm_calls.lock().unwrap().push(call);
+    }
+
+    /// Everything recorded so far, leaving the log empty for the next run.
+    pub fn take(&self) -> RunRecord {
+        let mut files = std::mem::take(&mut *self.files.lock().unwrap());
+        files.sort_by(|a, b| a.file.cmp(&b.file));
+        RunRecord {
+            metadata: RunMetadata::default(),
+            files,
+            llm_calls: std::mem::take(&mut *self.llm_calls.lock().unwrap()),
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// One run's log, written next to its report as `<report>.run.json` for postmortems of
+/// slow or expensive runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub metadata: RunMetadata,
+    pub files: Vec<FileRecord>,
+    pub llm_calls: Vec<LlmCall>,
+    pub errors: Vec<AnalysisFailure>,
+}
+
+impl RunRecord {
+    /// Renames files the way the report shows them; `shown` maps the path a file was
+    /// read from to the path in the report.
+    pub fn rename_files(&mut self, shown: &HashMap<String, String>) {
+        let rename = |file: &mut String| {
+            if let Some(name) = shown.get(file) {
+                *file = name.clone();
+            }
+        };
+        for record in &mut self.files {
+            rename(&mut record.file);
+        }
+        for call in &mut self.llm_calls {
+            if let Some(file) = &mut call.file {
+                rename(file);
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::error::FolderSummaryError;
+use chrono::{DateTime, Datelike, Duration, Local, Timelike};
+use std::str::FromStr;
+
+/// A five-field cron expression: minute, hour, day of month, month and day of week.
+///
+/// Each field takes `*`, numbers, ranges (`1-5`), lists (`1,15`) and steps (`*/15`,
+/// `0-30/10`). Day of week runs from 0 (Sunday) to 6, with 7 also meaning Sunday.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    // Like cron, a day matches either day field when neither starts with `*`.
+    restricted_days: bool,
+}
+
+impl FromStr for Schedule {
+    type Err = FolderSummaryError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(invalid(expression, "expected 5 fields"));
+        };
+        let mut days_of_week = parse_field(expression, day_of_week, 0, 7)?;
+        if days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        Ok(Schedule {
+            minutes: parse_field(expression, minute, 0, 59)?,
+            hours: parse_field(expression, hour, 0, 23)?,
+            days_of_month: parse_field(expression, day_of_month, 1, 31)?,
+            months: parse_field(expression, month, 1, 12)?,
+            days_of_week,
+            restricted_days: !day_of_month.starts_with('*') && !day_of_week.starts_with('*'),
+        })
+    }
+}
+
+impl Schedule {
+    /// The first matching minute strictly after `after`, if there is one within a year.
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        for _ in 0..366 * 24 * 60 {
+            if self.matches(&time) {
+                return Some(time);
+            }
+            time += Duration::minutes(1);
+        }
+        None
+    }
+
+    fn matches(&self, time: &DateTime<Local>) -> bool {
+        let day_of_month = self.days_of_month[time.day() as usize];
+        let day_of_week = self.days_of_week[time.weekday().num_days_from_sunday() as usize];
+        let day = if self.restricted_days {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        };
+        day && self.minutes[time.minute() as usize]
+            && self.hours[time.hour() as usize]
+            && self.months[time.month() as usize]
+    }
+}
+
+// Indexed by value, so the vector is `max + 1` long whatever `min` is.
+fn parse_field(expression: &str, field: &str, min: u32, max: u32) -> Result<Vec<bool>, FolderSummaryError> {
+    let mut allowed = vec![false; max as usize + 1];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, parse_number(expression, step)?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (parse_number(expression, start)?, parse_number(expression, end)?),
+                None => {
+                    let value = parse_number(expression, range)?;
+                    // `5/10` means from 5 to the end in steps of 10.
+                    (value, if part.contains('/') { max } else { value })
+                }
+            },
+        };
+        if step == 0 || start < min || end > max || start > end {
+            return Err(invalid(expression, &format!("{:?} is out of range {}-{}", part, min, max)));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+    Ok(allowed)
+}
+
+fn parse_number(expression: &str, text: &str) -> Result<u32, FolderSummaryError> {
+    text.parse()
+        .map_err(|_| invalid(expression, &format!("{:?} is not a number", text)))
+}
+
+fn invalid(expression: &str, reason: &str) -> FolderSummaryError {
+    FolderSummaryError::ConfigError(format!("Invalid schedule {:?}: {}", expression, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // Dates are picked away from daylight saving changes, so the local time zone the
+    // tests run in doesn't matter.
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    fn next(expression: &str, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        expression.parse::<Schedule>().unwrap().next_after(after)
+    }
+
+    #[test]
+    fn next_run_is_strictly_after() {
+        assert_eq!(next("* * * * *", at(2024, 1, 10, 10, 7)), Some(at(2024, 1, 10, 10, 8)));
+        assert_eq!(next("0 9 * * *", at(2024, 1, 10, 9, 0)), Some(at(2024, 1, 11, 9, 0)));
+    }
+
+    #[test]
+    fn steps_and_lists() {
+        assert_eq!(next("*/15 * * * *", at(2024, 1, 10, 10, 7)), Some(at(2024, 1, 10, 10, 15)));
+        assert_eq!(next("0-30/10 * * * *", at(2024, 1, 10, 10, 31)), Some(at(2024, 1, 10, 11, 0)));
+        assert_eq!(next("0 6,18 * * *", at(2024, 1, 10, 7, 0)), Some(at(2024, 1, 10, 18, 0)));
+    }
+
+    #[test]
+    fn day_of_week() {
+        // Friday the 5th to Monday the 8th.
+        assert_eq!(next("0 9 * * 1", at(2024, 1, 5, 12, 0)), Some(at(2024, 1, 8, 9, 0)));
+        assert_eq!(next("0 9 * * 1-5", at(2024, 1, 5, 12, 0)), Some(at(2024, 1, 8, 9, 0)));
+        // 0 and 7 are both Sunday.
+        assert_eq!(next("0 0 * * 0", at(2024, 1, 1, 0, 0)), Some(at(2024, 1, 7, 0, 0)));
+        assert_eq!(next("0 0 * * 7", at(2024, 1, 1, 0, 0)), Some(at(2024, 1, 7, 0, 0)));
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 13th or any Friday, whichever comes first.
+        assert_eq!(next("0 0 13 * 5", at(2024, 1, 1, 0, 0)), Some(at(2024, 1, 5, 0, 0)));
+        assert_eq!(next("0 0 13 * 5", at(2024, 1, 12, 1, 0)), Some(at(2024, 1, 13, 0, 0)));
+        // With one starting with `*`, both have to match, and `*/1` is every weekday.
+        assert_eq!(next("0 0 13 * */1", at(2024, 1, 1, 0, 0)), Some(at(2024, 1, 13, 0, 0)));
+        assert_eq!(next("0 0 * * 5", at(2024, 1, 6, 0, 0)), Some(at(2024, 1, 12, 0, 0)));
+    }
+
+    #[test]
+    fn month_and_year_rollover() {
+        assert_eq!(next("0 9 1 * *", at(2024, 1, 31, 10, 0)), Some(at(2024, 2, 1, 9, 0)));
+        // February 2024 has 29 days.
+        assert_eq!(next("0 9 30 * *", at(2024, 1, 31, 10, 0)), Some(at(2024, 3, 30, 9, 0)));
+        assert_eq!(next("30 0 1 1 *", at(2024, 12, 31, 23, 59)), Some(at(2025, 1, 1, 0, 30)));
+        assert_eq!(next("59 23 31 12 *", at(2024, 12, 31, 23, 59)), Some(at(2025, 12, 31, 23, 59)));
+    }
+
+    #[test]
+    fn impossible_date_never_matches() {
+        assert_eq!(next("0 0 30 2 *", at(2024, 1, 1, 0, 0)), None);
+    }
+
+    #[test]
+    fn invalid_expressions() {
+        for expression in [
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(expression.parse::<Schedule>().is_err(), "{}", expression);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use super::jsonrpc::{parse_params, serve_stdio, Framing, Handler, RpcError};
+use crate::analyzer::{
+    find_analyzer_by_language, find_analyzer_for_file, summarize_within_limit, CodeAnalysis,
+    FunctionRoute, LanguageAnalyzer,
+};
+use crate::cache::{modified_secs, Cache};
+use crate::config::Config;
+use crate::error::FolderSummaryError;
+use crate::llm::{validated, LLM};
+use crate::utils::decode_text;
+use async_trait::async_trait;
+use log::info;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Serves per-file and per-function summaries to editors over stdio.
+///
+/// Messages use LSP-style `Content-Length` framing so existing language-client libraries
+/// can talk to it. Saved files go through the shared analysis cache; unsaved buffers sent
+/// as `text` are analyzed fresh.
+pub async fn serve(
+    config: Config,
+    llm: Arc<dyn LLM>,
+    cache: Cache,
+) -> Result<(), FolderSummaryError> {
+    info!("Starting editor server on stdio");
+    let mut route = FunctionRoute::from_config(&config)?;
+    if let Some(route) = &mut route {
+        route.llm = validated(&config, route.llm.clone());
+    }
+    let server = EditorServer {
+        max_function_lines: config.get_max_function_lines(),
+        route,
+        no_llm: config.no_llm.unwrap_or(false),
+        llm: validated(&config, llm),
+        cache: Mutex::new(cache),
+    };
+    serve_stdio(&server, Framing::ContentLength).await
+}
+
+struct EditorServer {
+    max_function_lines: usize,
+    route: Option<FunctionRoute>,
+    no_llm: bool,
+    llm: Arc<dyn LLM>,
+    cache: Mutex<Cache>,
+}
+
+#[derive(Deserialize)]
+struct FileParams {
+    path: String,
+    /// Current buffer contents, when they differ from what is on disk.
+    text: Option<String>,
+    /// Overrides the language detected from the extension.
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FunctionParams {
+    #[serde(flatten)]
+    file: FileParams,
+    name: String,
+}
+
+#[async_trait]
+impl Handler for EditorServer {
+    async fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(json!({
+                "serverInfo": { "name": "folder-summary", "version": env!("CARGO_PKG_VERSION") },
+                "methods": ["summarizeFile", "summarizeFunction"],
+            })),
+            "initialized" | "shutdown" => Ok(Value::Null),
+            "exit" => std::process::exit(0),
+            "summarizeFile" => {
+                let params: FileParams = parse_params(params)?;
+                let analysis = self.summarize_file(&params).await?;
+                Ok(serde_json::to_value(analysis).map_err(FolderSummaryError::from)?)
+            }
+            "summarizeFunction" => {
+                let params: FunctionParams = parse_params(params)?;
+                let analysis = self.summarize_file(&params.file).await?;
+                let func = analysis
+                    .functions
+                    .into_iter()
+                    .find(|func| func.name == params.name)
+                    .ok_or_else(|| {
+                        RpcError::invalid_params(format!(
+                            "No function named {} in {}",
+                            params.name, params.file.path
+                        ))
+                    })?;
+                Ok(serde_json::to_value(func).map_err(FolderSummaryError::from)?)
+            }
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+}
+
+impl EditorServer {
+    async fn summarize_file(&self, params: &FileParams) -> Result<CodeAnalysis, RpcError> {
+        let analyzer = analyzer_for(params)?;
+        let modified = match &params.text {
+            Some(_) => None,
+            None => Some(modified_secs(&params.path).await?),
+        };
+        if let Some(modified) = modified.filter(|_| !self.no_llm) {
+            if let Some(cached) = self.cache.lock().await.get(&params.path, modified) {
+                return Ok(cached.clone());
+            }
+        }
+
+        let content = match &params.text {
+            Some(text) => text.clone(),
+            None => {
+                let bytes = tokio::fs::read(&params.path)
+                    .await
+                    .map_err(|e| FolderSummaryError::file(&params.path, e))?;
+                decode_text(&bytes)
+                    .map(|(content, _)| content)
+                    .ok_or_else(|| {
+                        RpcError::invalid_params(format!("{} looks like a binary file", params.path))
+                    })?
+            }
+        };
+
+        let analysis = analyzer.analyze(&content)?;
+        if self.no_llm {
+            return Ok(analysis);
+        }
+        let summarized = summarize_within_limit(
+            analyzer.as_ref(),
+            analysis,
+            self.llm.as_ref(),
+            self.max_function_lines,
+            self.route.as_ref(),
+        )
+        .await?;
+        if let Some(modified) = modified {
+            self.cache
+                .lock()
+                .await
+                .set(params.path.clone(), modified, summarized.clone(), true);
+        }
+        Ok(summarized)
+    }
+}
+
+fn analyzer_for(params: &FileParams) -> Result<Box<dyn LanguageAnalyzer>, RpcError> {
+    let analyzer = match &params.language {
+        Some(language) => find_analyzer_by_language(language),
+        None => find_analyzer_for_file(&params.path),
+    };
+    analyzer.ok_or_else(|| {
+        RpcError::invalid_params(format!("No suitable analyzer found for file: {}", params.path))
+    })
+}
@@ -0,0 +1,174 @@
+use crate::error::FolderSummaryError;
+use async_trait::async_trait;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{self, AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    /// Absent for notifications, which get no response.
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method))
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+impl From<FolderSummaryError> for RpcError {
+    fn from(err: FolderSummaryError) -> Self {
+        RpcError::new(INTERNAL_ERROR, err.to_string())
+    }
+}
+
+#[async_trait]
+pub trait Handler: Send + Sync {
+    async fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError>;
+}
+
+/// Deserializes `params` into the type a method expects.
+pub fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::invalid_params(e.to_string()))
+}
+
+/// How messages are delimited on the stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// One JSON message per line, as MCP uses.
+    Lines,
+    /// `Content-Length` headers before each message, as the Language Server Protocol uses.
+    ContentLength,
+}
+
+/// Serves JSON-RPC 2.0 over stdin/stdout until stdin closes.
+///
+/// Requests are handled one at a time, in order. Anything else the process prints must
+/// go to stderr, or it would corrupt the stream.
+pub async fn serve_stdio(handler: &dyn Handler, framing: Framing) -> Result<(), FolderSummaryError> {
+    let mut stdin = BufReader::new(io::stdin());
+    let mut stdout = io::stdout();
+
+    while let Some(message) = read_message(&mut stdin, framing).await? {
+        if message.trim().is_empty() {
+            continue;
+        }
+        let Some(response) = handle_message(handler, &message).await else {
+            continue;
+        };
+        let body = serde_json::to_vec(&response)?;
+        match framing {
+            Framing::Lines => {
+                stdout.write_all(&body).await?;
+                stdout.write_all(b"\n").await?;
+            }
+            Framing::ContentLength => {
+                let header = format!("Content-Length: {}\r\n\r\n", body.len());
+                stdout.write_all(header.as_bytes()).await?;
+                stdout.write_all(&body).await?;
+            }
+        }
+        stdout.flush().await?;
+    }
+    Ok(())
+}
+
+// Returns `None` once the input is exhausted.
+async fn read_message<R: AsyncBufRead + Unpin>(
+    input: &mut R,
+    framing: Framing,
+) -> Result<Option<String>, FolderSummaryError> {
+    let mut line = String::new();
+    if framing == Framing::Lines {
+        return Ok(match input.read_line(&mut line).await? {
+            0 => None,
+            _ => Some(line),
+        });
+    }
+
+    let mut content_length = None;
+    loop {
+        line.clear();
+        if input.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let Some(length) = content_length else {
+        return Err(FolderSummaryError::IoError(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "JSON-RPC message without a Content-Length header",
+        )));
+    };
+    let mut body = vec![0; length];
+    input.read_exact(&mut body).await?;
+    Ok(Some(String::from_utf8_lossy(&body).into_owned()))
+}
+
+async fn handle_message(handler: &dyn Handler, message: &str) -> Option<Value> {
+    let value: Value = match serde_json::from_str(message) {
+        Ok(value) => value,
+        Err(e) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string()))),
+    };
+    let request: Request = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(INVALID_REQUEST, e.to_string()),
+            ))
+        }
+    };
+
+    debug!("JSON-RPC request: {}", request.method);
+    let result = handler.handle(&request.method, request.params).await;
+    let Some(id) = request.id else {
+        if let Err(e) = result {
+            warn!("Notification {} failed: {}", request.method, e.message);
+        }
+        return None;
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => error_response(id, error),
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": error })
+}
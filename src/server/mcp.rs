@@ -0,0 +1,198 @@
+use super::jsonrpc::{parse_params, serve_stdio, Framing, Handler, RpcError};
+use crate::analyzer::{analyze_file, AnalysisOptions};
+use crate::config::Config;
+use crate::error::FolderSummaryError;
+use crate::llm::{validated, LLM};
+use crate::paths;
+use crate::render::write_summary;
+use crate::report::Report;
+use crate::summarizer::FolderSummarizer;
+use async_trait::async_trait;
+use log::info;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Serves the analyzer as a Model Context Protocol server over stdio.
+pub async fn serve(config: Config, llm: Arc<dyn LLM>) -> Result<(), FolderSummaryError> {
+    info!("Starting MCP server on stdio");
+    serve_stdio(&McpServer { config, llm }, Framing::Lines).await
+}
+
+struct McpServer {
+    config: Config,
+    llm: Arc<dyn LLM>,
+}
+
+#[derive(Deserialize)]
+struct ToolCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Deserialize)]
+struct FolderArgs {
+    path: String,
+    #[serde(default)]
+    no_llm: bool,
+}
+
+#[derive(Deserialize)]
+struct FileArgs {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct SearchArgs {
+    path: String,
+    query: String,
+}
+
+#[async_trait]
+impl Handler for McpServer {
+    async fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": "folder-summary",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "notifications/initialized" | "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => {
+                let call: ToolCall = parse_params(params)?;
+                // Tool failures are reported to the model as results, not protocol errors.
+                Ok(match self.call_tool(&call.name, call.arguments).await {
+                    Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+                    Err(e) => json!({
+                        "content": [{ "type": "text", "text": e.message }],
+                        "isError": true,
+                    }),
+                })
+            }
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+}
+
+impl McpServer {
+    async fn call_tool(&self, name: &str, arguments: Value) -> Result<String, RpcError> {
+        match name {
+            "analyze_folder" => {
+                let args: FolderArgs = parse_params(arguments)?;
+                let report = self.analyze_folder(&args.path, args.no_llm).await?;
+                let mut markdown = Vec::new();
+                write_summary(&mut markdown, &report)
+                    .map_err(|e| RpcError::from(FolderSummaryError::IoError(e)))?;
+                Ok(String::from_utf8_lossy(&markdown).into_owned())
+            }
+            "get_file_summary" => {
+                let args: FileArgs = parse_params(arguments)?;
+                let mut options = AnalysisOptions::from_config(&self.config)?;
+                if let Some(route) = &mut options.route {
+                    route.llm = validated(&self.config, route.llm.clone());
+                }
+                let llm = validated(&self.config, self.llm.clone());
+                match analyze_file(&args.path, llm.as_ref(), &options).await? {
+                    Some(analysis) => Ok(serde_json::to_string_pretty(&analysis)
+                        .map_err(FolderSummaryError::from)?),
+                    None => Err(RpcError::invalid_params(format!(
+                        "{} looks like a binary file",
+                        args.path
+                    ))),
+                }
+            }
+            "search_functions" => {
+                let args: SearchArgs = parse_params(arguments)?;
+                let report = self.analyze_folder(&args.path, true).await?;
+                Ok(search_functions(&report, &args.query))
+            }
+            _ => Err(RpcError::invalid_params(format!("Unknown tool: {}", name))),
+        }
+    }
+
+    async fn analyze_folder(&self, path: &str, no_llm: bool) -> Result<Report, RpcError> {
+        let mut config = self.config.clone();
+        if no_llm {
+            config.no_llm = Some(true);
+        }
+        let summarizer = FolderSummarizer::builder()
+            .directory(path)
+            .config(config)
+            .llm(self.llm.clone())
+            .cache_file(paths::cache_file())
+            .build()?;
+        Ok(summarizer.run().await?)
+    }
+}
+
+// Case-insensitive match on function names and signatures, one `file: signature` per line.
+fn search_functions(report: &Report, query: &str) -> String {
+    let query = query.to_lowercase();
+    let matches: Vec<String> = report
+        .files
+        .iter()
+        .filter(|(file, _)| !report.duplicates.contains_key(*file))
+        .flat_map(|(file, analysis)| {
+            analysis
+                .functions
+                .iter()
+                .filter(|func| {
+                    func.name.to_lowercase().contains(&query)
+                        || func.signature.to_lowercase().contains(&query)
+                })
+                .map(move |func| format!("{}: {}", file, func.signature))
+        })
+        .collect();
+    if matches.is_empty() {
+        format!("No functions match {:?}", query)
+    } else {
+        matches.join("\n")
+    }
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "analyze_folder",
+            "description": "Summarize a project folder: documentation, packages and a per-file breakdown of imports, functions, types and exports, with LLM summaries of functions.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Folder to analyze" },
+                    "no_llm": { "type": "boolean", "description": "Skip LLM summaries and return static analysis only" },
+                },
+                "required": ["path"],
+            },
+        },
+        {
+            "name": "get_file_summary",
+            "description": "Analyze a single source file and return its imports, functions (with summaries), types and exports as JSON.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Source file to analyze" },
+                },
+                "required": ["path"],
+            },
+        },
+        {
+            "name": "search_functions",
+            "description": "Find functions in a project folder whose name or signature contains the query.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Folder to search" },
+                    "query": { "type": "string", "description": "Case-insensitive text to look for" },
+                },
+                "required": ["path", "query"],
+            },
+        },
+    ])
+}
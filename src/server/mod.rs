@@ -0,0 +1,3 @@
+pub mod editor;
+pub mod jsonrpc;
+pub mod mcp;
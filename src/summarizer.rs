@@ -0,0 +1,512 @@
+use crate::analyzer::{
+    analyze_code_files, check_layers, collect_routes, diff_public_api, generics_heavy_items,
+    is_api_spec, is_ci_file, is_infrastructure_file, load_api_snapshot, marker_heatmap,
+    parse_layers, public_api, recent_changes, sample_files, summarize_api_specs,
+    summarize_ci_files, summarize_config_files, summarize_data_model, summarize_error_handling,
+    summarize_infrastructure, warm_cache, AnalysisOptions, ThreadSafeCache, WarmResults,
+};
+use crate::cache::Cache;
+use crate::collector::{FileCollector, WalkCollector};
+use crate::config::Config;
+use crate::docs::{project_overview, rank_docs, summarize_docs};
+use crate::error::FolderSummaryError;
+use crate::estimate::{estimate_run, RunEstimate};
+use crate::findings::{collect_findings, load_baseline, remove_baselined, Finding, FindingRules};
+use crate::llm::{
+    get_llm, validated, Budgeted, Logged, RateLimited, RequestLimits, TokenBudget, LLM,
+};
+use crate::progress::{NoProgress, Phase, ProgressReporter};
+use crate::report::{Report, RunMetadata};
+use crate::timings::Timings;
+use crate::utils::{
+    asset_inventory, get_project_description, get_project_name, git_info, is_test_file,
+    parse_package_files, text_hygiene, LanguageStats, ProjectFiles,
+};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// Summarizes one directory without any of the CLI's prompts or output.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), folder_summary::FolderSummaryError> {
+/// use folder_summary::{Config, FolderSummarizer};
+///
+/// let report = FolderSummarizer::builder()
+///     .directory("path/to/project")
+///     .config(Config::default())
+///     .build()?
+///     .run()
+///     .await?;
+/// println!("{} files analyzed", report.metrics.files);
+/// # Ok(())
+/// # }
+/// ```
+pub struct FolderSummarizer {
+    directory: PathBuf,
+    config: Config,
+    llm: Arc<dyn LLM>,
+    progress: Arc<dyn ProgressReporter>,
+    cache: ThreadSafeCache,
+    options: AnalysisOptions,
+    rules: FindingRules,
+    baseline: Option<Vec<Finding>>,
+}
+
+#[derive(Default)]
+pub struct FolderSummarizerBuilder {
+    directory: Option<PathBuf>,
+    config: Option<Config>,
+    llm: Option<Arc<dyn LLM>>,
+    progress: Option<Arc<dyn ProgressReporter>>,
+    cache_file: Option<PathBuf>,
+    cancel: Option<CancellationToken>,
+    collector: Option<Arc<dyn FileCollector>>,
+    cache: Option<ThreadSafeCache>,
+    timings: Option<Arc<Timings>>,
+    token_budget: Option<Arc<TokenBudget>>,
+    request_limits: Option<RequestLimits>,
+}
+
+impl FolderSummarizerBuilder {
+    /// The directory to summarize; defaults to the current directory.
+    pub fn directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// Defaults to [`Config::default`].
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// The provider to summarize with; defaults to the one selected by the config.
+    pub fn llm(mut self, llm: Arc<dyn LLM>) -> Self {
+        self.llm = Some(llm);
+        self
+    }
+
+    /// Where progress is reported; defaults to [`NoProgress`].
+    pub fn progress(mut self, progress: Arc<dyn ProgressReporter>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Persists the analysis cache to `cache_file`; without it the cache lives in memory.
+    pub fn cache_file(mut self, cache_file: impl Into<PathBuf>) -> Self {
+        self.cache_file = Some(cache_file.into());
+        self
+    }
+
+    /// Shares an already open cache, e.g. between summarizers of a batch; takes
+    /// precedence over [`cache_file`](Self::cache_file).
+    pub fn cache(mut self, cache: ThreadSafeCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Records durations into `timings`, so a batch can report them together.
+    pub fn timings(mut self, timings: Arc<Timings>) -> Self {
+        self.timings = Some(timings);
+        self
+    }
+
+    /// Draws LLM calls from `budget`, e.g. one shared by every summarizer of a batch;
+    /// defaults to a budget of the config's `max_total_tokens`, if set.
+    pub fn token_budget(mut self, budget: Arc<TokenBudget>) -> Self {
+        self.token_budget = Some(budget);
+        self
+    }
+
+    /// Spaces out requests to the main and `[route]` providers by `limits`, e.g. ones
+    /// shared by every summarizer of a batch; defaults to limits of the config's
+    /// `llm_requests_per_minute`, if set.
+    pub fn request_limits(mut self, limits: RequestLimits) -> Self {
+        self.request_limits = Some(limits);
+        self
+    }
+
+    /// Where files come from; defaults to walking [`directory`](Self::directory).
+    pub fn collector(mut self, collector: Arc<dyn FileCollector>) -> Self {
+        self.collector = Some(collector);
+        self
+    }
+
+    /// Lets the caller abort the run; see [`FolderSummarizer::cancellation_token`].
+    pub fn cancellation_token(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn build(self) -> Result<FolderSummarizer, FolderSummaryError> {
+        let config = self.config.unwrap_or_default();
+        if config.skip_tests == Some(true) && config.tests_only == Some(true) {
+            return Err(FolderSummaryError::ConfigError(
+                "skip_tests and tests_only can't both be set".to_string(),
+            ));
+        }
+        let llm = match self.llm {
+            Some(llm) => llm,
+            None => get_llm(&config)?,
+        };
+        let budget = self.token_budget.or_else(|| {
+            config
+                .max_total_tokens
+                .map(|limit| Arc::new(TokenBudget::new(limit)))
+        });
+        let cache = match (self.cache, &self.cache_file) {
+            (Some(cache), _) => cache,
+            (None, Some(cache_file)) => Arc::new(Mutex::new(Cache::new(cache_file)?)),
+            (None, None) => Arc::new(Mutex::new(Cache::in_memory())),
+        };
+        let directory = self.directory.unwrap_or_else(|| PathBuf::from("."));
+        let mut options = AnalysisOptions::from_config(&config)?;
+        let rules = FindingRules::from_config(&config)?;
+        config.get_on_existing_summary()?;
+        let baseline = match &config.findings_baseline {
+            Some(baseline) => Some(load_baseline(Path::new(baseline))?),
+            None => None,
+        };
+        if let Some(cancel) = self.cancel {
+            options.cancel = cancel;
+        }
+        if let Some(timings) = self.timings {
+            options.timings = timings;
+        }
+        if config.get_prompt_context() {
+            let mut project = format!("Project: {}", project_name(&directory));
+            if let Some(description) = get_project_description(&directory) {
+                project.push_str(&format!(" ({})", description));
+            }
+            options.project_context = Some(project);
+        }
+        options.source = self
+            .collector
+            .unwrap_or_else(|| Arc::new(WalkCollector::new(&directory)));
+        // Every handle to a provider goes through its limiter: function and callee
+        // summaries, documentation and configuration alike.
+        let limits =
+            self.request_limits.or_else(|| config.llm_requests_per_minute.map(RequestLimits::new));
+        let llm: Arc<dyn LLM> = match &limits {
+            Some(limits) => {
+                if let Some(route) = &mut options.route {
+                    route.llm =
+                        Arc::new(RateLimited::sharing(route.llm.clone(), limits.route.clone()));
+                }
+                Arc::new(RateLimited::sharing(llm, limits.main.clone()))
+            }
+            None => llm,
+        };
+        // Logged inside the budget, so calls it turns away aren't logged as calls.
+        let llm: Arc<dyn LLM> = Arc::new(Logged::new(llm, options.run_log.clone()));
+        if let Some(route) = &mut options.route {
+            route.llm = Arc::new(Logged::new(route.llm.clone(), options.run_log.clone()));
+        }
+        let llm: Arc<dyn LLM> = match &budget {
+            Some(budget) => {
+                if let Some(route) = &mut options.route {
+                    route.llm = Arc::new(Budgeted::new(route.llm.clone(), budget.clone()));
+                }
+                Arc::new(Budgeted::new(llm, budget.clone()))
+            }
+            None => llm,
+        };
+        // Validated outermost, so each retry is rate-limited, logged and budgeted like
+        // the first attempt.
+        let llm = validated(&config, llm);
+        if let Some(route) = &mut options.route {
+            route.llm = validated(&config, route.llm.clone());
+        }
+        Ok(FolderSummarizer {
+            directory,
+            options,
+            rules,
+            baseline,
+            config,
+            llm,
+            progress: self
+                .progress
+                .unwrap_or_else(|| Arc::new(NoProgress)),
+            cache,
+        })
+    }
+}
+
+impl FolderSummarizer {
+    pub fn builder() -> FolderSummarizerBuilder {
+        FolderSummarizerBuilder::default()
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn llm(&self) -> &dyn LLM {
+        self.llm.as_ref()
+    }
+
+    pub fn cache(&self) -> &ThreadSafeCache {
+        &self.cache
+    }
+
+    pub fn timings(&self) -> &Arc<Timings> {
+        &self.options.timings
+    }
+
+    /// Cancelling this token stops walking, analysis and in-flight LLM calls, and makes
+    /// the running method return [`FolderSummaryError::Cancelled`]. Files finished before
+    /// that stay cached.
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.options.cancel
+    }
+
+    /// Asks the collector for the files a run would look at, keeping or dropping test
+    /// files as `skip_tests` and `tests_only` ask, and sampling them when `sample` is set
+    /// and the project is large enough; see [`sample_files`].
+    pub fn collect(&self) -> Result<ProjectFiles, FolderSummaryError> {
+        let mut files = self.options.timings.time("File walking", || {
+            self.options.source.collect(&self.config, &self.options.cancel)
+        })?;
+        let keep_tests = match (self.config.skip_tests, self.config.tests_only) {
+            (Some(true), _) => Some(false),
+            (_, Some(true)) => Some(true),
+            _ => None,
+        };
+        if let Some(keep_tests) = keep_tests {
+            files.code.retain(|file| {
+                let path = Path::new(file);
+                is_test_file(path.strip_prefix(&self.directory).unwrap_or(path)) == keep_tests
+            });
+        }
+        let lines: usize = files.line_stats.values().map(|stats| stats.code).sum();
+        if self.config.sample == Some(true)
+            && lines > self.config.get_sample_threshold_lines()
+            && files.code.len() > self.config.get_sample_files()
+        {
+            let sizes: Vec<(String, u64)> = files
+                .code
+                .iter()
+                .map(|file| (file.clone(), files.code_sizes.get(file).copied().unwrap_or(0)))
+                .collect();
+            files.sampled_from = Some(files.code.len());
+            files.code = sample_files(&sizes, self.config.get_sample_files());
+            info!(
+                "{} lines of code; analyzing a sample of {} of its {} code files",
+                lines,
+                files.code.len(),
+                sizes.len()
+            );
+        }
+        self.progress.finish(
+            Phase::Collection,
+            &format!("Found {} code files", files.code.len()),
+        );
+        Ok(files)
+    }
+
+    /// Predicts the LLM work needed to summarize `files`.
+    pub async fn estimate(&self, files: &ProjectFiles) -> RunEstimate {
+        let llm = if self.options.no_llm {
+            None
+        } else {
+            Some(self.llm.as_ref())
+        };
+        estimate_run(
+            &files.code,
+            self.options.source.as_ref(),
+            &*self.cache.lock().await,
+            &self.options.languages,
+            llm,
+        )
+        .await
+    }
+
+    /// Analyzes (and unless `no_llm` is set, summarizes) the collected files.
+    ///
+    /// The cache is flushed at the end, including when the run fails or is cancelled.
+    pub async fn summarize(&self, files: ProjectFiles) -> Result<Report, FolderSummaryError> {
+        let report = self.build_report(files).await;
+        let flushed = self.cache.lock().await.flush().await;
+        let report = report?;
+        flushed?;
+        Ok(report)
+    }
+
+    async fn build_report(&self, files: ProjectFiles) -> Result<Report, FolderSummaryError> {
+        let run_start = Instant::now();
+        // Checked up front so a typo doesn't surface only after a long analysis.
+        let layers = parse_layers(self.config.layers.as_deref().unwrap_or_default())?;
+        let results = analyze_code_files(
+            &files.code,
+            self.llm.clone(),
+            &self.progress,
+            &self.cache,
+            &self.options_for(&files),
+        )
+        .await?;
+
+        // Configuration contents only reach the LLM when summarizing and not opted out.
+        let config_llm = (!self.options.no_llm
+            && !self.config.exclude_config_from_llm.unwrap_or(false))
+        .then(|| self.llm.as_ref());
+        let start = Instant::now();
+        let (api_specs, config_files): (Vec<String>, Vec<String>) = files
+            .config_files
+            .into_iter()
+            .partition(|file| is_api_spec(Path::new(file)));
+        let (ci_files, config_files): (Vec<String>, Vec<String>) = config_files
+            .into_iter()
+            .partition(|file| is_ci_file(Path::new(file)));
+        let (infrastructure_files, config_files): (Vec<String>, Vec<String>) = config_files
+            .into_iter()
+            .partition(|file| is_infrastructure_file(Path::new(file)));
+        let api_specs = summarize_api_specs(&api_specs, self.options.source.as_ref()).await;
+        let ci_pipelines = summarize_ci_files(&ci_files, self.options.source.as_ref()).await;
+        let infrastructure =
+            summarize_infrastructure(&infrastructure_files, self.options.source.as_ref()).await;
+        let data_model =
+            summarize_data_model(&files.schema_files, self.options.source.as_ref()).await;
+        let routes = collect_routes(&files.code, self.options.source.as_ref()).await;
+        let markers =
+            marker_heatmap(&files.code, self.options.source.as_ref(), &self.directory).await;
+        let error_handling =
+            summarize_error_handling(&files.code, self.options.source.as_ref()).await;
+        let public_api = public_api(&files.manifests, &files.code, self.options.source.as_ref()).await;
+        let generics = generics_heavy_items(&files.code, self.options.source.as_ref()).await;
+        let api_diff = match &self.config.api_baseline {
+            Some(baseline) => {
+                let previous = load_api_snapshot(Path::new(baseline))?;
+                Some(diff_public_api(baseline, &previous, &public_api))
+            }
+            None => None,
+        };
+        let configuration =
+            summarize_config_files(&config_files, self.options.source.as_ref(), config_llm).await;
+        self.options
+            .timings
+            .record("Configuration files", start.elapsed());
+
+        let mut languages: Vec<LanguageStats> = files.line_stats.into_values().collect();
+        languages.sort_by(|a, b| b.code.cmp(&a.code).then_with(|| a.language.cmp(&b.language)));
+        let assets = asset_inventory(&files.assets, &self.directory);
+        let hygiene = text_hygiene(&files.text_files);
+        let changelog = recent_changes(&files.docs, self.options.source.as_ref()).await;
+        let (docs, omitted_docs) =
+            rank_docs(files.docs, &self.directory, self.config.get_max_listed_docs());
+        let llm_model = (!self.options.no_llm).then(|| self.llm.model_name());
+        let mut report = Report {
+            languages,
+            assets,
+            hygiene,
+            configuration,
+            api_specs,
+            routes,
+            markers,
+            error_handling,
+            public_api,
+            api_diff,
+            generics,
+            ci_pipelines,
+            infrastructure,
+            data_model,
+            changelog,
+            omitted_docs,
+            sampled_from: files.sampled_from,
+            ..Report::new(
+                project_name(&self.directory),
+                RunMetadata::now(self.directory.display().to_string(), llm_model),
+                docs,
+                parse_package_files(&self.directory, &files.manifests),
+                results,
+            )
+        };
+        report.layer_violations = check_layers(&layers, &report.files, &self.directory);
+        if let Some(git) = git_info(&self.directory) {
+            report.metadata.git_commit = Some(git.commit);
+            report.metadata.git_branch = git.branch;
+        }
+        if !self.options.no_llm {
+            let start = Instant::now();
+            report.doc_summaries = summarize_docs(
+                &report.docs,
+                self.options.source.as_ref(),
+                self.llm.as_ref(),
+                &self.cache,
+                self.config.get_max_doc_summaries(),
+            )
+            .await;
+            match project_overview(&report, self.llm.as_ref()).await {
+                Ok(overview) => report.overview = Some(overview),
+                Err(e) => warn!("Could not write the project overview: {}", e),
+            }
+            self.options.timings.record("Documentation", start.elapsed());
+        }
+        report.relativize_paths(&self.directory, self.config.absolute_paths.unwrap_or(false));
+        report.findings = collect_findings(&report, &self.rules);
+        if let Some(baseline) = &self.baseline {
+            report.baselined_findings = remove_baselined(&mut report.findings, baseline);
+        }
+        report.metadata.duration_secs = run_start.elapsed().as_secs_f64();
+        let mut run_log = self.options.run_log.take();
+        run_log.metadata = report.metadata.clone();
+        run_log.errors = report.failures.clone();
+        let shown: HashMap<_, _> = report
+            .source_paths
+            .iter()
+            .map(|(shown, source)| (source.clone(), shown.clone()))
+            .collect();
+        run_log.rename_files(&shown);
+        report.run_log = Some(run_log);
+        Ok(report)
+    }
+
+    /// Statically analyzes the collected code files into the cache without calling the
+    /// LLM or building a report, so the next run only spends time summarizing.
+    pub async fn warm(&self, files: &ProjectFiles) -> Result<WarmResults, FolderSummaryError> {
+        let results =
+            warm_cache(&files.code, &self.progress, &self.cache, &self.options_for(files)).await;
+        let flushed = self.cache.lock().await.flush().await;
+        let results = results?;
+        flushed?;
+        Ok(results)
+    }
+
+    // The analysis options, told which directories of `files` hold a package and how
+    // large each file is.
+    fn options_for(&self, files: &ProjectFiles) -> AnalysisOptions {
+        let mut package_dirs: Vec<PathBuf> = files
+            .manifests
+            .iter()
+            .filter_map(|manifest| Path::new(manifest).parent().map(Path::to_path_buf))
+            .collect();
+        package_dirs.sort();
+        package_dirs.dedup();
+        AnalysisOptions {
+            package_dirs,
+            file_sizes: files.code_sizes.clone(),
+            ..self.options.clone()
+        }
+    }
+
+    /// Collects and summarizes in one go.
+    pub async fn run(&self) -> Result<Report, FolderSummaryError> {
+        let files = self.collect()?;
+        self.summarize(files).await
+    }
+}
+
+fn project_name(directory: &Path) -> String {
+    get_project_name(directory)
+        .or_else(|| {
+            directory
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(String::from)
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
@@ -1,97 +1,286 @@
-use crate::analyzer::CodeAnalysis;
-use crate::config::Config;
+use crate::config::{Config, FilenameVars};
+use crate::error::FolderSummaryError;
+use crate::progress::{Phase, ProgressReporter};
+use crate::render::Renderer;
+use crate::report::Report;
+use crate::utils::{clock, write_atomic, write_atomic_with};
 use log::info;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
-use crate::utils::file_utils::get_project_name;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+/// What happens when the summary about to be written already exists, as it does when a
+/// project is summarized twice on the same day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExistingSummary {
+    /// Replace it.
+    Overwrite,
+    /// Keep it and write `summary-x-2024-05-01-2.md`, `-3` and so on.
+    Version,
+    /// Keep it and add the time to the new name: `summary-x-2024-05-01-143005.md`.
+    Timestamp,
+    /// Keep it and fail the run.
+    Error,
+}
+
+impl ExistingSummary {
+    pub fn parse(strategy: &str) -> Result<Self, FolderSummaryError> {
+        match strategy {
+            "overwrite" => Ok(ExistingSummary::Overwrite),
+            "version" => Ok(ExistingSummary::Version),
+            "timestamp" => Ok(ExistingSummary::Timestamp),
+            "error" => Ok(ExistingSummary::Error),
+            other => Err(FolderSummaryError::ConfigError(format!(
+                "Invalid on_existing_summary \"{}\": expected \"overwrite\", \"version\", \"timestamp\" or \"error\"",
+                other
+            ))),
+        }
+    }
+}
+
+/// What [`generate_summary`] wrote.
+#[derive(Debug, Default)]
+pub struct SummaryOutput {
+    /// Every file written, main reports first.
+    pub files: Vec<PathBuf>,
+    /// The public API snapshot among `files`, if the project has a public API.
+    pub public_api: Option<PathBuf>,
+    /// The run log among `files`, if one was written.
+    pub run_log: Option<PathBuf>,
+    /// Per-file sections rendered from scratch, for [`Cache::set_rendered`](crate::cache::Cache::set_rendered).
+    pub sections: HashMap<String, String>,
+}
+
+/// Renders `report` with each of `renderers` into the configured output directory. Their
+/// files share one name, with `{format}` in it standing for the first renderer's format.
 pub fn generate_summary(
-    docs: Vec<String>,
-    package_info: HashMap<String, String>,
-    analysis: HashMap<String, CodeAnalysis>,
+    report: &Report,
     config: &Config,
-    analyzed_folder: &Path,
-) {
-    info!("Generating summary...");
-    let mut summary = String::new();
+    renderers: &[Arc<dyn Renderer>],
+    progress: &dyn ProgressReporter,
+) -> Result<SummaryOutput, FolderSummaryError> {
+    let formats: Vec<&str> = renderers.iter().map(|renderer| renderer.format()).collect();
+    info!("Generating {} summary...", formats.join(", "));
+    progress.start(Phase::Rendering, renderers.len() as u64);
 
-    summary.push_str("# Code Summary\n\n");
+    // Scoped npm packages and Composer packages are named `scope/name`, which can't go
+    // into a file name as it is.
+    let folder = report.project_name.replace(['/', '\\'], "-");
+    // Projects summarized side by side into one output directory each get their own.
+    let mut output_path = config.get_summary_output_path()?;
+    if config.get_project_subdirectory() {
+        output_path.push(&folder);
+    }
+    fs::create_dir_all(&output_path)
+        .map_err(|e| FolderSummaryError::file(output_path.display().to_string(), e))?;
 
-    summary.push_str("## Documentation Files\n");
-    for doc in docs {
-        summary.push_str(&format!("- {}\n", doc));
+    let mut artifacts = Vec::new();
+    for renderer in renderers {
+        artifacts.extend(renderer.render(report)?);
+        progress.advance(Phase::Rendering, 1);
     }
+    // Every file of the run shares one name, so a versioned report keeps its JSON and
+    // public API snapshot next to it under the same suffix.
+    let mut extensions: Vec<&str> = artifacts
+        .iter()
+        .filter(|artifact| !artifact.to_stdout && artifact.path.is_none())
+        .map(|artifact| artifact.extension.as_str())
+        .collect();
+    extensions.extend(["public-api.json", "run.json"]);
+    let filename = config.get_summary_filename(&FilenameVars {
+        folder: &folder,
+        git_sha: report.metadata.git_commit.as_deref(),
+        branch: report.metadata.git_branch.as_deref(),
+        model: report.metadata.llm_model.as_deref(),
+        format: formats.first().copied().unwrap_or("markdown"),
+    });
+    let summary_path = summary_path(
+        &output_path.join(filename),
+        &extensions,
+        config.get_on_existing_summary()?,
+    )?;
 
-    summary.push_str("\n## Package Information\n");
-    for (package, version) in package_info {
-        summary.push_str(&format!("- {}: {}\n", package, version));
+    let mut output = SummaryOutput::default();
+    let mut main_reports = 0;
+    for artifact in artifacts {
+        if artifact.to_stdout {
+            let mut stdout = io::stdout().lock();
+            artifact.contents.write_to(&mut stdout)?;
+            stdout.flush()?;
+            continue;
+        }
+        let path = match &artifact.path {
+            Some(path) => output_path.join(path),
+            None => summary_path.with_extension(&artifact.extension),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| FolderSummaryError::file(parent.display().to_string(), e))?;
+        }
+        let sections = write_atomic_with(&path, |out| artifact.contents.write_to(out))?;
+        info!("Summary saved as {}", path.display());
+        output.sections.extend(sections);
+        if artifact.path.is_none() {
+            output.files.insert(main_reports, path);
+            main_reports += 1;
+        } else {
+            output.files.push(path);
+        }
     }
 
-    summary.push_str("\n## Code Analysis\n");
-    for (file_path, code_analysis) in analysis {
-        summary.push_str(&format!("## {}\n\n", file_path));
+    // The public API snapshot is the baseline a later `--api-baseline` run compares to.
+    let public_api: Vec<_> = report.parts().into_iter().flat_map(|part| &part.public_api).collect();
+    if !public_api.is_empty() {
+        let path = summary_path.with_extension("public-api.json");
+        write_atomic(&path, &serde_json::to_vec_pretty(&public_api)?)?;
+        info!("Public API snapshot saved as {}", path.display());
+        output.files.push(path.clone());
+        output.public_api = Some(path);
+    }
 
-        if !code_analysis.imports.is_empty() {
-            summary.push_str("**Imports:**\n");
-            for import in &code_analysis.imports {
-                summary.push_str(&format!("- {}\n", import));
-            }
-            summary.push('\n');
-        }
+    let run_logs: Vec<_> =
+        report.parts().into_iter().filter_map(|part| part.run_log.as_ref()).collect();
+    if config.get_write_run_log() && !run_logs.is_empty() {
+        let path = summary_path.with_extension("run.json");
+        // A combined report logs each of its repositories' runs.
+        let contents = match run_logs.as_slice() {
+            [run_log] => serde_json::to_vec_pretty(run_log)?,
+            run_logs => serde_json::to_vec_pretty(run_logs)?,
+        };
+        write_atomic(&path, &contents)?;
+        info!("Run log saved as {}", path.display());
+        output.files.push(path.clone());
+        output.run_log = Some(path);
+    }
 
-        if !code_analysis.functions.is_empty() {
-            summary.push_str("**Functions:**\n");
-            for func in &code_analysis.functions {
-                summary.push_str(&format!("- {}\n", func.name));
-                summary.push_str(&format!("  Signature: {}\n", func.signature));
-                summary.push_str(&format!("  Lines of code: {}\n", func.lines_of_code));
-                summary.push_str(&format!(
-                    "  Cyclomatic complexity: {}\n",
-                    func.cyclomatic_complexity
-                ));
-                summary.push_str(&format!("  Parameters: {}\n", func.parameters));
-                summary.push_str(&format!("  Returns: {}\n", func.returns));
-                if let Some(sum) = &func.summary {
-                    summary.push_str(&format!("  Summary: {}\n", sum));
-                }
-                summary.push('\n');
-            }
-        }
+    progress.finish(Phase::Rendering, "done");
+    Ok(output)
+}
 
-        if !code_analysis.types.is_empty() {
-            summary.push_str("**Types:**\n");
-            for type_def in &code_analysis.types {
-                summary.push_str(&format!("```rust\n{}\n```\n\n", type_def));
+// The name the run's files are written under, before their extension is swapped in:
+// `path` itself unless one of them already exists and `strategy` says to keep it.
+fn summary_path(
+    path: &Path,
+    extensions: &[&str],
+    strategy: ExistingSummary,
+) -> Result<PathBuf, FolderSummaryError> {
+    let taken = |path: &Path| {
+        extensions
+            .iter()
+            .map(|extension| path.with_extension(extension))
+            .find(|path| path.exists())
+    };
+    let Some(existing) = taken(path) else {
+        return Ok(path.to_path_buf());
+    };
+    let with_suffix = |suffix: &str| {
+        let mut name = path.file_stem().unwrap_or_default().to_os_string();
+        name.push(format!("-{}", suffix));
+        if let Some(extension) = path.extension() {
+            name.push(".");
+            name.push(extension);
+        }
+        path.with_file_name(name)
+    };
+    match strategy {
+        ExistingSummary::Overwrite => Ok(path.to_path_buf()),
+        ExistingSummary::Version => {
+            let mut version = 2;
+            while taken(&with_suffix(&version.to_string())).is_some() {
+                version += 1;
             }
+            Ok(with_suffix(&version.to_string()))
+        }
+        ExistingSummary::Timestamp => {
+            Ok(with_suffix(&clock::now().format("%H%M%S").to_string()))
         }
+        ExistingSummary::Error => Err(FolderSummaryError::file(
+            existing.display().to_string(),
+            io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "a summary with this name already exists; set on_existing_summary to keep both or overwrite it",
+            ),
+        )),
+    }
+}
 
-        if !code_analysis.exports.is_empty() {
-            summary.push_str("**Exports:**\n");
-            for export in &code_analysis.exports {
-                summary.push_str(&format!("- {}\n", export));
-            }
-            summary.push('\n');
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A scratch output directory holding the given files.
+    fn output(name: &str, existing: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("folder-summary-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for file in existing {
+            fs::write(dir.join(file), "").unwrap();
         }
+        dir
+    }
+
+    const EXTENSIONS: &[&str] = &["md", "json", "public-api.json", "run.json"];
 
-        summary.push_str("\n\n");
+    fn resolve(dir: &Path, strategy: ExistingSummary) -> Result<PathBuf, FolderSummaryError> {
+        summary_path(&dir.join("summary-x.md"), EXTENSIONS, strategy)
     }
 
-    let project_name = get_project_name(analyzed_folder)
-        .or_else(|| {
-            analyzed_folder
-                .file_name()
-                .and_then(|name| name.to_str())
-                .map(String::from)
-        })
-        .unwrap_or_else(|| "unknown".to_string());
+    #[test]
+    fn strategies_parse() {
+        assert_eq!(ExistingSummary::parse("overwrite").unwrap(), ExistingSummary::Overwrite);
+        assert_eq!(ExistingSummary::parse("version").unwrap(), ExistingSummary::Version);
+        assert_eq!(ExistingSummary::parse("timestamp").unwrap(), ExistingSummary::Timestamp);
+        assert_eq!(ExistingSummary::parse("error").unwrap(), ExistingSummary::Error);
+        assert!(ExistingSummary::parse("append").is_err());
+    }
 
-    let output_path = config.get_summary_output_path();
-    fs::create_dir_all(&output_path).expect("Unable to create summary output directory");
+    #[test]
+    fn free_name_is_kept_by_every_strategy() {
+        let dir = output("free-name", &[]);
+        for strategy in [
+            ExistingSummary::Overwrite,
+            ExistingSummary::Version,
+            ExistingSummary::Timestamp,
+            ExistingSummary::Error,
+        ] {
+            assert_eq!(resolve(&dir, strategy).unwrap(), dir.join("summary-x.md"));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
 
-    let filename = config.get_summary_filename(&project_name);
-    let summary_path = output_path.join(filename);
+    #[test]
+    fn overwrite_reuses_the_name() {
+        let dir = output("overwrite", &["summary-x.md"]);
+        assert_eq!(resolve(&dir, ExistingSummary::Overwrite).unwrap(), dir.join("summary-x.md"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
 
-    fs::write(&summary_path, summary).expect("Unable to write summary");
-    println!("Summary generated and saved as {}", summary_path.display());
+    #[test]
+    fn version_takes_the_first_free_suffix() {
+        // Any of the run's files takes the name, not only the main report.
+        let dir = output("version", &["summary-x.json", "summary-x-2.md"]);
+        assert_eq!(resolve(&dir, ExistingSummary::Version).unwrap(), dir.join("summary-x-3.md"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn timestamp_adds_the_time() {
+        let dir = output("timestamp", &["summary-x.md"]);
+        let path = resolve(&dir, ExistingSummary::Timestamp).unwrap();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let suffix = name.strip_prefix("summary-x-").and_then(|rest| rest.strip_suffix(".md"));
+        let is_time = |time: &str| time.len() == 6 && time.chars().all(|c| c.is_ascii_digit());
+        assert!(suffix.is_some_and(is_time), "{}", name);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn error_names_the_existing_file() {
+        let dir = output("error", &["summary-x.run.json"]);
+        let error = resolve(&dir, ExistingSummary::Error).unwrap_err();
+        assert!(error.to_string().contains("summary-x.run.json"), "{}", error);
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
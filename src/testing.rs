@@ -0,0 +1,97 @@
+//! Checks for [`LanguageAnalyzer`] implementations, for this crate's snapshot tests and
+//! for authors of analyzers outside it.
+//!
+//! ```
+//! use folder_summary::analyzer::RustAnalyzer;
+//! use folder_summary::testing::assert_valid_analysis;
+//!
+//! let analysis = assert_valid_analysis(&RustAnalyzer, "fn add(a: u32, b: u32) -> u32 { a + b }");
+//! assert_eq!(analysis.functions[0].parameters, 2);
+//! ```
+
+use crate::analyzer::{CodeAnalysis, LanguageAnalyzer};
+
+/// What is wrong with the shape of `analysis`, as analyzed from `content`: functions
+/// without a name or signature, lines outside the file, zero line counts or a
+/// complexity below 1, blocking calls outside async functions and summaries the LLM
+/// hasn't written yet. Empty when it looks right.
+pub fn analysis_problems(analysis: &CodeAnalysis, content: &str) -> Vec<String> {
+    let line_count = content.lines().count();
+    let mut problems = Vec::new();
+    for func in &analysis.functions {
+        let name = &func.name;
+        if name.trim().is_empty() {
+            problems.push(format!(
+                "a function has no name (signature {:?})",
+                func.signature
+            ));
+        }
+        if func.signature.trim().is_empty() {
+            problems.push(format!("{} has no signature", name));
+        }
+        match func.line {
+            Some(line) if line == 0 || line > line_count => problems.push(format!(
+                "{} is on line {}, outside the file's {} lines",
+                name, line, line_count
+            )),
+            _ => {}
+        }
+        if func.lines_of_code == 0 {
+            problems.push(format!("{} has no lines of code", name));
+        }
+        if func.lines_of_code > line_count {
+            problems.push(format!(
+                "{} has {} lines of code, more than the file's {}",
+                name, func.lines_of_code, line_count
+            ));
+        }
+        if func.cyclomatic_complexity == 0 {
+            problems.push(format!("{} has a cyclomatic complexity of 0", name));
+        }
+        if !func.is_async && !func.blocking_calls.is_empty() {
+            problems.push(format!("{} lists blocking calls but is not async", name));
+        }
+        if func.summary.is_some() {
+            problems.push(format!("{} has a summary before any LLM call", name));
+        }
+    }
+    for name in analysis
+        .imports
+        .iter()
+        .chain(&analysis.types)
+        .chain(&analysis.exports)
+    {
+        if name.trim().is_empty() {
+            problems.push("an import, type or export is empty".to_string());
+        }
+    }
+    for suppression in &analysis.suppressions {
+        if suppression
+            .line
+            .is_some_and(|line| line == 0 || line > line_count)
+        {
+            problems.push(format!(
+                "a suppression is on line {:?}, outside the file",
+                suppression.line
+            ));
+        }
+    }
+    problems
+}
+
+/// Analyzes `content` with `analyzer` and panics, listing the problems, if the analysis
+/// fails or has the wrong shape; see [`analysis_problems`].
+pub fn assert_valid_analysis(analyzer: &dyn LanguageAnalyzer, content: &str) -> CodeAnalysis {
+    let analysis = match analyzer.analyze(content) {
+        Ok(analysis) => analysis,
+        Err(e) => panic!("{} failed to analyze the input: {}", analyzer.name(), e),
+    };
+    let problems = analysis_problems(&analysis, content);
+    assert!(
+        problems.is_empty(),
+        "{} produced a malformed analysis:\n  {}",
+        analyzer.name(),
+        problems.join("\n  ")
+    );
+    analysis
+}
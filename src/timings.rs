@@ -0,0 +1,47 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Accumulates wall-clock time per named phase, in the order phases are first seen.
+///
+/// Per-file entries (static analysis per language, LLM calls, cache IO) add up across
+/// concurrent workers, so they can exceed the wall-clock time of the phase they run in.
+#[derive(Debug, Default)]
+pub struct Timings {
+    entries: Mutex<Vec<(String, Duration)>>,
+}
+
+impl Timings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, phase: &str, elapsed: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.iter_mut().find(|(name, _)| name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => entries.push((phase.to_string(), elapsed)),
+        }
+    }
+
+    pub fn time<T>(&self, phase: &str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(phase, start.elapsed());
+        result
+    }
+
+    pub fn entries(&self) -> Vec<(String, Duration)> {
+        self.entries.lock().unwrap().clone()
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Timings:")?;
+        for (phase, elapsed) in self.entries() {
+            writeln!(f, "  {:<40} {:>10.3}s", phase, elapsed.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
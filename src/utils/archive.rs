@@ -0,0 +1,141 @@
+use super::image::{is_image_layout, save_image, unpack_image};
+use crate::error::FolderSummaryError;
+use log::{debug, warn};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const ARCHIVE_SUFFIXES: &[&str] = &[".zip", ".tar.gz", ".tgz", ".tar"];
+
+/// Whether `path` is an archive that can be analyzed in place of a directory: a `.zip`,
+/// `.tar.gz`, `.tgz` or `.tar` file.
+pub fn is_archive(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    path.is_file() && ARCHIVE_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// An archive or container image unpacked into a directory of its own under the system
+/// temp directory, so nothing is written next to the archive or into a working tree. The
+/// directory is removed when this is dropped.
+#[derive(Debug)]
+pub struct ExtractedArchive {
+    dir: PathBuf,
+    root: PathBuf,
+}
+
+impl ExtractedArchive {
+    /// Unpacks `archive` with the system's `unzip` or `tar`. An image tarball, as
+    /// `docker save` writes, is unpacked to the image's filesystem.
+    pub fn extract(archive: &Path) -> Result<Self, FolderSummaryError> {
+        let mut extracted = Self::create()?;
+        debug!("Extracting {} to {}", archive.display(), extracted.dir.display());
+        unpack(archive, &extracted.dir)?;
+        if is_image_layout(&extracted.dir) {
+            extracted.root = unpack_image(&extracted.dir, &extracted.dir.join("rootfs"))?;
+        } else if let Some(root) = single_directory(&extracted.dir) {
+            extracted.root = root;
+        }
+        Ok(extracted)
+    }
+
+    /// Unpacks the filesystem of the image in `layout`, a directory as `docker save` or an
+    /// OCI image layout leaves it.
+    pub fn from_image_layout(layout: &Path) -> Result<Self, FolderSummaryError> {
+        let mut extracted = Self::create()?;
+        extracted.root = unpack_image(layout, &extracted.dir.join("rootfs"))?;
+        Ok(extracted)
+    }
+
+    /// Saves `reference` from the local Docker or Podman image store and unpacks its
+    /// filesystem.
+    pub fn from_image(reference: &str) -> Result<Self, FolderSummaryError> {
+        let mut extracted = Self::create()?;
+        let tarball = extracted.dir.join("image.tar");
+        let layout = extracted.dir.join("image");
+        save_image(reference, &tarball)?;
+        fs::create_dir_all(&layout)
+            .map_err(|e| FolderSummaryError::file(layout.display().to_string(), e))?;
+        unpack(&tarball, &layout)?;
+        // Images are large; the tarball isn't needed once it is unpacked.
+        let _ = fs::remove_file(&tarball);
+        extracted.root = unpack_image(&layout, &extracted.dir.join("rootfs"))?;
+        Ok(extracted)
+    }
+
+    // Owned before anything is unpacked, so a failed extraction is cleaned up as well. The
+    // name is random and the directory new and private to this user, so nothing already
+    // at that path on a shared temp directory is written into or removed.
+    fn create() -> Result<Self, FolderSummaryError> {
+        let dir = tempfile::Builder::new()
+            .prefix("folder-summary-")
+            .tempdir()
+            .map_err(|e| FolderSummaryError::file(std::env::temp_dir().display().to_string(), e))?
+            .keep();
+        Ok(ExtractedArchive {
+            root: dir.clone(),
+            dir,
+        })
+    }
+
+    /// The directory to analyze: the archive's only top-level directory, as release
+    /// archives usually have, an image's working directory or whole filesystem, or else
+    /// the directory it was unpacked into.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl Drop for ExtractedArchive {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.dir) {
+            warn!("Could not remove {}: {}", self.dir.display(), e);
+        }
+    }
+}
+
+fn unpack(archive: &Path, dir: &Path) -> Result<(), FolderSummaryError> {
+    let is_zip = archive.to_string_lossy().to_lowercase().ends_with(".zip");
+    // tar detects gzip compression by itself.
+    let tar = || Command::new("tar").arg("-xf").arg(archive).arg("-C").arg(dir).output();
+    let output = if is_zip {
+        match Command::new("unzip").arg("-q").arg(archive).arg("-d").arg(dir).output() {
+            // bsdtar, the `tar` Windows ships, reads zip files too.
+            Err(e) if e.kind() == io::ErrorKind::NotFound => tar(),
+            output => output,
+        }
+    } else {
+        tar()
+    };
+    let failed =
+        |message: String| FolderSummaryError::file(archive.display().to_string(), io::Error::other(message));
+    match output {
+        Ok(Output { status, .. }) if status.success() => Ok(()),
+        Ok(Output { stderr, .. }) => Err(failed(format!(
+            "could not extract the archive: {}",
+            String::from_utf8_lossy(&stderr).trim()
+        ))),
+        Err(e) => Err(failed(format!(
+            "could not extract the archive ({} needs `unzip` or `tar`): {}",
+            if is_zip { "a zip file" } else { "a tarball" },
+            e
+        ))),
+    }
+}
+
+// The only entry of `dir` when it is a directory, leaving out the `__MACOSX` metadata
+// that zip files made on macOS carry next to it.
+fn single_directory(dir: &Path) -> Option<PathBuf> {
+    let entries: Vec<_> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name() != "__MACOSX")
+        .collect();
+    match entries.as_slice() {
+        [entry] if entry.path().is_dir() => Some(entry.path()),
+        _ => None,
+    }
+}
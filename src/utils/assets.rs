@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Directories listed in the inventory, largest first.
+const MAX_DIRECTORIES: usize = 20;
+
+const ASSET_KINDS: &[(&str, &[&str])] = &[
+    ("image", &["png", "jpg", "jpeg", "gif", "bmp", "svg", "webp", "ico", "tif", "tiff", "psd", "avif"]),
+    ("font", &["ttf", "otf", "woff", "woff2", "eot"]),
+    ("dataset", &["csv", "tsv", "parquet", "jsonl", "ndjson", "xls", "xlsx", "h5", "hdf5", "npy", "npz", "pkl", "pickle", "sqlite", "sqlite3", "db", "arrow", "feather", "avro", "orc"]),
+    ("binary", &["exe", "dll", "so", "dylib", "a", "lib", "o", "obj", "bin", "wasm", "class", "jar", "pyc", "rlib"]),
+    ("media", &["mp3", "wav", "ogg", "flac", "mp4", "mov", "webm", "avi", "mkv"]),
+    ("archive", &["zip", "tar", "gz", "tgz", "bz2", "xz", "7z", "rar"]),
+    ("document", &["pdf", "doc", "docx", "ppt", "pptx", "odt"]),
+];
+
+/// Asset files and bytes of one kind.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssetTotal {
+    /// `image`, `font`, `dataset`, `binary`, `media`, `archive` or `document`.
+    pub kind: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Asset files directly inside one directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DirectoryAssets {
+    /// Relative to the analyzed directory; `.` for the directory itself.
+    pub directory: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// File counts by kind.
+    pub kinds: BTreeMap<String, usize>,
+}
+
+/// The non-code files of a repository: images, fonts, datasets, compiled binaries and
+/// the like.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssetInventory {
+    pub kinds: Vec<AssetTotal>,
+    /// The directories holding the most bytes of assets.
+    pub directories: Vec<DirectoryAssets>,
+    pub omitted_directories: usize,
+}
+
+/// What kind of asset `path` is, by extension; extensionless files are binaries if
+/// `head` starts like an ELF, Mach-O or PE executable.
+pub fn asset_kind(path: &Path, head: Option<&[u8]>) -> Option<&'static str> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(extension) => {
+            let extension = extension.to_ascii_lowercase();
+            ASSET_KINDS
+                .iter()
+                .find(|(_, extensions)| extensions.contains(&extension.as_str()))
+                .map(|(kind, _)| *kind)
+        }
+        None => {
+            let head = head?;
+            let executable = head.starts_with(b"\x7fELF")
+                || head.starts_with(b"MZ")
+                || head.starts_with(&[0xCF, 0xFA, 0xED, 0xFE])
+                || head.starts_with(&[0xCE, 0xFA, 0xED, 0xFE])
+                || head.starts_with(&[0xCA, 0xFE, 0xBA, 0xBE]);
+            executable.then_some("binary")
+        }
+    }
+}
+
+/// Groups asset files (path, kind and size, as collected by the walk) by kind and by
+/// directory below `root`. `None` when there are none.
+pub fn asset_inventory(assets: &[(String, String, u64)], root: &Path) -> Option<AssetInventory> {
+    if assets.is_empty() {
+        return None;
+    }
+    let mut kinds: BTreeMap<&str, AssetTotal> = BTreeMap::new();
+    let mut directories: BTreeMap<String, DirectoryAssets> = BTreeMap::new();
+    for (file, kind, bytes) in assets {
+        let total = kinds.entry(kind).or_insert_with(|| AssetTotal {
+            kind: kind.clone(),
+            ..AssetTotal::default()
+        });
+        total.files += 1;
+        total.bytes += bytes;
+
+        let path = Path::new(file);
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let directory = match relative.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.display().to_string(),
+            _ => ".".to_string(),
+        };
+        let entry = directories
+            .entry(directory.clone())
+            .or_insert_with(|| DirectoryAssets {
+                directory,
+                ..DirectoryAssets::default()
+            });
+        entry.files += 1;
+        entry.bytes += bytes;
+        *entry.kinds.entry(kind.clone()).or_default() += 1;
+    }
+
+    let mut kinds: Vec<AssetTotal> = kinds.into_values().collect();
+    kinds.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.kind.cmp(&b.kind)));
+    let mut directories: Vec<DirectoryAssets> = directories.into_values().collect();
+    directories.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.directory.cmp(&b.directory)));
+    let omitted_directories = directories.len().saturating_sub(MAX_DIRECTORIES);
+    directories.truncate(MAX_DIRECTORIES);
+    Some(AssetInventory {
+        kinds,
+        directories,
+        omitted_directories,
+    })
+}
+
+/// `1536` as `1.5 KiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
@@ -0,0 +1,13 @@
+use chrono::{DateTime, Local, TimeZone};
+use std::env;
+
+/// The current time, or the one in `SOURCE_DATE_EPOCH` (seconds since the Unix epoch)
+/// when it is set, so reproducible builds and end-to-end tests get the same dates in
+/// report names and metadata on every run.
+pub fn now() -> DateTime<Local> {
+    env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.trim().parse::<i64>().ok())
+        .and_then(|epoch| Local.timestamp_opt(epoch, 0).single())
+        .unwrap_or_else(Local::now)
+}
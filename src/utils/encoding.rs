@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Only the head of a file is sniffed; binaries almost always show a NUL byte early on.
+const SNIFF_LEN: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextEncoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+impl fmt::Display for TextEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextEncoding::Utf8 => write!(f, "UTF-8"),
+            TextEncoding::Utf8Bom => write!(f, "UTF-8 (BOM)"),
+            TextEncoding::Utf16Le => write!(f, "UTF-16LE"),
+            TextEncoding::Utf16Be => write!(f, "UTF-16BE"),
+            TextEncoding::Latin1 => write!(f, "Latin-1"),
+        }
+    }
+}
+
+/// Decodes file contents into text, or returns `None` for binary content.
+///
+/// UTF-8 (with or without BOM) and UTF-16 (BOM or NUL-interleaved ASCII) are decoded
+/// exactly; anything else that isn't binary falls back to Latin-1, which never fails.
+pub fn decode_text(bytes: &[u8]) -> Option<(String, TextEncoding)> {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return Some((String::from_utf8_lossy(rest).into_owned(), TextEncoding::Utf8Bom));
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return Some((decode_utf16(rest, u16::from_le_bytes), TextEncoding::Utf16Le));
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return Some((decode_utf16(rest, u16::from_be_bytes), TextEncoding::Utf16Be));
+    }
+
+    let head = &bytes[..bytes.len().min(SNIFF_LEN)];
+    if head.contains(&0) {
+        return match utf16_without_bom(head) {
+            Some(TextEncoding::Utf16Le) => {
+                Some((decode_utf16(bytes, u16::from_le_bytes), TextEncoding::Utf16Le))
+            }
+            Some(TextEncoding::Utf16Be) => {
+                Some((decode_utf16(bytes, u16::from_be_bytes), TextEncoding::Utf16Be))
+            }
+            _ => None,
+        };
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Some((text.to_string(), TextEncoding::Utf8)),
+        Err(_) => Some((bytes.iter().map(|&b| b as char).collect(), TextEncoding::Latin1)),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| from_bytes([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+// ASCII-heavy UTF-16 text puts a NUL in every other byte; which half tells the byte order.
+fn utf16_without_bom(head: &[u8]) -> Option<TextEncoding> {
+    let pairs = head.len() / 2;
+    if pairs == 0 {
+        return None;
+    }
+    let even_nuls = head.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd_nuls = head.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+    if odd_nuls * 10 >= pairs * 9 && even_nuls == 0 {
+        Some(TextEncoding::Utf16Le)
+    } else if even_nuls * 10 >= pairs * 9 && odd_nuls == 0 {
+        Some(TextEncoding::Utf16Be)
+    } else {
+        None
+    }
+}
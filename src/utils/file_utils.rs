@@ -1,177 +1,531 @@
+use crate::analyzer::{get_analyzers, is_dockerfile, schema_kind};
 use crate::config::Config;
+use crate::error::FolderSummaryError;
+use crate::utils::{
+    add_line_stats, asset_kind, count_lines, decode_text, language_name, line_ending, LanguageStats,
+    LineEnding, TextEncoding,
+};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::{WalkBuilder, WalkState};
-use log::{debug, info};
-use std::collections::{HashMap, HashSet};
+use log::{debug, info, warn};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs;
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
 use toml;
-use walkdir::WalkDir;
-use globset::{Glob, GlobSetBuilder};
+use sha2::{Digest, Sha256};
 
 use serde_json;
 
-pub fn collect_documentation_files(dir: &Path) -> Vec<String> {
-    WalkDir::new(dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| {
-            e.file_type().is_file()
-                && e.file_name().to_str().map_or(false, |s| {
-                    s.ends_with(".md") || s.ends_with(".txt") || s.ends_with(".rst")
+/// Ignore file read from the root of the analyzed directory, with .gitignore syntax.
+const IGNORE_FILE: &str = ".folder-summaryignore";
+
+/// The name and version of the package each manifest declares, grouped by directory: `dir`
+/// itself, keyed `.`, and each directory holding one of `manifests`, keyed by its path
+/// relative to `dir`. See [`manifest_packages`]. Packages without a version are listed as
+/// `unversioned`, and directories without a package are left out.
+pub fn parse_package_files(
+    dir: &Path,
+    manifests: &[String],
+) -> BTreeMap<String, BTreeMap<String, String>> {
+    let code_dirs: BTreeSet<&Path> = manifests
+        .iter()
+        .filter_map(|manifest| Path::new(manifest).parent())
+        .filter(|code_dir| *code_dir != dir)
+        .collect();
+    std::iter::once(dir)
+        .chain(code_dirs)
+        .filter_map(|code_dir| {
+            let key = match code_dir.strip_prefix(dir) {
+                Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+                Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+                Err(_) => code_dir.display().to_string(),
+            };
+            let packages: BTreeMap<String, String> = manifest_packages(code_dir)
+                .into_iter()
+                .map(|package| {
+                    let version = package.version.unwrap_or_else(|| "unversioned".to_string());
+                    (package.name, version)
                 })
+                .collect();
+            (!packages.is_empty()).then_some((key, packages))
         })
-        .map(|e| e.path().to_string_lossy().into_owned())
         .collect()
 }
 
-pub fn parse_package_files(dir: &Path) -> HashMap<String, String> {
-    let mut package_info = HashMap::new();
-
-    // Parse package.json for npm projects
-    if let Ok(contents) = fs::read_to_string(dir.join("package.json")) {
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&contents) {
-            if let Some(name) = json["name"].as_str() {
-                if let Some(version) = json["version"].as_str() {
-                    package_info.insert(name.to_string(), version.to_string());
-                }
-            }
-        }
-    }
-
-    // Parse Cargo.toml for Rust projects
-    if let Ok(contents) = fs::read_to_string(dir.join("Cargo.toml")) {
-        if let Ok(toml) = toml::from_str::<toml::Value>(&contents) {
-            if let Some(package) = toml.get("package") {
-                if let (Some(name), Some(version)) =
-                    (package["name"].as_str(), package["version"].as_str())
-                {
-                    package_info.insert(name.to_string(), version.to_string());
-                }
-            }
-        }
-    }
-
-    package_info
+/// Everything the summary needs from the directory tree, gathered in a single walk.
+#[derive(Debug, Default, Clone)]
+pub struct ProjectFiles {
+    /// Markdown, text and reStructuredText files.
+    pub docs: Vec<String>,
+    /// Files named in `code_identifiers`; their directories are the code directories.
+    pub manifests: Vec<String>,
+    /// Source files inside a code directory that some analyzer can handle.
+    pub code: Vec<String>,
+    /// The size in bytes of each code file the collector could size, as it found it; may
+    /// also hold files later dropped from `code`.
+    pub code_sizes: BTreeMap<String, u64>,
+    /// TOML, YAML and JSON settings, `.env` templates and CI workflows.
+    pub config_files: Vec<String>,
+    /// Schema definitions and database migrations; see [`schema_kind`].
+    pub schema_files: Vec<String>,
+    /// Files and code, comment and blank lines per language, over every file the walk
+    /// saw rather than only the analyzed ones.
+    pub line_stats: BTreeMap<String, LanguageStats>,
+    /// Images, fonts, datasets, binaries and other non-code files, as (path, kind, size in
+    /// bytes); see [`asset_kind`].
+    pub assets: Vec<(String, String, u64)>,
+    /// Encoding and line endings of every file counted in `line_stats`.
+    pub text_files: Vec<(String, TextEncoding, LineEnding)>,
+    /// How many code files there were before `sample` kept a subset of them; `None` when
+    /// every one is analyzed.
+    pub sampled_from: Option<usize>,
 }
 
-pub fn collect_code_files(dir: &Path, config: &Config) -> Vec<String> {
-    let code_files = Arc::new(Mutex::new(Vec::new()));
-    let ignore_patterns = create_ignore_set(config);
-    let code_identifiers = config.get_code_identifiers();
+/// Walks `dir` once, bucketing entries into documentation, manifests and code files.
+///
+/// A file can land in more than one bucket (`requirements.txt` is both a doc and a
+/// manifest). Code files are only kept when they sit at or below a code directory, or
+/// all of them when there is no code directory at all.
+/// Cancelling `cancel` stops the walk and returns [`FolderSummaryError::Cancelled`].
+pub fn walk_project(
+    dir: &Path,
+    config: &Config,
+    cancel: &CancellationToken,
+) -> Result<ProjectFiles, FolderSummaryError> {
+    let ignore_patterns = create_ignore_set(dir, config)?;
+    let code_identifiers: HashSet<String> = config.get_code_identifiers().into_iter().collect();
+    let code_extensions: Vec<String> = get_analyzers()
+        .iter()
+        .flat_map(|analyzer| analyzer.extensions().iter().map(|ext| ext.to_string()))
+        .chain(config.get_language_overrides().into_iter().map(|(ext, _)| ext))
+        .collect();
 
-    info!("Starting to collect code files from: {:?}", dir);
+    info!("Starting to collect files from: {:?}", dir);
     debug!("Ignore patterns: {:?}", ignore_patterns);
     debug!("Code identifiers: {:?}", code_identifiers);
 
-    let code_dirs = find_code_directories(dir, &code_identifiers, &ignore_patterns);
-    debug!("Found code directories: {:?}", code_dirs);
+    let collected = Arc::new(Mutex::new(ProjectFiles::default()));
+    // Followed symlinks can reach the same file twice.
+    let seen = Arc::new(Mutex::new(HashSet::new()));
+
+    // Honor .gitignore (including the global excludes file and .git/info/exclude) and
+    // .ignore files everywhere, even when the directory isn't inside a git repository.
+    WalkBuilder::new(dir)
+        .hidden(false)
+        .max_depth(config.max_depth)
+        .follow_links(config.get_follow_symlinks())
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .ignore(true)
+        .require_git(false)
+        .filter_entry(move |entry| {
+            let path = entry.path();
+            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+            let should_include =
+                entry.depth() == 0 || !ignore_patterns.matched(path, is_dir).is_ignore();
+            debug!("Checking entry: {:?}, should include: {}", path, should_include);
+            should_include
+        })
+        .build_parallel()
+        .run(|| {
+            let collected = Arc::clone(&collected);
+            let seen = Arc::clone(&seen);
+            let code_identifiers = &code_identifiers;
+            let code_extensions = &code_extensions;
+            let cancel = cancel.clone();
+            Box::new(move |entry| {
+                if cancel.is_cancelled() {
+                    return WalkState::Quit;
+                }
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        debug!("Error processing entry: {:?}", e);
+                        return WalkState::Continue;
+                    }
+                };
+                if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                    return WalkState::Continue;
+                }
 
-    for code_dir in code_dirs {
-        let ignore_patterns_clone = ignore_patterns.clone(); // Clone inside the loop
-        WalkBuilder::new(&code_dir)
-            .hidden(false)
-            .add_custom_ignore_filename(".gitignore")
-            .filter_entry(move |entry| {
                 let path = entry.path();
-                let should_include = !is_ignored(path, &ignore_patterns_clone); // Use the cloned ignore patterns
-                debug!("Checking entry: {:?}, should include: {}", path, should_include);
-                should_include
+                let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+                if !seen.lock().unwrap().insert(canonical) {
+                    debug!("Skipping already collected file: {:?}", path);
+                    return WalkState::Continue;
+                }
+
+                // Counted before taking the lock, so files are read in parallel.
+                let text = language_name(path)
+                    .and_then(|_| fs::read(path).ok())
+                    .and_then(|bytes| decode_text(&bytes));
+                let line_stats = text.as_ref().and_then(|(contents, _)| count_lines(path, contents));
+                let hygiene = text.map(|(contents, encoding)| (encoding, line_ending(&contents)));
+                let head = path.extension().is_none().then(|| read_head(path)).flatten();
+                let size = || entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+                let asset = asset_kind(path, head.as_deref()).map(|kind| (kind.to_string(), size()));
+                let path_str = path.to_string_lossy().into_owned();
+                let code_size = code_extensions
+                    .iter()
+                    .any(|ext| path_str.ends_with(ext.as_str()))
+                    .then(size);
+
+                let file_name = entry.file_name().to_string_lossy();
+                let mut collected = collected.lock().unwrap();
+                if let Some(stats) = line_stats {
+                    add_line_stats(&mut collected.line_stats, stats);
+                }
+                if let Some((encoding, ending)) = hygiene {
+                    collected.text_files.push((path_str.clone(), encoding, ending));
+                }
+                if let Some((kind, size)) = asset {
+                    collected.assets.push((path_str.clone(), kind, size));
+                }
+                if is_documentation_file(&file_name) {
+                    collected.docs.push(path_str.clone());
+                }
+                if code_identifiers.contains(file_name.as_ref()) {
+                    collected.manifests.push(path_str.clone());
+                }
+                if is_config_file(path) {
+                    collected.config_files.push(path_str.clone());
+                }
+                if schema_kind(path).is_some() {
+                    collected.schema_files.push(path_str.clone());
+                }
+                if let Some(size) = code_size {
+                    collected.code_sizes.insert(path_str.clone(), size);
+                    collected.code.push(path_str);
+                }
+                WalkState::Continue
             })
-            .build_parallel()
-            .run(|| {
-                let code_files = Arc::clone(&code_files);
-                Box::new(move |entry| {
-                    let entry = match entry {
-                        Ok(entry) => entry,
-                        Err(e) => {
-                            debug!("Error processing entry: {:?}", e);
-                            return WalkState::Continue;
-                        }
-                    };
-
-                    if entry.file_type().map_or(false, |ft| ft.is_file()) {
-                        if let Some(ext) = entry.path().extension() {
-                            if ext == "rs" || ext == "js" || ext == "ts" || ext == "py" {
-                                let mut code_files = code_files.lock().unwrap();
-                                code_files.push(entry.path().to_string_lossy().into_owned());
-                                debug!("Added code file: {:?}", entry.path());
-                            } else {
-                                debug!("Skipping non-code file: {:?}", entry.path());
-                            }
-                        }
-                    }
+        });
 
-                    WalkState::Continue
-                })
-            });
+    if cancel.is_cancelled() {
+        return Err(FolderSummaryError::Cancelled);
     }
 
-    let collected_files = Arc::try_unwrap(code_files)
+    let mut project = Arc::try_unwrap(collected)
         .unwrap()
         .into_inner()
         .unwrap();
-    
-    info!("Collected {} code files", collected_files.len());
-    collected_files
+
+    let code_dirs: HashSet<PathBuf> = project
+        .manifests
+        .iter()
+        .filter_map(|manifest| Path::new(manifest).parent().map(Path::to_path_buf))
+        .collect();
+    debug!("Found code directories: {:?}", code_dirs);
+    // A folder of plain scripts has no manifest; rather than finding no code at all, its
+    // code files are all analyzed.
+    if code_dirs.is_empty() {
+        if !project.code.is_empty() {
+            warn!(
+                "None of the code_identifiers files found in {}; analyzing all of its code files",
+                dir.display()
+            );
+        }
+    } else {
+        project
+            .code
+            .retain(|file| Path::new(file).ancestors().any(|dir| code_dirs.contains(dir)));
+    }
+
+    project.docs.sort();
+    project.manifests.sort();
+    project.code.sort();
+    project.config_files.sort();
+    project.schema_files.sort();
+    project.assets.sort();
+    project.text_files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    info!(
+        "Collected {} code files, {} documentation files, {} manifests and {} configuration files",
+        project.code.len(),
+        project.docs.len(),
+        project.manifests.len(),
+        project.config_files.len()
+    );
+    Ok(project)
 }
 
-fn create_ignore_set(config: &Config) -> globset::GlobSet {
-    let mut builder = GlobSetBuilder::new();
-    for pattern in config.get_custom_ignore_paths() {
-        builder.add(Glob::new(&pattern).expect("Invalid glob pattern"));
+// The first bytes of a file, enough to recognize executable headers.
+fn read_head(path: &Path) -> Option<Vec<u8>> {
+    let mut head = [0; 4];
+    let read = fs::File::open(path).and_then(|mut file| file.read(&mut head)).ok()?;
+    Some(head[..read].to_vec())
+}
+
+pub fn is_documentation_file(file_name: &str) -> bool {
+    file_name.ends_with(".md") || file_name.ends_with(".txt") || file_name.ends_with(".rst")
+}
+
+/// Whether `path` holds configuration worth outlining: TOML, YAML or JSON settings (but
+/// not lock files), `.env` templates, CI workflow definitions (including `Jenkinsfile`),
+/// and Dockerfiles.
+pub fn is_config_file(path: &Path) -> bool {
+    let Some(file_name) = path.file_name().map(|name| name.to_string_lossy()) else {
+        return false;
+    };
+    if file_name.contains("lock") || file_name == "analysis_cache.json" {
+        return false;
     }
-    builder.build().expect("Failed to build GlobSet")
+    if matches!(
+        file_name.as_ref(),
+        ".env.example" | ".env.sample" | ".env.template" | "Jenkinsfile"
+    ) || is_dockerfile(path)
+    {
+        return true;
+    }
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("toml" | "yaml" | "yml" | "json")
+    )
 }
 
-fn is_ignored(path: &Path, ignore_set: &globset::GlobSet) -> bool {
-    ignore_set.is_match(path) || path.components().any(|c| ignore_set.is_match(c.as_os_str()))
+/// Whether `path`, relative to the analyzed directory, looks like a test: it sits under a
+/// `tests`, `test`, `__tests__` or `spec` directory, or follows a test naming convention
+/// (`test_*.py`, `*_test.go`, `*_test.rs`, `*.test.ts`, `*.spec.js`, `conftest.py`, ...).
+pub fn is_test_file(path: &Path) -> bool {
+    const TEST_DIRS: &[&str] = &["tests", "test", "__tests__", "spec", "specs"];
+    let in_test_dir = path
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .any(|component| TEST_DIRS.contains(&component.as_os_str().to_string_lossy().as_ref()));
+    if in_test_dir {
+        return true;
+    }
+
+    let Some(file_name) = path.file_name().map(|name| name.to_string_lossy()) else {
+        return false;
+    };
+    let stem = file_name.split('.').next().unwrap_or("");
+    file_name == "conftest.py"
+        || stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with("_tests")
+        || file_name.contains(".test.")
+        || file_name.contains(".spec.")
 }
 
-fn find_code_directories(dir: &Path, code_identifiers: &[String], ignore_set: &globset::GlobSet) -> HashSet<PathBuf> {
-    let mut code_dirs = HashSet::new();
-    let walker = WalkDir::new(dir).into_iter();
-    for entry in walker.filter_entry(|e| !is_ignored(e.path(), ignore_set)) {
-        let entry = match entry {
-            Ok(entry) => entry,
-            Err(e) => {
-                debug!("Error accessing entry: {:?}", e);
-                continue;
-            }
-        };
-        let path = entry.path();
-        if path.is_dir() {
-            if code_identifiers.iter().any(|id| path.join(id).exists()) {
-                code_dirs.insert(path.to_path_buf());
-                debug!("Found code directory: {:?}", path);
-            }
+// `custom_ignore_paths`, then the lines of `dir`'s `.folder-summaryignore`, read as a
+// .gitignore at the root of `dir`: `/build` only matches there, `build/` only matches
+// directories and `!pattern` re-includes what an earlier pattern, including a default
+// one, left out.
+fn create_ignore_set(dir: &Path, config: &Config) -> Result<Gitignore, FolderSummaryError> {
+    let mut builder = GitignoreBuilder::new(dir);
+    for pattern in config.get_custom_ignore_paths() {
+        builder.add_line(None, &pattern).map_err(|e| {
+            FolderSummaryError::ConfigError(format!(
+                "Invalid custom_ignore_paths pattern {:?}: {}",
+                pattern, e
+            ))
+        })?;
+    }
+    // The project's own ignore file comes last, so it can re-include a default pattern.
+    let ignore_file = dir.join(IGNORE_FILE);
+    if ignore_file.is_file() {
+        debug!("Reading ignore patterns from {:?}", ignore_file);
+        if let Some(e) = builder.add(&ignore_file) {
+            return Err(FolderSummaryError::ConfigError(format!(
+                "Invalid {}: {}",
+                ignore_file.display(),
+                e
+            )));
         }
     }
-    code_dirs
+    builder
+        .build()
+        .map_err(|e| FolderSummaryError::ConfigError(format!("Invalid custom_ignore_paths: {}", e)))
 }
 
+/// The name of the first package [`manifest_packages`] finds. A Go module is named by the
+/// last segment of its path, leaving out a `/v2`-style major version.
 pub fn get_project_name(dir: &Path) -> Option<String> {
-    // Check for Cargo.toml
-    if let Ok(content) = fs::read_to_string(dir.join("Cargo.toml")) {
-        if let Ok(toml) = content.parse::<toml::Value>() {
-            if let Some(package) = toml.get("package") {
-                if let Some(name) = package.get("name") {
-                    return Some(name.as_str().unwrap().to_string());
-                }
-            }
-        }
+    let package = manifest_packages(dir).into_iter().next()?;
+    if package.manifest != "go.mod" {
+        return Some(package.name);
     }
+    package
+        .name
+        .rsplit('/')
+        .find(|segment| {
+            !(segment.starts_with('v') && segment.len() > 1 && segment[1..].chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(String::from)
+}
 
-    // Check for package.json
-    if let Ok(content) = fs::read_to_string(dir.join("package.json")) {
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-            if let Some(name) = json.get("name") {
-                if let Some(name_str) = name.as_str() {
-                    return Some(name_str.to_string());
-                }
-            }
-        }
-    }
+/// A package declared in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestPackage {
+    /// The manifest's file name, such as `Cargo.toml`.
+    pub manifest: &'static str,
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/// The packages the manifests in `dir` declare, in this order: `Cargo.toml`,
+/// `package.json`, `pyproject.toml` (`[project]` or `[tool.poetry]`), `go.mod`, `pom.xml`,
+/// `build.gradle(.kts)`, `composer.json` and the `*.gemspec` a Gemfile builds. A Go
+/// module has no version in its `go.mod`; it comes from the tags it is released under.
+pub fn manifest_packages(dir: &Path) -> Vec<ManifestPackage> {
+    let read = |file: &str| fs::read_to_string(dir.join(file)).ok();
+    let package = |manifest: &'static str, name: String, version: Option<String>| ManifestPackage {
+        manifest,
+        name,
+        version,
+    };
+    let toml_package = |manifest: &'static str, tables: &[&[&str]]| {
+        let toml = read(manifest)?.parse::<toml::Value>().ok()?;
+        tables.iter().find_map(|path| {
+            let table = path.iter().try_fold(&toml, |value, key| value.get(key))?;
+            let name = table.get("name")?.as_str()?.to_string();
+            let version = table.get("version").and_then(|v| v.as_str()).map(String::from);
+            Some(package(manifest, name, version))
+        })
+    };
+    let json_package = |manifest: &'static str| {
+        let json = serde_json::from_str::<serde_json::Value>(&read(manifest)?).ok()?;
+        let name = json.get("name")?.as_str()?.to_string();
+        let version = json.get("version").and_then(|v| v.as_str()).map(String::from);
+        Some(package(manifest, name, version))
+    };
+    let capture = |pattern: &str, text: &str| {
+        Regex::new(pattern)
+            .unwrap()
+            .captures(text)
+            .map(|caps| caps[1].trim().to_string())
+    };
+
+    let go_module = || {
+        let content = read("go.mod")?;
+        let name = capture(r"(?m)^module\s+(\S+)", &content)?;
+        Some(package("go.mod", name, None))
+    };
+    let maven_package = || {
+        let content = read("pom.xml")?;
+        // The project's own coordinates, not those of its parent, dependencies or plugins;
+        // the version is inherited from the parent when the project doesn't set one.
+        let parent = capture(r"(?s)<parent>(.*?)</parent>", &content).unwrap_or_default();
+        let nested = Regex::new(
+            r"(?s)<!--.*?-->|<parent>.*?</parent>|<dependencies>.*?</dependencies>|<dependencyManagement>.*?</dependencyManagement>|<build>.*?</build>|<profiles>.*?</profiles>|<reporting>.*?</reporting>",
+        )
+        .unwrap();
+        let own = nested.replace_all(&content, "");
+        let name = capture(r"<artifactId>([^<]+)</artifactId>", &own)?;
+        let version = capture(r"<version>([^<]+)</version>", &own)
+            .or_else(|| capture(r"<version>([^<]+)</version>", &parent));
+        Some(package("pom.xml", name, version))
+    };
+    let gradle_package = || {
+        let (manifest, build) = match read("build.gradle") {
+            Some(build) => ("build.gradle", build),
+            None => ("build.gradle.kts", read("build.gradle.kts")?),
+        };
+        // Gradle names a project after its directory unless the settings rename it.
+        let name = read("settings.gradle")
+            .or_else(|| read("settings.gradle.kts"))
+            .and_then(|settings| capture(r#"rootProject\.name\s*=\s*["']([^"']+)["']"#, &settings))
+            .or_else(|| {
+                let dir = dir.canonicalize().ok()?;
+                dir.file_name().map(|name| name.to_string_lossy().into_owned())
+            })?;
+        let version = capture(r#"(?m)^\s*version\s*=?\s*["']([^"']+)["']"#, &build);
+        Some(package(manifest, name, version))
+    };
+    // A Gemfile lists dependencies; the gem itself, if the project is one, is described by
+    // its gemspec.
+    let gem_package = || {
+        read("Gemfile")?;
+        let gemspec = fs::read_dir(dir)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "gemspec"))?;
+        let content = fs::read_to_string(gemspec).ok()?;
+        let name = capture(r#"\.name\s*=\s*["']([^"']+)["']"#, &content)?;
+        let version = capture(r#"\.version\s*=\s*["']([^"']+)["']"#, &content);
+        Some(package("Gemfile", name, version))
+    };
+
+    [
+        toml_package("Cargo.toml", &[&["package"]]),
+        json_package("package.json"),
+        toml_package("pyproject.toml", &[&["project"], &["tool", "poetry"]]),
+        go_module(),
+        maven_package(),
+        gradle_package(),
+        json_package("composer.json"),
+        gem_package(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
 
-    None
+/// The one-line description in the project's `Cargo.toml`, `package.json` or
+/// `pyproject.toml`.
+pub fn get_project_description(dir: &Path) -> Option<String> {
+    let toml_description = |file: &str, table: &str| {
+        let content = fs::read_to_string(dir.join(file)).ok()?;
+        let toml = content.parse::<toml::Value>().ok()?;
+        toml.get(table)?.get("description")?.as_str().map(String::from)
+    };
+    toml_description("Cargo.toml", "package")
+        .or_else(|| {
+            let content = fs::read_to_string(dir.join("package.json")).ok()?;
+            let json = serde_json::from_str::<serde_json::Value>(&content).ok()?;
+            json.get("description")?.as_str().map(String::from)
+        })
+        .or_else(|| toml_description("pyproject.toml", "project"))
+        .map(|description| description.trim().to_string())
+        .filter(|description| !description.is_empty())
+}
+
+/// SHA-256 of a file's contents, used to spot byte-identical copies.
+pub fn hash_contents(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it into place, so
+/// readers and other runs writing the same file never see it half written.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), FolderSummaryError> {
+    write_atomic_with(path, |out| Ok(out.write_all(contents)?))
+}
+
+/// Like [`write_atomic`], with the contents written by `write` as they are produced
+/// rather than handed over in one buffer.
+pub fn write_atomic_with<T>(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> Result<T, FolderSummaryError>,
+) -> Result<T, FolderSummaryError> {
+    let error = |e| FolderSummaryError::file(path.display().to_string(), e);
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(path.file_name().unwrap_or_default());
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp = path.with_file_name(temp_name);
+    let written = fs::File::create(&temp).map_err(error).and_then(|file| {
+        let mut out = io::BufWriter::new(file);
+        let value = write(&mut out)?;
+        out.flush().map_err(error)?;
+        Ok(value)
+    });
+    let value = match written {
+        Ok(value) => value,
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+    };
+    fs::rename(&temp, path).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        error(e)
+    })?;
+    Ok(value)
 }
@@ -0,0 +1,27 @@
+use std::path::Path;
+use std::process::Command;
+
+/// The commit a directory's git checkout is at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitInfo {
+    pub commit: String,
+    /// `None` for a detached HEAD.
+    pub branch: Option<String>,
+}
+
+/// Asks `git` which commit and branch `dir` is checked out at; `None` when it isn't in
+/// a repository or git isn't installed.
+pub fn git_info(dir: &Path) -> Option<GitInfo> {
+    let commit = git(dir, &["rev-parse", "HEAD"])?;
+    let branch = git(dir, &["symbolic-ref", "--short", "-q", "HEAD"]);
+    Some(GitInfo { commit, branch })
+}
+
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
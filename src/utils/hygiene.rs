@@ -0,0 +1,121 @@
+use super::TextEncoding;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Anomalies listed in the report; the rest are counted.
+const MAX_ISSUES: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    /// Bare carriage returns, as classic Mac OS wrote them.
+    Cr,
+    /// More than one of the above in the same file.
+    Mixed,
+    /// A single line without a terminator, or an empty file.
+    None,
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LineEnding::Lf => write!(f, "LF"),
+            LineEnding::Crlf => write!(f, "CRLF"),
+            LineEnding::Cr => write!(f, "CR"),
+            LineEnding::Mixed => write!(f, "mixed"),
+            LineEnding::None => write!(f, "none"),
+        }
+    }
+}
+
+/// Which line terminators `text` uses.
+pub fn line_ending(text: &str) -> LineEnding {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    let cr = text.matches('\r').count() - crlf;
+    match (lf > 0, crlf > 0, cr > 0) {
+        (false, false, false) => LineEnding::None,
+        (true, false, false) => LineEnding::Lf,
+        (false, true, false) => LineEnding::Crlf,
+        (false, false, true) => LineEnding::Cr,
+        _ => LineEnding::Mixed,
+    }
+}
+
+/// Rewrites CRLF and bare CR line endings as LF and drops a leading byte order mark, so
+/// analyzers only ever see one kind of line break.
+pub fn normalize_text(text: &str) -> String {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    if text.contains('\r') {
+        text.replace("\r\n", "\n").replace('\r', "\n")
+    } else {
+        text.to_string()
+    }
+}
+
+/// A file whose encoding or line endings stand out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HygieneIssue {
+    pub file: String,
+    pub issue: String,
+}
+
+/// Encodings and line endings across the repository's text files.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Hygiene {
+    /// Files per encoding, e.g. `UTF-8`, `UTF-8 (BOM)` or `Latin-1`.
+    pub encodings: BTreeMap<String, usize>,
+    /// Files per line ending style: `LF`, `CRLF`, `CR` or `mixed`.
+    pub line_endings: BTreeMap<String, usize>,
+    pub issues: Vec<HygieneIssue>,
+    pub omitted_issues: usize,
+}
+
+/// Tallies encodings and line endings of the text files the walk read, and lists the
+/// anomalies: byte order marks, encodings other than UTF-8, mixed or bare-CR line
+/// endings, and files whose line endings differ from the rest of the repository.
+pub fn text_hygiene(files: &[(String, TextEncoding, LineEnding)]) -> Option<Hygiene> {
+    if files.is_empty() {
+        return None;
+    }
+    let mut hygiene = Hygiene::default();
+    for (_, encoding, ending) in files {
+        *hygiene.encodings.entry(encoding.to_string()).or_default() += 1;
+        if *ending != LineEnding::None {
+            *hygiene.line_endings.entry(ending.to_string()).or_default() += 1;
+        }
+    }
+    let lf = hygiene.line_endings.get("LF").copied().unwrap_or(0);
+    let crlf = hygiene.line_endings.get("CRLF").copied().unwrap_or(0);
+    let predominant = if crlf > lf { LineEnding::Crlf } else { LineEnding::Lf };
+
+    let mut issues = Vec::new();
+    for (file, encoding, ending) in files {
+        let mut problems = Vec::new();
+        match encoding {
+            TextEncoding::Utf8 => {}
+            TextEncoding::Utf8Bom => problems.push("UTF-8 byte order mark".to_string()),
+            other => problems.push(format!("{} instead of UTF-8", other)),
+        }
+        match ending {
+            LineEnding::Mixed => problems.push("mixed line endings".to_string()),
+            LineEnding::Cr => problems.push("bare CR line endings".to_string()),
+            LineEnding::Lf | LineEnding::Crlf if *ending != predominant => {
+                problems.push(format!("{} line endings where most files use {}", ending, predominant))
+            }
+            _ => {}
+        }
+        if !problems.is_empty() {
+            issues.push(HygieneIssue {
+                file: file.clone(),
+                issue: problems.join(", "),
+            });
+        }
+    }
+    hygiene.omitted_issues = issues.len().saturating_sub(MAX_ISSUES);
+    issues.truncate(MAX_ISSUES);
+    hygiene.issues = issues;
+    Some(hygiene)
+}
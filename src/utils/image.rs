@@ -0,0 +1,313 @@
+use crate::error::FolderSummaryError;
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::thread;
+
+// How many image indexes deep `oci_manifest` looks for an image before giving up, so an
+// index that points back at itself can't loop forever.
+const MAX_INDEX_DEPTH: usize = 8;
+
+// `manifest.json` as `docker save` writes it: one entry per saved image.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DockerManifest {
+    config: String,
+    layers: Vec<String>,
+}
+
+// An OCI image index, or a manifest when `config` is set.
+#[derive(Deserialize)]
+struct OciManifest {
+    #[serde(default)]
+    manifests: Vec<Descriptor>,
+    config: Option<Descriptor>,
+    #[serde(default)]
+    layers: Vec<Descriptor>,
+}
+
+#[derive(Deserialize)]
+struct Descriptor {
+    digest: String,
+    platform: Option<Platform>,
+}
+
+#[derive(Deserialize)]
+struct Platform {
+    os: String,
+}
+
+#[derive(Deserialize)]
+struct ImageConfig {
+    config: Option<RunConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RunConfig {
+    working_dir: Option<String>,
+}
+
+/// Whether `dir` holds a container image as `docker save` writes it (`manifest.json`) or
+/// as an OCI image layout (`oci-layout` and `index.json`).
+pub fn is_image_layout(dir: &Path) -> bool {
+    dir.join("manifest.json").is_file()
+        || (dir.join("oci-layout").is_file() && dir.join("index.json").is_file())
+}
+
+/// Saves `reference`, an image in the local Docker (or Podman) image store, to the tarball
+/// `archive`.
+pub fn save_image(reference: &str, archive: &Path) -> Result<(), FolderSummaryError> {
+    let failed = |message: String| FolderSummaryError::AnalysisError(format!("{}: {}", reference, message));
+    for tool in ["docker", "podman"] {
+        debug!("Saving image {} with {}", reference, tool);
+        match Command::new(tool).arg("save").arg("-o").arg(archive).arg(reference).output() {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Ok(Output { status, .. }) if status.success() => return Ok(()),
+            Ok(Output { stderr, .. }) => {
+                return Err(failed(format!(
+                    "could not save the image: {}",
+                    String::from_utf8_lossy(&stderr).trim()
+                )))
+            }
+            Err(e) => return Err(failed(format!("could not save the image: {}", e))),
+        }
+    }
+    Err(failed("saving an image needs `docker` or `podman`".to_string()))
+}
+
+/// Applies the layers of the image in `layout` in order to build its filesystem in
+/// `rootfs`, and returns the directory to analyze: the image's working directory when it
+/// sets one, or else the whole filesystem.
+pub fn unpack_image(layout: &Path, rootfs: &Path) -> Result<PathBuf, FolderSummaryError> {
+    let (config, layers) = if layout.join("manifest.json").is_file() {
+        let manifests: Vec<DockerManifest> = read_json(&layout.join("manifest.json"))?;
+        let manifest = manifests
+            .into_iter()
+            .next()
+            .ok_or_else(|| invalid(layout, "manifest.json lists no image"))?;
+        let config = inside(layout, &manifest.config)?;
+        let layers = manifest
+            .layers
+            .iter()
+            .map(|layer| inside(layout, layer))
+            .collect::<Result<Vec<_>, _>>()?;
+        (config, layers)
+    } else {
+        let manifest = oci_manifest(layout)?;
+        let config = manifest
+            .config
+            .ok_or_else(|| invalid(layout, "the image manifest has no config"))?;
+        let layers = manifest
+            .layers
+            .iter()
+            .map(|layer| blob(layout, &layer.digest))
+            .collect::<Result<Vec<_>, _>>()?;
+        (blob(layout, &config.digest)?, layers)
+    };
+
+    fs::create_dir_all(rootfs)
+        .map_err(|e| FolderSummaryError::file(rootfs.display().to_string(), e))?;
+    for layer in &layers {
+        apply_layer(layer, rootfs)?;
+    }
+
+    let working_dir = read_json::<ImageConfig>(&config)?
+        .config
+        .and_then(|config| config.working_dir)
+        .unwrap_or_default();
+    let working_dir = rootfs.join(working_dir.trim_start_matches('/'));
+    Ok(if working_dir.is_dir() && within(rootfs, &working_dir) {
+        working_dir
+    } else {
+        rootfs.to_path_buf()
+    })
+}
+
+// The image manifest an OCI layout's index points to, following a multi-platform index to
+// its first image; attestation manifests are marked with an `unknown` platform.
+fn oci_manifest(layout: &Path) -> Result<OciManifest, FolderSummaryError> {
+    let mut manifest: OciManifest = read_json(&layout.join("index.json"))?;
+    for _ in 0..MAX_INDEX_DEPTH {
+        if manifest.config.is_some() {
+            return Ok(manifest);
+        }
+        let descriptor = manifest
+            .manifests
+            .iter()
+            .find(|descriptor| descriptor.platform.as_ref().is_none_or(|platform| platform.os != "unknown"))
+            .ok_or_else(|| invalid(layout, "index.json lists no image"))?;
+        manifest = read_json(&blob(layout, &descriptor.digest)?)?;
+    }
+    Err(invalid(
+        layout,
+        &format!("index.json nests image indexes more than {} deep", MAX_INDEX_DEPTH),
+    ))
+}
+
+// Deletes what the layer's whiteout entries hide from the layers below, then unpacks the
+// layer over them. Entries whose directory resolves outside `rootfs`, through a symlink
+// an earlier layer left, are skipped. A layer that only partly unpacks, such as one with
+// device files that need root to create, is warned about rather than failing the image.
+fn apply_layer(layer: &Path, rootfs: &Path) -> Result<(), FolderSummaryError> {
+    debug!("Applying layer {}", layer.display());
+    let error = |e| FolderSummaryError::file(layer.display().to_string(), e);
+    let listing = Command::new("tar").arg("-tf").arg(layer).output().map_err(error)?;
+    let listing = String::from_utf8_lossy(&listing.stdout);
+    let mut members = Vec::new();
+    for entry in listing.lines() {
+        let entry = Path::new(entry);
+        let Some(hidden) = entry
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(".wh."))
+        else {
+            members.push(entry);
+            continue;
+        };
+        if entry
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            continue;
+        }
+        let parent = rootfs.join(entry.parent().unwrap_or(Path::new("")));
+        // A symlinked parent could point outside the image.
+        if !within(rootfs, &parent) {
+            continue;
+        }
+        if hidden == ".wh..opq" {
+            // An opaque directory hides everything the layers below put in it.
+            for child in fs::read_dir(&parent).into_iter().flatten().flatten() {
+                remove(&child.path());
+            }
+        } else {
+            remove(&parent.join(hidden));
+        }
+    }
+    members.retain(|entry| {
+        let inside = stays_within(rootfs, entry);
+        if !inside {
+            warn!(
+                "Skipping {} in layer {}: it would be written outside the image",
+                entry.display(),
+                layer.display()
+            );
+        }
+        inside
+    });
+
+    // Only the listed members, each on its own: a directory named here must not bring
+    // along the entries under it that were skipped.
+    let mut tar = Command::new("tar")
+        .arg("-xf")
+        .arg(layer)
+        .arg("-C")
+        .arg(rootfs)
+        .arg("--no-same-owner")
+        .arg("--no-recursion")
+        .arg("--null")
+        .arg("-T")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(error)?;
+    let mut names = Vec::new();
+    for member in &members {
+        names.extend_from_slice(member.as_os_str().as_encoded_bytes());
+        names.push(b'\0');
+    }
+    // Fed from another thread, so tar can't block on a full stderr while we wait on stdin.
+    let stdin = tar.stdin.take();
+    let feeder = thread::spawn(move || stdin.map_or(Ok(()), |mut stdin| stdin.write_all(&names)));
+    let output = tar.wait_with_output().map_err(error)?;
+    let fed = feeder.join().unwrap_or(Ok(()));
+    if !output.status.success() {
+        warn!(
+            "Layer {} was only partly unpacked: {}",
+            layer.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return Ok(());
+    }
+    fed.map_err(error)
+}
+
+fn remove(path: &Path) {
+    let removed = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(_) => return,
+    };
+    if let Err(e) = removed {
+        warn!("Could not remove {}: {}", path.display(), e);
+    }
+}
+
+// Whether unpacking the archive entry `entry` into `rootfs` writes inside it: the entry's
+// closest directory that already exists has to resolve under `rootfs`, following any
+// symlink an earlier layer put there.
+fn stays_within(rootfs: &Path, entry: &Path) -> bool {
+    if !entry
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return false;
+    }
+    let mut dir = entry.parent();
+    while let Some(parent) = dir.filter(|parent| !parent.as_os_str().is_empty()) {
+        let path = rootfs.join(parent);
+        if fs::symlink_metadata(&path).is_ok() {
+            return within(rootfs, &path);
+        }
+        dir = parent.parent();
+    }
+    true
+}
+
+// Whether `path` resolves to somewhere under `root`, following symlinks.
+fn within(root: &Path, path: &Path) -> bool {
+    match (root.canonicalize(), path.canonicalize()) {
+        (Ok(root), Ok(path)) => path.starts_with(root),
+        _ => false,
+    }
+}
+
+// A path from a manifest, which has to stay inside the layout.
+fn inside(layout: &Path, relative: &str) -> Result<PathBuf, FolderSummaryError> {
+    let relative = Path::new(relative);
+    if relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        Ok(layout.join(relative))
+    } else {
+        Err(invalid(layout, &format!("{} is outside the image", relative.display())))
+    }
+}
+
+// Where an OCI layout keeps the blob with `digest`, such as `sha256:ab12...`.
+fn blob(layout: &Path, digest: &str) -> Result<PathBuf, FolderSummaryError> {
+    match digest.split_once(':') {
+        Some((algorithm, hash)) => inside(layout, &format!("blobs/{}/{}", algorithm, hash)),
+        None => Err(invalid(layout, &format!("{} is not a digest", digest))),
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, FolderSummaryError> {
+    let contents =
+        fs::read(path).map_err(|e| FolderSummaryError::file(path.display().to_string(), e))?;
+    serde_json::from_slice(&contents).map_err(|e| {
+        FolderSummaryError::AnalysisError(format!("{} is not a valid image file: {}", path.display(), e))
+    })
+}
+
+fn invalid(layout: &Path, message: &str) -> FolderSummaryError {
+    FolderSummaryError::AnalysisError(format!("{}: {}", layout.display(), message))
+}
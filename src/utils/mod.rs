@@ -1,3 +1,18 @@
+pub mod archive;
+pub mod assets;
+pub mod clock;
+pub mod encoding;
 pub mod file_utils;
+pub mod git;
+pub mod hygiene;
+pub mod image;
+pub mod line_stats;
 
+pub use archive::*;
+pub use assets::*;
+pub use encoding::*;
 pub use file_utils::*;
+pub use git::*;
+pub use hygiene::*;
+pub use image::*;
+pub use line_stats::*;
@@ -0,0 +1,56 @@
+//! Golden snapshots of each analyzer's output for the files in `tests/fixtures`. After
+//! an intended change in analyzer output, review and accept the new snapshots with
+//! `cargo insta review`.
+
+use folder_summary::analyzer::{
+    DeclarationAnalyzer, JavaAnalyzer, JavaScriptAnalyzer, LanguageAnalyzer, PhpAnalyzer,
+    PythonAnalyzer, RustAnalyzer, ShellAnalyzer, SwiftAnalyzer,
+};
+use folder_summary::testing::assert_valid_analysis;
+use std::fs;
+
+fn analyze_fixture(analyzer: &dyn LanguageAnalyzer, name: &str) -> folder_summary::CodeAnalysis {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let content = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    assert_valid_analysis(analyzer, &content)
+}
+
+#[test]
+fn rust_fixture() {
+    insta::assert_json_snapshot!(analyze_fixture(&RustAnalyzer, "sample.rs"));
+}
+
+#[test]
+fn javascript_fixture() {
+    insta::assert_json_snapshot!(analyze_fixture(&JavaScriptAnalyzer, "sample.js"));
+}
+
+#[test]
+fn declaration_fixture() {
+    insta::assert_json_snapshot!(analyze_fixture(&DeclarationAnalyzer, "sample.d.ts"));
+}
+
+#[test]
+fn python_fixture() {
+    insta::assert_json_snapshot!(analyze_fixture(&PythonAnalyzer, "sample.py"));
+}
+
+#[test]
+fn java_fixture() {
+    insta::assert_json_snapshot!(analyze_fixture(&JavaAnalyzer, "sample.java"));
+}
+
+#[test]
+fn php_fixture() {
+    insta::assert_json_snapshot!(analyze_fixture(&PhpAnalyzer, "sample.php"));
+}
+
+#[test]
+fn swift_fixture() {
+    insta::assert_json_snapshot!(analyze_fixture(&SwiftAnalyzer, "sample.swift"));
+}
+
+#[test]
+fn shell_fixture() {
+    insta::assert_json_snapshot!(analyze_fixture(&ShellAnalyzer, "sample.sh"));
+}
@@ -0,0 +1,109 @@
+//! Runs the CLI on projects built from the files in `tests/fixtures`, with the mock
+//! provider and `SOURCE_DATE_EPOCH` set, and checks the markdown reports. The run's
+//! duration is the one thing left that varies, so it is blanked out.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+// An empty project directory, unique to the test, with a mock-provider config.
+fn new_project(name: &str) -> PathBuf {
+    let project = std::env::temp_dir().join(format!("folder-summary-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&project);
+    fs::create_dir_all(&project).unwrap();
+    fs::write(
+        project.join("config.toml"),
+        "llm_provider = \"mock\"\nsummary_output_path = \"out\"\n",
+    )
+    .unwrap();
+    project
+}
+
+// Runs the CLI in `project` and returns the lines of the markdown report it wrote.
+fn run(project: &Path) -> Vec<String> {
+    // Run from inside the project so report paths are relative to it.
+    let mut child = Command::new(env!("CARGO_BIN_EXE_folder-summary"))
+        .current_dir(project)
+        .env("SOURCE_DATE_EPOCH", "1700000000")
+        .env("TZ", "UTC")
+        // Outside the project, where the walk would pick the cache up as a JSON file.
+        .env("XDG_CACHE_HOME", project.with_extension("cache"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"y\n").unwrap();
+    assert!(child.wait().unwrap().success());
+
+    // In the subdirectory named after the project.
+    let report = fs::read_dir(project.join("out"))
+        .unwrap()
+        .flat_map(|entry| fs::read_dir(entry.unwrap().path()).unwrap())
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .expect("no markdown report written");
+    fs::read_to_string(report)
+        .unwrap()
+        .lines()
+        .map(|line| match line.strip_prefix("- Duration: ") {
+            Some(_) => "- Duration: [duration]".to_string(),
+            None => line.to_string(),
+        })
+        .collect()
+}
+
+fn remove(project: &Path) {
+    fs::remove_dir_all(project).unwrap();
+    let _ = fs::remove_dir_all(project.with_extension("cache"));
+}
+
+#[test]
+fn markdown_report() {
+    let project = new_project("e2e");
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(
+        project.join("Cargo.toml"),
+        "[package]\nname = \"fixture\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    for name in ["sample.rs", "sample.js", "sample.py"] {
+        fs::copy(fixtures().join(name), project.join("src").join(name)).unwrap();
+    }
+
+    let report = run(&project);
+    insta::assert_snapshot!(report.join("\n"));
+    remove(&project);
+}
+
+// A Python package declared only by `pyproject.toml`, next to a Cargo crate, is a code
+// directory of its own: its files are analyzed and its package is listed.
+#[test]
+fn pyproject_package_next_to_crate() {
+    let project = new_project("pyproject");
+    fs::create_dir_all(project.join("crate/src")).unwrap();
+    fs::create_dir_all(project.join("pylib")).unwrap();
+    fs::write(
+        project.join("crate/Cargo.toml"),
+        "[package]\nname = \"native\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    fs::copy(fixtures().join("sample.rs"), project.join("crate/src/sample.rs")).unwrap();
+    fs::write(
+        project.join("pylib/pyproject.toml"),
+        "[project]\nname = \"pylib\"\nversion = \"2.0.0\"\n",
+    )
+    .unwrap();
+    fs::copy(fixtures().join("sample.py"), project.join("pylib/sample.py")).unwrap();
+
+    let report = run(&project);
+    assert!(report.iter().any(|line| line == "## crate/src/sample.rs"), "{:#?}", report);
+    assert!(report.iter().any(|line| line == "## pylib/sample.py"), "{:#?}", report);
+    assert!(report.iter().any(|line| line == "- pylib"), "{:#?}", report);
+    assert!(report.iter().any(|line| line == "  - pylib: 2.0.0"), "{:#?}", report);
+    remove(&project);
+}
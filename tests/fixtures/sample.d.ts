@@ -0,0 +1,24 @@
+import { Readable } from 'stream';
+
+export interface StoreOptions {
+  root: string;
+  // Files larger than this are skipped.
+  maxSize?: number;
+  readonly encoding: string;
+  onError(error: Error): void;
+  filter?: (name: string) => boolean;
+}
+
+export declare class Store {
+  constructor(options: StoreOptions);
+  get(name: string): Promise<string>;
+}
+
+export declare function resolve(root: string, name: string): string;
+export declare function load(file: string): Promise<string>;
+export function stream(
+  file: string,
+  highWaterMark?: number,
+): Readable;
+
+export default Store;
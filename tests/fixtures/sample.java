@@ -0,0 +1,62 @@
+package com.example.inventory;
+
+import java.util.ArrayList;
+import java.util.List;
+import java.util.Map;
+import static java.util.Objects.requireNonNull;
+
+/**
+ * Keeps track of items in stock.
+ */
+public class Inventory {
+    private final Map<String, Integer> counts;
+
+    public Inventory(Map<String, Integer> counts) {
+        this.counts = requireNonNull(counts);
+    }
+
+    public int count(String item) {
+        return counts.getOrDefault(item, 0);
+    }
+
+    @Override
+    public String toString() {
+        return "Inventory{" + counts.size() + " items}";
+    }
+
+    public void add(String item, int quantity) throws IllegalArgumentException {
+        if (quantity <= 0) {
+            throw new IllegalArgumentException("quantity must be positive");
+        }
+        counts.merge(item, quantity, Integer::sum);
+    }
+
+    public boolean remove(String item, int quantity) {
+        Integer current = counts.get(item);
+        if (current == null || current < quantity) {
+            return false;
+        }
+        counts.put(item, current - quantity);
+        return true;
+    }
+
+    @SuppressWarnings("unchecked")
+    public static <T extends Comparable<T>> List<T> sorted(List<T> items, Map<String, List<T>> extra) {
+        List<T> result = new ArrayList<>(items);
+        for (List<T> more : extra.values()) {
+            result.addAll(more);
+        }
+        try {
+            result.sort(null);
+        } catch (ClassCastException e) {
+            return items;
+        }
+        return result;
+    }
+}
+
+interface Store {
+    void open();
+}
+
+enum Unit { PIECE, BOX }
@@ -0,0 +1,31 @@
+import { readFileSync } from 'fs';
+const path = require('path');
+
+export class Store {
+  constructor(root) {
+    this.root = root;
+  }
+}
+
+export function resolve(root, name) {
+  if (!name) {
+    return root;
+  }
+  return path.join(root, name);
+}
+
+// folder-summary:ignore blocking-in-async
+export async function load(file) {
+  return readFileSync(file, 'utf8');
+}
+
+const double = (x) => x * 2;
+
+export function* ids() {
+  let id = 0;
+  while (true) yield id++;
+}
+
+export { resolve as resolvePath };
+export * from './util';
+export default Store;
@@ -0,0 +1,84 @@
+<?php
+
+namespace App\Services;
+
+use App\Models\Order;
+use Illuminate\Support\Facades\{Cache, Log as Logger};
+use function App\Helpers\money;
+
+interface Notifier
+{
+    public function notify(Order $order): void;
+}
+
+trait LogsActivity
+{
+    protected function log(string $message): void
+    {
+        Logger::info($message);
+    }
+}
+
+abstract class Handler
+{
+    abstract protected function handle(Order $order): bool;
+}
+
+final class OrderService
+{
+    use LogsActivity;
+
+    private array $orders = [];
+
+    public function __construct(private Notifier $notifier)
+    {
+    }
+
+    #[Deprecated(reason: "use place()")]
+    public function add(Order $order): void
+    {
+        $this->orders[] = $order;
+    }
+
+    public function place(Order $order, bool $notify = true): ?Order
+    {
+        if ($order->total() <= 0 || !$order->isValid()) {
+            return null;
+        }
+        // A stray } inside a comment doesn't end the method.
+        $this->orders[$order->id] = $order;
+        if ($notify) {
+            $this->notifier->notify($order);
+        }
+        $this->log("Placed {$order->id}");
+        return $order;
+    }
+
+    public function total()
+    {
+        $sum = 0;
+        foreach ($this->orders as $order) {
+            $sum += $order->total() ?? 0;
+        }
+        return money($sum);
+    }
+
+    public static function cached(string $key, $default = array())
+    {
+        return Cache::remember($key, 60, fn () => $default);
+    }
+
+    public function count(): int
+    {
+        return count($this->orders);
+    }
+}
+
+function format_order(Order $order, string $currency = 'EUR'): string
+{
+    $label = match ($currency) {
+        'EUR' => '€',
+        default => '$',
+    };
+    return $label . number_format($order->total(), 2);
+}
@@ -0,0 +1,36 @@
+import os
+from time import sleep
+
+__all__ = ["Cache", "classify", "DEFAULT_LIMIT"]
+
+DEFAULT_LIMIT = 10
+
+
+class Cache:
+    def __init__(self, root):
+        self.root = root
+
+    def path(self, key):
+        return os.path.join(self.root, key)
+
+    @property
+    def size(self):
+        total = 0
+
+        for name in os.listdir(self.root):
+            total += 1
+        return total
+
+
+def classify(count, limit=10):
+    if count == 0:
+        return "empty"
+    elif count > limit:
+        return "over"
+    return "ok"
+
+
+# folder-summary:ignore blocking-in-async
+async def poll(interval):
+    sleep(interval)
+    return True
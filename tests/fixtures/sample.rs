@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+use std::fs;
+
+pub struct Inventory {
+    items: HashMap<String, u32>,
+}
+
+pub enum Change {
+    Added(u32),
+    Removed,
+    Renamed(String),
+    Restocked { amount: u32 },
+    Discontinued,
+}
+
+impl Inventory {
+    pub fn count(&self, name: &str) -> u32 {
+        self.items.get(name).copied().unwrap_or(0)
+    }
+}
+
+pub fn classify(count: u32, limit: u32) -> &'static str {
+    if count == 0 {
+        "empty"
+    } else if count > limit {
+        "over"
+    } else {
+        "ok"
+    }
+}
+
+// folder-summary:ignore blocking-in-async -- runs once at startup
+pub async fn load(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_default()
+}
@@ -0,0 +1,68 @@
+#!/usr/bin/env bash
+# Deploys the service to a Kubernetes cluster.
+set -euo pipefail
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+source "$SCRIPT_DIR/lib/common.sh"
+. ./env.sh
+
+export NAMESPACE="${NAMESPACE:-staging}" IMAGE_TAG
+export -f log
+
+packages=(
+  curl
+  jq
+)
+
+log() {
+  echo "[$(date +%H:%M:%S)] $*" >&2
+}
+
+function require {
+  for tool in "$@"; do
+    if ! command -v "$tool" >/dev/null 2>&1; then
+      log "missing $tool"
+      return 1
+    fi
+  done
+}
+
+build_image() {
+  local tag="$1"
+  local context="${2:-.}"
+  # Braces in a comment { don't count.
+  docker build -t "registry.example.com/app:${tag}" "$context"
+  docker push "registry.example.com/app:${tag}" || { log "push failed"; return 1; }
+  echo "${#tag}"
+}
+
+deploy() {
+  case "$1" in
+    staging|dev)
+      kubectl apply -f k8s/ --namespace "$NAMESPACE"
+      ;;
+    production)
+      sudo helm upgrade --install app ./chart
+      ;;
+    *)
+      log "unknown environment $1"
+      return 2
+      ;;
+  esac
+  retries=$(( 3 + 1 ))
+  while ! curl -fsS "https://app.example.com/health" | jq -e '.ok' >/dev/null; do
+    sleep 5
+  done
+  cat <<EOF > /tmp/deployed
+environment: $1
+ignored until EOF
+EOF
+}
+
+function broken (
+  ls
+)
+
+require docker kubectl
+build_image "$(git rev-parse --short HEAD)"
+deploy "${1:-staging}" && notify-send "deployed"
@@ -0,0 +1,92 @@
+import Foundation
+@testable import NetworkKit
+import struct Combine.AnyPublisher
+
+public protocol Cache {
+    associatedtype Value
+    var count: Int { get }
+    func value(for key: String) -> Value?
+    func store(_ value: Value, for key: String)
+    func remove(key: String)
+    mutating func clear()
+}
+
+public struct Account: Codable, Equatable {
+    let id: UUID
+    var name: String
+    var email: String
+    var balance: Decimal
+    var isActive: Bool = true
+    static let empty = Account(id: UUID(), name: "", email: "", balance: 0)
+
+    var displayName: String {
+        name.isEmpty ? email : name
+    }
+
+    static func == (lhs: Account, rhs: Account) -> Bool {
+        lhs.id == rhs.id
+    }
+}
+
+@MainActor
+public final class AccountStore {
+    private var accounts: [UUID: Account] = [:]
+    private let client: APIClient
+
+    init(client: APIClient) {
+        self.client = client
+    }
+
+    func load() async throws {
+        let fetched = try await client.fetch([Account].self, from: "/accounts")
+        for account in fetched where account.isActive {
+            accounts[account.id] = account
+        }
+    }
+
+    @discardableResult
+    func deposit(_ amount: Decimal, into id: UUID) -> Decimal? {
+        guard amount > 0, var account = accounts[id] else {
+            return nil
+        }
+        // A stray } inside a comment doesn't end the method.
+        account.balance += amount
+        accounts[id] = account
+        return account.balance
+    }
+
+    func transfer<T: Numeric>(
+        _ amount: T,
+        from source: UUID,
+        to target: UUID,
+        completion: @escaping (Result<Void, Error>) -> Void
+    ) {
+        if source == target || amount == 0 {
+            completion(.failure(StoreError.sameAccount))
+            return
+        }
+        completion(.success(()))
+    }
+
+    func total() -> Decimal {
+        accounts.values.reduce(0) { $0 + $1.balance }
+    }
+}
+
+extension AccountStore {
+    var isEmpty: Bool {
+        accounts.isEmpty
+    }
+}
+
+enum StoreError: Error {
+    case sameAccount
+    case notFound(UUID)
+}
+
+public func format(_ balance: Decimal, currency: String = "EUR") -> String {
+    let formatter = NumberFormatter()
+    formatter.numberStyle = .currency
+    formatter.currencyCode = currency
+    return formatter.string(from: balance as NSDecimalNumber) ?? "\(balance)"
+}